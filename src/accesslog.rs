@@ -0,0 +1,135 @@
+//! Access log of tool activity.
+//!
+//! Writes one JSON line per tool call (tool, duration, status, bytes). Unlike
+//! the debug trace it never includes parameters or payloads, so it is suitable
+//! for long-running shared deployments.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+
+/// Outcome of a tool call as recorded in the access log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallStatus {
+    /// The tool returned a successful result.
+    Ok,
+    /// The tool returned a result flagged as an error.
+    ToolError,
+    /// The call failed with an MCP error.
+    Error,
+}
+
+/// One access log line.
+#[derive(Debug, Serialize)]
+pub struct AccessLogEntry<'a> {
+    pub ts: String,
+    pub tool: &'a str,
+    pub duration_ms: u128,
+    pub status: CallStatus,
+    pub bytes: usize,
+}
+
+impl<'a> AccessLogEntry<'a> {
+    pub fn new(tool: &'a str, duration: Duration, status: CallStatus, bytes: usize) -> Self {
+        Self {
+            ts: Utc::now().to_rfc3339(),
+            tool,
+            duration_ms: duration.as_millis(),
+            status,
+            bytes,
+        }
+    }
+}
+
+/// Access log sink. Disabled when no target is configured.
+pub struct AccessLog {
+    sink: Option<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl AccessLog {
+    /// Create an access log writing to `target`: "stderr", "stdout" (HTTP
+    /// transport only, checked at startup) or a file path (appended). Failure to open the file disables the log with a warning.
+    pub fn new(target: Option<&str>) -> Self {
+        let sink: Option<Box<dyn Write + Send>> = match target {
+            None => None,
+            Some("stderr") => Some(Box::new(std::io::stderr())),
+            Some("stdout") => Some(Box::new(std::io::stdout())),
+            Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Some(Box::new(file)),
+                Err(e) => {
+                    tracing::warn!(path = %path, error = %e, "Failed to open access log");
+                    None
+                }
+            },
+        };
+
+        Self {
+            sink: sink.map(Mutex::new),
+        }
+    }
+
+    /// Check if the access log is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Append an entry as a single JSON line.
+    pub fn record(&self, entry: &AccessLogEntry<'_>) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        let Ok(line) = serde_json::to_string(entry) else {
+            return;
+        };
+        if let Ok(mut sink) = sink.lock() {
+            let _ = writeln!(sink, "{}", line);
+            let _ = sink.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_serializes_as_single_line() {
+        let entry = AccessLogEntry::new(
+            "list_features",
+            Duration::from_millis(42),
+            CallStatus::ToolError,
+            128,
+        );
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(!line.contains('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["tool"], "list_features");
+        assert_eq!(value["duration_ms"], 42);
+        assert_eq!(value["status"], "tool_error");
+        assert_eq!(value["bytes"], 128);
+    }
+
+    #[test]
+    fn test_file_target_appends_lines() {
+        let path = std::env::temp_dir().join(format!(
+            "sap_calm_access_log_test_{}.log",
+            std::process::id()
+        ));
+        let log = AccessLog::new(path.to_str());
+        assert!(log.is_enabled());
+
+        let entry = AccessLogEntry::new("get_task", Duration::ZERO, CallStatus::Ok, 0);
+        log.record(&entry);
+        log.record(&entry);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(content.lines().count(), 2);
+    }
+}
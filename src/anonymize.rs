@@ -0,0 +1,271 @@
+//! Anonymization of exported data.
+//!
+//! Exports passed `anonymize: true` can be shared with external consultants
+//! or kept as test fixtures: user names and email addresses become stable
+//! pseudonyms (the same person gets the same pseudonym throughout an export,
+//! so assignments stay comparable) and free-text fields are masked. Which
+//! fields count as identities and free text is configurable:
+//!
+//! ```json
+//! "anonymization": {
+//!   "identity_fields": ["assigneeName", "createdBy", "email"],
+//!   "text_fields": ["description", "comment"],
+//!   "mask": "[MASKED]",
+//!   "salt": "a secret of the exporting team"
+//! }
+//! ```
+//!
+//! Field names match case-insensitively, also as the end of a key (`email`
+//! matches `contactEmail`). Email addresses in other string fields are
+//! replaced as well.
+//!
+//! Without a configured `salt`, a random salt is generated when the server
+//! starts: pseudonyms are stable within one server process, but differ
+//! after a restart. Exports that must stay comparable across restarts need
+//! a configured salt.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Anonymization rules of exports.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AnonymizationConfig {
+    /// Fields holding user names, user IDs or emails; replaced by pseudonyms
+    #[serde(default = "default_identity_fields")]
+    pub identity_fields: Vec<String>,
+    /// Free-text fields; replaced by the mask
+    #[serde(default = "default_text_fields")]
+    pub text_fields: Vec<String>,
+    /// Replacement of masked free text
+    #[serde(default = "default_mask")]
+    pub mask: String,
+    /// Secret mixed into pseudonyms so they cannot be reversed by hashing
+    /// known names. Without it a random salt is used, so pseudonyms change
+    /// when the server restarts
+    pub salt: Option<String>,
+}
+
+impl Default for AnonymizationConfig {
+    fn default() -> Self {
+        Self {
+            identity_fields: default_identity_fields(),
+            text_fields: default_text_fields(),
+            mask: default_mask(),
+            salt: None,
+        }
+    }
+}
+
+fn default_identity_fields() -> Vec<String> {
+    [
+        "email",
+        "user",
+        "username",
+        "userid",
+        "createdby",
+        "changedby",
+        "modifiedby",
+        "assignee",
+        "assigneeid",
+        "assigneename",
+        "responsible",
+        "responsibleid",
+        "owner",
+        "author",
+    ]
+    .iter()
+    .map(|f| f.to_string())
+    .collect()
+}
+
+fn default_text_fields() -> Vec<String> {
+    [
+        "description",
+        "comment",
+        "content",
+        "text",
+        "note",
+        "body",
+        "message",
+    ]
+    .iter()
+    .map(|f| f.to_string())
+    .collect()
+}
+
+fn default_mask() -> String {
+    "[MASKED]".to_string()
+}
+
+/// Applies the anonymization rules to exported values.
+#[derive(Debug)]
+pub struct Anonymizer {
+    identity_fields: Vec<String>,
+    text_fields: Vec<String>,
+    mask: String,
+    salt: String,
+}
+
+impl Anonymizer {
+    pub fn new(config: &AnonymizationConfig) -> Self {
+        let lowercase = |fields: &[String]| fields.iter().map(|f| f.to_lowercase()).collect();
+        Self {
+            identity_fields: lowercase(&config.identity_fields),
+            text_fields: lowercase(&config.text_fields),
+            mask: config.mask.clone(),
+            salt: config
+                .salt
+                .clone()
+                .unwrap_or_else(|| process_salt().to_string()),
+        }
+    }
+
+    /// Stable pseudonym of a user name, user ID or email address. Emails
+    /// keep the shape of an email address.
+    pub fn pseudonym(&self, value: &str) -> String {
+        let normalized = value.trim().to_lowercase();
+        let hash = Sha256::digest(format!("{}\u{0}{}", self.salt, normalized).as_bytes());
+        let id: String = hash[..6].iter().map(|b| format!("{:02x}", b)).collect();
+        if looks_like_email(&normalized) {
+            format!("user-{}@example.invalid", id)
+        } else {
+            format!("user-{}", id)
+        }
+    }
+
+    /// Anonymize a value: identity fields at any depth get pseudonyms,
+    /// free-text fields are masked, emails elsewhere are replaced.
+    pub fn apply(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        let value = self.apply_field(&key, value);
+                        (key, value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.apply(v)).collect()),
+            Value::String(text) if looks_like_email(&text) => Value::String(self.pseudonym(&text)),
+            other => other,
+        }
+    }
+
+    fn apply_field(&self, key: &str, value: Value) -> Value {
+        let key = key.to_lowercase();
+        let matches = |fields: &[String]| fields.iter().any(|f| key.ends_with(f.as_str()));
+        match value {
+            Value::String(text) if text.trim().is_empty() => Value::String(text),
+            Value::String(text) if matches(&self.identity_fields) => {
+                Value::String(self.pseudonym(&text))
+            }
+            Value::String(_) if matches(&self.text_fields) => Value::String(self.mask.clone()),
+            other => self.apply(other),
+        }
+    }
+}
+
+/// Random salt of this server process, used when none is configured.
+fn process_salt() -> &'static str {
+    static SALT: OnceLock<String> = OnceLock::new();
+    SALT.get_or_init(|| {
+        let mut hasher = Sha256::new();
+        for _ in 0..4 {
+            // RandomState is seeded from the operating system's random source
+            hasher.update(RandomState::new().build_hasher().finish().to_le_bytes());
+        }
+        hasher.update(std::process::id().to_le_bytes());
+        hasher.update(
+            chrono::Utc::now()
+                .timestamp_nanos_opt()
+                .unwrap_or_default()
+                .to_le_bytes(),
+        );
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    })
+}
+
+fn looks_like_email(text: &str) -> bool {
+    let text = text.trim();
+    match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !text.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_pseudonymizes_and_masks() {
+        let anonymizer = Anonymizer::new(&AnonymizationConfig::default());
+        let record = json!({
+            "displayId": "6-12",
+            "assigneeName": "Jane Doe",
+            "createdBy": "JANE.DOE@example.com",
+            "contactEmail": "jane.doe@example.com",
+            "description": "Call Jane about the invoice run",
+            "note": "",
+            "effort": 3,
+            "comments": [{"author": "Jane Doe", "text": "done", "link": "ops@example.com"}]
+        });
+        let result = anonymizer.apply(record);
+
+        let jane = anonymizer.pseudonym("Jane Doe");
+        assert!(jane.starts_with("user-") && jane.len() == 17);
+        assert_eq!(result["displayId"], "6-12");
+        assert_eq!(result["assigneeName"], json!(jane));
+        assert_eq!(result["comments"][0]["author"], json!(jane));
+        // Emails are normalized, so the same address gets the same pseudonym
+        assert_eq!(result["createdBy"], result["contactEmail"]);
+        assert!(result["createdBy"]
+            .as_str()
+            .unwrap()
+            .ends_with("@example.invalid"));
+        assert_eq!(result["description"], "[MASKED]");
+        assert_eq!(result["comments"][0]["text"], "[MASKED]");
+        assert_eq!(
+            result["comments"][0]["link"],
+            json!(anonymizer.pseudonym("ops@example.com"))
+        );
+        assert_eq!(result["note"], "");
+        assert_eq!(result["effort"], 3);
+    }
+
+    #[test]
+    fn test_salt_changes_pseudonyms() {
+        let salted = Anonymizer::new(&AnonymizationConfig {
+            salt: Some("team secret".to_string()),
+            ..Default::default()
+        });
+        let unsalted = Anonymizer::new(&AnonymizationConfig::default());
+        assert_ne!(salted.pseudonym("Jane Doe"), unsalted.pseudonym("Jane Doe"));
+        // Without a configured salt the process salt is used, not an empty one
+        let empty = Anonymizer::new(&AnonymizationConfig {
+            salt: Some(String::new()),
+            ..Default::default()
+        });
+        assert_ne!(unsalted.pseudonym("Jane Doe"), empty.pseudonym("Jane Doe"));
+        assert_eq!(
+            unsalted.pseudonym("Jane Doe"),
+            Anonymizer::new(&AnonymizationConfig::default()).pseudonym("Jane Doe")
+        );
+        assert!(!looks_like_email("not an @ email"));
+    }
+}
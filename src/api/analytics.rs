@@ -1,9 +1,82 @@
 //! Analytics API client (OData v4) - CALM_ANALYTICS_ODATA.
 
-use serde_json::Value;
+use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::client::CalmApiClient;
 use crate::error::ApiError;
-use crate::odata::{ODataClient, ODataQuery};
+use crate::odata::{ODataClient, ODataCollection, ODataQuery, Probe};
+
+/// Record of the Tasks analytics provider.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRecord {
+    pub id: Option<String>,
+    pub display_id: Option<String>,
+    pub title: Option<String>,
+    #[serde(rename = "type")]
+    pub task_type: Option<String>,
+    pub status: Option<String>,
+    pub project_id: Option<String>,
+    pub assignee: Option<String>,
+    pub due_date: Option<String>,
+    pub created_at: Option<String>,
+    pub modified_at: Option<String>,
+    /// Fields without a typed counterpart
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Record of the Defects analytics provider.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefectRecord {
+    pub id: Option<String>,
+    pub display_id: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub severity: Option<String>,
+    pub project_id: Option<String>,
+    pub assignee: Option<String>,
+    pub created_at: Option<String>,
+    pub modified_at: Option<String>,
+    /// Fields without a typed counterpart
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Record of the Tests analytics provider (one test case execution).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestRecord {
+    pub test_case_id: Option<String>,
+    pub test_case_name: Option<String>,
+    pub test_plan_id: Option<String>,
+    pub test_plan_name: Option<String>,
+    pub project_id: Option<String>,
+    pub status: Option<String>,
+    pub executed_at: Option<String>,
+    /// Fields without a typed counterpart
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Record of the QualityGates analytics provider.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityGateRecord {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub project_id: Option<String>,
+    pub status: Option<String>,
+    pub planned_date: Option<String>,
+    pub actual_date: Option<String>,
+    /// Fields without a typed counterpart
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
 
 /// Analytics API client.
 #[derive(Clone)]
@@ -21,6 +94,11 @@ impl AnalyticsClient {
         Self { odata_client }
     }
 
+    /// Generic API access to the Analytics service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.odata_client
+    }
+
     /// Query a generic dataset by provider name.
     /// The provider is passed as a $filter parameter: provider eq 'ProviderName'
     pub async fn query_dataset(
@@ -29,6 +107,7 @@ impl AnalyticsClient {
         additional_filter: Option<String>,
         top: Option<u32>,
         skip: Option<u32>,
+        custom_params: Option<BTreeMap<String, String>>,
     ) -> Result<Value, ApiError> {
         let provider_filter = format!("provider eq '{}'", provider);
 
@@ -46,12 +125,34 @@ impl AnalyticsClient {
         if let Some(s) = skip {
             query = query.skip(s);
         }
+        if let Some(params) = custom_params {
+            query = query.custom_params(params)?;
+        }
 
         self.odata_client
             .get_collection_raw("/DataSet", Some(query))
             .await
     }
 
+    /// Probe a dataset: its record count and the latency of an empty page.
+    pub async fn probe_dataset(
+        &self,
+        provider: &str,
+        additional_filter: Option<String>,
+    ) -> Result<Probe, ApiError> {
+        let provider_filter = format!("provider eq '{}'", provider);
+        let full_filter = match additional_filter {
+            Some(existing) => format!("{} and {}", provider_filter, existing),
+            None => provider_filter,
+        };
+        self.odata_client.probe("/DataSet", Some(full_filter)).await
+    }
+
+    /// The `$metadata` document of the Analytics API.
+    pub async fn metadata(&self) -> Result<String, ApiError> {
+        self.odata_client.metadata().await
+    }
+
     /// Get requirements analytics.
     pub async fn get_requirements(&self, query: Option<ODataQuery>) -> Result<Value, ApiError> {
         self.odata_client
@@ -60,8 +161,11 @@ impl AnalyticsClient {
     }
 
     /// Get tasks analytics.
-    pub async fn get_tasks_analytics(&self, query: Option<ODataQuery>) -> Result<Value, ApiError> {
-        self.odata_client.get_collection_raw("/Tasks", query).await
+    pub async fn get_tasks_analytics(
+        &self,
+        query: Option<ODataQuery>,
+    ) -> Result<ODataCollection<TaskRecord>, ApiError> {
+        self.odata_client.get_collection("/Tasks", query).await
     }
 
     /// List available providers (static list based on available entity sets).
@@ -104,15 +208,34 @@ impl AnalyticsClient {
             .await
     }
 
+    /// Get defects analytics as typed records.
+    pub async fn get_defect_records(
+        &self,
+        query: Option<ODataQuery>,
+    ) -> Result<ODataCollection<DefectRecord>, ApiError> {
+        self.odata_client.get_collection("/Defects", query).await
+    }
+
     /// Get tests analytics.
     pub async fn get_tests(&self, query: Option<ODataQuery>) -> Result<Value, ApiError> {
         self.odata_client.get_collection_raw("/Tests", query).await
     }
 
+    /// Get tests analytics as typed records.
+    pub async fn get_test_records(
+        &self,
+        query: Option<ODataQuery>,
+    ) -> Result<ODataCollection<TestRecord>, ApiError> {
+        self.odata_client.get_collection("/Tests", query).await
+    }
+
     /// Get quality gates analytics.
-    pub async fn get_quality_gates(&self, query: Option<ODataQuery>) -> Result<Value, ApiError> {
+    pub async fn get_quality_gates(
+        &self,
+        query: Option<ODataQuery>,
+    ) -> Result<ODataCollection<QualityGateRecord>, ApiError> {
         self.odata_client
-            .get_collection_raw("/QualityGates", query)
+            .get_collection("/QualityGates", query)
             .await
     }
 
@@ -206,3 +329,56 @@ impl std::fmt::Debug for AnalyticsClient {
         f.debug_struct("AnalyticsClient").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records<T: serde::de::DeserializeOwned>(payload: &str) -> Vec<T> {
+        serde_json::from_str::<ODataCollection<T>>(payload)
+            .unwrap()
+            .value
+    }
+
+    #[test]
+    fn test_task_records_from_recorded_payload() {
+        let tasks: Vec<TaskRecord> = records(include_str!("../../fixtures/analytics/tasks.json"));
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].task_type.as_deref(), Some("CALMUS"));
+        assert_eq!(tasks[0].due_date.as_deref(), Some("2024-07-15"));
+        assert_eq!(tasks[0].other["sprintName"], "Sprint 7");
+        assert!(tasks[1].assignee.is_none());
+
+        // Unknown fields survive a round trip
+        let value = serde_json::to_value(&tasks[0]).unwrap();
+        assert_eq!(value["projectName"], "S/4HANA Rollout");
+        assert_eq!(value["type"], "CALMUS");
+    }
+
+    #[test]
+    fn test_defect_and_test_records_from_recorded_payloads() {
+        let collection: ODataCollection<DefectRecord> =
+            serde_json::from_str(include_str!("../../fixtures/analytics/defects.json")).unwrap();
+        assert_eq!(collection.count, Some(2));
+        assert_eq!(collection.value[0].severity.as_deref(), Some("HIGH"));
+        assert_eq!(collection.value[1].status.as_deref(), Some("CLOSED"));
+
+        let tests: Vec<TestRecord> = records(include_str!("../../fixtures/analytics/tests.json"));
+        assert_eq!(tests[0].status.as_deref(), Some("FAILED"));
+        assert_eq!(tests[0].other["defectCount"], 1);
+        assert_eq!(tests[1].test_plan_name, None);
+    }
+
+    #[test]
+    fn test_quality_gate_records_from_recorded_payload() {
+        let gates: Vec<QualityGateRecord> =
+            records(include_str!("../../fixtures/analytics/quality_gates.json"));
+        assert_eq!(
+            gates[0].name.as_deref(),
+            Some("Q-Gate 3: Realize to Deploy")
+        );
+        assert_eq!(gates[0].planned_date.as_deref(), Some("2024-08-01"));
+        assert!(gates[0].actual_date.is_none());
+        assert_eq!(gates[0].other["checklistItemsDone"], 7);
+    }
+}
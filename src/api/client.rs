@@ -0,0 +1,176 @@
+//! Backend-neutral access to SAP Cloud ALM APIs.
+//!
+//! Both the OData services and the REST services are reachable through
+//! [`CalmApiClient`], so features that apply to every backend can be written
+//! once against the trait instead of per client. The entity clients read and
+//! write single entities through the trait. Paths are relative to the service
+//! base URL and include the entity key where needed, e.g. `/Features(...)` or
+//! `/tasks/{id}`.
+
+use std::time::Instant;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::rest::RestClient;
+use crate::error::ApiError;
+use crate::odata::{ODataClient, ODataCollection, ODataQuery, Probe};
+
+/// Generic read/write access to one SAP Cloud ALM service.
+#[async_trait]
+pub trait CalmApiClient: Send + Sync {
+    /// Read a single resource.
+    async fn get(&self, path: &str) -> Result<Value, ApiError>;
+
+    /// Read a collection. Query parameters are passed as name/value pairs.
+    async fn list(&self, path: &str, query: &[(&str, String)]) -> Result<Vec<Value>, ApiError>;
+
+    /// Create a resource.
+    async fn create(&self, path: &str, body: &Value) -> Result<Value, ApiError>;
+
+    /// Partially update a resource.
+    async fn update(&self, path: &str, body: &Value) -> Result<Value, ApiError>;
+
+    /// Delete a resource.
+    async fn delete(&self, path: &str) -> Result<(), ApiError>;
+
+    /// Check that a collection is readable, with its size where the
+    /// service can count without transferring records.
+    async fn probe(&self, path: &str) -> Result<Probe, ApiError>;
+}
+
+/// Typed reads and writes on top of the JSON operations.
+impl dyn CalmApiClient + '_ {
+    /// Read a single resource as `T`.
+    pub async fn get_as<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        Ok(serde_json::from_value(self.get(path).await?)?)
+    }
+
+    /// Create a resource and return the created entity as `T`.
+    pub async fn create_as<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ApiError> {
+        let body = serde_json::to_value(body)?;
+        Ok(serde_json::from_value(self.create(path, &body).await?)?)
+    }
+
+    /// Partially update a resource and return the updated entity as `T`.
+    pub async fn update_as<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ApiError> {
+        let body = serde_json::to_value(body)?;
+        Ok(serde_json::from_value(self.update(path, &body).await?)?)
+    }
+}
+
+/// Build an OData query from name/value pairs. `$filter`, `$select`, `$apply`,
+/// `$top` and `$skip` use the query builder; all other pairs are passed as
+/// custom options.
+fn odata_query(pairs: &[(&str, String)]) -> Result<ODataQuery, ApiError> {
+    let mut query = ODataQuery::new();
+    for (name, value) in pairs {
+        let number = || {
+            value.parse::<u32>().map_err(|_| {
+                ApiError::InvalidQuery(format!("{} must be a number, got '{}'", name, value))
+            })
+        };
+        query = match *name {
+            "$filter" => query.filter(value.clone()),
+            "$select" => query.select(value.split(',').map(str::to_string).collect()),
+            "$apply" => query.apply(value.clone()),
+            "$top" => query.top(number()?),
+            "$skip" => query.skip(number()?),
+            _ => query.custom(*name, value.clone())?,
+        };
+    }
+    Ok(query)
+}
+
+#[async_trait]
+impl CalmApiClient for ODataClient {
+    async fn get(&self, path: &str) -> Result<Value, ApiError> {
+        self.get_entity(path).await
+    }
+
+    async fn list(&self, path: &str, query: &[(&str, String)]) -> Result<Vec<Value>, ApiError> {
+        let query = odata_query(query)?;
+        let collection: ODataCollection<Value> = self.get_collection(path, Some(query)).await?;
+        Ok(collection.value)
+    }
+
+    async fn create(&self, path: &str, body: &Value) -> Result<Value, ApiError> {
+        self.create_entity(path, body).await
+    }
+
+    async fn update(&self, path: &str, body: &Value) -> Result<Value, ApiError> {
+        self.update_entity(path, body).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), ApiError> {
+        self.delete_entity(path).await
+    }
+
+    async fn probe(&self, path: &str) -> Result<Probe, ApiError> {
+        ODataClient::probe(self, path, None).await
+    }
+}
+
+#[async_trait]
+impl CalmApiClient for RestClient {
+    async fn get(&self, path: &str) -> Result<Value, ApiError> {
+        RestClient::get(self, path, &[]).await
+    }
+
+    async fn list(&self, path: &str, query: &[(&str, String)]) -> Result<Vec<Value>, ApiError> {
+        RestClient::get(self, path, query).await
+    }
+
+    async fn create(&self, path: &str, body: &Value) -> Result<Value, ApiError> {
+        self.post(path, &[], body).await
+    }
+
+    async fn update(&self, path: &str, body: &Value) -> Result<Value, ApiError> {
+        self.patch(path, body).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), ApiError> {
+        RestClient::delete(self, path).await
+    }
+
+    /// REST services have no `$count`: the probe reads one record.
+    async fn probe(&self, path: &str) -> Result<Probe, ApiError> {
+        let started = Instant::now();
+        let _: Value = RestClient::get(self, path, &[("limit", "1".to_string())]).await?;
+        Ok(Probe {
+            count: None,
+            latency_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::odata::ODataVersion;
+
+    #[test]
+    fn test_odata_query_from_pairs() {
+        let query = odata_query(&[
+            ("$filter", "status eq 'OPEN'".to_string()),
+            ("$top", "10".to_string()),
+            ("sap-language", "DE".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$filter=status%20eq%20%27OPEN%27&$top=10&sap-language=DE"
+        );
+        assert!(odata_query(&[("$skip", "many".to_string())]).is_err());
+    }
+}
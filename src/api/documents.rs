@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::client::CalmApiClient;
+use super::lenient::{self, Entity, Extra};
 use crate::error::ApiError;
 use crate::odata::{ODataClient, ODataCollection, ODataQuery};
 
@@ -14,10 +16,13 @@ pub struct Document {
     pub display_id: Option<String>,
     pub title: Option<String>,
     pub content: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub status_code: Option<i32>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub priority_code: Option<i32>,
     #[serde(rename = "documentTypeCode")]
     pub type_code: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_string")]
     pub source_code: Option<String>,
     pub project_id: Option<String>,
     pub scope_id: Option<String>,
@@ -25,6 +30,14 @@ pub struct Document {
     pub created_at: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for Document {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Document type code.
@@ -74,6 +87,42 @@ pub struct UpdateDocumentRequest {
     pub type_code: Option<String>,
 }
 
+/// Step of the document sign-off flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalAction {
+    Submit,
+    Approve,
+    Reject,
+}
+
+impl ApprovalAction {
+    /// Status names a document must be in before the action can be applied.
+    pub fn allowed_from(self) -> &'static [&'static str] {
+        match self {
+            ApprovalAction::Submit => &["Draft", "In Progress", "Rejected"],
+            ApprovalAction::Approve | ApprovalAction::Reject => &["In Review"],
+        }
+    }
+
+    /// Status name the document is moved to.
+    pub fn target_status(self) -> &'static str {
+        match self {
+            ApprovalAction::Submit => "In Review",
+            ApprovalAction::Approve => "Approved",
+            ApprovalAction::Reject => "Rejected",
+        }
+    }
+
+    /// Label used for the note appended to the document content.
+    pub fn label(self) -> &'static str {
+        match self {
+            ApprovalAction::Submit => "Submitted for approval",
+            ApprovalAction::Approve => "Approved",
+            ApprovalAction::Reject => "Rejected",
+        }
+    }
+}
+
 /// Documents API client.
 #[derive(Clone)]
 pub struct DocumentsClient {
@@ -86,19 +135,33 @@ impl DocumentsClient {
         Self { odata_client }
     }
 
+    /// Generic API access to the Documents service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.odata_client
+    }
+
     /// List documents with optional OData query.
     pub async fn list_documents(
         &self,
         query: Option<ODataQuery>,
     ) -> Result<ODataCollection<Document>, ApiError> {
-        self.odata_client.get_collection("/Documents", query).await
+        self.odata_client
+            .get_collection("/Documents", query)
+            .await
+            .map(lenient::reconcile_collection)
+    }
+
+    /// Count documents matching an optional filter.
+    pub async fn count_documents(&self, filter: Option<String>) -> Result<i64, ApiError> {
+        self.odata_client.count("/Documents", filter).await
     }
 
     /// Get a single document by UUID.
     pub async fn get_document(&self, uuid: &str) -> Result<Document, ApiError> {
-        self.odata_client
-            .get_entity_by_uuid("/Documents", uuid)
+        self.api()
+            .get_as(&format!("/Documents/{}", uuid))
             .await
+            .map(lenient::reconcile)
     }
 
     /// Create a new document.
@@ -106,7 +169,7 @@ impl DocumentsClient {
         &self,
         request: &CreateDocumentRequest,
     ) -> Result<Document, ApiError> {
-        self.odata_client.create_entity("/Documents", request).await
+        self.api().create_as("/Documents", request).await
     }
 
     /// Update an existing document.
@@ -115,16 +178,14 @@ impl DocumentsClient {
         uuid: &str,
         request: &UpdateDocumentRequest,
     ) -> Result<Document, ApiError> {
-        self.odata_client
-            .update_entity_by_uuid("/Documents", uuid, request)
+        self.api()
+            .update_as(&format!("/Documents/{}", uuid), request)
             .await
     }
 
     /// Delete a document.
     pub async fn delete_document(&self, uuid: &str) -> Result<(), ApiError> {
-        self.odata_client
-            .delete_entity_by_uuid("/Documents", uuid)
-            .await
+        self.api().delete(&format!("/Documents/{}", uuid)).await
     }
 
     /// List document types.
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::client::CalmApiClient;
+use super::lenient::{self, Entity, Extra};
 use crate::error::ApiError;
 use crate::odata::{ODataClient, ODataCollection, ODataQuery};
 
@@ -15,7 +17,9 @@ pub struct Feature {
     pub title: Option<String>,
     pub description: Option<String>,
     pub project_id: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_string")]
     pub status_code: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub priority_code: Option<i32>,
     pub release_id: Option<String>,
     pub scope_id: Option<String>,
@@ -27,6 +31,14 @@ pub struct Feature {
     pub workstream_id: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for Feature {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// External reference entity.
@@ -110,19 +122,51 @@ impl FeaturesClient {
         Self { odata_client }
     }
 
+    /// Generic API access to the Features service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.odata_client
+    }
+
     /// List features with optional OData query.
     pub async fn list_features(
         &self,
         query: Option<ODataQuery>,
     ) -> Result<ODataCollection<Feature>, ApiError> {
-        self.odata_client.get_collection("/Features", query).await
+        self.odata_client
+            .get_collection("/Features", query)
+            .await
+            .map(lenient::reconcile_collection)
+    }
+
+    /// Count features matching an optional filter.
+    pub async fn count_features(&self, filter: Option<String>) -> Result<i64, ApiError> {
+        self.odata_client.count("/Features", filter).await
     }
 
     /// Get a single feature by UUID.
     pub async fn get_feature(&self, uuid: &str) -> Result<Feature, ApiError> {
-        self.odata_client
-            .get_entity_by_uuid("/Features", uuid)
+        self.api()
+            .get_as(&format!("/Features/{}", uuid))
             .await
+            .map(lenient::reconcile)
+    }
+
+    /// Get features by UUID, querying them in chunks of OR-combined filters.
+    /// UUIDs without a matching feature are missing from the result.
+    pub async fn get_features_by_uuids(&self, uuids: &[String]) -> Result<Vec<Feature>, ApiError> {
+        const CHUNK_SIZE: usize = 20;
+
+        let mut features = Vec::with_capacity(uuids.len());
+        for chunk in uuids.chunks(CHUNK_SIZE) {
+            let filter = chunk
+                .iter()
+                .map(|uuid| format!("uuid eq '{}'", uuid))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            let query = ODataQuery::new().filter(filter).top(chunk.len() as u32);
+            features.extend(self.list_features(Some(query)).await?.value);
+        }
+        Ok(features)
     }
 
     /// Get a single feature by display ID (e.g., "6-123").
@@ -192,7 +236,7 @@ impl FeaturesClient {
         &self,
         request: &CreateFeatureRequest,
     ) -> Result<Feature, ApiError> {
-        self.odata_client.create_entity("/Features", request).await
+        self.api().create_as("/Features", request).await
     }
 
     /// Update an existing feature.
@@ -201,16 +245,14 @@ impl FeaturesClient {
         uuid: &str,
         request: &UpdateFeatureRequest,
     ) -> Result<Feature, ApiError> {
-        self.odata_client
-            .update_entity_by_uuid("/Features", uuid, request)
+        self.api()
+            .update_as(&format!("/Features/{}", uuid), request)
             .await
     }
 
     /// Delete a feature.
     pub async fn delete_feature(&self, uuid: &str) -> Result<(), ApiError> {
-        self.odata_client
-            .delete_entity_by_uuid("/Features", uuid)
-            .await
+        self.api().delete(&format!("/Features/{}", uuid)).await
     }
 
     /// List external references with optional query.
@@ -228,9 +270,7 @@ impl FeaturesClient {
         &self,
         request: &CreateExternalReferenceRequest,
     ) -> Result<ExternalReference, ApiError> {
-        self.odata_client
-            .create_entity("/ExternalReferences", request)
-            .await
+        self.api().create_as("/ExternalReferences", request).await
     }
 
     /// Delete an external reference.
@@ -239,8 +279,9 @@ impl FeaturesClient {
         id: &str,
         parent_uuid: &str,
     ) -> Result<(), ApiError> {
-        let endpoint = format!("/ExternalReferences/{}/{}", id, parent_uuid);
-        self.odata_client.delete_entity_by_uuid(&endpoint, "").await
+        self.api()
+            .delete(&format!("/ExternalReferences/{}/{}", id, parent_uuid))
+            .await
     }
 
     /// List priority codes.
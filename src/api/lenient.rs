@@ -0,0 +1,164 @@
+//! Tolerant deserialization of API entities.
+//!
+//! Payloads of the SAP Cloud ALM services do not always match the documented
+//! shapes: code fields arrive as numbers in one tenant and as strings in
+//! another, and some keys differ from the documented names in case or
+//! underscores (`parent_ID`, `parentId`). Entity structs therefore
+//!
+//! - capture fields they don't declare in a flattened `extra` map, which is
+//!   serialized back at the top level so tool output keeps them,
+//! - accept numbers and strings for code fields (`opt_string`, `opt_i32`),
+//! - are passed through [`reconcile`], which moves captured keys that differ
+//!   from a declared field only in case or underscores into that field.
+
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::odata::ODataCollection;
+
+/// Fields of a payload that the entity struct does not declare.
+pub type Extra = Map<String, Value>;
+
+/// An entity struct with a flattened `extra` map.
+pub trait Entity: Serialize + DeserializeOwned {
+    fn extra(&self) -> &Extra;
+}
+
+/// Fold a key for case- and underscore-insensitive comparison.
+fn fold(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Move captured fields whose key differs from an empty declared field only
+/// in case or underscores into that field. Entities without captured fields
+/// are returned unchanged.
+pub fn reconcile<T: Entity>(entity: T) -> T {
+    if entity.extra().is_empty() {
+        return entity;
+    }
+    let Ok(Value::Object(mut object)) = serde_json::to_value(&entity) else {
+        return entity;
+    };
+    let empty_fields: Vec<String> = object
+        .iter()
+        .filter(|(key, value)| value.is_null() && !entity.extra().contains_key(*key))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let mut moved = false;
+    for key in entity.extra().keys() {
+        let folded = fold(key);
+        let Some(field) = empty_fields.iter().find(|f| fold(f) == folded) else {
+            continue;
+        };
+        if let Some(value) = object.remove(key) {
+            object.insert(field.clone(), value);
+            moved = true;
+        }
+    }
+    if !moved {
+        return entity;
+    }
+    serde_json::from_value(Value::Object(object)).unwrap_or(entity)
+}
+
+/// Reconcile every entity of a collection.
+pub fn reconcile_collection<T: Entity>(mut collection: ODataCollection<T>) -> ODataCollection<T> {
+    collection.value = collection.value.into_iter().map(reconcile).collect();
+    collection
+}
+
+/// Reconcile every entity of a list.
+pub fn reconcile_all<T: Entity>(entities: Vec<T>) -> Vec<T> {
+    entities.into_iter().map(reconcile).collect()
+}
+
+/// Deserialize an optional string that may arrive as a number or boolean.
+pub fn opt_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        Some(Value::Bool(b)) => Some(b.to_string()),
+        _ => None,
+    })
+}
+
+/// Deserialize an optional integer that may arrive as a numeric string.
+/// Other values are read as absent and logged with their raw value.
+pub fn opt_i32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i32>, D::Error> {
+    let value = Option::<Value>::deserialize(deserializer)?;
+    let parsed = match &value {
+        Some(Value::Number(n)) => n.as_i64().and_then(|n| i32::try_from(n).ok()),
+        Some(Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    };
+    if parsed.is_none() {
+        if let Some(raw) = value.filter(|v| v.as_str() != Some("")) {
+            tracing::warn!(value = %raw, "Ignoring non-integer value of an integer field");
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Sample {
+        uuid: Option<String>,
+        #[serde(rename = "parent_ID")]
+        parent_id: Option<String>,
+        #[serde(default, deserialize_with = "opt_string")]
+        status_code: Option<String>,
+        #[serde(default, deserialize_with = "opt_i32")]
+        priority_code: Option<i32>,
+        #[serde(flatten)]
+        extra: Extra,
+    }
+
+    impl Entity for Sample {
+        fn extra(&self) -> &Extra {
+            &self.extra
+        }
+    }
+
+    #[test]
+    fn test_codes_accept_numbers_and_strings() {
+        let sample: Sample =
+            serde_json::from_value(json!({"statusCode": 20, "priorityCode": "10"})).unwrap();
+        assert_eq!(sample.status_code.as_deref(), Some("20"));
+        assert_eq!(sample.priority_code, Some(10));
+
+        let sample: Sample =
+            serde_json::from_value(json!({"statusCode": "CIPDONE", "priorityCode": null})).unwrap();
+        assert_eq!(sample.status_code.as_deref(), Some("CIPDONE"));
+        assert_eq!(sample.priority_code, None);
+    }
+
+    #[test]
+    fn test_reconcile_matches_keys_and_keeps_unknown_fields() {
+        let sample: Sample = serde_json::from_value(json!({
+            "UUID": "u1",
+            "parentId": "p1",
+            "isReleased": true,
+        }))
+        .unwrap();
+        assert_eq!(sample.uuid, None);
+        assert_eq!(sample.extra.len(), 3);
+
+        let sample = reconcile(sample);
+        assert_eq!(sample.uuid.as_deref(), Some("u1"));
+        assert_eq!(sample.parent_id.as_deref(), Some("p1"));
+        assert_eq!(sample.extra.keys().collect::<Vec<_>>(), vec!["isReleased"]);
+
+        let output = serde_json::to_value(&sample).unwrap();
+        assert_eq!(output["isReleased"], json!(true));
+        assert_eq!(output["parent_ID"], json!("p1"));
+    }
+}
@@ -1,10 +1,19 @@
 //! Logs API client (REST) - CALM_LOGS.
 //! OpenTelemetry format for log records.
+//!
+//! Inbound payloads larger than the ingestion size limit are split into
+//! several POSTs: a JSON array of log entries by entries, an OpenTelemetry
+//! `resourceLogs` payload by log records, keeping each record's resource
+//! and scope.
 
-use reqwest::Client;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use reqwest::StatusCode;
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{Map, Value};
 
+use super::rest::RestClient;
 use crate::auth::OAuth2Client;
 use crate::error::ApiError;
 
@@ -38,14 +47,222 @@ pub struct PostLogsParams {
     pub tag: Option<String>,
 }
 
+impl GetLogsParams {
+    /// Query parameters of the outbound request (encoded by the HTTP client).
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("provider", self.provider.clone())];
+        let optional = [
+            ("format", &self.format),
+            ("version", &self.version),
+            ("period", &self.period),
+            ("from", &self.from),
+            ("to", &self.to),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                query.push((name, value.clone()));
+            }
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            query.push(("offset", offset.to_string()));
+        }
+        if let Some(service_id) = &self.service_id {
+            query.push(("logsFilters[serviceId]", service_id.clone()));
+        }
+        if let Some(observed) = self.observed_timestamp {
+            query.push(("observedTimestamp", observed.to_string()));
+        }
+        if let Some(on_limit) = &self.on_limit {
+            query.push(("onLimit", on_limit.clone()));
+        }
+        query
+    }
+}
+
+impl PostLogsParams {
+    /// Query parameters of the inbound request (encoded by the HTTP client).
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![
+            ("useCase", self.use_case.clone()),
+            ("serviceId", self.service_id.clone()),
+        ];
+        if let Some(version) = &self.version {
+            query.push(("version", version.clone()));
+        }
+        if let Some(dev) = self.dev {
+            query.push(("dev", dev.to_string()));
+        }
+        if let Some(tag) = &self.tag {
+            query.push(("tag", tag.clone()));
+        }
+        query
+    }
+}
+
+/// Default size limit of one inbound POST in bytes.
+pub const MAX_POST_BYTES: usize = 1024 * 1024;
+
+/// Result of one POST of a chunked inbound payload.
+#[derive(Debug)]
+pub struct LogChunk {
+    pub records: usize,
+    pub bytes: usize,
+    pub result: Result<Value, ApiError>,
+}
+
+fn json_len(value: &Value) -> usize {
+    serde_json::to_vec(value).map(|b| b.len()).unwrap_or(0)
+}
+
+/// Number of log records in an inbound payload.
+pub fn record_count(logs: &Value) -> usize {
+    match logs {
+        Value::Array(entries) => entries.len(),
+        Value::Object(payload) => payload
+            .get("resourceLogs")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.get("scopeLogs")?.as_array())
+            .flatten()
+            .filter_map(|s| s.get("logRecords")?.as_array())
+            .map(Vec::len)
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Split an inbound payload into chunks of at most `max_bytes` serialized
+/// bytes. A single record larger than the limit forms its own chunk;
+/// payloads of an unknown shape are not split.
+pub fn chunk_logs(logs: &Value, max_bytes: usize) -> Vec<Value> {
+    if json_len(logs) <= max_bytes {
+        return vec![logs.clone()];
+    }
+    let chunks = match logs {
+        Value::Array(entries) => Some(chunk_entries(entries, max_bytes)),
+        Value::Object(payload) => chunk_resource_logs(payload, max_bytes),
+        _ => None,
+    };
+    chunks.unwrap_or_else(|| vec![logs.clone()])
+}
+
+fn chunk_entries(entries: &[Value], max_bytes: usize) -> Vec<Value> {
+    let mut chunks = Vec::new();
+    let mut current = Vec::new();
+    let mut size = 2;
+    for entry in entries {
+        let len = json_len(entry) + 1;
+        if !current.is_empty() && size + len > max_bytes {
+            chunks.push(Value::Array(std::mem::take(&mut current)));
+            size = 2;
+        }
+        size += len;
+        current.push(entry.clone());
+    }
+    if !current.is_empty() {
+        chunks.push(Value::Array(current));
+    }
+    chunks
+}
+
+/// Copy of `object` with `key` set to an empty array.
+fn envelope(object: &Map<String, Value>, key: &str) -> Map<String, Value> {
+    let mut envelope = object.clone();
+    envelope.insert(key.to_string(), Value::Array(Vec::new()));
+    envelope
+}
+
+/// Records of a chunk grouped by resource and scope envelope.
+type ResourceGroup = (Map<String, Value>, Vec<(Map<String, Value>, Vec<Value>)>);
+
+fn chunk_resource_logs(payload: &Map<String, Value>, max_bytes: usize) -> Option<Vec<Value>> {
+    let payload_envelope = envelope(payload, "resourceLogs");
+    let base = json_len(&Value::Object(payload_envelope.clone()));
+    let build = |groups: Vec<ResourceGroup>| {
+        let resource_logs = groups
+            .into_iter()
+            .map(|(mut resource, scopes)| {
+                let scope_logs = scopes
+                    .into_iter()
+                    .map(|(mut scope, records)| {
+                        scope.insert("logRecords".to_string(), Value::Array(records));
+                        Value::Object(scope)
+                    })
+                    .collect();
+                resource.insert("scopeLogs".to_string(), Value::Array(scope_logs));
+                Value::Object(resource)
+            })
+            .collect();
+        let mut chunk = payload_envelope.clone();
+        chunk.insert("resourceLogs".to_string(), Value::Array(resource_logs));
+        Value::Object(chunk)
+    };
+
+    let mut chunks = Vec::new();
+    let mut current: Vec<ResourceGroup> = Vec::new();
+    let mut size = base;
+    for resource in payload.get("resourceLogs")?.as_array()? {
+        let resource = resource.as_object()?;
+        let resource_envelope = envelope(resource, "scopeLogs");
+        let resource_len = json_len(&Value::Object(resource_envelope.clone())) + 1;
+        let mut resource_open = false;
+        for scope in resource.get("scopeLogs")?.as_array()? {
+            let scope = scope.as_object()?;
+            let scope_envelope = envelope(scope, "logRecords");
+            let scope_len = json_len(&Value::Object(scope_envelope.clone())) + 1;
+            let mut scope_open = false;
+            for record in scope.get("logRecords")?.as_array()? {
+                let record_len = json_len(record) + 1;
+                let mut needed = record_len;
+                if !scope_open {
+                    needed += scope_len;
+                }
+                if !resource_open {
+                    needed += resource_len;
+                }
+                if !current.is_empty() && size + needed > max_bytes {
+                    chunks.push(build(std::mem::take(&mut current)));
+                    size = base;
+                    resource_open = false;
+                    scope_open = false;
+                    needed = record_len + scope_len + resource_len;
+                }
+                if !resource_open {
+                    current.push((resource_envelope.clone(), Vec::new()));
+                    resource_open = true;
+                }
+                let scopes = &mut current.last_mut()?.1;
+                if !scope_open {
+                    scopes.push((scope_envelope.clone(), Vec::new()));
+                    scope_open = true;
+                }
+                scopes.last_mut()?.1.push(record.clone());
+                size += needed;
+            }
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(build(current));
+    }
+    Some(chunks)
+}
+
+fn is_payload_too_large(error: &ApiError) -> bool {
+    matches!(
+        error,
+        ApiError::HttpError { status, .. } | ApiError::ODataError { status, .. }
+            if *status == StatusCode::PAYLOAD_TOO_LARGE
+    )
+}
+
 /// Logs API client.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct LogsClient {
-    base_url: String,
-    http_client: Client,
-    auth_client: OAuth2Client,
-    debug: bool,
-    is_sandbox: bool,
+    rest: RestClient,
 }
 
 impl LogsClient {
@@ -54,66 +271,20 @@ impl LogsClient {
     /// # Errors
     /// Returns `ApiError::HttpClientInit` if the HTTP client cannot be created.
     pub fn new(base_url: String, auth_client: OAuth2Client, debug: bool) -> Result<Self, ApiError> {
-        let is_sandbox = auth_client.is_sandbox();
-        let http_client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| ApiError::HttpClientInit(e.to_string()))?;
-
         Ok(Self {
-            base_url,
-            http_client,
-            auth_client,
-            debug,
-            is_sandbox,
+            rest: RestClient::new(
+                "Logs",
+                base_url,
+                auth_client,
+                debug,
+                Duration::from_secs(60),
+            )?,
         })
     }
 
-    /// Get the appropriate auth header name and value.
-    fn auth_header(&self, token: &str) -> (&'static str, String) {
-        if self.is_sandbox {
-            ("APIKey", token.to_string())
-        } else {
-            ("Authorization", format!("Bearer {}", token))
-        }
-    }
-
     /// Get logs (outbound).
     pub async fn get_logs(&self, params: &GetLogsParams) -> Result<Value, ApiError> {
-        let mut url = format!("{}/logs?provider={}", self.base_url, params.provider);
-
-        if let Some(ref f) = params.format {
-            url.push_str(&format!("&format={}", f));
-        }
-        if let Some(ref v) = params.version {
-            url.push_str(&format!("&version={}", v));
-        }
-        if let Some(ref p) = params.period {
-            url.push_str(&format!("&period={}", p));
-        }
-        if let Some(ref from) = params.from {
-            url.push_str(&format!("&from={}", from));
-        }
-        if let Some(ref to) = params.to {
-            url.push_str(&format!("&to={}", to));
-        }
-        if let Some(limit) = params.limit {
-            url.push_str(&format!("&limit={}", limit));
-        }
-        if let Some(offset) = params.offset {
-            url.push_str(&format!("&offset={}", offset));
-        }
-        if let Some(ref sid) = params.service_id {
-            url.push_str(&format!("&logsFilters[serviceId]={}", sid));
-        }
-        if let Some(ot) = params.observed_timestamp {
-            url.push_str(&format!("&observedTimestamp={}", ot));
-        }
-        if let Some(ref ol) = params.on_limit {
-            url.push_str(&format!("&onLimit={}", ol));
-        }
-
-        self.get(&url).await
+        self.rest.get("/logs", &params.query()).await
     }
 
     /// Post logs (inbound).
@@ -122,81 +293,100 @@ impl LogsClient {
         params: &PostLogsParams,
         logs: &Value,
     ) -> Result<Value, ApiError> {
-        let mut url = format!(
-            "{}/logs?useCase={}&serviceId={}",
-            self.base_url, params.use_case, params.service_id
-        );
+        self.rest.post("/logs", &params.query(), logs).await
+    }
 
-        if let Some(ref v) = params.version {
-            url.push_str(&format!("&version={}", v));
-        }
-        if let Some(d) = params.dev {
-            url.push_str(&format!("&dev={}", d));
-        }
-        if let Some(ref t) = params.tag {
-            url.push_str(&format!("&tag={}", urlencoding::encode(t)));
+    /// Post logs (inbound) in chunks of at most `max_bytes`. A chunk the
+    /// API still rejects as too large (413) is split in half and retried.
+    pub async fn post_logs_chunked(
+        &self,
+        params: &PostLogsParams,
+        logs: &Value,
+        max_bytes: usize,
+    ) -> Vec<LogChunk> {
+        let mut pending: VecDeque<Value> = chunk_logs(logs, max_bytes).into();
+        let mut results = Vec::new();
+        while let Some(chunk) = pending.pop_front() {
+            let bytes = json_len(&chunk);
+            let result = self.post_logs(params, &chunk).await;
+            if let Err(e) = &result {
+                let halves = chunk_logs(&chunk, bytes / 2);
+                if is_payload_too_large(e) && halves.len() > 1 {
+                    for half in halves.into_iter().rev() {
+                        pending.push_front(half);
+                    }
+                    continue;
+                }
+            }
+            results.push(LogChunk {
+                records: record_count(&chunk),
+                bytes,
+                result,
+            });
         }
-
-        self.post(&url, logs).await
+        results
     }
+}
 
-    async fn get(&self, url: &str) -> Result<Value, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Logs API GET request");
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .get(url)
-            .header(header_name, header_value)
-            .header("Accept", "application/json")
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
+    #[test]
+    fn test_get_logs_query_is_encoded() {
+        let params = GetLogsParams {
+            provider: "Cloud ALM".to_string(),
+            from: Some("2024-06-01T10:00:00+02:00".to_string()),
+            service_id: Some("svc 1".to_string()),
+            limit: Some(10),
+            ..Default::default()
+        };
+        let request = reqwest::Client::new()
+            .get("https://example.com/logs")
+            .query(&params.query())
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().query(),
+            Some("provider=Cloud+ALM&from=2024-06-01T10%3A00%3A00%2B02%3A00&limit=10&logsFilters%5BserviceId%5D=svc+1")
+        );
     }
 
-    async fn post(&self, url: &str, body: &Value) -> Result<Value, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Logs API POST request");
-        }
+    #[test]
+    fn test_chunk_logs_keeps_records_and_envelopes() {
+        let entries: Vec<Value> = (0..10)
+            .map(|i| json!({"body": format!("message {:02}", i)}))
+            .collect();
+        let array = Value::Array(entries.clone());
+        assert_eq!(chunk_logs(&array, MAX_POST_BYTES), vec![array.clone()]);
+        let chunks = chunk_logs(&array, 80);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| json_len(c) <= 80));
+        let rejoined: Vec<Value> = chunks
+            .iter()
+            .flat_map(|c| c.as_array().unwrap().clone())
+            .collect();
+        assert_eq!(rejoined, entries);
 
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .post(url)
-            .header(header_name, header_value)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await.unwrap_or(Value::Null))
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
+        let otel = json!({"resourceLogs": [
+            {"resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "billing"}}]},
+             "scopeLogs": [
+                {"scope": {"name": "a"}, "logRecords": entries[..6]},
+                {"scope": {"name": "b"}, "logRecords": entries[6..]}
+             ]}
+        ]});
+        assert_eq!(record_count(&otel), 10);
+        let chunks = chunk_logs(&otel, 300);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| json_len(c) <= 300));
+        assert_eq!(chunks.iter().map(record_count).sum::<usize>(), 10);
+        for chunk in &chunks {
+            let resource = &chunk["resourceLogs"][0];
+            assert_eq!(resource["resource"], otel["resourceLogs"][0]["resource"]);
+            assert!(resource["scopeLogs"][0]["scope"]["name"].is_string());
         }
-    }
-}
 
-impl std::fmt::Debug for LogsClient {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LogsClient")
-            .field("base_url", &self.base_url)
-            .finish()
+        assert_eq!(chunk_logs(&json!("text"), 1), vec![json!("text")]);
     }
 }
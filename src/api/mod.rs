@@ -1,21 +1,31 @@
 //! SAP Cloud ALM API clients.
 
+#[cfg(feature = "analytics")]
 pub mod analytics;
+pub mod client;
 pub mod documents;
 pub mod features;
+pub mod lenient;
+#[cfg(feature = "logs")]
 pub mod logs;
 pub mod processhierarchy;
+#[cfg(feature = "processmonitoring")]
 pub mod processmonitoring;
 pub mod projects;
+pub mod rest;
 pub mod tasks;
 pub mod testmanagement;
 
 // Re-export commonly used types
+#[cfg(feature = "analytics")]
 pub use analytics::AnalyticsClient;
+pub use client::CalmApiClient;
 pub use documents::DocumentsClient;
 pub use features::FeaturesClient;
+#[cfg(feature = "logs")]
 pub use logs::LogsClient;
 pub use processhierarchy::ProcessHierarchyClient;
+#[cfg(feature = "processmonitoring")]
 pub use processmonitoring::ProcessMonitoringClient;
 pub use projects::ProjectsClient;
 pub use tasks::TasksClient;
@@ -3,8 +3,11 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::client::CalmApiClient;
+use super::lenient::{self, Entity, Extra};
 use crate::error::ApiError;
 use crate::odata::{ODataClient, ODataCollection, ODataQuery};
+use crate::traversal::{Traversal, TraversalConfig, TraversalReport};
 
 /// Hierarchy Node entity.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -14,13 +17,23 @@ pub struct HierarchyNode {
     pub display_id: Option<String>,
     pub title: Option<String>,
     pub description: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub hierarchy_level: Option<i32>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub sequence: Option<i32>,
     pub parent_titles: Option<String>,
     pub parent_node_uuid: Option<String>,
     pub root_node_uuid: Option<String>,
     pub created_at: Option<String>,
     pub modified_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for HierarchyNode {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Request to create a hierarchy node.
@@ -64,6 +77,11 @@ impl ProcessHierarchyClient {
         Self { odata_client }
     }
 
+    /// Generic API access to the Process Hierarchy service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.odata_client
+    }
+
     /// Lists hierarchy nodes with optional OData query parameters.
     ///
     /// Hierarchy nodes represent the process structure in SAP Cloud ALM,
@@ -87,6 +105,7 @@ impl ProcessHierarchyClient {
         self.odata_client
             .get_collection("/HierarchyNodes", query)
             .await
+            .map(lenient::reconcile_collection)
     }
 
     /// Retrieves a single hierarchy node by its UUID.
@@ -103,9 +122,58 @@ impl ProcessHierarchyClient {
     ///
     /// Returns `ApiError` if the node is not found or request fails.
     pub async fn get_node(&self, uuid: &str) -> Result<HierarchyNode, ApiError> {
-        self.odata_client
-            .get_entity_by_uuid("/HierarchyNodes", uuid)
+        self.api()
+            .get_as(&format!("/HierarchyNodes/{}", uuid))
             .await
+            .map(lenient::reconcile)
+    }
+
+    /// Retrieves a node and its descendants.
+    ///
+    /// Children are fetched level by level using `parentNodeUuid` filters and
+    /// ordered by sequence, title and UUID. The walk stops at the configured
+    /// depth and node limits and visits each node only once.
+    ///
+    /// # Arguments
+    ///
+    /// * `root_uuid` - The unique identifier of the subtree root
+    /// * `limits` - Depth and node limits of the walk
+    ///
+    /// # Returns
+    ///
+    /// The root node followed by its descendants in breadth-first order, and
+    /// a report telling whether the limits cut the walk short.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError` if the root is not found or any request fails.
+    pub async fn get_subtree(
+        &self,
+        root_uuid: &str,
+        limits: &TraversalConfig,
+    ) -> Result<(Vec<HierarchyNode>, TraversalReport), ApiError> {
+        let root = self.get_node(root_uuid).await?;
+        let mut nodes = vec![root];
+        let mut traversal = Traversal::new(root_uuid, limits);
+
+        while let Some((parent, depth)) = traversal.next_node() {
+            let query = ODataQuery::new().filter(format!("parentNodeUuid eq '{}'", parent));
+            let mut children = self.list_nodes(Some(query)).await?.value;
+            children.sort_by(|a, b| {
+                (a.sequence, &a.title, &a.uuid).cmp(&(b.sequence, &b.title, &b.uuid))
+            });
+            for child in children {
+                if child
+                    .uuid
+                    .as_deref()
+                    .is_some_and(|uuid| traversal.discover(uuid, depth))
+                {
+                    nodes.push(child);
+                }
+            }
+        }
+
+        Ok((nodes, traversal.into_report()))
     }
 
     /// Retrieves a hierarchy node with expanded navigation properties.
@@ -152,9 +220,7 @@ impl ProcessHierarchyClient {
         &self,
         request: &CreateHierarchyNodeRequest,
     ) -> Result<HierarchyNode, ApiError> {
-        self.odata_client
-            .create_entity("/HierarchyNodes", request)
-            .await
+        self.api().create_as("/HierarchyNodes", request).await
     }
 
     /// Updates an existing hierarchy node.
@@ -176,8 +242,8 @@ impl ProcessHierarchyClient {
         uuid: &str,
         request: &UpdateHierarchyNodeRequest,
     ) -> Result<HierarchyNode, ApiError> {
-        self.odata_client
-            .update_entity_by_uuid("/HierarchyNodes", uuid, request)
+        self.api()
+            .update_as(&format!("/HierarchyNodes/{}", uuid), request)
             .await
     }
 
@@ -191,8 +257,8 @@ impl ProcessHierarchyClient {
     ///
     /// Returns `ApiError` if the node is not found or deletion fails.
     pub async fn delete_node(&self, uuid: &str) -> Result<(), ApiError> {
-        self.odata_client
-            .delete_entity_by_uuid("/HierarchyNodes", uuid)
+        self.api()
+            .delete(&format!("/HierarchyNodes/{}", uuid))
             .await
     }
 }
@@ -2,6 +2,7 @@
 
 use serde_json::Value;
 
+use super::client::CalmApiClient;
 use crate::error::ApiError;
 use crate::odata::{ODataClient, ODataQuery};
 
@@ -16,6 +17,11 @@ impl ProcessMonitoringClient {
         Self { odata_client }
     }
 
+    /// Generic API access to the Process Monitoring service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.odata_client
+    }
+
     /// List business processes.
     pub async fn list_business_processes(
         &self,
@@ -28,9 +34,7 @@ impl ProcessMonitoringClient {
 
     /// Get a business process by ID.
     pub async fn get_business_process(&self, id: &str) -> Result<Value, ApiError> {
-        self.odata_client
-            .get_entity_by_uuid::<Value>("/businessProcesses", id)
-            .await
+        self.api().get(&format!("/businessProcesses/{}", id)).await
     }
 
     /// List solution processes.
@@ -45,9 +49,7 @@ impl ProcessMonitoringClient {
 
     /// Get a solution process by ID.
     pub async fn get_solution_process(&self, id: &str) -> Result<Value, ApiError> {
-        self.odata_client
-            .get_entity_by_uuid::<Value>("/solutionProcesses", id)
-            .await
+        self.api().get(&format!("/solutionProcesses/{}", id)).await
     }
 
     /// List solution process flows.
@@ -1,9 +1,13 @@
 //! Projects API client (REST) - CALM_PJM.
 //! Note: This is a REST API, not OData.
 
-use reqwest::Client;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use super::client::CalmApiClient;
+use super::lenient::{self, Entity, Extra};
+use super::rest::RestClient;
 use crate::auth::OAuth2Client;
 use crate::error::ApiError;
 
@@ -20,6 +24,14 @@ pub struct Project {
     pub program_id: Option<String>,
     pub created_at: Option<String>,
     pub modified_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for Project {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Program entity.
@@ -41,6 +53,9 @@ pub struct Timebox {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
     pub status: Option<String>,
+    /// Timebox type (e.g. phase, sprint or milestone)
+    #[serde(rename = "type")]
+    pub timebox_type: Option<String>,
 }
 
 /// Team member entity.
@@ -67,13 +82,9 @@ pub struct CreateProjectRequest {
 }
 
 /// Projects API client.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ProjectsClient {
-    base_url: String,
-    http_client: Client,
-    auth_client: OAuth2Client,
-    debug: bool,
-    is_sandbox: bool,
+    rest: RestClient,
 }
 
 impl ProjectsClient {
@@ -82,40 +93,31 @@ impl ProjectsClient {
     /// # Errors
     /// Returns `ApiError::HttpClientInit` if the HTTP client cannot be created.
     pub fn new(base_url: String, auth_client: OAuth2Client, debug: bool) -> Result<Self, ApiError> {
-        let is_sandbox = auth_client.is_sandbox();
-        let http_client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(|e| ApiError::HttpClientInit(e.to_string()))?;
-
         Ok(Self {
-            base_url,
-            http_client,
-            auth_client,
-            debug,
-            is_sandbox,
+            rest: RestClient::new(
+                "Projects",
+                base_url,
+                auth_client,
+                debug,
+                Duration::from_secs(30),
+            )?,
         })
     }
 
-    /// Get the appropriate auth header name and value.
-    fn auth_header(&self, token: &str) -> (&'static str, String) {
-        if self.is_sandbox {
-            ("APIKey", token.to_string())
-        } else {
-            ("Authorization", format!("Bearer {}", token))
-        }
-    }
-
     /// List all projects.
     pub async fn list_projects(&self) -> Result<Vec<Project>, ApiError> {
-        let url = format!("{}/projects", self.base_url);
-        self.get(&url).await
+        self.rest
+            .get("/projects", &[])
+            .await
+            .map(lenient::reconcile_all)
     }
 
     /// Get a single project by ID.
     pub async fn get_project(&self, id: &str) -> Result<Project, ApiError> {
-        let url = format!("{}/projects/{}", self.base_url, id);
-        self.get(&url).await
+        self.api()
+            .get_as(&format!("/projects/{}", id))
+            .await
+            .map(lenient::reconcile)
     }
 
     /// Create a new project.
@@ -123,97 +125,35 @@ impl ProjectsClient {
         &self,
         request: &CreateProjectRequest,
     ) -> Result<Project, ApiError> {
-        let url = format!("{}/projects", self.base_url);
-        self.post(&url, request).await
+        self.api().create_as("/projects", request).await
     }
 
     /// List timeboxes (sprints) for a project.
     pub async fn list_timeboxes(&self, project_id: &str) -> Result<Vec<Timebox>, ApiError> {
-        let url = format!("{}/projects/{}/timeboxes", self.base_url, project_id);
-        self.get(&url).await
+        self.rest
+            .get(&format!("/projects/{}/timeboxes", project_id), &[])
+            .await
     }
 
     /// List team members for a project.
     pub async fn list_team_members(&self, project_id: &str) -> Result<Vec<TeamMember>, ApiError> {
-        let url = format!("{}/projects/{}/teams", self.base_url, project_id);
-        self.get(&url).await
+        self.rest
+            .get(&format!("/projects/{}/teams", project_id), &[])
+            .await
     }
 
     /// List all programs.
     pub async fn list_programs(&self) -> Result<Vec<Program>, ApiError> {
-        let url = format!("{}/programs", self.base_url);
-        self.get(&url).await
+        self.rest.get("/programs", &[]).await
     }
 
     /// Get a single program by ID.
     pub async fn get_program(&self, id: &str) -> Result<Program, ApiError> {
-        let url = format!("{}/programs/{}", self.base_url, id);
-        self.get(&url).await
-    }
-
-    /// Execute GET request.
-    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Projects API GET request");
-        }
-
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .get(url)
-            .header(header_name, header_value)
-            .header("Accept", "application/json")
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
+        self.rest.get(&format!("/programs/{}", id), &[]).await
     }
 
-    /// Execute POST request.
-    async fn post<T: serde::de::DeserializeOwned, B: Serialize>(
-        &self,
-        url: &str,
-        body: &B,
-    ) -> Result<T, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Projects API POST request");
-        }
-
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .post(url)
-            .header(header_name, header_value)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
-    }
-}
-
-impl std::fmt::Debug for ProjectsClient {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ProjectsClient")
-            .field("base_url", &self.base_url)
-            .finish()
+    /// Generic API access to the Projects service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.rest
     }
 }
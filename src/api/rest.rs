@@ -0,0 +1,158 @@
+//! Shared HTTP plumbing of the REST (non-OData) API clients.
+
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::auth::OAuth2Client;
+use crate::error::ApiError;
+
+/// REST client for SAP Cloud ALM APIs.
+#[derive(Clone)]
+pub struct RestClient {
+    name: &'static str,
+    base_url: String,
+    http_client: Client,
+    auth_client: OAuth2Client,
+    debug: bool,
+    is_sandbox: bool,
+}
+
+impl RestClient {
+    /// Create a new REST client. `name` identifies the API in debug logs.
+    ///
+    /// # Errors
+    /// Returns `ApiError::HttpClientInit` if the HTTP client cannot be created.
+    pub fn new(
+        name: &'static str,
+        base_url: String,
+        auth_client: OAuth2Client,
+        debug: bool,
+        timeout: Duration,
+    ) -> Result<Self, ApiError> {
+        let is_sandbox = auth_client.is_sandbox();
+        let http_client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| ApiError::HttpClientInit(e.to_string()))?;
+
+        Ok(Self {
+            name,
+            base_url,
+            http_client,
+            auth_client,
+            debug,
+            is_sandbox,
+        })
+    }
+
+    /// Get the appropriate auth header name and value.
+    fn auth_header(&self, token: &str) -> (&'static str, String) {
+        if self.is_sandbox {
+            ("APIKey", token.to_string())
+        } else {
+            ("Authorization", format!("Bearer {}", token))
+        }
+    }
+
+    /// Execute GET request with query parameters.
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        if self.debug {
+            tracing::debug!(url = %url, query = ?query, "{} API GET request", self.name);
+        }
+        let request = self.http_client.get(&url).query(query);
+        self.send(request).await
+    }
+
+    /// Execute POST request with query parameters.
+    pub async fn post<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        body: &B,
+    ) -> Result<T, ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        if self.debug {
+            tracing::debug!(url = %url, query = ?query, "{} API POST request", self.name);
+        }
+        let request = self
+            .http_client
+            .post(&url)
+            .query(query)
+            .header("Content-Type", "application/json")
+            .json(body);
+        self.send(request).await
+    }
+
+    /// Execute PATCH request.
+    pub async fn patch<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        if self.debug {
+            tracing::debug!(url = %url, "{} API PATCH request", self.name);
+        }
+        let request = self
+            .http_client
+            .patch(&url)
+            .header("Content-Type", "application/json")
+            .json(body);
+        self.send(request).await
+    }
+
+    /// Execute DELETE request.
+    pub async fn delete(&self, path: &str) -> Result<(), ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        if self.debug {
+            tracing::debug!(url = %url, "{} API DELETE request", self.name);
+        }
+        let _: serde::de::IgnoredAny = self.send(self.http_client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Authenticate and send a request, parsing the JSON response.
+    /// An empty response body is parsed as `null`.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ApiError> {
+        let token = self.auth_client.get_token().await?;
+        let (header_name, header_value) = self.auth_header(&token);
+
+        let response = request
+            .header(header_name, header_value)
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            self.auth_client.health().record_api_success();
+            let body = response.text().await?;
+            let body = if body.trim().is_empty() {
+                "null"
+            } else {
+                &body
+            };
+            Ok(serde_json::from_str(body)?)
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(ApiError::HttpError { status, body })
+        }
+    }
+}
+
+impl std::fmt::Debug for RestClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RestClient")
+            .field("name", &self.name)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
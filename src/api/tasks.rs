@@ -1,9 +1,13 @@
 //! Tasks API client (REST) - CALM_TKM.
 //! Note: This is a REST API, not OData.
 
-use reqwest::Client;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use super::client::CalmApiClient;
+use super::lenient::{self, Entity, Extra};
+use super::rest::RestClient;
 use crate::auth::OAuth2Client;
 use crate::error::ApiError;
 
@@ -21,6 +25,7 @@ pub struct Task {
     pub sub_status: Option<String>,
     pub external_id: Option<String>,
     pub due_date: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub priority_id: Option<i32>,
     pub assignee_id: Option<String>,
     pub assignee_name: Option<String>,
@@ -28,6 +33,14 @@ pub struct Task {
     pub timebox_start_date: Option<String>,
     pub timebox_end_date: Option<String>,
     pub last_changed_date: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for Task {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Task comment entity.
@@ -114,6 +127,16 @@ pub struct CreateTaskCommentRequest {
     pub content: String,
 }
 
+/// Request to create a task reference.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTaskReferenceRequest {
+    pub external_id: String,
+    pub external_system: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
 /// Query parameters for listing tasks.
 #[derive(Debug, Clone, Default)]
 pub struct ListTasksParams {
@@ -128,14 +151,39 @@ pub struct ListTasksParams {
     pub tags: Option<Vec<String>>,
 }
 
+impl ListTasksParams {
+    /// Query parameters of the list request (encoded by the HTTP client).
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("projectId", self.project_id.clone())];
+        if let Some(offset) = self.offset {
+            query.push(("offset", offset.to_string()));
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        let optional = [
+            ("type", &self.task_type),
+            ("status", &self.status),
+            ("subStatus", &self.sub_status),
+            ("assigneeId", &self.assignee_id),
+            ("lastChangedDate", &self.last_changed_date),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                query.push((name, value.clone()));
+            }
+        }
+        for tag in self.tags.iter().flatten() {
+            query.push(("tags", tag.clone()));
+        }
+        query
+    }
+}
+
 /// Tasks API client.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct TasksClient {
-    base_url: String,
-    http_client: Client,
-    auth_client: OAuth2Client,
-    debug: bool,
-    is_sandbox: bool,
+    rest: RestClient,
 }
 
 impl TasksClient {
@@ -144,74 +192,41 @@ impl TasksClient {
     /// # Errors
     /// Returns `ApiError::HttpClientInit` if the HTTP client cannot be created.
     pub fn new(base_url: String, auth_client: OAuth2Client, debug: bool) -> Result<Self, ApiError> {
-        let is_sandbox = auth_client.is_sandbox();
-        let http_client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(|e| ApiError::HttpClientInit(e.to_string()))?;
-
         Ok(Self {
-            base_url,
-            http_client,
-            auth_client,
-            debug,
-            is_sandbox,
+            rest: RestClient::new(
+                "Tasks",
+                base_url,
+                auth_client,
+                debug,
+                Duration::from_secs(30),
+            )?,
         })
     }
 
-    /// Get the appropriate auth header name and value.
-    fn auth_header(&self, token: &str) -> (&'static str, String) {
-        if self.is_sandbox {
-            ("APIKey", token.to_string())
-        } else {
-            ("Authorization", format!("Bearer {}", token))
-        }
+    /// Generic API access to the Tasks service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.rest
     }
 
     /// List tasks for a project.
     pub async fn list_tasks(&self, params: &ListTasksParams) -> Result<Vec<Task>, ApiError> {
-        let mut url = format!("{}/tasks?projectId={}", self.base_url, params.project_id);
-
-        if let Some(offset) = params.offset {
-            url.push_str(&format!("&offset={}", offset));
-        }
-        if let Some(limit) = params.limit {
-            url.push_str(&format!("&limit={}", limit));
-        }
-        if let Some(ref t) = params.task_type {
-            url.push_str(&format!("&type={}", t));
-        }
-        if let Some(ref s) = params.status {
-            url.push_str(&format!("&status={}", s));
-        }
-        if let Some(ref ss) = params.sub_status {
-            url.push_str(&format!("&subStatus={}", ss));
-        }
-        if let Some(ref a) = params.assignee_id {
-            url.push_str(&format!("&assigneeId={}", a));
-        }
-        if let Some(ref d) = params.last_changed_date {
-            url.push_str(&format!("&lastChangedDate={}", d));
-        }
-        if let Some(ref tags) = params.tags {
-            for tag in tags {
-                url.push_str(&format!("&tags={}", urlencoding::encode(tag)));
-            }
-        }
-
-        self.get(&url).await
+        self.rest
+            .get("/tasks", &params.query())
+            .await
+            .map(lenient::reconcile_all)
     }
 
     /// Get a single task by ID.
     pub async fn get_task(&self, id: &str) -> Result<Task, ApiError> {
-        let url = format!("{}/tasks/{}", self.base_url, id);
-        self.get(&url).await
+        self.api()
+            .get_as(&format!("/tasks/{}", id))
+            .await
+            .map(lenient::reconcile)
     }
 
     /// Create a new task.
     pub async fn create_task(&self, request: &CreateTaskRequest) -> Result<Task, ApiError> {
-        let url = format!("{}/tasks", self.base_url);
-        self.post(&url, request).await
+        self.api().create_as("/tasks", request).await
     }
 
     /// Update an existing task.
@@ -220,20 +235,21 @@ impl TasksClient {
         id: &str,
         request: &UpdateTaskRequest,
     ) -> Result<Task, ApiError> {
-        let url = format!("{}/tasks/{}", self.base_url, id);
-        self.patch(&url, request).await
+        self.api()
+            .update_as(&format!("/tasks/{}", id), request)
+            .await
     }
 
     /// Delete a task.
     pub async fn delete_task(&self, id: &str) -> Result<(), ApiError> {
-        let url = format!("{}/tasks/{}", self.base_url, id);
-        self.delete(&url).await
+        self.api().delete(&format!("/tasks/{}", id)).await
     }
 
     /// List comments for a task.
     pub async fn list_task_comments(&self, task_id: &str) -> Result<Vec<TaskComment>, ApiError> {
-        let url = format!("{}/tasks/{}/comments", self.base_url, task_id);
-        self.get(&url).await
+        self.rest
+            .get(&format!("/tasks/{}/comments", task_id), &[])
+            .await
     }
 
     /// Create a comment on a task.
@@ -242,8 +258,9 @@ impl TasksClient {
         task_id: &str,
         request: &CreateTaskCommentRequest,
     ) -> Result<TaskComment, ApiError> {
-        let url = format!("{}/tasks/{}/comments", self.base_url, task_id);
-        self.post(&url, request).await
+        self.api()
+            .create_as(&format!("/tasks/{}/comments", task_id), request)
+            .await
     }
 
     /// List references for a task.
@@ -251,142 +268,33 @@ impl TasksClient {
         &self,
         task_id: &str,
     ) -> Result<Vec<TaskReference>, ApiError> {
-        let url = format!("{}/tasks/{}/references", self.base_url, task_id);
-        self.get(&url).await
+        self.rest
+            .get(&format!("/tasks/{}/references", task_id), &[])
+            .await
+    }
+
+    /// Create a reference to an external object on a task.
+    pub async fn create_task_reference(
+        &self,
+        task_id: &str,
+        request: &CreateTaskReferenceRequest,
+    ) -> Result<TaskReference, ApiError> {
+        self.api()
+            .create_as(&format!("/tasks/{}/references", task_id), request)
+            .await
     }
 
     /// List workstreams for a project.
     pub async fn list_workstreams(&self, project_id: &str) -> Result<Vec<Workstream>, ApiError> {
-        let url = format!("{}/workstreams?projectId={}", self.base_url, project_id);
-        self.get(&url).await
+        self.rest
+            .get("/workstreams", &[("projectId", project_id.to_string())])
+            .await
     }
 
     /// List deliverables for a project.
     pub async fn list_deliverables(&self, project_id: &str) -> Result<Vec<Deliverable>, ApiError> {
-        let url = format!("{}/deliverables?projectId={}", self.base_url, project_id);
-        self.get(&url).await
-    }
-
-    /// Execute GET request.
-    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Tasks API GET request");
-        }
-
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .get(url)
-            .header(header_name, header_value)
-            .header("Accept", "application/json")
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
-    }
-
-    /// Execute POST request.
-    async fn post<T: serde::de::DeserializeOwned, B: Serialize>(
-        &self,
-        url: &str,
-        body: &B,
-    ) -> Result<T, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Tasks API POST request");
-        }
-
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .post(url)
-            .header(header_name, header_value)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
-    }
-
-    /// Execute PATCH request.
-    async fn patch<T: serde::de::DeserializeOwned, B: Serialize>(
-        &self,
-        url: &str,
-        body: &B,
-    ) -> Result<T, ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Tasks API PATCH request");
-        }
-
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .patch(url)
-            .header(header_name, header_value)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
-    }
-
-    /// Execute DELETE request.
-    async fn delete(&self, url: &str) -> Result<(), ApiError> {
-        if self.debug {
-            tracing::debug!(url = %url, "Tasks API DELETE request");
-        }
-
-        let token = self.auth_client.get_token().await?;
-        let (header_name, header_value) = self.auth_header(&token);
-
-        let response = self
-            .http_client
-            .delete(url)
-            .header(header_name, header_value)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() || status == reqwest::StatusCode::NO_CONTENT {
-            Ok(())
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ApiError::HttpError { status, body })
-        }
-    }
-}
-
-impl std::fmt::Debug for TasksClient {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("TasksClient")
-            .field("base_url", &self.base_url)
-            .finish()
+        self.rest
+            .get("/deliverables", &[("projectId", project_id.to_string())])
+            .await
     }
 }
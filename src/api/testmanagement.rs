@@ -2,8 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::client::CalmApiClient;
+use super::lenient::{self, Entity, Extra};
 use crate::error::ApiError;
-use crate::odata::{ODataClient, ODataCollection, ODataQuery};
+use crate::odata::{ODataClient, ODataCollection, ODataQuery, SortOrder};
 
 /// Manual Test Case entity.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -12,10 +14,19 @@ pub struct TestCase {
     pub uuid: Option<String>,
     pub title: Option<String>,
     pub description: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_string")]
     pub status_code: Option<String>,
     pub project_id: Option<String>,
     pub modified_at: Option<String>,
     pub created_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for TestCase {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Test Activity entity.
@@ -25,10 +36,19 @@ pub struct TestActivity {
     pub uuid: Option<String>,
     pub title: Option<String>,
     pub description: Option<String>,
+    #[serde(default, deserialize_with = "lenient::opt_i32")]
     pub sequence: Option<i32>,
     #[serde(rename = "parent_ID")]
     pub parent_id: Option<String>,
     pub modified_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for TestActivity {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Test Action entity.
@@ -44,6 +64,14 @@ pub struct TestAction {
     #[serde(rename = "parent_ID")]
     pub parent_id: Option<String>,
     pub modified_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Entity for TestAction {
+    fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 /// Request to create a test case.
@@ -99,6 +127,70 @@ pub struct CreateTestActionRequest {
     pub is_evidence_required: Option<bool>,
 }
 
+/// Request to update a test action.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTestActionRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_evidence_required: Option<bool>,
+}
+
+/// Compute new sequence numbers for the actions of an activity.
+///
+/// `order` lists action UUIDs in the desired order; actions not listed keep
+/// their relative order after the listed ones. Returns `(uuid, new_sequence)`
+/// for every action whose sequence changes, or an error naming a UUID that
+/// does not belong to the activity or is listed twice.
+pub fn plan_action_sequence(
+    actions: &[TestAction],
+    order: &[String],
+) -> Result<Vec<(String, i32)>, String> {
+    let known: Vec<&str> = actions.iter().filter_map(|a| a.uuid.as_deref()).collect();
+    if let Some(unknown) = order.iter().find(|u| !known.contains(&u.as_str())) {
+        return Err(format!(
+            "Action {} does not belong to this activity",
+            unknown
+        ));
+    }
+    if let Some(duplicate) = order
+        .iter()
+        .enumerate()
+        .find_map(|(index, uuid)| order[..index].contains(uuid).then_some(uuid))
+    {
+        return Err(format!("Action {} is listed more than once", duplicate));
+    }
+
+    let mut current: Vec<&TestAction> = actions.iter().filter(|a| a.uuid.is_some()).collect();
+    current.sort_by_key(|a| a.sequence.unwrap_or(i32::MAX));
+
+    let ordered = order.iter().map(String::as_str).chain(
+        current
+            .iter()
+            .filter_map(|a| a.uuid.as_deref())
+            .filter(|u| !order.iter().any(|o| o == u)),
+    );
+
+    let mut changes = Vec::new();
+    for (index, uuid) in ordered.enumerate() {
+        let sequence = index as i32 + 1;
+        let unchanged = current
+            .iter()
+            .any(|a| a.uuid.as_deref() == Some(uuid) && a.sequence == Some(sequence));
+        if !unchanged {
+            changes.push((uuid.to_string(), sequence));
+        }
+    }
+    Ok(changes)
+}
+
 /// Test Management API client.
 #[derive(Clone)]
 pub struct TestManagementClient {
@@ -115,6 +207,11 @@ impl TestManagementClient {
         Self { odata_client }
     }
 
+    /// Generic API access to the Test Management service.
+    pub fn api(&self) -> &dyn CalmApiClient {
+        &self.odata_client
+    }
+
     /// Lists manual test cases with optional OData query parameters.
     ///
     /// # Arguments
@@ -135,6 +232,20 @@ impl TestManagementClient {
         self.odata_client
             .get_collection("/ManualTestCases", query)
             .await
+            .map(lenient::reconcile_collection)
+    }
+
+    /// Counts test cases matching an optional filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Optional OData filter expression
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError` if the request fails or the service returns no count.
+    pub async fn count_testcases(&self, filter: Option<String>) -> Result<i64, ApiError> {
+        self.odata_client.count("/ManualTestCases", filter).await
     }
 
     /// Retrieves a single test case by its UUID.
@@ -151,9 +262,10 @@ impl TestManagementClient {
     ///
     /// Returns `ApiError` if the test case is not found or request fails.
     pub async fn get_testcase(&self, uuid: &str) -> Result<TestCase, ApiError> {
-        self.odata_client
-            .get_entity_by_uuid("/ManualTestCases", uuid)
+        self.api()
+            .get_as(&format!("/ManualTestCases/{}", uuid))
             .await
+            .map(lenient::reconcile)
     }
 
     /// Creates a new manual test case.
@@ -173,9 +285,7 @@ impl TestManagementClient {
         &self,
         request: &CreateTestCaseRequest,
     ) -> Result<TestCase, ApiError> {
-        self.odata_client
-            .create_entity("/ManualTestCases", request)
-            .await
+        self.api().create_as("/ManualTestCases", request).await
     }
 
     /// Updates an existing test case.
@@ -197,8 +307,8 @@ impl TestManagementClient {
         uuid: &str,
         request: &UpdateTestCaseRequest,
     ) -> Result<TestCase, ApiError> {
-        self.odata_client
-            .update_entity_by_uuid("/ManualTestCases", uuid, request)
+        self.api()
+            .update_as(&format!("/ManualTestCases/{}", uuid), request)
             .await
     }
 
@@ -212,8 +322,8 @@ impl TestManagementClient {
     ///
     /// Returns `ApiError` if the test case is not found or deletion fails.
     pub async fn delete_testcase(&self, uuid: &str) -> Result<(), ApiError> {
-        self.odata_client
-            .delete_entity_by_uuid("/ManualTestCases", uuid)
+        self.api()
+            .delete(&format!("/ManualTestCases/{}", uuid))
             .await
     }
 
@@ -236,7 +346,10 @@ impl TestManagementClient {
         &self,
         query: Option<ODataQuery>,
     ) -> Result<ODataCollection<TestActivity>, ApiError> {
-        self.odata_client.get_collection("/Activities", query).await
+        self.odata_client
+            .get_collection("/Activities", query)
+            .await
+            .map(lenient::reconcile_collection)
     }
 
     /// Creates a new test activity for a test case.
@@ -256,9 +369,19 @@ impl TestManagementClient {
         &self,
         request: &CreateTestActivityRequest,
     ) -> Result<TestActivity, ApiError> {
-        self.odata_client
-            .create_entity("/Activities", request)
+        self.api().create_as("/Activities", request).await
+    }
+
+    /// Retrieves a single test activity by its UUID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError` if the test activity is not found or request fails.
+    pub async fn get_activity(&self, uuid: &str) -> Result<TestActivity, ApiError> {
+        self.api()
+            .get_as(&format!("/Activities/{}", uuid))
             .await
+            .map(lenient::reconcile)
     }
 
     /// Lists test actions with optional OData query parameters.
@@ -280,7 +403,10 @@ impl TestManagementClient {
         &self,
         query: Option<ODataQuery>,
     ) -> Result<ODataCollection<TestAction>, ApiError> {
-        self.odata_client.get_collection("/Actions", query).await
+        self.odata_client
+            .get_collection("/Actions", query)
+            .await
+            .map(lenient::reconcile_collection)
     }
 
     /// Creates a new test action for a test activity.
@@ -300,7 +426,62 @@ impl TestManagementClient {
         &self,
         request: &CreateTestActionRequest,
     ) -> Result<TestAction, ApiError> {
-        self.odata_client.create_entity("/Actions", request).await
+        self.api().create_as("/Actions", request).await
+    }
+
+    /// Retrieves a single test action by its UUID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError` if the test action is not found or request fails.
+    pub async fn get_action(&self, uuid: &str) -> Result<TestAction, ApiError> {
+        self.api()
+            .get_as(&format!("/Actions/{}", uuid))
+            .await
+            .map(lenient::reconcile)
+    }
+
+    /// Lists the test actions of an activity ordered by sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `activity_uuid` - The unique identifier of the parent activity
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError` if the request fails or response parsing fails.
+    pub async fn list_actions_for_activity(
+        &self,
+        activity_uuid: &str,
+    ) -> Result<Vec<TestAction>, ApiError> {
+        let query = ODataQuery::new()
+            .filter(format!("parent_ID eq '{}'", activity_uuid))
+            .orderby("sequence", SortOrder::Asc);
+        Ok(self.list_actions(Some(query)).await?.value)
+    }
+
+    /// Updates an existing test action.
+    ///
+    /// # Arguments
+    ///
+    /// * `uuid` - The unique identifier of the test action to update
+    /// * `request` - The update request containing fields to modify
+    ///
+    /// # Returns
+    ///
+    /// The updated test action with new values applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError` if the test action is not found or update fails.
+    pub async fn update_action(
+        &self,
+        uuid: &str,
+        request: &UpdateTestActionRequest,
+    ) -> Result<TestAction, ApiError> {
+        self.api()
+            .update_as(&format!("/Actions/{}", uuid), request)
+            .await
     }
 }
 
@@ -309,3 +490,47 @@ impl std::fmt::Debug for TestManagementClient {
         f.debug_struct("TestManagementClient").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(uuid: &str, sequence: i32) -> TestAction {
+        TestAction {
+            uuid: Some(uuid.to_string()),
+            title: None,
+            description: None,
+            expected_result: None,
+            sequence: Some(sequence),
+            is_evidence_required: None,
+            parent_id: None,
+            modified_at: None,
+            extra: Extra::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan_action_sequence_moves_listed_first() {
+        let actions = vec![action("a", 1), action("b", 2), action("c", 3)];
+        let changes = plan_action_sequence(&actions, &["c".to_string()]).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                ("c".to_string(), 1),
+                ("a".to_string(), 2),
+                ("b".to_string(), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_action_sequence_skips_unchanged_and_rejects_unknown() {
+        let actions = vec![action("a", 1), action("b", 2)];
+        assert!(plan_action_sequence(&actions, &["a".to_string()])
+            .unwrap()
+            .is_empty());
+        assert!(plan_action_sequence(&actions, &["x".to_string()]).is_err());
+        let err = plan_action_sequence(&actions, &["b".to_string(), "b".to_string()]).unwrap_err();
+        assert!(err.contains("more than once"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Archive-instead-of-delete semantics of the delete tools.
+//!
+//! With `prefer_archive_over_delete`, the delete tools set an archive status
+//! instead of deleting. They fall back to a hard delete only when the tenant
+//! has no archive status; any other archive failure (network, authorization,
+//! validation) is returned unchanged, since a delete cannot be undone.
+
+use std::future::Future;
+
+use thiserror::Error;
+
+use crate::error::ApiError;
+
+/// Why an entity could not be archived.
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    /// The tenant has no status to archive the entity with.
+    #[error("{0}")]
+    NoArchiveStatus(String),
+
+    #[error(transparent)]
+    Api(#[from] ApiError),
+}
+
+/// How an entity was removed.
+#[derive(Debug, PartialEq)]
+pub enum Removal {
+    /// Set to the archive status with this name.
+    Archived(String),
+    /// Deleted; with the reason if archiving was preferred but unavailable.
+    Deleted { no_archive_status: Option<String> },
+}
+
+/// Archive the entity if `prefer_archive` is set, otherwise delete it.
+/// Deletes only if the archive status is missing; other archive errors are
+/// returned without deleting.
+pub async fn archive_or_delete<A, D>(
+    prefer_archive: bool,
+    archive: A,
+    delete: D,
+) -> Result<Removal, ApiError>
+where
+    A: Future<Output = Result<String, ArchiveError>>,
+    D: Future<Output = Result<(), ApiError>>,
+{
+    let mut no_archive_status = None;
+    if prefer_archive {
+        match archive.await {
+            Ok(status) => return Ok(Removal::Archived(status)),
+            Err(ArchiveError::NoArchiveStatus(reason)) => no_archive_status = Some(reason),
+            Err(ArchiveError::Api(e)) => return Err(e),
+        }
+    }
+    delete.await?;
+    Ok(Removal::Deleted { no_archive_status })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_failed_archive_does_not_delete() {
+        let deleted = AtomicBool::new(false);
+        let delete = async {
+            deleted.store(true, Ordering::SeqCst);
+            Ok(())
+        };
+        let archive = async { Err(ApiError::InvalidQuery("status not allowed".into()).into()) };
+        let result = archive_or_delete(true, archive, delete).await;
+        assert!(result.is_err());
+        assert!(!deleted.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_delete_without_archive_status() {
+        let archive = async { Err(ArchiveError::NoArchiveStatus("none".into())) };
+        let result = archive_or_delete(true, archive, async { Ok(()) }).await;
+        assert_eq!(
+            result.unwrap(),
+            Removal::Deleted {
+                no_archive_status: Some("none".into())
+            }
+        );
+
+        let archive = async { Ok("Obsolete".to_string()) };
+        let result = archive_or_delete(true, archive, async { Ok(()) }).await;
+        assert_eq!(result.unwrap(), Removal::Archived("Obsolete".into()));
+    }
+}
@@ -5,10 +5,14 @@ use chrono::{DateTime, Duration, Utc};
 use reqwest::Client;
 use serde::Deserialize;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::config::Config;
 use crate::error::AuthError;
+use crate::health::HealthState;
+
+/// Clock skew beyond which a warning is logged and token expiry is shortened.
+const CLOCK_SKEW_THRESHOLD_SECONDS: i64 = 30;
 
 /// OAuth2 token response from SAP.
 #[derive(Debug, Deserialize)]
@@ -17,7 +21,6 @@ struct TokenResponse {
     #[allow(dead_code)]
     token_type: String,
     expires_in: i64,
-    #[allow(dead_code)]
     #[serde(default)]
     scope: String,
 }
@@ -27,6 +30,8 @@ struct TokenResponse {
 struct CachedToken {
     access_token: String,
     expires_at: DateTime<Utc>,
+    /// Scopes granted with the token (space-separated `scope` of the response)
+    scopes: Vec<String>,
 }
 
 impl CachedToken {
@@ -43,6 +48,9 @@ pub struct OAuth2Client {
     config: Config,
     http_client: Client,
     token_cache: Arc<RwLock<Option<CachedToken>>>,
+    /// Serializes token refreshes so concurrent callers share one request.
+    refresh_lock: Arc<Mutex<()>>,
+    health: HealthState,
 }
 
 impl OAuth2Client {
@@ -60,6 +68,8 @@ impl OAuth2Client {
             config,
             http_client,
             token_cache: Arc::new(RwLock::new(None)),
+            refresh_lock: Arc::new(Mutex::new(())),
+            health: HealthState::new(),
         })
     }
 
@@ -72,24 +82,57 @@ impl OAuth2Client {
         }
 
         // Check cache first
-        {
-            let cache = self.token_cache.read().await;
-            if let Some(ref cached) = *cache {
-                if !cached.is_expired(self.config.token_buffer()) {
-                    return Ok(cached.access_token.clone());
-                }
-            }
+        if let Some(token) = self.cached_token().await {
+            return Ok(token);
+        }
+
+        // Single-flight refresh: only one caller fetches, the others wait for
+        // the lock and then pick up the freshly cached token.
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(token) = self.cached_token().await {
+            return Ok(token);
         }
 
         // Fetch new token
         self.fetch_token().await
     }
 
+    /// Return the cached token if it is still valid.
+    async fn cached_token(&self) -> Option<String> {
+        let cache = self.token_cache.read().await;
+        cache
+            .as_ref()
+            .filter(|cached| !cached.is_expired(self.config.token_buffer()))
+            .map(|cached| cached.access_token.clone())
+    }
+
     /// Check if running in sandbox mode.
     pub fn is_sandbox(&self) -> bool {
         self.config.sandbox
     }
 
+    /// Expiration time of the cached access token, if any.
+    #[cfg(feature = "http-transport")]
+    pub async fn token_expires_at(&self) -> Option<DateTime<Utc>> {
+        self.token_cache.read().await.as_ref().map(|t| t.expires_at)
+    }
+
+    /// Scopes granted to the client, as listed in the token response.
+    /// Empty in sandbox mode or if the token server does not list scopes.
+    pub async fn granted_scopes(&self) -> Result<Vec<String>, AuthError> {
+        if self.config.sandbox {
+            return Ok(Vec::new());
+        }
+        self.get_token().await?;
+        let cache = self.token_cache.read().await;
+        Ok(cache.as_ref().map(|t| t.scopes.clone()).unwrap_or_default())
+    }
+
+    /// Health timestamps shared by all API clients.
+    pub fn health(&self) -> &HealthState {
+        &self.health
+    }
+
     /// Fetch a new token from the OAuth2 token endpoint.
     async fn fetch_token(&self) -> Result<String, AuthError> {
         let token_url = self
@@ -134,13 +177,29 @@ impl OAuth2Client {
             return Err(AuthError::TokenRequestFailed { status, body });
         }
 
+        let server_date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+
         let token_response: TokenResponse = response
             .json()
             .await
             .map_err(|e| AuthError::TokenParse(format!("Failed to parse token response: {}", e)))?;
 
-        // Calculate expiration time
-        let expires_at = Utc::now() + Duration::seconds(token_response.expires_in);
+        // Calculate expiration time, accounting for clock skew against the token server
+        let now = Utc::now();
+        let skew = server_date.as_deref().and_then(|d| clock_skew(d, now));
+        if let Some(skew) = skew.filter(|s| s.num_seconds().abs() > CLOCK_SKEW_THRESHOLD_SECONDS) {
+            tracing::warn!(
+                skew_seconds = skew.num_seconds(),
+                "Local clock differs significantly from the token server; \
+                token expiry is shortened accordingly. Sync the system clock \
+                (NTP) if authentication errors persist."
+            );
+        }
+        let expires_at = token_expiry(now, token_response.expires_in, skew);
 
         if self.config.debug {
             tracing::debug!(
@@ -153,6 +212,11 @@ impl OAuth2Client {
         let cached = CachedToken {
             access_token: token_response.access_token.clone(),
             expires_at,
+            scopes: token_response
+                .scope
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
         };
 
         {
@@ -178,3 +242,100 @@ impl std::fmt::Debug for OAuth2Client {
         }
     }
 }
+
+/// Difference between the server's `Date` header and the local clock
+/// (positive when the server is ahead). Returns None if the header cannot be parsed.
+fn clock_skew(server_date: &str, local_now: DateTime<Utc>) -> Option<Duration> {
+    let server = DateTime::parse_from_rfc2822(server_date).ok()?;
+    Some(server.with_timezone(&Utc) - local_now)
+}
+
+/// Compute the local expiry time of a token.
+/// Significant skew shortens the lifetime by its magnitude so the token is
+/// refreshed before the server starts rejecting it.
+fn token_expiry(now: DateTime<Utc>, expires_in: i64, skew: Option<Duration>) -> DateTime<Utc> {
+    let skew_seconds = skew
+        .map(|s| s.num_seconds().abs())
+        .filter(|s| *s > CLOCK_SKEW_THRESHOLD_SECONDS)
+        .unwrap_or(0);
+    now + Duration::seconds((expires_in - skew_seconds).max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_skew_from_date_header() {
+        let now = DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let skew = clock_skew("Fri, 01 Mar 2024 12:02:00 GMT", now).unwrap();
+        assert_eq!(skew.num_seconds(), 120);
+        assert!(clock_skew("not a date", now).is_none());
+    }
+
+    #[test]
+    fn test_token_expiry_adjusts_for_significant_skew() {
+        let now = Utc::now();
+        assert_eq!(token_expiry(now, 3600, None), now + Duration::seconds(3600));
+        assert_eq!(
+            token_expiry(now, 3600, Some(Duration::seconds(10))),
+            now + Duration::seconds(3600)
+        );
+        assert_eq!(
+            token_expiry(now, 3600, Some(Duration::seconds(-600))),
+            now + Duration::seconds(3000)
+        );
+        assert_eq!(token_expiry(now, 60, Some(Duration::seconds(600))), now);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_get_token_fetches_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = stream.read(&mut buf).await;
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                    let body = r#"{"access_token":"tok","token_type":"bearer","expires_in":3600}"#;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "tenant": "mycompany",
+            "region": "eu10",
+            "client_id": "test-client",
+            "client_secret": "test-secret",
+            "token_url": format!("http://{}/oauth/token", addr),
+        }))
+        .unwrap();
+        let client = OAuth2Client::new(config).unwrap();
+
+        let mut calls = tokio::task::JoinSet::new();
+        for _ in 0..8 {
+            let client = client.clone();
+            calls.spawn(async move { client.get_token().await });
+        }
+        while let Some(token) = calls.join_next().await {
+            assert_eq!(token.unwrap().unwrap(), "tok");
+        }
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,88 @@
+//! Per-tool-call time budget.
+//!
+//! A call runs inside a budget scope. Tools that page through data or loop
+//! over items check [`exhausted`] between requests and return what they have
+//! with an explanatory note. A call still running [`GRACE`] after the budget
+//! ran out is aborted.
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Time granted after the budget to assemble partial results.
+pub const GRACE: Duration = Duration::from_secs(10);
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Run a tool call with a time budget. Returns `None` if the call was aborted.
+/// A call nested in another budget scope never outlives the enclosing budget.
+pub async fn run<F: Future>(budget: Duration, call: F) -> Option<F::Output> {
+    run_with_grace(budget, GRACE, call).await
+}
+
+async fn run_with_grace<F: Future>(
+    budget: Duration,
+    grace: Duration,
+    call: F,
+) -> Option<F::Output> {
+    let deadline = Instant::now() + budget;
+    let deadline = DEADLINE
+        .try_with(|outer| deadline.min(*outer))
+        .unwrap_or(deadline);
+    tokio::time::timeout_at(deadline + grace, DEADLINE.scope(deadline, call))
+        .await
+        .ok()
+}
+
+/// Check if the budget of the current tool call is used up.
+/// Always false outside a budget scope.
+pub fn exhausted() -> bool {
+    DEADLINE
+        .try_with(|deadline| Instant::now() >= *deadline)
+        .unwrap_or(false)
+}
+
+/// Note attached to results cut short by the budget.
+pub const PARTIAL_NOTE: &str = "Time budget of the tool call exhausted; results are partial.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_budget_marks_exhaustion_then_aborts() {
+        assert!(!exhausted());
+        let budget = Duration::from_millis(20);
+        let grace = Duration::from_millis(200);
+
+        let result = run_with_grace(budget, grace, async {
+            let before = exhausted();
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            (before, exhausted())
+        })
+        .await;
+        assert_eq!(result, Some((false, true)));
+
+        let aborted = run_with_grace(budget, grace, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        })
+        .await;
+        assert!(aborted.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_nested_budget_keeps_outer_deadline() {
+        let outer = Duration::from_millis(20);
+        let grace = Duration::from_millis(200);
+
+        let result = run_with_grace(outer, grace, async {
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            run_with_grace(Duration::from_secs(60), grace, async { exhausted() }).await
+        })
+        .await;
+        assert_eq!(result, Some(Some(true)));
+    }
+}
@@ -0,0 +1,137 @@
+//! iCalendar (RFC 5545) export of project schedules.
+//!
+//! Timeboxes, milestones and task due dates become all-day events, so the
+//! file can be imported into or subscribed from any calendar application.
+//! Event UIDs are derived from the SAP Cloud ALM object IDs; re-importing an
+//! updated export replaces the events instead of duplicating them.
+
+use chrono::{NaiveDate, Utc};
+
+/// Maximum line length in octets before folding.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// An all-day calendar event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    /// Unique ID, stable across exports
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: NaiveDate,
+    /// Last day of the event (inclusive)
+    pub end: NaiveDate,
+    pub category: &'static str,
+}
+
+/// Parse the date part of a date or timestamp (`2024-05-01`, `2024-05-01T08:00:00Z`).
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let date = value.get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Render events as an iCalendar file.
+pub fn to_ics(calendar_name: &str, events: &[CalendarEvent]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//sap-cloud-alm-mcp//Project Calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(calendar_name)),
+    ];
+    for event in events {
+        // DTEND of all-day events is exclusive
+        let end = event.end.max(event.start).succ_opt().unwrap_or(event.end);
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!(
+            "DTSTART;VALUE=DATE:{}",
+            event.start.format("%Y%m%d")
+        ));
+        lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        lines.push(format!("CATEGORIES:{}", escape(event.category)));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        ics.push_str(&fold(&line));
+        ics.push_str("\r\n");
+    }
+    ics
+}
+
+/// Escape a text value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ics_all_day_events() {
+        let events = vec![CalendarEvent {
+            uid: "timebox-1@sap-cloud-alm".to_string(),
+            summary: "Sprint 1; Build, Test".to_string(),
+            description: Some("Line 1\nLine 2".to_string()),
+            start: parse_date("2024-05-01T00:00:00Z").unwrap(),
+            end: parse_date("2024-05-14").unwrap(),
+            category: "Timebox",
+        }];
+        let ics = to_ics("Project X", &events);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240501\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20240515\r\n"));
+        assert!(ics.contains("SUMMARY:Sprint 1\\; Build\\, Test\r\n"));
+        assert!(ics.contains("DESCRIPTION:Line 1\\nLine 2\r\n"));
+        assert!(parse_date("n/a").is_none());
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let line = format!("SUMMARY:{}", "ä".repeat(60));
+        let folded = fold(&line);
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,170 @@
+//! SAP Solution Manager Change Request Management (ChaRM) IDs.
+//!
+//! Projects moving from Solution Manager to SAP Cloud ALM usually keep the
+//! ChaRM change document number in an external reference of the migrated
+//! feature. The numbers show up in many spellings: `8000001234`,
+//! `SMMJ 8000001234`, `ChaRM: 1234`, or inside a CRM Web UI link with
+//! `object_id=8000001234`. These helpers normalize them to the 10-digit
+//! document number so references can be matched regardless of spelling.
+
+/// Length of a CRM document number.
+const NUMBER_LENGTH: usize = 10;
+
+/// Words that mark the following number as a change document number.
+const MARKERS: &[&str] = &["charm", "cd", "solman"];
+
+/// Standard ChaRM transaction types (customer copies start with Y or Z).
+pub const TRANSACTION_TYPES: &[&str] = &["SMCR", "SMHF", "SMMJ", "SMAD", "SMGC", "SMTM"];
+
+/// A ChaRM change document number found in text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharmId {
+    /// Document number padded to 10 digits
+    pub number: String,
+    /// Transaction type preceding the number, if any
+    pub transaction_type: Option<String>,
+}
+
+/// Check if a token is a ChaRM transaction type (e.g. SMMJ or a ZMMJ copy).
+fn is_transaction_type(token: &str) -> bool {
+    let upper = token.to_uppercase();
+    TRANSACTION_TYPES.contains(&upper.as_str())
+        || (upper.len() == 4
+            && (upper.starts_with('Z') || upper.starts_with('Y'))
+            && TRANSACTION_TYPES.iter().any(|t| t[1..] == upper[1..]))
+}
+
+fn pad(digits: &str) -> Option<String> {
+    let trimmed = digits.trim_start_matches('0');
+    (!trimmed.is_empty() && trimmed.len() <= NUMBER_LENGTH)
+        .then(|| format!("{:0>width$}", trimmed, width = NUMBER_LENGTH))
+}
+
+/// Find change document numbers in text. A 10-digit number always counts;
+/// shorter numbers only when preceded by a transaction type or a ChaRM marker.
+pub fn find_charm_ids(text: &str) -> Vec<CharmId> {
+    let tokens: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut ids: Vec<CharmId> = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !token.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|p| tokens[p]);
+        let transaction_type = previous
+            .filter(|p| is_transaction_type(p))
+            .map(str::to_uppercase);
+        let marked = transaction_type.is_some()
+            || previous.is_some_and(|p| {
+                let lower = p.to_lowercase();
+                MARKERS.contains(&lower.as_str()) || lower == "object_id"
+            });
+        if token.len() != NUMBER_LENGTH && !marked {
+            continue;
+        }
+        if let Some(number) = pad(token) {
+            if !ids.iter().any(|id| id.number == number) {
+                ids.push(CharmId {
+                    number,
+                    transaction_type,
+                });
+            }
+        }
+    }
+    ids
+}
+
+/// Normalize a ChaRM ID given by a user. Bare numbers of any length are accepted.
+pub fn parse_charm_id(input: &str) -> Option<CharmId> {
+    let input = input.trim();
+    if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+        return pad(input).map(|number| CharmId {
+            number,
+            transaction_type: None,
+        });
+    }
+    find_charm_ids(input).into_iter().next()
+}
+
+/// Check if an external system name refers to Solution Manager.
+pub fn is_solman_system(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    ["solution manager", "solman", "charm"]
+        .iter()
+        .any(|n| lower.contains(n))
+}
+
+/// Change document numbers of an external reference (ID, name and URL).
+/// The ID of a Solution Manager reference counts as a number even when it
+/// is short and unmarked.
+pub fn reference_charm_ids(
+    id: Option<&str>,
+    name: Option<&str>,
+    url: Option<&str>,
+) -> Vec<CharmId> {
+    let mut ids = Vec::new();
+    if let (Some(id), Some(name)) = (id, name) {
+        if is_solman_system(name) {
+            ids.extend(parse_charm_id(id));
+        }
+    }
+    for text in [id, name, url].into_iter().flatten() {
+        for found in find_charm_ids(text) {
+            if !ids.iter().any(|i: &CharmId| i.number == found.number) {
+                ids.push(found);
+            }
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_charm_ids_in_reference_spellings() {
+        let ids = find_charm_ids("SMMJ 8000001234");
+        assert_eq!(
+            ids,
+            vec![CharmId {
+                number: "8000001234".to_string(),
+                transaction_type: Some("SMMJ".to_string()),
+            }]
+        );
+        assert_eq!(
+            find_charm_ids("zmhf-8000001234")[0]
+                .transaction_type
+                .as_deref(),
+            Some("ZMHF")
+        );
+        assert_eq!(find_charm_ids("ChaRM: 1234")[0].number, "0000001234");
+        assert_eq!(
+            find_charm_ids("https://solman.example.com/sap/bc/bsp/sap/crm_ui_start/default.htm?crm-object-type=AIC_OB_CMCR&crm-object-value=x&object_id=8000005678")[0].number,
+            "8000005678"
+        );
+        // Short numbers without a marker are not change documents
+        assert!(find_charm_ids("PROJ-1234").is_empty());
+        assert!(find_charm_ids("Release 2024").is_empty());
+    }
+
+    #[test]
+    fn test_parse_and_match_charm_id() {
+        let id = parse_charm_id("1234").unwrap();
+        assert_eq!(id.number, "0000001234");
+        let found = |id, name, url| reference_charm_ids(id, name, url);
+        assert_eq!(
+            found(Some("1234"), Some("SAP Solution Manager"), None)[0],
+            id
+        );
+        assert_eq!(found(Some("CD 0000001234"), None, None)[0], id);
+        assert!(found(Some("1234"), Some("Jira"), None).is_empty());
+
+        let typed = parse_charm_id("SMCR 8000000042").unwrap();
+        assert_eq!(typed.transaction_type.as_deref(), Some("SMCR"));
+        assert!(parse_charm_id("no number").is_none());
+        assert!(parse_charm_id("000").is_none());
+    }
+}
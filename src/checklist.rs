@@ -0,0 +1,318 @@
+//! "Ready for deployment" checklist of a feature.
+//!
+//! `check_feature_readiness` gathers the tasks, test runs, transports and
+//! documents related to a feature and evaluates each configured criterion
+//! to pass, fail or not evaluated, with the items it looked at as evidence.
+//! The criteria checked by default are configurable:
+//!
+//! ```json
+//! "feature_checklist": ["tasks_done", "tests_passed", "approvals_granted"]
+//! ```
+
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::reports::is_closed_status;
+
+/// A checklist criterion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    /// Every task related to the feature is done
+    TasksDone,
+    /// Test runs of the feature exist and all of them passed
+    TestsPassed,
+    /// At least one transport is assigned to the feature
+    TransportsAssigned,
+    /// Every document related to the feature is approved
+    ApprovalsGranted,
+}
+
+impl Criterion {
+    pub const ALL: [Criterion; 4] = [
+        Criterion::TasksDone,
+        Criterion::TestsPassed,
+        Criterion::TransportsAssigned,
+        Criterion::ApprovalsGranted,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Criterion::TasksDone => "tasks_done",
+            Criterion::TestsPassed => "tests_passed",
+            Criterion::TransportsAssigned => "transports_assigned",
+            Criterion::ApprovalsGranted => "approvals_granted",
+        }
+    }
+}
+
+impl FromStr for Criterion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Criterion::ALL
+            .into_iter()
+            .find(|c| c.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Criterion::ALL.iter().map(|c| c.name()).collect();
+                format!(
+                    "Unknown checklist criterion '{}'. Expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Parse criterion names; no names means all criteria.
+pub fn parse_criteria(names: &[String]) -> Result<Vec<Criterion>, String> {
+    if names.is_empty() {
+        return Ok(Criterion::ALL.to_vec());
+    }
+    let mut criteria = Vec::new();
+    for name in names {
+        let criterion: Criterion = name.parse()?;
+        if !criteria.contains(&criterion) {
+            criteria.push(criterion);
+        }
+    }
+    Ok(criteria)
+}
+
+/// Outcome of a criterion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    NotEvaluated,
+}
+
+/// An item a criterion was evaluated on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Evidence {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Whether this item satisfies the criterion
+    pub ok: bool,
+}
+
+impl Evidence {
+    pub fn new(kind: &'static str, id: impl Into<String>, status: Option<String>) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+            title: None,
+            status,
+            url: None,
+            ok: false,
+        }
+    }
+
+    pub fn title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
+    pub fn url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+}
+
+/// Result of one criterion.
+#[derive(Debug, Clone, Serialize)]
+pub struct CriterionResult {
+    pub criterion: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+    pub evidence: Vec<Evidence>,
+}
+
+impl CriterionResult {
+    /// A criterion whose data could not be read.
+    pub fn not_evaluated(criterion: Criterion, reason: impl Into<String>) -> Self {
+        Self {
+            criterion: criterion.name(),
+            outcome: Outcome::NotEvaluated,
+            detail: reason.into(),
+            evidence: Vec::new(),
+        }
+    }
+}
+
+/// Whether an external reference system denotes a transport (CTS, cTMS).
+pub fn is_transport_system(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("transport") || name == "cts" || name == "ctms"
+}
+
+/// Transports listed in fields of an entity whose key mentions transports,
+/// as IDs or objects with an ID.
+pub fn transports(fields: &Map<String, Value>) -> Vec<Evidence> {
+    let mut evidence = Vec::new();
+    for (key, value) in fields {
+        if !key.to_lowercase().contains("transport") {
+            continue;
+        }
+        let items = match value {
+            Value::Array(items) => items.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        for item in items {
+            let id = match item {
+                Value::String(id) => Some(id.clone()),
+                Value::Object(transport) => ["id", "transportId", "name"]
+                    .iter()
+                    .find_map(|k| transport.get(*k)?.as_str())
+                    .map(str::to_string),
+                _ => None,
+            };
+            let Some(id) = id.filter(|id| !id.is_empty()) else {
+                continue;
+            };
+            let status = item
+                .get("status")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let url = item.get("url").and_then(Value::as_str).map(str::to_string);
+            evidence.push(Evidence::new("transport", id, status).url(url));
+        }
+    }
+    evidence
+}
+
+/// Document statuses that count as approved.
+const APPROVED_STATUSES: &[&str] = &["approved", "released"];
+
+/// Evaluate a criterion on the related items. Tasks and documents pass
+/// when none are related; tests and transports need at least one item.
+pub fn evaluate(criterion: Criterion, mut evidence: Vec<Evidence>) -> CriterionResult {
+    for item in &mut evidence {
+        let status = item.status.as_deref().unwrap_or_default();
+        item.ok = match criterion {
+            Criterion::TasksDone => is_closed_status(status),
+            Criterion::TestsPassed => status == "passed",
+            Criterion::TransportsAssigned => true,
+            Criterion::ApprovalsGranted => {
+                let status = status.to_lowercase();
+                APPROVED_STATUSES.iter().any(|a| status.contains(a))
+            }
+        };
+    }
+    let open = evidence.iter().filter(|e| !e.ok).count();
+    let (outcome, detail) = match (criterion, evidence.len(), open) {
+        (Criterion::TasksDone, 0, _) => (Outcome::Pass, "No related tasks".to_string()),
+        (Criterion::ApprovalsGranted, 0, _) => (Outcome::Pass, "No related documents".to_string()),
+        (Criterion::TestsPassed, 0, _) => (Outcome::Fail, "No test runs found".to_string()),
+        (Criterion::TransportsAssigned, 0, _) => {
+            (Outcome::Fail, "No transport assigned".to_string())
+        }
+        (Criterion::TransportsAssigned, total, _) => {
+            (Outcome::Pass, format!("{} transport(s) assigned", total))
+        }
+        (_, total, 0) => (Outcome::Pass, format!("All {} item(s) satisfied", total)),
+        (Criterion::TasksDone, total, open) => (
+            Outcome::Fail,
+            format!("{} of {} task(s) not done", open, total),
+        ),
+        (Criterion::TestsPassed, total, open) => (
+            Outcome::Fail,
+            format!("{} of {} test run(s) not passed", open, total),
+        ),
+        (Criterion::ApprovalsGranted, total, open) => (
+            Outcome::Fail,
+            format!("{} of {} document(s) not approved", open, total),
+        ),
+    };
+    CriterionResult {
+        criterion: criterion.name(),
+        outcome,
+        detail,
+        evidence,
+    }
+}
+
+/// Overall outcome: fail if any criterion failed, not evaluated if any
+/// could not be evaluated, pass otherwise.
+pub fn overall(results: &[CriterionResult]) -> Outcome {
+    let any = |outcome| results.iter().any(|r| r.outcome == outcome);
+    if any(Outcome::Fail) {
+        Outcome::Fail
+    } else if any(Outcome::NotEvaluated) {
+        Outcome::NotEvaluated
+    } else {
+        Outcome::Pass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_criteria() {
+        assert_eq!(parse_criteria(&[]).unwrap().len(), 4);
+        let names = vec!["Tests_Passed".to_string(), "tests_passed".to_string()];
+        assert_eq!(
+            parse_criteria(&names).unwrap(),
+            vec![Criterion::TestsPassed]
+        );
+        assert!(parse_criteria(&["signed_off".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_criteria() {
+        let task = |id: &str, status: &str| Evidence::new("task", id, Some(status.to_string()));
+        let tasks = evaluate(
+            Criterion::TasksDone,
+            vec![task("t1", "CIPDONE"), task("t2", "CIPINPROGRESS")],
+        );
+        assert_eq!(tasks.outcome, Outcome::Fail);
+        assert_eq!(tasks.detail, "1 of 2 task(s) not done");
+        assert!(tasks.evidence[0].ok && !tasks.evidence[1].ok);
+
+        assert_eq!(
+            evaluate(Criterion::TasksDone, vec![]).outcome,
+            Outcome::Pass
+        );
+        assert_eq!(
+            evaluate(Criterion::TestsPassed, vec![]).outcome,
+            Outcome::Fail
+        );
+        let runs = vec![Evidence::new("test_run", "r1", Some("passed".to_string()))];
+        let tests = evaluate(Criterion::TestsPassed, runs);
+        assert_eq!(tests.outcome, Outcome::Pass);
+
+        let documents = vec![
+            Evidence::new("document", "d1", Some("Approved".to_string())),
+            Evidence::new("document", "d2", Some("In Review".to_string())),
+        ];
+        let approvals = evaluate(Criterion::ApprovalsGranted, documents);
+        assert_eq!(approvals.outcome, Outcome::Fail);
+
+        let results = [
+            tests,
+            CriterionResult::not_evaluated(Criterion::TransportsAssigned, "unavailable"),
+        ];
+        assert_eq!(overall(&results), Outcome::NotEvaluated);
+        assert_eq!(overall(&[approvals]), Outcome::Fail);
+
+        let fields = serde_json::json!({
+            "transports": [{"transportId": "S4DK900123", "status": "released"}, "S4DK900124"],
+            "title": "not a transport"
+        });
+        let found = transports(fields.as_object().unwrap());
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id, "S4DK900123");
+        assert!(is_transport_system("cTMS") && !is_transport_system("Jira"));
+    }
+}
@@ -0,0 +1,397 @@
+//! API compatibility check against recorded response fixtures.
+//!
+//! `--check-api-compatibility` replays the response payloads recorded under
+//! `fixtures/` against the serde models the server deserializes them into,
+//! so a model change that no longer accepts a recorded payload is reported
+//! before it reaches a tenant. With `--live`, the service's `$metadata` is
+//! fetched as well and compared with the models and fixtures: removed and
+//! new fields, and changed types that would break deserialization. The
+//! process exits with status 1 if breaking drift was found.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::api::analytics::{DefectRecord, QualityGateRecord, TaskRecord, TestRecord};
+use crate::metadata;
+
+/// Recorded payload of an analytics provider and the model it is read into.
+struct Fixture {
+    provider: &'static str,
+    path: &'static str,
+    payload: &'static str,
+    replay: fn(&str) -> Result<Replay, String>,
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        provider: "Tasks",
+        path: "fixtures/analytics/tasks.json",
+        payload: include_str!("../fixtures/analytics/tasks.json"),
+        replay: replay::<TaskRecord>,
+    },
+    Fixture {
+        provider: "Defects",
+        path: "fixtures/analytics/defects.json",
+        payload: include_str!("../fixtures/analytics/defects.json"),
+        replay: replay::<DefectRecord>,
+    },
+    Fixture {
+        provider: "Tests",
+        path: "fixtures/analytics/tests.json",
+        payload: include_str!("../fixtures/analytics/tests.json"),
+        replay: replay::<TestRecord>,
+    },
+    Fixture {
+        provider: "QualityGates",
+        path: "fixtures/analytics/quality_gates.json",
+        payload: include_str!("../fixtures/analytics/quality_gates.json"),
+        replay: replay::<QualityGateRecord>,
+    },
+];
+
+/// Severity of a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Breaking,
+}
+
+/// A compatibility finding for one provider.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub provider: String,
+    pub detail: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, provider: &str, detail: impl Into<String>) -> Self {
+        Self {
+            severity,
+            provider: provider.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Outcome of replaying a fixture against its model.
+#[derive(Debug, Default)]
+struct Replay {
+    records: usize,
+    /// Typed fields of the model
+    model_fields: BTreeSet<String>,
+    /// Recorded fields and the JSON kinds of their non-null values
+    recorded_fields: BTreeMap<String, BTreeSet<&'static str>>,
+    /// Records the model rejects
+    errors: Vec<String>,
+}
+
+fn json_kind(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some("boolean"),
+        Value::Number(_) => Some("number"),
+        Value::String(_) => Some("string"),
+        Value::Array(_) => Some("array"),
+        Value::Object(_) => Some("object"),
+    }
+}
+
+/// Typed fields of a model: the keys of a record deserialized from `{}`.
+/// Every model field is optional and fields without a typed counterpart
+/// go to an empty flattened map.
+fn model_fields<T: DeserializeOwned + Serialize>() -> Result<BTreeSet<String>, String> {
+    let empty: T = serde_json::from_value(json!({})).map_err(|e| e.to_string())?;
+    match serde_json::to_value(empty).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map.into_iter().map(|(k, _)| k).collect()),
+        _ => Err("The model does not serialize to an object".to_string()),
+    }
+}
+
+fn replay<T: DeserializeOwned + Serialize>(payload: &str) -> Result<Replay, String> {
+    let collection: Value =
+        serde_json::from_str(payload).map_err(|e| format!("Invalid fixture: {}", e))?;
+    let records = collection
+        .get("value")
+        .and_then(Value::as_array)
+        .ok_or("The fixture has no value array")?;
+
+    let mut replay = Replay {
+        records: records.len(),
+        model_fields: model_fields::<T>()?,
+        ..Default::default()
+    };
+    for (index, record) in records.iter().enumerate() {
+        if let Err(e) = serde_json::from_value::<T>(record.clone()) {
+            replay.errors.push(format!("record {}: {}", index + 1, e));
+        }
+        for (field, value) in record.as_object().into_iter().flatten() {
+            let kinds = replay.recorded_fields.entry(field.clone()).or_default();
+            kinds.extend(json_kind(value));
+        }
+    }
+    Ok(replay)
+}
+
+/// JSON kinds an EDM type is serialized as. 64-bit integers and decimals
+/// may be strings (IEEE754Compatible).
+fn edm_kinds(edm_type: &str) -> &'static [&'static str] {
+    match edm_type {
+        "Edm.Boolean" => &["boolean"],
+        "Edm.Byte" | "Edm.SByte" | "Edm.Int16" | "Edm.Int32" | "Edm.Double" | "Edm.Single" => {
+            &["number"]
+        }
+        "Edm.Int64" | "Edm.Decimal" => &["number", "string"],
+        t if t.starts_with("Collection(") => &["array"],
+        t if t.starts_with("Edm.") => &["string"],
+        // Complex and enumeration types
+        _ => &["object", "string"],
+    }
+}
+
+/// Entity type of a provider in `$metadata`: same name, or its singular,
+/// ignoring case.
+fn entity_type<'a>(
+    entity_types: &'a HashMap<String, Vec<(String, String)>>,
+    provider: &str,
+) -> Option<&'a Vec<(String, String)>> {
+    let provider = provider.to_lowercase();
+    let singular = provider.strip_suffix('s').unwrap_or(&provider);
+    entity_types
+        .iter()
+        .find(|(name, _)| {
+            let name = name.to_lowercase();
+            name == provider || name == singular
+        })
+        .map(|(_, properties)| properties)
+}
+
+/// Findings of a fixture replay, compared with the live `$metadata` when
+/// given.
+fn check_fixture(
+    provider: &str,
+    path: &str,
+    replay: &Replay,
+    live: Option<&HashMap<String, Vec<(String, String)>>>,
+) -> Vec<Finding> {
+    use Severity::*;
+
+    let mut findings: Vec<Finding> = replay
+        .errors
+        .iter()
+        .map(|e| Finding::new(Breaking, provider, format!("{} {}", path, e)))
+        .collect();
+    let unmodeled: Vec<&str> = replay
+        .recorded_fields
+        .keys()
+        .filter(|f| !f.starts_with('@') && !replay.model_fields.contains(*f))
+        .map(String::as_str)
+        .collect();
+    if !unmodeled.is_empty() {
+        findings.push(Finding::new(
+            Info,
+            provider,
+            format!("Recorded fields kept untyped: {}", unmodeled.join(", ")),
+        ));
+    }
+    let unrecorded: Vec<&str> = replay
+        .model_fields
+        .iter()
+        .filter(|f| !replay.recorded_fields.contains_key(*f))
+        .map(String::as_str)
+        .collect();
+    if !unrecorded.is_empty() {
+        findings.push(Finding::new(
+            Warning,
+            provider,
+            format!(
+                "Model fields missing from {}: {}",
+                path,
+                unrecorded.join(", ")
+            ),
+        ));
+    }
+
+    let Some(entity_types) = live else {
+        return findings;
+    };
+    let Some(properties) = entity_type(entity_types, provider) else {
+        findings.push(Finding::new(
+            Warning,
+            provider,
+            "No entity type of this provider in the live $metadata",
+        ));
+        return findings;
+    };
+    let live_types: HashMap<&str, &str> = properties
+        .iter()
+        .map(|(name, edm_type)| (name.as_str(), edm_type.as_str()))
+        .collect();
+
+    let removed: Vec<&str> = replay
+        .model_fields
+        .iter()
+        .filter(|f| !live_types.contains_key(f.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !removed.is_empty() {
+        findings.push(Finding::new(
+            Warning,
+            provider,
+            format!(
+                "Model fields no longer in $metadata (always empty): {}",
+                removed.join(", ")
+            ),
+        ));
+    }
+    let added: Vec<&str> = properties
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|f| !replay.model_fields.contains(*f) && !replay.recorded_fields.contains_key(*f))
+        .collect();
+    if !added.is_empty() {
+        findings.push(Finding::new(
+            Info,
+            provider,
+            format!("New fields in $metadata: {}", added.join(", ")),
+        ));
+    }
+    for field in &replay.model_fields {
+        let (Some(edm_type), Some(kinds)) = (
+            live_types.get(field.as_str()),
+            replay.recorded_fields.get(field),
+        ) else {
+            continue;
+        };
+        let expected = edm_kinds(edm_type);
+        if let Some(kind) = kinds.iter().find(|k| !expected.contains(k)) {
+            findings.push(Finding::new(
+                Breaking,
+                provider,
+                format!("{} is now {} but was recorded as {}", field, edm_type, kind),
+            ));
+        }
+    }
+    findings
+}
+
+/// Replay all fixtures, comparing them with the live `$metadata` document
+/// of the Analytics API when given.
+pub fn check(live_metadata: Option<&str>) -> Vec<Finding> {
+    let live = live_metadata.map(metadata::entity_properties);
+    let mut findings = Vec::new();
+    for fixture in FIXTURES {
+        match (fixture.replay)(fixture.payload) {
+            Ok(replay) if replay.records == 0 => findings.push(Finding::new(
+                Severity::Warning,
+                fixture.provider,
+                format!("{} has no records", fixture.path),
+            )),
+            Ok(replay) => findings.extend(check_fixture(
+                fixture.provider,
+                fixture.path,
+                &replay,
+                live.as_ref(),
+            )),
+            Err(e) => findings.push(Finding::new(
+                Severity::Breaking,
+                fixture.provider,
+                format!("{}: {}", fixture.path, e),
+            )),
+        }
+    }
+    findings
+}
+
+/// Render the findings as markdown.
+pub fn render(findings: &[Finding], live: bool) -> String {
+    let count = |severity: Severity| findings.iter().filter(|f| f.severity == severity).count();
+    let mut out = String::from("# API compatibility\n\n");
+    out.push_str(&format!(
+        "Replayed {} recorded fixtures{}.\n\n",
+        FIXTURES.len(),
+        if live {
+            " and compared them with the live $metadata"
+        } else {
+            ""
+        }
+    ));
+    let mut sorted: Vec<&Finding> = findings.iter().collect();
+    sorted.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    for finding in sorted {
+        let label = match finding.severity {
+            Severity::Breaking => "BREAKING",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+        out.push_str(&format!(
+            "- {} ({}): {}\n",
+            finding.provider, label, finding.detail
+        ));
+    }
+    out.push_str(&format!(
+        "\n{} breaking, {} warnings, {} info\n",
+        count(Severity::Breaking),
+        count(Severity::Warning),
+        count(Severity::Info)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_fixtures_are_compatible() {
+        let findings = check(None);
+        assert!(
+            findings.iter().all(|f| f.severity != Severity::Breaking),
+            "{}",
+            render(&findings, false)
+        );
+        let tasks = replay::<TaskRecord>(FIXTURES[0].payload).unwrap();
+        assert!(tasks.model_fields.contains("type"));
+        assert!(!tasks.model_fields.contains("other"));
+        assert_eq!(
+            tasks.recorded_fields["sprintName"],
+            BTreeSet::from(["string"])
+        );
+    }
+
+    #[test]
+    fn test_drift_against_live_metadata() {
+        let replay = replay::<QualityGateRecord>(
+            r#"{"value": [{"id": "g1", "name": "Q-Gate 1", "status": 3, "plannedDate": "2024-08-01"}]}"#,
+        )
+        .unwrap();
+        assert!(replay.errors[0].starts_with("record 1: invalid type: integer"));
+
+        let metadata = r#"<EntityType Name="QualityGate">
+            <Property Name="id" Type="Edm.Guid"/>
+            <Property Name="name" Type="Edm.String"/>
+            <Property Name="projectId" Type="Edm.Guid"/>
+            <Property Name="status" Type="Edm.String"/>
+            <Property Name="actualDate" Type="Edm.Date"/>
+            <Property Name="plannedDate" Type="Edm.Int32"/>
+            <Property Name="riskLevel" Type="Edm.String"/>
+        </EntityType>"#;
+        let live = metadata::entity_properties(metadata);
+        let findings = check_fixture("QualityGates", "gates.json", &replay, Some(&live));
+        let details: Vec<&str> = findings.iter().map(|f| f.detail.as_str()).collect();
+        assert!(details.contains(&"New fields in $metadata: riskLevel"));
+        assert!(details.contains(&"plannedDate is now Edm.Int32 but was recorded as string"));
+        assert_eq!(
+            findings
+                .iter()
+                .filter(|f| f.severity == Severity::Breaking)
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Partial success semantics for bulk tools.
+//!
+//! Used by `reassign_features_to_release`, `reorder_test_actions`,
+//! `import_requirements_from_text` and `import_external_references`. These
+//! tools keep going when a single item fails. Every item ends up as succeeded,
+//! failed or skipped (with a reason). When items failed, the result carries a
+//! resume token listing the items that already succeeded; passing it to the
+//! same tool with the same parameters skips those items, so an interrupted run
+//! continues where it left off instead of starting over. Items not attempted
+//! because the call ran out of time budget are skipped and also produce a
+//! resume token.
+
+use std::collections::BTreeSet;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Reason recorded for items completed by an earlier run.
+const COMPLETED_REASON: &str = "completed in a previous run";
+
+/// Reason recorded for items not attempted because the time budget ran out.
+const BUDGET_REASON: &str = "not attempted: time budget exhausted";
+
+/// Outcome of a single item.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemOutcome {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub detail: Value,
+}
+
+/// Decoded resume token.
+#[derive(Debug, Serialize, Deserialize)]
+struct ResumeToken {
+    operation: String,
+    /// Fingerprint of the call parameters
+    params: String,
+    completed: BTreeSet<String>,
+}
+
+/// Result of a bulk operation.
+#[derive(Debug, Serialize)]
+pub struct CompositeResult {
+    pub succeeded: Vec<ItemOutcome>,
+    pub failed: Vec<ItemOutcome>,
+    pub skipped: Vec<ItemOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_token: Option<String>,
+    #[serde(skip)]
+    operation: String,
+    #[serde(skip)]
+    params: String,
+    #[serde(skip)]
+    completed: BTreeSet<String>,
+    #[serde(skip)]
+    interrupted: bool,
+}
+
+impl CompositeResult {
+    /// Start a bulk operation, optionally resuming from a token of an earlier run.
+    ///
+    /// `params` are the parameters of the call; the token is only accepted
+    /// by a call with the same parameters. Fails if the token is malformed
+    /// or was issued by another operation or for other parameters.
+    pub fn resume(operation: &str, params: &Value, token: Option<&str>) -> Result<Self, String> {
+        let params = fingerprint(params, &["resume_token"]);
+        let completed = match token {
+            None => BTreeSet::new(),
+            Some(token) => {
+                let decoded: ResumeToken = BASE64
+                    .decode(token)
+                    .ok()
+                    .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+                    .ok_or_else(|| "Invalid resume token".to_string())?;
+                if decoded.operation != operation {
+                    return Err(format!(
+                        "Resume token was issued by '{}', not '{}'",
+                        decoded.operation, operation
+                    ));
+                }
+                if decoded.params != params {
+                    return Err(format!(
+                        "Resume token was issued for '{}' with different parameters",
+                        operation
+                    ));
+                }
+                decoded.completed
+            }
+        };
+
+        Ok(Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+            skipped: Vec::new(),
+            resume_token: None,
+            operation: operation.to_string(),
+            params,
+            completed,
+            interrupted: false,
+        })
+    }
+
+    /// Skip the item if an earlier run already completed it. Returns true if skipped.
+    pub fn skip_completed(&mut self, id: &str) -> bool {
+        if !self.completed.contains(id) {
+            return false;
+        }
+        self.skip(id, COMPLETED_REASON);
+        true
+    }
+
+    /// Skip the item if the time budget of the call is exhausted. Returns true if skipped.
+    pub fn skip_over_budget(&mut self, id: &str) -> bool {
+        if !crate::budget::exhausted() {
+            return false;
+        }
+        self.skip(id, BUDGET_REASON);
+        self.interrupted = true;
+        true
+    }
+
+    pub fn succeed(&mut self, id: impl Into<String>, detail: Value) {
+        self.succeeded.push(ItemOutcome {
+            id: id.into(),
+            reason: None,
+            detail,
+        });
+    }
+
+    pub fn fail(&mut self, id: impl Into<String>, reason: impl ToString, detail: Value) {
+        self.failed.push(ItemOutcome {
+            id: id.into(),
+            reason: Some(reason.to_string()),
+            detail,
+        });
+    }
+
+    pub fn skip(&mut self, id: impl Into<String>, reason: impl ToString) {
+        self.skipped.push(ItemOutcome {
+            id: id.into(),
+            reason: Some(reason.to_string()),
+            detail: Value::Null,
+        });
+    }
+
+    /// Finish the operation, issuing a resume token if any item failed or
+    /// was not attempted.
+    pub fn finish(mut self) -> Self {
+        if self.failed.is_empty() && !self.interrupted {
+            return self;
+        }
+        let mut completed = std::mem::take(&mut self.completed);
+        completed.extend(self.succeeded.iter().map(|o| o.id.clone()));
+        let token = ResumeToken {
+            operation: self.operation.clone(),
+            params: self.params.clone(),
+            completed,
+        };
+        self.resume_token = serde_json::to_vec(&token).ok().map(|b| BASE64.encode(b));
+        self
+    }
+}
+
+/// Hash of the call parameters without the `ignored` ones. `Value` objects
+/// are sorted by key, so equal parameters serialize identically.
+pub fn fingerprint(params: &Value, ignored: &[&str]) -> String {
+    let mut params = params.clone();
+    if let Some(object) = params.as_object_mut() {
+        for name in ignored {
+            object.remove(*name);
+        }
+    }
+    let hash = Sha256::digest(params.to_string().as_bytes());
+    hash[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resume_skips_completed_items() {
+        let params = json!({"uuids": ["a", "b", "c"]});
+        let mut first = CompositeResult::resume("bulk_op", &params, None).unwrap();
+        first.succeed("a", json!({"n": 1}));
+        first.fail("b", "timeout", Value::Null);
+        first.skip("c", "unchanged");
+        let first = first.finish();
+        let token = first.resume_token.clone().unwrap();
+
+        let mut params = params;
+        params["resume_token"] = json!(token);
+        let mut second = CompositeResult::resume("bulk_op", &params, Some(&token)).unwrap();
+        assert!(second.skip_completed("a"));
+        assert!(!second.skip_completed("b"));
+        assert!(!second.skip_completed("c"));
+        second.succeed("b", Value::Null);
+        let second = second.finish();
+        assert!(second.resume_token.is_none());
+
+        let value = serde_json::to_value(&second).unwrap();
+        assert_eq!(value["skipped"][0]["reason"], COMPLETED_REASON);
+        assert_eq!(value["succeeded"][0], json!({"id": "b"}));
+        assert!(value.get("resume_token").is_none());
+    }
+
+    #[test]
+    fn test_resume_rejects_foreign_or_invalid_tokens() {
+        let params = json!({"uuids": ["a"], "release_id": "r1"});
+        let mut result = CompositeResult::resume("bulk_op", &params, None).unwrap();
+        result.fail("a", "error", Value::Null);
+        let token = result.finish().resume_token.unwrap();
+
+        let err = CompositeResult::resume("other_op", &params, Some(&token)).unwrap_err();
+        assert!(err.contains("bulk_op"));
+        let other = json!({"uuids": ["a"], "release_id": "r2"});
+        let err = CompositeResult::resume("bulk_op", &other, Some(&token)).unwrap_err();
+        assert!(err.contains("different parameters"));
+        assert!(CompositeResult::resume("bulk_op", &params, Some("not a token")).is_err());
+    }
+}
@@ -1,13 +1,32 @@
 //! Configuration management for SAP Cloud ALM MCP Server.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
+use crate::anonymize::AnonymizationConfig;
+use crate::checklist;
+use crate::defaults::{self, CreationDefaultsConfig};
 use crate::error::ConfigError;
+use crate::locale::{Locale, LocaleConfig};
+use crate::odata::ODataVersion;
+use crate::transform::TransformerConfig;
+use crate::translate::TranslationConfig;
+use crate::traversal::TraversalConfig;
 
 /// Sandbox API base URL for SAP Cloud ALM.
 const SANDBOX_BASE_URL: &str = "https://sandbox.api.sap.com/SAPCALM";
 
+/// OData-based services whose protocol version can be configured.
+const ODATA_SERVICES: &[&str] = &[
+    "features",
+    "documents",
+    "testmanagement",
+    "processhierarchy",
+    "analytics",
+    "processmonitoring",
+];
+
 /// Main configuration structure.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -34,6 +53,14 @@ pub struct Config {
     /// Required in OAuth2 mode, ignored in sandbox mode.
     pub client_secret: Option<String>,
 
+    /// OAuth2 token endpoint override for custom IAS/UAA domains.
+    /// Defaults to https://{tenant}.authentication.{region}.hana.ondemand.com/oauth/token
+    pub token_url: Option<String>,
+
+    /// Explicit API base URL (e.g., "https://my-company.eu10.alm.cloud.sap").
+    /// Bypasses tenant/region templating and the region allowlist.
+    pub api_base_url: Option<String>,
+
     /// Enable debug mode for MCP message logging
     #[serde(default)]
     pub debug: bool,
@@ -45,6 +72,162 @@ pub struct Config {
     /// Buffer before token expiration to refresh (seconds)
     #[serde(default = "default_token_buffer")]
     pub token_refresh_buffer_seconds: u64,
+
+    /// Wall-time budget of a single tool call in seconds, across all requests
+    /// it makes. Paging and bulk tools return partial results when it runs out.
+    pub tool_budget_seconds: Option<u64>,
+
+    /// Field aliases mapping organization-specific names to API field names
+    /// (e.g. "sapModule" -> "customField01"). Applied to filter/select/orderby
+    /// arguments and to field names in tool output.
+    #[serde(default)]
+    pub field_aliases: HashMap<String, String>,
+
+    /// OData protocol version per service (e.g. {"analytics": "v2"}).
+    /// Keys: features, documents, testmanagement, processhierarchy, analytics,
+    /// processmonitoring. Services not listed use OData v4.
+    #[serde(default)]
+    pub odata_versions: HashMap<String, ODataVersion>,
+
+    /// Rewrite filter literals according to the property types in each
+    /// service's $metadata (numbers, booleans and GUIDs unquoted, strings quoted).
+    #[serde(default)]
+    pub typed_filter_literals: bool,
+
+    /// Access log target: "stderr", "stdout" or a file path.
+    /// Writes one JSON line per tool call (tool, duration, status, bytes).
+    pub access_log: Option<String>,
+
+    /// Additional named tenants (e.g. "test", "prod") used by cross-tenant
+    /// tools. Each profile overrides the connection settings of this config.
+    #[serde(default)]
+    pub profiles: HashMap<String, TenantProfile>,
+
+    /// Bearer token required by the /mcp endpoint of the HTTP transport.
+    /// Without it, the HTTP transport only listens on loopback addresses.
+    pub http_token: Option<String>,
+
+    /// Inbound webhook receiver (HTTP transport only).
+    pub webhook: Option<WebhookConfig>,
+
+    /// Date and number formats of rendered reports (e.g. {"locale": "de-DE"}).
+    #[serde(default)]
+    pub report_locale: LocaleConfig,
+
+    /// Transformers applied to every tool output, in order
+    /// (redact, truncate, flatten, markdown).
+    #[serde(default)]
+    pub output_transformers: Vec<TransformerConfig>,
+
+    /// Verify that the target project exists and the authenticated user has a
+    /// role in it before create and update tools write anything.
+    #[serde(default)]
+    pub verify_project_membership: bool,
+
+    /// Hide and refuse all tools that create, update or delete data.
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Make delete_document and delete_task set an obsolete status instead
+    /// of deleting, where the tenant supports it.
+    #[serde(default)]
+    pub prefer_archive_over_delete: bool,
+
+    /// Refuse a write tool call identical to one that succeeded within this
+    /// many seconds unless it passes `force: true`.
+    pub duplicate_call_window_seconds: Option<u64>,
+
+    /// Queue create and update calls while the API is unreachable and replay
+    /// them when it is reachable again.
+    pub write_queue: Option<WriteQueueConfig>,
+
+    /// Depth and node limits of recursive tools (hierarchy subtrees and
+    /// snapshots).
+    #[serde(default)]
+    pub traversal: TraversalConfig,
+
+    /// Translate text fields of tool results into a target language.
+    pub translation: Option<TranslationConfig>,
+
+    /// Directory that file-writing and file-reading tools are confined to;
+    /// enables list_workspace_files and read_workspace_file.
+    pub workspace_dir: Option<String>,
+
+    /// Fields pseudonymized and masked by exports passed `anonymize: true`.
+    #[serde(default)]
+    pub anonymization: AnonymizationConfig,
+
+    /// Criteria evaluated by check_feature_readiness (default: all).
+    #[serde(default)]
+    pub feature_checklist: Vec<String>,
+
+    /// Argument values create tools use when the caller omits them, per
+    /// entity type and optionally per project.
+    #[serde(default)]
+    pub creation_defaults: Vec<CreationDefaultsConfig>,
+}
+
+/// Inbound webhook receiver settings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    /// Path receiving callbacks (default: /webhooks/calm)
+    #[serde(default = "default_webhook_path")]
+    pub path: String,
+
+    /// Shared secret expected in the X-Webhook-Secret header
+    /// (or as `Authorization: Bearer <secret>`)
+    pub secret: String,
+
+    /// Number of most recent events kept in memory
+    #[serde(default = "default_webhook_max_events")]
+    pub max_events: usize,
+}
+
+/// Offline write queue settings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WriteQueueConfig {
+    /// JSON file persisting queued operations across restarts
+    /// (default: in memory only)
+    pub path: Option<String>,
+
+    /// Maximum number of queued operations
+    #[serde(default = "default_write_queue_max_operations")]
+    pub max_operations: usize,
+}
+
+fn default_write_queue_max_operations() -> usize {
+    100
+}
+
+/// Paths served by the HTTP transport itself, unavailable to the webhook.
+const RESERVED_HTTP_PATHS: &[&str] = &["/", "/healthz", "/readyz", "/mcp"];
+
+/// Whether `path` is a valid webhook path: absolute and not served by the
+/// HTTP transport (including paths under `/mcp/`).
+fn is_valid_webhook_path(path: &str) -> bool {
+    path.starts_with('/') && !RESERVED_HTTP_PATHS.contains(&path) && !path.starts_with("/mcp/")
+}
+
+fn default_webhook_path() -> String {
+    "/webhooks/calm".to_string()
+}
+
+fn default_webhook_max_events() -> usize {
+    100
+}
+
+/// Connection settings of an additional tenant.
+/// Unset fields are inherited from the main configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TenantProfile {
+    pub sandbox: Option<bool>,
+    pub api_key: Option<String>,
+    pub tenant: Option<String>,
+    pub region: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub token_url: Option<String>,
+    pub api_base_url: Option<String>,
 }
 
 fn default_timeout() -> u64 {
@@ -55,6 +238,18 @@ fn default_token_buffer() -> u64 {
     5
 }
 
+/// Check that a configured URL is an absolute http(s) URL.
+fn validate_url(field: &str, url: &str) -> Result<(), ConfigError> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid(format!(
+            "Invalid {} '{}'. Must be an absolute http(s) URL",
+            field, url
+        )))
+    }
+}
+
 impl Config {
     /// Load configuration from a file path.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
@@ -64,8 +259,119 @@ impl Config {
         Ok(config)
     }
 
+    /// Resolve the configuration of a named tenant profile.
+    pub fn profile(&self, name: &str) -> Result<Config, ConfigError> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| ConfigError::Invalid(format!("Unknown profile '{}'", name)))?;
+
+        let mut config = self.clone();
+        config.profiles = HashMap::new();
+        if let Some(sandbox) = profile.sandbox {
+            config.sandbox = sandbox;
+        }
+        let overrides = [
+            (&mut config.api_key, &profile.api_key),
+            (&mut config.tenant, &profile.tenant),
+            (&mut config.region, &profile.region),
+            (&mut config.client_id, &profile.client_id),
+            (&mut config.client_secret, &profile.client_secret),
+            (&mut config.token_url, &profile.token_url),
+            (&mut config.api_base_url, &profile.api_base_url),
+        ];
+        for (field, value) in overrides {
+            if value.is_some() {
+                field.clone_from(value);
+            }
+        }
+
+        config
+            .validate()
+            .map_err(|e| ConfigError::Invalid(format!("Profile '{}': {}", name, e)))?;
+        Ok(config)
+    }
+
     /// Validate configuration values.
     fn validate(&self) -> Result<(), ConfigError> {
+        for name in self.profiles.keys() {
+            self.profile(name)?;
+        }
+
+        if self
+            .http_token
+            .as_deref()
+            .is_some_and(|t| t.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid("http_token must not be empty".into()));
+        }
+
+        if let Some(webhook) = &self.webhook {
+            if webhook.secret.is_empty() {
+                return Err(ConfigError::MissingField("webhook.secret".into()));
+            }
+            if !is_valid_webhook_path(&webhook.path) {
+                return Err(ConfigError::Invalid(format!(
+                    "Invalid webhook.path '{}'. Must be an absolute path other than {} and paths under /mcp/",
+                    webhook.path,
+                    RESERVED_HTTP_PATHS.join(", ")
+                )));
+            }
+        }
+
+        Locale::from_config(&self.report_locale).map_err(ConfigError::Invalid)?;
+
+        if self.tool_budget_seconds == Some(0) {
+            return Err(ConfigError::Invalid(
+                "tool_budget_seconds must be greater than 0".into(),
+            ));
+        }
+
+        if self.duplicate_call_window_seconds == Some(0) {
+            return Err(ConfigError::Invalid(
+                "duplicate_call_window_seconds must be greater than 0".into(),
+            ));
+        }
+
+        if self
+            .write_queue
+            .as_ref()
+            .is_some_and(|q| q.max_operations == 0)
+        {
+            return Err(ConfigError::Invalid(
+                "write_queue.max_operations must be greater than 0".into(),
+            ));
+        }
+
+        if let Some(translation) = &self.translation {
+            if translation.target_language.trim().is_empty() {
+                return Err(ConfigError::MissingField(
+                    "translation.target_language".into(),
+                ));
+            }
+            if let Some(endpoint) = &translation.endpoint {
+                validate_url("translation.endpoint", endpoint)?;
+            }
+        }
+
+        checklist::parse_criteria(&self.feature_checklist).map_err(ConfigError::Invalid)?;
+        defaults::validate(&self.creation_defaults).map_err(ConfigError::Invalid)?;
+
+        if self.traversal.max_depth == 0 || self.traversal.max_nodes == 0 {
+            return Err(ConfigError::Invalid(
+                "traversal.max_depth and traversal.max_nodes must be greater than 0".into(),
+            ));
+        }
+
+        for service in self.odata_versions.keys() {
+            if !ODATA_SERVICES.contains(&service.as_str()) {
+                return Err(ConfigError::Invalid(format!(
+                    "Unknown service '{}' in odata_versions. Valid services: {:?}",
+                    service, ODATA_SERVICES
+                )));
+            }
+        }
+
         if self.sandbox {
             // Sandbox mode: require api_key
             match &self.api_key {
@@ -82,17 +388,7 @@ impl Config {
                 _ => {}
             }
         } else {
-            // OAuth2 mode: require tenant, region, client_id, client_secret
-            match &self.tenant {
-                None => return Err(ConfigError::MissingField("tenant".into())),
-                Some(t) if t.is_empty() => return Err(ConfigError::MissingField("tenant".into())),
-                _ => {}
-            }
-            match &self.region {
-                None => return Err(ConfigError::MissingField("region".into())),
-                Some(r) if r.is_empty() => return Err(ConfigError::MissingField("region".into())),
-                _ => {}
-            }
+            // OAuth2 mode: require client_id, client_secret
             match &self.client_id {
                 None => return Err(ConfigError::MissingField("client_id".into())),
                 Some(c) if c.is_empty() => {
@@ -107,6 +403,36 @@ impl Config {
                 }
                 _ => {}
             }
+            if let Some(url) = &self.token_url {
+                validate_url("token_url", url)?;
+            }
+
+            if let Some(url) = &self.api_base_url {
+                // Explicit base URL mode: tenant/region templating and the
+                // region allowlist are bypassed.
+                validate_url("api_base_url", url)?;
+                let has_tenant_region = self.tenant.as_deref().is_some_and(|t| !t.is_empty())
+                    && self.region.as_deref().is_some_and(|r| !r.is_empty());
+                if self.token_url.is_none() && !has_tenant_region {
+                    return Err(ConfigError::MissingField(
+                        "token_url (required with api_base_url when tenant/region are not set)"
+                            .into(),
+                    ));
+                }
+                return Ok(());
+            }
+
+            // Templated mode: require tenant, region
+            match &self.tenant {
+                None => return Err(ConfigError::MissingField("tenant".into())),
+                Some(t) if t.is_empty() => return Err(ConfigError::MissingField("tenant".into())),
+                _ => {}
+            }
+            match &self.region {
+                None => return Err(ConfigError::MissingField("region".into())),
+                Some(r) if r.is_empty() => return Err(ConfigError::MissingField("region".into())),
+                _ => {}
+            }
 
             // Validate region is one of the known values
             let valid_regions = [
@@ -118,7 +444,7 @@ impl Config {
                 .expect("region guaranteed present by Config::validate()");
             if !valid_regions.contains(&region.as_str()) {
                 return Err(ConfigError::Invalid(format!(
-                    "Invalid region '{}'. Valid regions: {:?}",
+                    "Invalid region '{}'. Valid regions: {:?}. Use api_base_url for other data centers",
                     region, valid_regions
                 )));
             }
@@ -128,7 +454,7 @@ impl Config {
     }
 
     /// Get the OAuth2 token URL.
-    /// Returns None in sandbox mode.
+    /// Returns None in sandbox mode. An explicit `token_url` takes precedence.
     ///
     /// # Panics
     /// Panics if called in OAuth2 mode without tenant/region being set.
@@ -136,6 +462,8 @@ impl Config {
     pub fn token_url(&self) -> Option<String> {
         if self.sandbox {
             None
+        } else if let Some(url) = &self.token_url {
+            Some(url.clone())
         } else {
             Some(format!(
                 "https://{}.authentication.{}.hana.ondemand.com/oauth/token",
@@ -150,6 +478,7 @@ impl Config {
     }
 
     /// Get the API base URL.
+    /// An explicit `api_base_url` takes precedence over tenant/region.
     ///
     /// # Panics
     /// Panics if called in OAuth2 mode without tenant/region being set.
@@ -157,6 +486,8 @@ impl Config {
     pub fn api_base_url(&self) -> String {
         if self.sandbox {
             SANDBOX_BASE_URL.to_string()
+        } else if let Some(url) = &self.api_base_url {
+            url.trim_end_matches('/').to_string()
         } else {
             format!(
                 "https://{}.{}.alm.cloud.sap",
@@ -235,6 +566,7 @@ impl Config {
     }
 
     /// Get the Analytics API URL.
+    #[cfg(feature = "analytics")]
     pub fn analytics_api_url(&self) -> String {
         format!(
             "{}{}/calm-analytics/v1/odata/v4/analytics",
@@ -244,6 +576,7 @@ impl Config {
     }
 
     /// Get the Process Monitoring API URL.
+    #[cfg(feature = "processmonitoring")]
     pub fn processmonitoring_api_url(&self) -> String {
         format!(
             "{}{}/calm-processmonitoring/v1",
@@ -253,6 +586,7 @@ impl Config {
     }
 
     /// Get the Logs API URL.
+    #[cfg(feature = "logs")]
     pub fn logs_api_url(&self) -> String {
         format!(
             "{}{}/calm-logs/v1",
@@ -261,6 +595,14 @@ impl Config {
         )
     }
 
+    /// Get the OData protocol version configured for a service.
+    pub fn odata_version(&self, service: &str) -> ODataVersion {
+        self.odata_versions
+            .get(service)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Get timeout as Duration.
     pub fn timeout(&self) -> std::time::Duration {
         std::time::Duration::from_secs(self.timeout_seconds)
@@ -291,9 +633,32 @@ mod tests {
             region: Some("eu10".to_string()),
             client_id: Some("test-client".to_string()),
             client_secret: Some("test-secret".to_string()),
+            token_url: None,
+            api_base_url: None,
             debug: false,
             timeout_seconds: 30,
             token_refresh_buffer_seconds: 5,
+            tool_budget_seconds: None,
+            field_aliases: HashMap::new(),
+            access_log: None,
+            profiles: HashMap::new(),
+            report_locale: LocaleConfig::default(),
+            output_transformers: Vec::new(),
+            verify_project_membership: false,
+            duplicate_call_window_seconds: None,
+            write_queue: None,
+            webhook: None,
+            http_token: None,
+            odata_versions: HashMap::new(),
+            typed_filter_literals: false,
+            read_only: false,
+            prefer_archive_over_delete: false,
+            traversal: TraversalConfig::default(),
+            translation: None,
+            workspace_dir: None,
+            anonymization: AnonymizationConfig::default(),
+            feature_checklist: Vec::new(),
+            creation_defaults: Vec::new(),
         };
 
         assert_eq!(
@@ -320,9 +685,32 @@ mod tests {
             region: None,
             client_id: None,
             client_secret: None,
+            token_url: None,
+            api_base_url: None,
             debug: true,
             timeout_seconds: 30,
             token_refresh_buffer_seconds: 5,
+            tool_budget_seconds: None,
+            field_aliases: HashMap::new(),
+            access_log: None,
+            profiles: HashMap::new(),
+            report_locale: LocaleConfig::default(),
+            output_transformers: Vec::new(),
+            verify_project_membership: false,
+            duplicate_call_window_seconds: None,
+            write_queue: None,
+            webhook: None,
+            http_token: None,
+            odata_versions: HashMap::new(),
+            typed_filter_literals: false,
+            read_only: false,
+            prefer_archive_over_delete: false,
+            traversal: TraversalConfig::default(),
+            translation: None,
+            workspace_dir: None,
+            anonymization: AnonymizationConfig::default(),
+            feature_checklist: Vec::new(),
+            creation_defaults: Vec::new(),
         };
 
         assert_eq!(config.token_url(), None);
@@ -333,4 +721,198 @@ mod tests {
         );
         assert!(config.is_sandbox());
     }
+
+    #[test]
+    fn test_token_url_override() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "tenant": "mycompany",
+                "region": "eu10",
+                "client_id": "test-client",
+                "client_secret": "test-secret",
+                "token_url": "https://auth.example.com/oauth/token"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.token_url(),
+            Some("https://auth.example.com/oauth/token".to_string())
+        );
+        assert_eq!(
+            config.api_base_url(),
+            "https://mycompany.eu10.alm.cloud.sap"
+        );
+    }
+
+    #[test]
+    fn test_explicit_base_url_mode() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "client_id": "test-client",
+                "client_secret": "test-secret",
+                "api_base_url": "https://calm.private.example.com/",
+                "token_url": "https://auth.example.com/oauth/token"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.api_base_url(), "https://calm.private.example.com");
+        assert_eq!(
+            config.features_api_url(),
+            "https://calm.private.example.com/api/calm-features/v1"
+        );
+    }
+
+    #[test]
+    fn test_explicit_base_url_requires_token_url_without_tenant() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "client_id": "test-client",
+                "client_secret": "test-secret",
+                "api_base_url": "https://calm.private.example.com"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::MissingField(_))
+        ));
+    }
+
+    #[test]
+    fn test_explicit_base_url_skips_region_allowlist() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "tenant": "mycompany",
+                "region": "us30",
+                "client_id": "test-client",
+                "client_secret": "test-secret",
+                "api_base_url": "https://mycompany.us30.alm.cloud.sap"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.token_url(),
+            Some("https://mycompany.authentication.us30.hana.ondemand.com/oauth/token".to_string())
+        );
+    }
+
+    #[test]
+    fn test_profile_overrides_connection_settings() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "tenant": "mycompany",
+            "region": "eu10",
+            "client_id": "id",
+            "client_secret": "secret",
+            "profiles": {
+                "test": {"tenant": "mycompany-test", "client_secret": "test-secret"}
+            }
+        }))
+        .unwrap();
+        config.validate().unwrap();
+
+        let test = config.profile("test").unwrap();
+        assert_eq!(
+            test.api_base_url(),
+            "https://mycompany-test.eu10.alm.cloud.sap"
+        );
+        assert_eq!(test.client_id.as_deref(), Some("id"));
+        assert_eq!(test.client_secret.as_deref(), Some("test-secret"));
+        assert!(test.profiles.is_empty());
+        assert!(config.profile("prod").is_err());
+    }
+
+    #[test]
+    fn test_invalid_profile_fails_validation() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "tenant": "mycompany",
+            "region": "eu10",
+            "client_id": "id",
+            "client_secret": "secret",
+            "profiles": {"prod": {"region": "xx99"}}
+        }))
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_webhook_config_defaults_and_validation() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "sandbox": true,
+            "api_key": "key",
+            "webhook": {"secret": "s3cret"}
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        let webhook = config.webhook.as_mut().unwrap();
+        assert_eq!(webhook.path, "/webhooks/calm");
+        assert_eq!(webhook.max_events, 100);
+
+        for path in [
+            "/healthz",
+            "/readyz",
+            "/mcp",
+            "/mcp/events",
+            "/",
+            "webhooks",
+        ] {
+            config.webhook.as_mut().unwrap().path = path.to_string();
+            assert!(config.validate().is_err(), "{} accepted", path);
+        }
+        config.webhook.as_mut().unwrap().path = "/mcp-events".to_string();
+        config.validate().unwrap();
+
+        config.webhook.as_mut().unwrap().secret = String::new();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_write_queue_config_defaults_and_validation() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "sandbox": true,
+            "api_key": "key",
+            "write_queue": {}
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        let queue = config.write_queue.as_mut().unwrap();
+        assert_eq!(queue.path, None);
+        assert_eq!(queue.max_operations, 100);
+
+        queue.max_operations = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_traversal_limits_default_and_validation() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "sandbox": true,
+            "api_key": "key",
+            "traversal": {"max_depth": 5}
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.traversal.max_depth, 5);
+        assert_eq!(config.traversal.max_nodes, 5000);
+
+        config.traversal.max_nodes = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_unknown_report_locale_fails_validation() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "sandbox": true,
+            "api_key": "key",
+            "report_locale": {"locale": "tlh"}
+        }))
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
 }
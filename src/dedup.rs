@@ -0,0 +1,268 @@
+//! Suppression of duplicate write tool calls.
+//!
+//! Agents caught in a retry loop may repeat a create call that already
+//! succeeded, creating duplicate features or tasks. When enabled, a write call
+//! identical to one that succeeded within the window (same tool, same
+//! arguments after normalization) is refused unless it passes `force: true`.
+//! A call identical to one still running is refused as well: the check
+//! reserves the call, and the reservation is dropped if the call fails.
+//! Previews and dry runs are never suppressed, including calls that omit a
+//! `preview`/`dry_run` flag whose schema default is true.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rmcp::model::Tool;
+use serde_json::{Map, Value};
+
+/// Argument that explicitly repeats a suppressed call.
+pub const FORCE_ARGUMENT: &str = "force";
+
+/// Marker in the description of tools that write data.
+pub const WRITE_TOOL_MARKER: &str = "Requires user confirmation";
+
+/// Arguments that turn a write call into a simulation.
+const SIMULATION_FLAGS: &[&str] = &["preview", "dry_run"];
+
+/// State of a guarded call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CallState {
+    Running,
+    Succeeded(Instant),
+}
+
+/// Why a call was refused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Duplicate {
+    /// An identical call is still running
+    Running,
+    /// An identical call succeeded this long ago
+    Succeeded(Duration),
+}
+
+/// Tracks running and recently succeeded write calls.
+pub struct DuplicateGuard {
+    window: Duration,
+    write_tools: HashSet<String>,
+    /// Simulation flags that default to true, per tool
+    simulated_by_default: HashMap<String, Vec<&'static str>>,
+    recent: Mutex<HashMap<String, CallState>>,
+}
+
+impl DuplicateGuard {
+    pub fn new(window: Duration, write_tools: HashSet<String>) -> Self {
+        Self {
+            window,
+            write_tools,
+            simulated_by_default: HashMap::new(),
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take the defaults of the simulation flags from the tool schemas.
+    pub fn with_schema_defaults(mut self, tools: &[Tool]) -> Self {
+        for tool in tools {
+            let properties = tool
+                .input_schema
+                .get("properties")
+                .and_then(Value::as_object);
+            let flags: Vec<&'static str> = SIMULATION_FLAGS
+                .iter()
+                .copied()
+                .filter(|flag| {
+                    properties
+                        .and_then(|p| p.get(*flag))
+                        .and_then(|schema| schema.get("default"))
+                        == Some(&Value::Bool(true))
+                })
+                .collect();
+            if !flags.is_empty() {
+                self.simulated_by_default
+                    .insert(tool.name.to_string(), flags);
+            }
+        }
+        self
+    }
+
+    pub fn is_write_tool(&self, tool: &str) -> bool {
+        self.write_tools.contains(tool)
+    }
+
+    /// Key identifying a write call, or None if the call is not guarded
+    /// (read tools, previews and dry runs).
+    pub fn call_key(&self, tool: &str, arguments: Option<&Map<String, Value>>) -> Option<String> {
+        if !self.is_write_tool(tool) {
+            return None;
+        }
+        let arguments = arguments.cloned().unwrap_or_default();
+        let defaults = self.simulated_by_default.get(tool);
+        let simulated = SIMULATION_FLAGS.iter().any(|flag| {
+            match arguments.get(*flag).and_then(Value::as_bool) {
+                Some(value) => value,
+                None => defaults.is_some_and(|d| d.contains(flag)),
+            }
+        });
+        if simulated {
+            return None;
+        }
+        Some(format!("{}:{}", tool, normalize(Value::Object(arguments))))
+    }
+
+    /// Reserve a call, unless an identical call is running or succeeded
+    /// within the window. A reserved call must be recorded or released.
+    pub fn reserve(&self, key: &str) -> Result<(), Duplicate> {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.retain(|_, state| match state {
+            CallState::Running => true,
+            CallState::Succeeded(at) => at.elapsed() < self.window,
+        });
+        match recent.get(key) {
+            Some(CallState::Running) => Err(Duplicate::Running),
+            Some(CallState::Succeeded(at)) => Err(Duplicate::Succeeded(at.elapsed())),
+            None => {
+                recent.insert(key.to_string(), CallState::Running);
+                Ok(())
+            }
+        }
+    }
+
+    /// Record a succeeded call.
+    pub fn record(&self, key: String) {
+        self.recent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, CallState::Succeeded(Instant::now()));
+    }
+
+    /// Drop the reservation of a failed call.
+    pub fn release(&self, key: &str) {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.get(key) == Some(&CallState::Running) {
+            recent.remove(key);
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+/// Remove the `force` argument, returning its value.
+pub fn take_force(arguments: Option<&mut Map<String, Value>>) -> bool {
+    arguments
+        .and_then(|args| args.remove(FORCE_ARGUMENT))
+        .and_then(|force| force.as_bool())
+        .unwrap_or(false)
+}
+
+/// Canonical form of call arguments: keys sorted, null values dropped and
+/// strings trimmed, so cosmetic differences don't hide a repeat.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, normalize(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize).collect()),
+        Value::String(s) => Value::String(s.trim().to_string()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_identical_write_calls_are_detected() {
+        let guard = DuplicateGuard::new(
+            Duration::from_secs(60),
+            HashSet::from(["create_task".to_string()]),
+        );
+        let first = guard
+            .call_key(
+                "create_task",
+                Some(&args(json!({"title": "Fix login", "project_id": "p1"}))),
+            )
+            .unwrap();
+        assert!(guard.reserve(&first).is_ok());
+        assert_eq!(guard.reserve(&first), Err(Duplicate::Running));
+        guard.record(first);
+
+        let mut repeat =
+            args(json!({"project_id": "p1", "title": " Fix login ", "due_date": null}));
+        let key = guard.call_key("create_task", Some(&repeat)).unwrap();
+        assert!(matches!(guard.reserve(&key), Err(Duplicate::Succeeded(_))));
+        guard.release(&key);
+        assert!(guard.reserve(&key).is_err());
+
+        repeat.insert("force".to_string(), json!(true));
+        assert!(take_force(Some(&mut repeat)));
+        assert!(!repeat.contains_key("force"));
+
+        let other = guard
+            .call_key(
+                "create_task",
+                Some(&args(json!({"title": "Other", "project_id": "p1"}))),
+            )
+            .unwrap();
+        assert!(guard.reserve(&other).is_ok());
+        guard.release(&other);
+        assert!(guard.reserve(&other).is_ok());
+    }
+
+    #[test]
+    fn test_reads_and_previews_are_not_guarded() {
+        let guard = DuplicateGuard::new(
+            Duration::from_secs(60),
+            HashSet::from(["generate_incident_document".to_string()]),
+        );
+        assert!(guard.call_key("list_tasks", None).is_none());
+        assert!(guard
+            .call_key(
+                "generate_incident_document",
+                Some(&args(json!({"preview": true})))
+            )
+            .is_none());
+        assert!(guard.call_key("generate_incident_document", None).is_some());
+
+        let expired = DuplicateGuard::new(Duration::ZERO, HashSet::new());
+        expired.record("create_task:{}".to_string());
+        assert!(expired.reserve("create_task:{}").is_ok());
+    }
+
+    #[test]
+    fn test_omitted_flag_uses_schema_default() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"dry_run": {"type": ["boolean", "null"], "default": true}}
+        });
+        let tool = Tool::new(
+            "run_workflow",
+            "Requires user confirmation",
+            std::sync::Arc::new(schema.as_object().unwrap().clone()),
+        );
+        let guard = DuplicateGuard::new(
+            Duration::from_secs(60),
+            HashSet::from(["run_workflow".to_string()]),
+        )
+        .with_schema_defaults(&[tool]);
+        assert!(guard
+            .call_key("run_workflow", Some(&args(json!({"name": "w"}))))
+            .is_none());
+        assert!(guard
+            .call_key(
+                "run_workflow",
+                Some(&args(json!({"name": "w", "dry_run": false})))
+            )
+            .is_some());
+    }
+}
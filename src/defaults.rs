@@ -0,0 +1,227 @@
+//! Default field values of created entities.
+//!
+//! Organizations have conventions for new items: a default priority, the
+//! document type used in a project, an initial status. Creation defaults are
+//! arguments the create tools get when the caller omits them, so the
+//! conventions apply without the agent knowing them:
+//!
+//! ```json
+//! "creation_defaults": [
+//!   {"entity": "feature", "values": {"priority_code": "20"}},
+//!   {"entity": "document", "project_id": "4f2b...", "values": {"type_code": "SPEC"}}
+//! ]
+//! ```
+//!
+//! Defaults of the project of the call take precedence over defaults for all
+//! projects. Arguments passed by the caller, other than `null`, are kept.
+//! Composite tools that create entities internally (e.g. the task of
+//! `escalate_alert_to_task`) apply the defaults of the create tool as well.
+
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Entity types and their create tools.
+const CREATE_TOOLS: &[(&str, &str)] = &[
+    ("feature", "create_feature"),
+    ("document", "create_document"),
+    ("task", "create_task"),
+    ("testcase", "create_testcase"),
+    ("hierarchy_node", "create_hierarchy_node"),
+];
+
+/// Default argument values of one entity type.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreationDefaultsConfig {
+    /// Entity type: feature, document, task, testcase or hierarchy_node
+    pub entity: String,
+    /// Project the defaults apply to (default: all projects)
+    pub project_id: Option<String>,
+    /// Arguments of the create tool and their default values
+    pub values: Map<String, Value>,
+}
+
+/// Create tool of an entity type.
+fn create_tool(entity: &str) -> Option<&'static str> {
+    CREATE_TOOLS
+        .iter()
+        .find(|(name, _)| *name == entity)
+        .map(|(_, tool)| *tool)
+}
+
+/// Check that every entry names a known entity type.
+pub fn validate(entries: &[CreationDefaultsConfig]) -> Result<(), String> {
+    for entry in entries {
+        if create_tool(&entry.entity).is_none() {
+            let entities: Vec<&str> = CREATE_TOOLS.iter().map(|(name, _)| *name).collect();
+            return Err(format!(
+                "Unknown creation_defaults entity '{}'. Expected one of: {}",
+                entry.entity,
+                entities.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Applies the creation defaults to create tool arguments.
+#[derive(Debug, Clone, Default)]
+pub struct CreationDefaults {
+    /// (create tool, project, values)
+    entries: Vec<(&'static str, Option<String>, Map<String, Value>)>,
+}
+
+impl CreationDefaults {
+    pub fn new(entries: &[CreationDefaultsConfig]) -> Self {
+        Self {
+            entries: entries
+                .iter()
+                .filter_map(|entry| {
+                    let tool = create_tool(&entry.entity)?;
+                    Some((tool, entry.project_id.clone(), entry.values.clone()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Default arguments that the create tools do not accept, as
+    /// `tool.argument`.
+    pub fn unknown_arguments(&self, tools: &[Tool]) -> Vec<String> {
+        let mut unknown = Vec::new();
+        for (tool, _, values) in &self.entries {
+            let properties = tools
+                .iter()
+                .find(|t| t.name == *tool)
+                .and_then(|t| t.input_schema.get("properties"))
+                .and_then(Value::as_object);
+            for argument in values.keys() {
+                if !properties.is_some_and(|p| p.contains_key(argument)) {
+                    unknown.push(format!("{}.{}", tool, argument));
+                }
+            }
+        }
+        unknown
+    }
+
+    /// Set the omitted arguments of a create tool call to their defaults.
+    /// Returns the names of the arguments set.
+    pub fn apply(&self, tool: &str, arguments: &mut Map<String, Value>) -> Vec<String> {
+        let project = arguments
+            .get("project_id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let mut matching: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(t, p, _)| *t == tool && (p.is_none() || *p == project))
+            .collect();
+        // Project defaults first, so they take precedence
+        matching.sort_by_key(|(_, p, _)| p.is_none());
+
+        let mut applied = Vec::new();
+        for (_, _, values) in matching {
+            for (argument, value) in values {
+                if arguments.get(argument).is_none_or(Value::is_null) {
+                    arguments.insert(argument.clone(), value.clone());
+                    applied.push(argument.clone());
+                }
+            }
+        }
+        applied
+    }
+
+    /// Arguments of an entity created internally by a composite tool, as
+    /// arguments of the create tool `tool`, with the defaults applied.
+    /// `fallbacks` are the composite tool's own defaults, used for arguments
+    /// that neither the caller nor the creation defaults set.
+    pub fn complete(
+        &self,
+        tool: &str,
+        mut arguments: Map<String, Value>,
+        fallbacks: &[(&str, Value)],
+    ) -> (Map<String, Value>, Vec<String>) {
+        arguments.retain(|_, value| !value.is_null());
+        let applied = self.apply(tool, &mut arguments);
+        for (argument, value) in fallbacks {
+            if !arguments.contains_key(*argument) {
+                arguments.insert(argument.to_string(), value.clone());
+            }
+        }
+        (arguments, applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(entity: &str, project_id: Option<&str>, values: Value) -> CreationDefaultsConfig {
+        CreationDefaultsConfig {
+            entity: entity.to_string(),
+            project_id: project_id.map(str::to_string),
+            values: values.as_object().cloned().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_apply_project_defaults_first() {
+        let defaults = CreationDefaults::new(&[
+            entry(
+                "feature",
+                None,
+                json!({"priority_code": "20", "status_code": "CIPNEW"}),
+            ),
+            entry("feature", Some("p1"), json!({"priority_code": "10"})),
+            entry("document", None, json!({"type_code": "SPEC"})),
+        ]);
+
+        let mut arguments = json!({"project_id": "p1", "title": "A", "status_code": null});
+        let arguments = arguments.as_object_mut().unwrap();
+        let applied = defaults.apply("create_feature", arguments);
+        assert_eq!(applied, vec!["priority_code", "status_code"]);
+        assert_eq!(arguments["priority_code"], "10");
+        assert_eq!(arguments["status_code"], "CIPNEW");
+
+        let mut arguments = json!({"project_id": "p2", "priority_code": "30"});
+        let arguments = arguments.as_object_mut().unwrap();
+        assert_eq!(
+            defaults.apply("create_feature", arguments),
+            vec!["status_code"]
+        );
+        assert_eq!(arguments["priority_code"], "30");
+
+        assert!(defaults.apply("create_task", &mut Map::new()).is_empty());
+        assert!(validate(&[entry("epic", None, json!({}))]).is_err());
+    }
+
+    #[test]
+    fn test_complete_internal_create() {
+        let defaults = CreationDefaults::new(&[entry(
+            "task",
+            Some("p1"),
+            json!({"task_type": "CALMINC", "assignee_id": "u1"}),
+        )]);
+        // Task of escalate_alert_to_task: task type not given by the caller
+        let arguments = json!({"project_id": "p1", "title": "[Alert] CPU", "task_type": null});
+        let fallbacks = [("task_type", json!("CALMTASK"))];
+        let (arguments, applied) = defaults.complete(
+            "create_task",
+            arguments.as_object().cloned().unwrap(),
+            &fallbacks,
+        );
+        assert_eq!(applied, vec!["assignee_id", "task_type"]);
+        assert_eq!(arguments["task_type"], "CALMINC");
+        assert_eq!(arguments["assignee_id"], "u1");
+
+        let arguments = json!({"project_id": "p2", "title": "[Alert] CPU"});
+        let (arguments, applied) = defaults.complete(
+            "create_task",
+            arguments.as_object().cloned().unwrap(),
+            &fallbacks,
+        );
+        assert!(applied.is_empty());
+        assert_eq!(arguments["task_type"], "CALMTASK");
+    }
+}
@@ -0,0 +1,190 @@
+//! Field-level comparison of JSON records.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// A field whose value differs between two records.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDifference {
+    pub field: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+/// Compare the top-level fields of two records, skipping `ignore`.
+/// Missing fields are treated as `null`.
+pub fn field_differences(left: &Value, right: &Value, ignore: &[&str]) -> Vec<FieldDifference> {
+    let fields: BTreeSet<&String> = [left, right]
+        .iter()
+        .filter_map(|v| v.as_object())
+        .flat_map(|map| map.keys())
+        .filter(|k| !ignore.contains(&k.as_str()))
+        .collect();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let l = left.get(field).unwrap_or(&Value::Null);
+            let r = right.get(field).unwrap_or(&Value::Null);
+            (l != r).then(|| FieldDifference {
+                field: field.clone(),
+                left: l.clone(),
+                right: r.clone(),
+            })
+        })
+        .collect()
+}
+
+/// A field changed by a write.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Administrative timestamps that change with every write.
+const WRITE_TIMESTAMPS: &[&str] = &["modifiedAt", "lastChangedDate", "changedAt"];
+
+/// Fields changed by a write, comparing the entity before the write with the
+/// one returned by it. Only fields present in the returned entity are
+/// compared, as services may return a subset of the fields.
+pub fn field_changes(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let Some(fields) = after.as_object() else {
+        return Vec::new();
+    };
+    let mut keys: Vec<&String> = fields
+        .keys()
+        .filter(|k| !WRITE_TIMESTAMPS.contains(&k.as_str()))
+        .collect();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|field| {
+            let old = before.get(field).unwrap_or(&Value::Null);
+            let new = &fields[field];
+            (old != new).then(|| FieldChange {
+                field: field.clone(),
+                before: old.clone(),
+                after: new.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Records matched by key that differ in at least one field.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedRecord {
+    pub key: String,
+    pub differences: Vec<FieldDifference>,
+}
+
+/// Differences between two record sets matched by a key field.
+#[derive(Debug, Default, Serialize)]
+pub struct RecordSetDiff {
+    pub only_in_left: Vec<String>,
+    pub only_in_right: Vec<String>,
+    pub changed: Vec<ChangedRecord>,
+    pub identical: usize,
+}
+
+/// Match records by the string value of `key` and compare their fields.
+/// Records without the key are ignored; for duplicate keys the first record wins.
+pub fn diff_record_sets(
+    left: &[Value],
+    right: &[Value],
+    key: &str,
+    ignore: &[&str],
+) -> RecordSetDiff {
+    let index = |records: &[Value]| {
+        let mut map: BTreeMap<String, Value> = BTreeMap::new();
+        for record in records {
+            if let Some(k) = record.get(key).and_then(Value::as_str) {
+                map.entry(k.to_string()).or_insert_with(|| record.clone());
+            }
+        }
+        map
+    };
+    let (left, right) = (index(left), index(right));
+
+    let mut diff = RecordSetDiff::default();
+    for (k, l) in &left {
+        match right.get(k) {
+            None => diff.only_in_left.push(k.clone()),
+            Some(r) => {
+                let differences = field_differences(l, r, ignore);
+                if differences.is_empty() {
+                    diff.identical += 1;
+                } else {
+                    diff.changed.push(ChangedRecord {
+                        key: k.clone(),
+                        differences,
+                    });
+                }
+            }
+        }
+    }
+    diff.only_in_right = right
+        .keys()
+        .filter(|k| !left.contains_key(*k))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_differences_skips_ignored_fields() {
+        let left = json!({"uuid": "1", "title": "A", "statusCode": 10});
+        let right = json!({"uuid": "2", "title": "A", "priorityCode": 20});
+        let diffs = field_differences(&left, &right, &["uuid"]);
+        let fields: Vec<&str> = diffs.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(fields, vec!["priorityCode", "statusCode"]);
+        assert_eq!(diffs[1].right, Value::Null);
+    }
+
+    #[test]
+    fn test_field_changes_of_returned_fields() {
+        let before =
+            json!({"uuid": "f1", "title": "A", "statusCode": "CIPNEW", "modifiedAt": "t1"});
+        let after =
+            json!({"uuid": "f1", "title": "A", "statusCode": "CIPDONE", "modifiedAt": "t2"});
+        assert_eq!(
+            field_changes(&before, &after),
+            vec![FieldChange {
+                field: "statusCode".to_string(),
+                before: json!("CIPNEW"),
+                after: json!("CIPDONE"),
+            }]
+        );
+        // Fields missing from the returned entity are not reported as cleared
+        assert!(field_changes(&before, &json!({"title": "A"})).is_empty());
+    }
+
+    #[test]
+    fn test_diff_record_sets_by_key() {
+        let left = vec![
+            json!({"uuid": "a1", "title": "Invoice", "statusCode": 10}),
+            json!({"uuid": "a2", "title": "Payment", "statusCode": 10}),
+            json!({"uuid": "a3", "title": "Dunning"}),
+        ];
+        let right = vec![
+            json!({"uuid": "b1", "title": "Invoice", "statusCode": 20}),
+            json!({"uuid": "b2", "title": "Payment", "statusCode": 10}),
+            json!({"uuid": "b4", "title": "Treasury"}),
+        ];
+        let diff = diff_record_sets(&left, &right, "title", &["uuid"]);
+        assert_eq!(diff.only_in_left, vec!["Dunning"]);
+        assert_eq!(diff.only_in_right, vec!["Treasury"]);
+        assert_eq!(diff.identical, 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, "Invoice");
+        assert_eq!(diff.changed[0].differences[0].field, "statusCode");
+    }
+}
@@ -62,6 +62,30 @@ pub enum ApiError {
 
     #[error("Failed to create HTTP client: {0}")]
     HttpClientInit(String),
+
+    #[error("Invalid query parameter: {0}")]
+    InvalidQuery(String),
+}
+
+impl ApiError {
+    /// True if the request certainly did not reach SAP Cloud ALM: connection
+    /// and DNS failures, and 502/503 returned by the gateway in front of it.
+    /// Timeouts, 504 and failures reading a response are not included; the
+    /// request may have been applied.
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            ApiError::Request(e) | ApiError::Auth(AuthError::Request(e)) => {
+                e.is_connect() && !e.is_timeout()
+            }
+            ApiError::HttpError { status, .. } | ApiError::ODataError { status, .. } => {
+                matches!(
+                    *status,
+                    StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE
+                )
+            }
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -122,6 +146,28 @@ mod tests {
         assert!(display.contains("Invalid credentials"));
     }
 
+    #[tokio::test]
+    async fn test_api_error_is_unreachable() {
+        // Nothing listens on port 1: the connection is refused
+        let refused = reqwest::get("http://127.0.0.1:1/").await.unwrap_err();
+        assert!(ApiError::Request(refused).is_unreachable());
+
+        let unavailable = ApiError::HttpError {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            body: "maintenance".to_string(),
+        };
+        assert!(unavailable.is_unreachable());
+        for status in [StatusCode::GATEWAY_TIMEOUT, StatusCode::BAD_REQUEST] {
+            let error = ApiError::HttpError {
+                status,
+                body: String::new(),
+            };
+            assert!(!error.is_unreachable());
+        }
+        let parse = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert!(!ApiError::JsonParse(parse).is_unreachable());
+    }
+
     #[test]
     fn test_api_error_http_error_display() {
         let error = ApiError::HttpError {
@@ -0,0 +1,221 @@
+//! Catalog of SAP Cloud ALM API errors with explanations and remediations.
+//!
+//! Entries are matched by code (HTTP status, OData error code, OAuth error)
+//! or by a characteristic fragment of the error message, so both a bare code
+//! like `403` and a complete error message returned by a tool can be looked
+//! up. Keep entries short and actionable; add new ones when a support case
+//! turns up an error that is not covered.
+
+use serde::Serialize;
+
+/// A known error.
+#[derive(Debug, Serialize)]
+pub struct CatalogEntry {
+    pub title: &'static str,
+    /// Codes matched exactly (case-insensitive)
+    pub codes: &'static [&'static str],
+    /// Lowercase message fragments that identify the error
+    #[serde(skip)]
+    pub patterns: &'static [&'static str],
+    pub explanation: &'static str,
+    pub remediation: &'static [&'static str],
+}
+
+pub const CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        title: "Invalid request",
+        codes: &["400", "BadRequest", "InvalidQuery"],
+        patterns: &["invalid query parameter", "syntax error", "invalid filter"],
+        explanation: "The service rejected the request. Usually a $filter/$orderby expression references an unknown property, has a syntax error, or compares a property with a literal of the wrong type.",
+        remediation: &[
+            "Check property names against a record returned by the corresponding get or list tool; names are case-sensitive",
+            "Quote string literals ('OPEN') and leave numbers and booleans unquoted, or enable typed_filter_literals",
+            "Check the request body fields of create/update tools against the tool schema",
+        ],
+    },
+    CatalogEntry {
+        title: "Authentication failed",
+        codes: &["401", "Unauthorized", "invalid_token"],
+        patterns: &["401 unauthorized"],
+        explanation: "The access token or sandbox API key was not accepted.",
+        remediation: &[
+            "OAuth2 mode: verify client_id, client_secret and token_url from the service key of the SAP Cloud ALM API instance",
+            "Sandbox mode: verify the api_key from the SAP Business Accelerator Hub",
+            "If the error appears after a while, check the system clock (token expiry is time-based)",
+        ],
+    },
+    CatalogEntry {
+        title: "Invalid client credentials",
+        codes: &["invalid_client", "unauthorized_client"],
+        patterns: &["token request failed with status 401", "bad credentials"],
+        explanation: "The OAuth2 token endpoint rejected the client ID or secret.",
+        remediation: &[
+            "Copy clientid and clientsecret again from the service key; secrets of rotated keys become invalid",
+            "Make sure token_url points to the authentication endpoint of the same subaccount and region",
+        ],
+    },
+    CatalogEntry {
+        title: "Missing scope or authorization",
+        codes: &["403", "Forbidden", "insufficient_scope"],
+        patterns: &["403 forbidden", "not authorized", "no authorization"],
+        explanation: "The caller is authenticated but lacks the authorization for this API or object. Service keys only carry the scopes selected when the SAP Cloud ALM API instance was created, and some objects are restricted to project members.",
+        remediation: &[
+            "Add the missing scope (e.g. calm-api.features.write) to the API service instance and create a new service key",
+            "For project objects, check that the user is a member of the project team (see verify_project_membership)",
+            "The sandbox only allows read access; write tools need a real tenant",
+        ],
+    },
+    CatalogEntry {
+        title: "Not found",
+        codes: &["404", "NotFound"],
+        patterns: &["404 not found", "not found"],
+        explanation: "The object does not exist, or the URL points to the wrong tenant, region or API.",
+        remediation: &[
+            "Check the UUID or ID; display IDs only work with tools that accept display_id",
+            "Check tenant and region (or api_base_url) in the configuration",
+            "Objects deleted or moved to another project return 404 as well",
+        ],
+    },
+    CatalogEntry {
+        title: "Method not allowed",
+        codes: &["405", "MethodNotAllowed"],
+        patterns: &["method not allowed"],
+        explanation: "The endpoint does not support this operation. The sandbox rejects all write operations, and some entities are read-only.",
+        remediation: &[
+            "Use a real tenant for create, update and delete tools",
+            "Check the API reference on the SAP Business Accelerator Hub for the supported operations",
+        ],
+    },
+    CatalogEntry {
+        title: "Conflict or outdated version",
+        codes: &["409", "412", "Conflict", "PreconditionFailed"],
+        patterns: &["conflict", "precondition failed", "etag"],
+        explanation: "The object was changed concurrently or already exists in the requested state.",
+        remediation: &[
+            "Read the object again and repeat the change on the current version",
+            "For creates, check whether an earlier attempt already succeeded",
+        ],
+    },
+    CatalogEntry {
+        title: "Rate limit exceeded",
+        codes: &["429", "TooManyRequests"],
+        patterns: &["too many requests", "rate limit"],
+        explanation: "Too many requests in a short time. The sandbox has a low request quota per API key.",
+        remediation: &[
+            "Wait a moment and retry",
+            "Prefer filtered queries with $top over paging through full collections",
+        ],
+    },
+    CatalogEntry {
+        title: "Service error",
+        codes: &["500", "InternalServerError"],
+        patterns: &["internal server error"],
+        explanation: "The service failed while processing the request. Often caused by an unexpected payload or a temporary backend problem.",
+        remediation: &[
+            "Retry once; if the error persists, simplify the request (fewer fields, smaller $expand)",
+            "Enable debug mode to capture the request and report it to SAP support with the correlation ID",
+        ],
+    },
+    CatalogEntry {
+        title: "Service unavailable or timed out",
+        codes: &["502", "503", "504", "BadGateway", "ServiceUnavailable", "GatewayTimeout"],
+        patterns: &["service unavailable", "gateway timeout", "bad gateway", "operation timed out"],
+        explanation: "The service is temporarily unavailable (maintenance, overload) or did not answer in time.",
+        remediation: &[
+            "Retry later; check the SAP for Me / SAP Cloud ALM status page for maintenance windows",
+            "For large queries, reduce $top or raise timeout_seconds",
+        ],
+    },
+    CatalogEntry {
+        title: "Wrong region or tenant",
+        codes: &["dns", "ConnectionRefused"],
+        patterns: &["dns error", "failed to lookup address", "connection refused", "error sending request"],
+        explanation: "The host of the API URL could not be reached. Usually the tenant or region in the configuration is wrong.",
+        remediation: &[
+            "Check tenant and region: the API host is <tenant>.<region>.alm.cloud.sap (e.g. mycompany.eu10)",
+            "Behind a proxy, set HTTPS_PROXY for the server process",
+        ],
+    },
+    CatalogEntry {
+        title: "No token available",
+        codes: &["NoToken"],
+        patterns: &["no token available"],
+        explanation: "Sandbox mode is enabled but no api_key is configured.",
+        remediation: &["Set api_key in the configuration, or disable sandbox mode and configure OAuth2 credentials"],
+    },
+    CatalogEntry {
+        title: "Count not supported",
+        codes: &["NoCount"],
+        patterns: &["did not return a count"],
+        explanation: "The service ignored $count, so totals cannot be computed without transferring all records.",
+        remediation: &["Use a list tool with a filter and count the results, or use an analytics provider"],
+    },
+];
+
+/// Look up catalog entries for a code or an error message.
+pub fn explain(query: &str) -> Vec<&'static CatalogEntry> {
+    let query = query.trim();
+    let lower = query.to_lowercase();
+    let tokens: Vec<&str> = query
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    // Exact code match on the whole query wins
+    let exact: Vec<&CatalogEntry> = CATALOG
+        .iter()
+        .filter(|e| e.codes.iter().any(|c| c.eq_ignore_ascii_case(query)))
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    CATALOG
+        .iter()
+        .filter(|e| {
+            e.patterns.iter().any(|p| lower.contains(p))
+                || e.codes
+                    .iter()
+                    .any(|c| tokens.iter().any(|t| t.eq_ignore_ascii_case(c)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(query: &str) -> Vec<&'static str> {
+        explain(query).iter().map(|e| e.title).collect()
+    }
+
+    #[test]
+    fn test_explain_codes_and_messages() {
+        assert_eq!(titles("403"), vec!["Missing scope or authorization"]);
+        assert_eq!(titles("INVALID_CLIENT"), vec!["Invalid client credentials"]);
+        assert_eq!(
+            titles(
+                "Token request failed with status 401 Unauthorized: {\"error\":\"invalid_client\"}"
+            ),
+            vec!["Authentication failed", "Invalid client credentials"]
+        );
+        assert_eq!(
+            titles("HTTP request error: error sending request for url: dns error"),
+            vec!["Wrong region or tenant"]
+        );
+        assert!(explain("something unrelated").is_empty());
+    }
+
+    #[test]
+    fn test_catalog_codes_are_unique() {
+        let mut codes: Vec<String> = CATALOG
+            .iter()
+            .flat_map(|e| e.codes.iter().map(|c| c.to_lowercase()))
+            .collect();
+        let total = codes.len();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), total);
+        assert!(CATALOG.iter().all(|e| !e.remediation.is_empty()));
+    }
+}
@@ -0,0 +1,467 @@
+//! File export of analytics datasets.
+//!
+//! Records are written page by page so large extractions do not have to be
+//! held in memory. The column layout is inferred from the first page; later
+//! records are written against that schema (unknown fields are dropped,
+//! missing fields are empty/null). Without the `analytics` feature only
+//! `csv_field`, used by other modules, is compiled.
+
+#[cfg(feature = "analytics")]
+use std::fs::File;
+#[cfg(feature = "analytics")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "analytics")]
+use std::str::FromStr;
+
+#[cfg(feature = "analytics")]
+use serde::Serialize;
+#[cfg(feature = "analytics")]
+use serde_json::{Map, Value};
+
+/// Output file format.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+#[cfg(feature = "analytics")]
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "parquet" if cfg!(feature = "parquet") => Ok(Self::Parquet),
+            "parquet" => Err(
+                "Parquet export is not available in this build (enable the 'parquet' feature)"
+                    .to_string(),
+            ),
+            other => Err(format!(
+                "Unknown format '{}'. Valid formats: csv, ndjson, parquet",
+                other
+            )),
+        }
+    }
+}
+
+/// Column type inferred from JSON values.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Boolean,
+    Int64,
+    Double,
+    String,
+}
+
+/// A column of the export schema.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Column {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+}
+
+/// Infer columns from a page of records.
+///
+/// Columns are the union of all top-level fields. The type is taken from the
+/// non-null values: integers widen to double when mixed with decimals, any
+/// other mix (and nested values) becomes string.
+#[cfg(feature = "analytics")]
+pub fn infer_columns(records: &[Value]) -> Vec<Column> {
+    // A column only seen with null values has no type yet
+    let mut columns: Vec<(String, Option<ColumnType>)> = Vec::new();
+    for record in records.iter().filter_map(Value::as_object) {
+        for (name, value) in record {
+            let value_type = value_type(value);
+            match columns.iter_mut().find(|(n, _)| n == name) {
+                None => columns.push((name.clone(), value_type)),
+                Some((_, column_type)) => {
+                    *column_type = match (*column_type, value_type) {
+                        (Some(a), Some(b)) => Some(widen(a, b)),
+                        (a, b) => a.or(b),
+                    }
+                }
+            }
+        }
+    }
+    columns
+        .into_iter()
+        .map(|(name, column_type)| Column {
+            name,
+            column_type: column_type.unwrap_or(ColumnType::String),
+        })
+        .collect()
+}
+
+#[cfg(feature = "analytics")]
+fn value_type(value: &Value) -> Option<ColumnType> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(ColumnType::Boolean),
+        Value::Number(n) if n.is_i64() => Some(ColumnType::Int64),
+        Value::Number(_) => Some(ColumnType::Double),
+        _ => Some(ColumnType::String),
+    }
+}
+
+#[cfg(feature = "analytics")]
+fn widen(current: ColumnType, other: ColumnType) -> ColumnType {
+    use ColumnType::*;
+    match (current, other) {
+        (a, b) if a == b => a,
+        (Int64, Double) | (Double, Int64) => Double,
+        _ => String,
+    }
+}
+
+/// Check if a value can be stored in a column of the given type as is. The
+/// schema comes from the first page, so later pages may hold values that do
+/// not fit; those are written as null rather than converted.
+#[cfg(feature = "parquet")]
+fn fits(column_type: ColumnType, value: &Value) -> bool {
+    match column_type {
+        ColumnType::Boolean => value.is_boolean(),
+        ColumnType::Int64 => value.is_i64(),
+        ColumnType::Double => value.is_number(),
+        ColumnType::String => true,
+    }
+}
+
+/// String form of a value for text formats; nested values become JSON.
+#[cfg(feature = "analytics")]
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Streaming writer for one export file.
+#[cfg(feature = "analytics")]
+pub struct ExportWriter {
+    format: ExportFormat,
+    columns: Vec<Column>,
+    out: Option<BufWriter<File>>,
+    #[cfg(feature = "parquet")]
+    parquet: Option<parquet_writer::ParquetWriter>,
+    records: usize,
+}
+
+#[cfg(feature = "analytics")]
+impl ExportWriter {
+    /// Create the output file. The schema is inferred from `first_page`.
+    pub fn create(
+        path: &str,
+        format: ExportFormat,
+        first_page: &[Value],
+    ) -> Result<Self, std::io::Error> {
+        let columns = infer_columns(first_page);
+        let file = File::create(path)?;
+
+        let mut writer = Self {
+            format,
+            columns,
+            out: None,
+            #[cfg(feature = "parquet")]
+            parquet: None,
+            records: 0,
+        };
+        match format {
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => {
+                writer.parquet = Some(parquet_writer::ParquetWriter::new(file, &writer.columns)?);
+            }
+            _ => {
+                let mut out = BufWriter::new(file);
+                if format == ExportFormat::Csv {
+                    let header: Vec<String> =
+                        writer.columns.iter().map(|c| csv_field(&c.name)).collect();
+                    writeln!(out, "{}", header.join(","))?;
+                }
+                writer.out = Some(out);
+            }
+        }
+        Ok(writer)
+    }
+
+    /// Inferred columns.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Append a page of records.
+    pub fn write_page(&mut self, records: &[Value]) -> Result<(), std::io::Error> {
+        let empty = Map::new();
+        let objects: Vec<&Map<String, Value>> = records
+            .iter()
+            .map(|r| r.as_object().unwrap_or(&empty))
+            .collect();
+
+        #[cfg(feature = "parquet")]
+        if let Some(parquet) = self.parquet.as_mut() {
+            parquet.write_rows(&self.columns, &objects)?;
+            self.records += records.len();
+            return Ok(());
+        }
+
+        let Some(out) = self.out.as_mut() else {
+            return Ok(());
+        };
+        for (record, object) in records.iter().zip(&objects) {
+            match self.format {
+                ExportFormat::Csv => {
+                    let fields: Vec<String> = self
+                        .columns
+                        .iter()
+                        .map(|c| {
+                            csv_field(&value_text(object.get(&c.name).unwrap_or(&Value::Null)))
+                        })
+                        .collect();
+                    writeln!(out, "{}", fields.join(","))?;
+                }
+                _ => writeln!(out, "{}", record)?,
+            }
+        }
+        self.records += records.len();
+        Ok(())
+    }
+
+    /// Flush and close the file, returning the number of records written.
+    pub fn finish(self) -> Result<usize, std::io::Error> {
+        #[cfg(feature = "parquet")]
+        if let Some(parquet) = self.parquet {
+            parquet.close()?;
+        }
+        if let Some(mut out) = self.out {
+            out.flush()?;
+        }
+        Ok(self.records)
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_writer {
+    //! Parquet output using one row group per page.
+
+    use std::fs::File;
+    use std::sync::Arc;
+
+    use parquet::basic::{Compression, LogicalType, Repetition, Type as PhysicalType};
+    use parquet::column::writer::ColumnWriter;
+    use parquet::data_type::ByteArray;
+    use parquet::errors::ParquetError;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::types::Type;
+    use serde_json::{Map, Value};
+
+    use super::{fits, value_text, Column, ColumnType};
+
+    fn io_error(e: ParquetError) -> std::io::Error {
+        std::io::Error::other(e)
+    }
+
+    pub struct ParquetWriter {
+        writer: SerializedFileWriter<File>,
+    }
+
+    impl ParquetWriter {
+        pub fn new(file: File, columns: &[Column]) -> Result<Self, std::io::Error> {
+            let fields = columns
+                .iter()
+                .map(|column| {
+                    let (physical, logical) = match column.column_type {
+                        ColumnType::Boolean => (PhysicalType::BOOLEAN, None),
+                        ColumnType::Int64 => (PhysicalType::INT64, None),
+                        ColumnType::Double => (PhysicalType::DOUBLE, None),
+                        ColumnType::String => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+                    };
+                    Type::primitive_type_builder(&column.name, physical)
+                        .with_repetition(Repetition::OPTIONAL)
+                        .with_logical_type(logical)
+                        .build()
+                        .map(Arc::new)
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(io_error)?;
+            let schema = Type::group_type_builder("schema")
+                .with_fields(fields)
+                .build()
+                .map_err(io_error)?;
+            let properties = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build();
+            let writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+                .map_err(io_error)?;
+            Ok(Self { writer })
+        }
+
+        pub fn write_rows(
+            &mut self,
+            columns: &[Column],
+            rows: &[&Map<String, Value>],
+        ) -> Result<(), std::io::Error> {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            let mut row_group = self.writer.next_row_group().map_err(io_error)?;
+            for column in columns {
+                let Some(mut writer) = row_group.next_column().map_err(io_error)? else {
+                    break;
+                };
+                let values: Vec<Option<&Value>> = rows
+                    .iter()
+                    .map(|row| {
+                        row.get(&column.name)
+                            .filter(|v| !v.is_null() && fits(column.column_type, v))
+                    })
+                    .collect();
+                let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+                let present = values.iter().flatten();
+
+                let written = match writer.untyped() {
+                    ColumnWriter::BoolColumnWriter(w) => {
+                        let data: Vec<bool> = present.filter_map(|v| v.as_bool()).collect();
+                        w.write_batch(&data, Some(&levels), None)
+                    }
+                    ColumnWriter::Int64ColumnWriter(w) => {
+                        let data: Vec<i64> = present.filter_map(|v| v.as_i64()).collect();
+                        w.write_batch(&data, Some(&levels), None)
+                    }
+                    ColumnWriter::DoubleColumnWriter(w) => {
+                        let data: Vec<f64> = present.filter_map(|v| v.as_f64()).collect();
+                        w.write_batch(&data, Some(&levels), None)
+                    }
+                    ColumnWriter::ByteArrayColumnWriter(w) => {
+                        let data: Vec<ByteArray> = present
+                            .map(|v| ByteArray::from(value_text(v).as_str()))
+                            .collect();
+                        w.write_batch(&data, Some(&levels), None)
+                    }
+                    _ => Ok(0),
+                };
+                written.map_err(io_error)?;
+                writer.close().map_err(io_error)?;
+            }
+            row_group.close().map_err(io_error)?;
+            Ok(())
+        }
+
+        pub fn close(self) -> Result<(), std::io::Error> {
+            self.writer.close().map_err(io_error)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "analytics"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_infer_columns_widens_types() {
+        let records = vec![
+            json!({"id": 1, "score": 1, "done": true, "note": null}),
+            json!({"id": 2, "score": 2.5, "done": "n/a", "note": 3, "extra": {"a": 1}}),
+        ];
+        let columns = infer_columns(&records);
+        let types: Vec<(&str, ColumnType)> = columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.column_type))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("done", ColumnType::String),
+                ("id", ColumnType::Int64),
+                ("note", ColumnType::Int64),
+                ("score", ColumnType::Double),
+                ("extra", ColumnType::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!("CSV".parse::<ExportFormat>(), Ok(ExportFormat::Csv));
+        assert_eq!("jsonl".parse::<ExportFormat>(), Ok(ExportFormat::Ndjson));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+        assert_eq!(
+            "parquet".parse::<ExportFormat>().is_ok(),
+            cfg!(feature = "parquet")
+        );
+    }
+
+    #[test]
+    fn test_csv_export_uses_first_page_schema() {
+        let path =
+            std::env::temp_dir().join(format!("sap_calm_export_test_{}.csv", std::process::id()));
+        let first = vec![json!({"id": 1, "title": "a, \"quoted\""})];
+        let mut writer =
+            ExportWriter::create(path.to_str().unwrap(), ExportFormat::Csv, &first).unwrap();
+        writer.write_page(&first).unwrap();
+        writer
+            .write_page(&[json!({"id": 2, "unknown": true})])
+            .unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(content, "id,title\n1,\"a, \"\"quoted\"\"\"\n2,\n");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_values_not_fitting_the_column_type() {
+        assert!(fits(ColumnType::Int64, &json!(3)));
+        assert!(!fits(ColumnType::Int64, &json!(2.5)));
+        assert!(!fits(ColumnType::Int64, &json!(u64::MAX)));
+        assert!(!fits(ColumnType::Boolean, &json!("yes")));
+        assert!(fits(ColumnType::Double, &json!(2)));
+        assert!(!fits(ColumnType::Double, &json!("2.5")));
+        assert!(fits(ColumnType::String, &json!({"a": 1})));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_export_writes_all_rows() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let path = std::env::temp_dir().join(format!(
+            "sap_calm_export_test_{}.parquet",
+            std::process::id()
+        ));
+        let page = vec![
+            json!({"id": 1, "title": "a", "score": 1.5}),
+            json!({"id": 2, "title": null, "score": 2}),
+        ];
+        let mut writer =
+            ExportWriter::create(path.to_str().unwrap(), ExportFormat::Parquet, &page).unwrap();
+        writer.write_page(&page).unwrap();
+        writer.write_page(&page[..1]).unwrap();
+        assert_eq!(writer.finish().unwrap(), 3);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+    }
+}
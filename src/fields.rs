@@ -0,0 +1,330 @@
+//! Field-level helpers for tool inputs and outputs.
+//!
+//! Tenants often model organization-specific attributes as custom fields
+//! (e.g. `customField01` holding the SAP module). Aliases let tools accept and
+//! return the organization's vocabulary instead of the technical field names.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+/// Tool arguments that contain field names and are translated from aliases.
+const ALIASED_ARGUMENTS: &[&str] = &[
+    "filter",
+    "select",
+    "orderby",
+    "expand",
+    "include_fields",
+    "exclude_fields",
+];
+
+/// Bidirectional mapping between alias names and API field names.
+#[derive(Debug, Clone, Default)]
+pub struct FieldAliases {
+    to_field: HashMap<String, String>,
+    to_alias: HashMap<String, String>,
+}
+
+impl FieldAliases {
+    /// Build from a config map of `alias -> API field name`.
+    pub fn new(aliases: &HashMap<String, String>) -> Self {
+        Self {
+            to_field: aliases.clone(),
+            to_alias: aliases
+                .iter()
+                .map(|(alias, field)| (field.clone(), alias.clone()))
+                .collect(),
+        }
+    }
+
+    /// True if no aliases are configured.
+    pub fn is_empty(&self) -> bool {
+        self.to_field.is_empty()
+    }
+
+    /// Replace alias identifiers in an OData expression with API field names.
+    /// String literals are left untouched.
+    pub fn translate_expression(&self, expression: &str) -> String {
+        if self.is_empty() {
+            return expression.to_string();
+        }
+
+        let mut out = String::with_capacity(expression.len());
+        let mut ident = String::new();
+        let mut in_string = false;
+
+        for c in expression.chars() {
+            if in_string {
+                out.push(c);
+                if c == '\'' {
+                    in_string = false;
+                }
+                continue;
+            }
+            if c.is_alphanumeric() || c == '_' {
+                ident.push(c);
+                continue;
+            }
+            self.flush_ident(&mut ident, &mut out);
+            if c == '\'' {
+                in_string = true;
+            }
+            out.push(c);
+        }
+        self.flush_ident(&mut ident, &mut out);
+
+        out
+    }
+
+    fn flush_ident(&self, ident: &mut String, out: &mut String) {
+        if ident.is_empty() {
+            return;
+        }
+        match self.to_field.get(ident.as_str()) {
+            Some(field) => out.push_str(field),
+            None => out.push_str(ident),
+        }
+        ident.clear();
+    }
+
+    /// Translate aliases in the field-bearing arguments of a tool call.
+    pub fn translate_arguments(&self, arguments: &mut Map<String, Value>) {
+        if self.is_empty() {
+            return;
+        }
+        for key in ALIASED_ARGUMENTS {
+            if let Some(Value::String(expr)) = arguments.get_mut(*key) {
+                *expr = self.translate_expression(expr);
+            }
+        }
+    }
+
+    /// Rename API field names to their aliases throughout a JSON value.
+    pub fn rename_output(&self, value: Value) -> Value {
+        if self.is_empty() {
+            return value;
+        }
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        let key = self.to_alias.get(&k).cloned().unwrap_or(k);
+                        (key, self.rename_output(v))
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.rename_output(v)).collect())
+            }
+            other => other,
+        }
+    }
+}
+
+/// Post-deserialization field filter for REST APIs that lack `$select`.
+#[derive(Debug, Clone, Default)]
+pub struct FieldFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl FieldFilter {
+    /// Build from comma-separated include/exclude field lists.
+    pub fn new(include: Option<&str>, exclude: Option<&str>) -> Self {
+        Self {
+            include: split_fields(include),
+            exclude: split_fields(exclude),
+        }
+    }
+
+    /// Apply the filter to a tool result.
+    ///
+    /// `include` keeps only the listed fields on each record (the elements of a
+    /// top-level array or `value` collection, or the object itself). `exclude`
+    /// removes the listed fields at any depth.
+    pub fn apply(&self, value: Value) -> Value {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return value;
+        }
+        let value = self.exclude_fields(value);
+        if self.include.is_empty() {
+            return value;
+        }
+        match value {
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.include_fields(v)).collect())
+            }
+            Value::Object(mut map) if map.get("value").is_some_and(Value::is_array) => {
+                if let Some(Value::Array(items)) = map.remove("value") {
+                    let items = items.into_iter().map(|v| self.include_fields(v)).collect();
+                    map.insert("value".to_string(), Value::Array(items));
+                }
+                Value::Object(map)
+            }
+            other => self.include_fields(other),
+        }
+    }
+
+    /// Apply the filter to a payload whose records are nested in arrays named
+    /// `records_key`, such as the `logRecords` of an OpenTelemetry logs
+    /// payload. `include` keeps only the listed fields on each of these
+    /// records and leaves the envelope intact; `exclude` works as in `apply`.
+    #[cfg(feature = "logs")]
+    pub fn apply_nested(&self, value: Value, records_key: &str) -> Value {
+        let value = self.exclude_fields(value);
+        if self.include.is_empty() {
+            return value;
+        }
+        self.include_nested(value, records_key)
+    }
+
+    #[cfg(feature = "logs")]
+    fn include_nested(&self, value: Value, records_key: &str) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| match v {
+                        Value::Array(items) if k == records_key => {
+                            let items = items.into_iter().map(|v| self.include_fields(v));
+                            (k, Value::Array(items.collect()))
+                        }
+                        v => (k, self.include_nested(v, records_key)),
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|v| self.include_nested(v, records_key))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn include_fields(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .filter(|(k, _)| self.include.contains(k))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn exclude_fields(&self, value: Value) -> Value {
+        if self.exclude.is_empty() {
+            return value;
+        }
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .filter(|(k, _)| !self.exclude.contains(k))
+                    .map(|(k, v)| (k, self.exclude_fields(v)))
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.exclude_fields(v)).collect())
+            }
+            other => other,
+        }
+    }
+}
+
+fn split_fields(list: Option<&str>) -> Vec<String> {
+    list.map(|l| {
+        l.split(',')
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn aliases() -> FieldAliases {
+        FieldAliases::new(&HashMap::from([(
+            "sapModule".to_string(),
+            "customField01".to_string(),
+        )]))
+    }
+
+    #[test]
+    fn test_translate_expression_replaces_identifiers() {
+        assert_eq!(
+            aliases().translate_expression("sapModule eq 'FI' and projectId eq 'p1'"),
+            "customField01 eq 'FI' and projectId eq 'p1'"
+        );
+    }
+
+    #[test]
+    fn test_translate_expression_keeps_string_literals() {
+        assert_eq!(
+            aliases().translate_expression("title eq 'sapModule''s notes'"),
+            "title eq 'sapModule''s notes'"
+        );
+    }
+
+    #[test]
+    fn test_translate_arguments_only_touches_field_arguments() {
+        let mut args = json!({"filter": "sapModule eq 'FI'", "title": "sapModule"})
+            .as_object()
+            .cloned()
+            .unwrap();
+        aliases().translate_arguments(&mut args);
+        assert_eq!(args["filter"], "customField01 eq 'FI'");
+        assert_eq!(args["title"], "sapModule");
+    }
+
+    #[test]
+    fn test_rename_output_is_recursive() {
+        let value = json!({"value": [{"customField01": "FI", "title": "x"}]});
+        assert_eq!(
+            aliases().rename_output(value),
+            json!({"value": [{"sapModule": "FI", "title": "x"}]})
+        );
+    }
+
+    #[test]
+    fn test_field_filter_include_applies_per_record() {
+        let filter = FieldFilter::new(Some("id, title"), None);
+        let value = json!([{"id": "1", "title": "a", "timeboxStartDate": "x"}]);
+        assert_eq!(filter.apply(value), json!([{"id": "1", "title": "a"}]));
+    }
+
+    #[test]
+    fn test_field_filter_exclude_is_recursive() {
+        let filter = FieldFilter::new(None, Some("internalId"));
+        let value = json!({"value": [{"id": "1", "internalId": 7, "owner": {"internalId": 8}}]});
+        assert_eq!(
+            filter.apply(value),
+            json!({"value": [{"id": "1", "owner": {}}]})
+        );
+    }
+
+    #[cfg(feature = "logs")]
+    #[test]
+    fn test_field_filter_include_applies_to_nested_records() {
+        let filter = FieldFilter::new(Some("timeUnixNano, body"), None);
+        let value = json!({"resourceLogs": [{
+            "resource": {"attributes": []},
+            "scopeLogs": [{"logRecords": [
+                {"timeUnixNano": "1", "severityText": "ERROR", "body": {"stringValue": "x"}}
+            ]}]
+        }]});
+        assert_eq!(
+            filter.apply_nested(value, "logRecords"),
+            json!({"resourceLogs": [{
+                "resource": {"attributes": []},
+                "scopeLogs": [{"logRecords": [
+                    {"timeUnixNano": "1", "body": {"stringValue": "x"}}
+                ]}]
+            }]})
+        );
+    }
+}
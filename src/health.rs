@@ -0,0 +1,213 @@
+//! Health and readiness endpoints for the HTTP transport.
+//!
+//! `/healthz` reports liveness of the process. `/readyz` additionally checks
+//! that a valid access token can be obtained and reports when the last SAP
+//! Cloud ALM API call succeeded, so supervisors can restart a bridge whose
+//! credentials stopped working. `/readyz?probe=true` also probes the
+//! Features API with an empty read, catching missing authorizations and
+//! unreachable tenants that a token alone does not reveal. Without the
+//! `http-transport` feature only the recording of API successes is compiled.
+
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "http-transport")]
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "http-transport")]
+use serde::Deserialize;
+#[cfg(feature = "http-transport")]
+use serde_json::{json, Value};
+
+#[cfg(feature = "http-transport")]
+use crate::api::FeaturesClient;
+#[cfg(feature = "http-transport")]
+use crate::auth::OAuth2Client;
+#[cfg(feature = "http-transport")]
+use crate::odata::Probe;
+
+/// Shared timestamps used for health reporting.
+#[derive(Debug, Clone)]
+pub struct HealthState {
+    #[cfg(feature = "http-transport")]
+    started_at: DateTime<Utc>,
+    last_api_success: Arc<RwLock<Option<DateTime<Utc>>>>,
+}
+
+impl HealthState {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "http-transport")]
+            started_at: Utc::now(),
+            last_api_success: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Record a successful SAP Cloud ALM API call.
+    pub fn record_api_success(&self) {
+        if let Ok(mut last) = self.last_api_success.write() {
+            *last = Some(Utc::now());
+        }
+    }
+
+    /// Timestamp of the last successful API call, if any.
+    #[cfg(feature = "http-transport")]
+    pub fn last_api_success(&self) -> Option<DateTime<Utc>> {
+        self.last_api_success.read().ok().and_then(|last| *last)
+    }
+
+    /// Process start time.
+    #[cfg(feature = "http-transport")]
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "http-transport")]
+#[derive(Clone)]
+struct HealthRoutesState {
+    auth_client: OAuth2Client,
+    features: FeaturesClient,
+}
+
+#[cfg(feature = "http-transport")]
+#[derive(Deserialize)]
+struct ReadyzParams {
+    #[serde(default)]
+    probe: bool,
+}
+
+#[cfg(feature = "http-transport")]
+/// Build the `/healthz` and `/readyz` routes. `features` is probed by
+/// `/readyz?probe=true`.
+pub fn routes(auth_client: OAuth2Client, features: FeaturesClient) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(HealthRoutesState {
+            auth_client,
+            features,
+        })
+}
+
+#[cfg(feature = "http-transport")]
+async fn healthz(State(state): State<HealthRoutesState>) -> Json<Value> {
+    let health = state.auth_client.health();
+    Json(json!({
+        "status": "ok",
+        "started_at": health.started_at().to_rfc3339(),
+        "last_api_success": health.last_api_success().map(|t| t.to_rfc3339()),
+    }))
+}
+
+#[cfg(feature = "http-transport")]
+async fn readyz(
+    State(state): State<HealthRoutesState>,
+    Query(params): Query<ReadyzParams>,
+) -> (StatusCode, Json<Value>) {
+    let auth_client = &state.auth_client;
+    let token = auth_client.get_token().await;
+    // Without a token the probe would fail for the same reason
+    let api_probe = match (&token, params.probe) {
+        (Ok(_), true) => Some(
+            state
+                .features
+                .api()
+                .probe("/Features")
+                .await
+                .map_err(|e| e.to_string()),
+        ),
+        _ => None,
+    };
+    let body = readiness_report(
+        token.as_ref().err().map(|e| e.to_string()),
+        auth_client.token_expires_at().await,
+        auth_client.health().last_api_success(),
+        api_probe,
+    );
+    let status = if body["status"] == "ready" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(body))
+}
+
+/// Build the readiness response body. `api_probe` is the result of the
+/// optional API probe.
+#[cfg(feature = "http-transport")]
+fn readiness_report(
+    token_error: Option<String>,
+    token_expires_at: Option<DateTime<Utc>>,
+    last_api_success: Option<DateTime<Utc>>,
+    api_probe: Option<Result<Probe, String>>,
+) -> Value {
+    let ready = token_error.is_none() && !matches!(api_probe, Some(Err(_)));
+    let mut body = json!({
+        "status": if ready { "ready" } else { "unavailable" },
+        "token": {
+            "valid": token_error.is_none(),
+            "expires_at": token_expires_at.map(|t| t.to_rfc3339()),
+            "error": token_error,
+        },
+        "last_api_success": last_api_success.map(|t| t.to_rfc3339()),
+    });
+    if let Some(probe) = api_probe {
+        body["api"] = match probe {
+            Ok(probe) => json!({
+                "reachable": true,
+                "latency_ms": probe.latency_ms,
+                "features": probe.count,
+            }),
+            Err(e) => json!({"reachable": false, "error": e}),
+        };
+    }
+    body
+}
+
+#[cfg(all(test, feature = "http-transport"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_state_records_api_success() {
+        let health = HealthState::new();
+        assert!(health.last_api_success().is_none());
+        health.record_api_success();
+        assert!(health.last_api_success().is_some());
+    }
+
+    #[test]
+    fn test_readiness_report() {
+        let ready = readiness_report(None, None, None, None);
+        assert_eq!(ready["status"], "ready");
+        assert_eq!(ready["token"]["valid"], true);
+        assert!(ready.get("api").is_none());
+
+        let unavailable = readiness_report(Some("No token available".into()), None, None, None);
+        assert_eq!(unavailable["status"], "unavailable");
+        assert_eq!(unavailable["token"]["error"], "No token available");
+
+        let probe = Probe {
+            count: Some(12),
+            latency_ms: 85,
+        };
+        let probed = readiness_report(None, None, None, Some(Ok(probe)));
+        assert_eq!(probed["status"], "ready");
+        assert_eq!(probed["api"]["features"], 12);
+
+        let forbidden = readiness_report(None, None, None, Some(Err("HTTP 403".into())));
+        assert_eq!(forbidden["status"], "unavailable");
+        assert_eq!(forbidden["api"]["reachable"], false);
+    }
+}
@@ -0,0 +1,254 @@
+//! Process hierarchy snapshots and structural comparison.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::processhierarchy::HierarchyNode;
+
+/// Node as stored in a hierarchy snapshot.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SnapshotNode {
+    pub uuid: String,
+    pub display_id: Option<String>,
+    pub title: Option<String>,
+    pub parent_uuid: Option<String>,
+    pub sequence: Option<i32>,
+}
+
+impl From<&HierarchyNode> for SnapshotNode {
+    fn from(node: &HierarchyNode) -> Self {
+        Self {
+            uuid: node.uuid.clone().unwrap_or_default(),
+            display_id: node.display_id.clone(),
+            title: node.title.clone(),
+            parent_uuid: node.parent_node_uuid.clone(),
+            sequence: node.sequence,
+        }
+    }
+}
+
+/// Flattened snapshot of a hierarchy subtree.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HierarchySnapshot {
+    pub root_uuid: String,
+    pub taken_at: String,
+    pub nodes: Vec<SnapshotNode>,
+}
+
+impl HierarchySnapshot {
+    /// Create a snapshot of the given subtree nodes, taken now.
+    pub fn new(root_uuid: &str, nodes: &[HierarchyNode]) -> Self {
+        Self {
+            root_uuid: root_uuid.to_string(),
+            taken_at: chrono::Utc::now().to_rfc3339(),
+            nodes: nodes.iter().map(SnapshotNode::from).collect(),
+        }
+    }
+
+    /// Load a snapshot from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the snapshot to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, content)
+    }
+}
+
+/// Node that changed its parent between two snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MovedNode {
+    pub uuid: String,
+    pub title: Option<String>,
+    pub from_parent: Option<String>,
+    pub to_parent: Option<String>,
+}
+
+/// Node whose title changed between two snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenamedNode {
+    pub uuid: String,
+    pub from_title: Option<String>,
+    pub to_title: Option<String>,
+}
+
+/// Structural differences between two hierarchy snapshots.
+#[derive(Debug, Default, Serialize)]
+pub struct HierarchyDiff {
+    pub added: Vec<SnapshotNode>,
+    pub removed: Vec<SnapshotNode>,
+    pub moved: Vec<MovedNode>,
+    pub renamed: Vec<RenamedNode>,
+}
+
+impl HierarchyDiff {
+    /// True if the snapshots are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+/// Compare an earlier snapshot with the current one.
+pub fn diff_snapshots(before: &HierarchySnapshot, after: &HierarchySnapshot) -> HierarchyDiff {
+    let old: HashMap<&str, &SnapshotNode> =
+        before.nodes.iter().map(|n| (n.uuid.as_str(), n)).collect();
+    let new: HashMap<&str, &SnapshotNode> =
+        after.nodes.iter().map(|n| (n.uuid.as_str(), n)).collect();
+
+    let mut diff = HierarchyDiff::default();
+
+    for node in &after.nodes {
+        match old.get(node.uuid.as_str()) {
+            None => diff.added.push(node.clone()),
+            Some(prev) => {
+                if prev.parent_uuid != node.parent_uuid {
+                    diff.moved.push(MovedNode {
+                        uuid: node.uuid.clone(),
+                        title: node.title.clone(),
+                        from_parent: prev.parent_uuid.clone(),
+                        to_parent: node.parent_uuid.clone(),
+                    });
+                }
+                if prev.title != node.title {
+                    diff.renamed.push(RenamedNode {
+                        uuid: node.uuid.clone(),
+                        from_title: prev.title.clone(),
+                        to_title: node.title.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    diff.removed = before
+        .nodes
+        .iter()
+        .filter(|n| !new.contains_key(n.uuid.as_str()))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+/// Split a slash-separated title path ("Finance/Accounts Payable") into its
+/// segments. Surrounding whitespace and empty segments are ignored.
+pub fn split_title_path(path: &str) -> Vec<String> {
+    path.split('/')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Check if a node title matches a path segment, ignoring case and
+/// differences in whitespace.
+pub fn title_matches(title: Option<&str>, segment: &str) -> bool {
+    let normalize = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    title.is_some_and(|title| normalize(title) == normalize(segment))
+}
+
+/// Server-side filter for nodes whose title may match a path segment.
+/// Case-insensitive, like [`title_matches`], which checks the candidates.
+pub fn title_search_filter(segment: &str) -> String {
+    format!(
+        "contains(tolower(title),'{}')",
+        segment.to_lowercase().replace('\'', "''")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(uuid: &str, title: &str, parent: Option<&str>) -> SnapshotNode {
+        SnapshotNode {
+            uuid: uuid.to_string(),
+            display_id: None,
+            title: Some(title.to_string()),
+            parent_uuid: parent.map(String::from),
+            sequence: None,
+        }
+    }
+
+    fn snapshot(nodes: Vec<SnapshotNode>) -> HierarchySnapshot {
+        HierarchySnapshot {
+            root_uuid: "root".to_string(),
+            taken_at: "2024-01-01T00:00:00Z".to_string(),
+            nodes,
+        }
+    }
+
+    #[test]
+    fn test_identical_snapshots_have_no_diff() {
+        let a = snapshot(vec![
+            node("root", "Finance", None),
+            node("a", "AP", Some("root")),
+        ]);
+        assert!(diff_snapshots(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_detects_all_change_kinds() {
+        let before = snapshot(vec![
+            node("root", "Finance", None),
+            node("a", "Accounts Payable", Some("root")),
+            node("b", "Accounts Receivable", Some("root")),
+            node("c", "Invoice Processing", Some("a")),
+        ]);
+        let after = snapshot(vec![
+            node("root", "Finance", None),
+            node("a", "Payables", Some("root")),
+            node("c", "Invoice Processing", Some("root")),
+            node("d", "Treasury", Some("root")),
+        ]);
+
+        let diff = diff_snapshots(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].uuid, "d");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].uuid, "b");
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].from_parent.as_deref(), Some("a"));
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(diff.renamed[0].to_title.as_deref(), Some("Payables"));
+    }
+
+    #[test]
+    fn test_title_path_segments_match_loosely() {
+        assert_eq!(
+            split_title_path(" Finance / Accounts Payable//Invoice Processing/"),
+            vec!["Finance", "Accounts Payable", "Invoice Processing"]
+        );
+        assert!(split_title_path(" / ").is_empty());
+        assert!(title_matches(Some("Accounts  Payable"), "accounts payable"));
+        assert!(!title_matches(Some("Accounts Payable"), "Accounts"));
+        assert!(!title_matches(None, "Finance"));
+    }
+
+    #[test]
+    fn test_title_search_filter_ignores_case() {
+        assert_eq!(
+            title_search_filter("Accounts PAYABLE"),
+            "contains(tolower(title),'accounts payable')"
+        );
+        assert_eq!(
+            title_search_filter("Partner's Invoices"),
+            "contains(tolower(title),'partner''s invoices')"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Authentication of the MCP endpoint of the HTTP transport.
+//!
+//! `/mcp` exposes every tool, including the write tools, with the server's
+//! SAP Cloud ALM credentials. With `http_token` configured, requests must
+//! send `Authorization: Bearer <token>`. Without a token the server only
+//! listens on loopback addresses, where only local processes reach it.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde_json::json;
+
+use crate::webhook::constant_time_eq;
+
+/// Require the bearer token on every route of `router`.
+pub fn require_token(router: Router, token: &str) -> Router {
+    router.layer(middleware::from_fn_with_state(
+        Arc::<str>::from(token),
+        check_token,
+    ))
+}
+
+async fn check_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    if authorized(request.headers(), &token) {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(json!({"error": "Missing or invalid bearer token"})),
+        )
+            .into_response()
+    }
+}
+
+/// Check the bearer token in the request headers.
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()))
+}
+
+/// Refuse to serve `/mcp` without a token on an address reachable from
+/// other hosts.
+pub fn check_exposure(addr: SocketAddr, token: Option<&str>) -> Result<(), String> {
+    if token.is_none() && !addr.ip().is_loopback() {
+        return Err(format!(
+            "Refusing to serve /mcp on {} without authentication: set http_token or listen on a loopback address such as 127.0.0.1",
+            addr
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_bearer_token_and_exposure() {
+        let mut headers = HeaderMap::new();
+        assert!(!authorized(&headers, "t0ken"));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer t0ken"),
+        );
+        assert!(authorized(&headers, "t0ken"));
+        assert!(!authorized(&headers, "other"));
+
+        let local: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        let public: SocketAddr = "0.0.0.0:8080".parse().unwrap();
+        assert!(check_exposure(local, None).is_ok());
+        assert!(check_exposure(public, None).is_err());
+        assert!(check_exposure(public, Some("t0ken")).is_ok());
+    }
+}
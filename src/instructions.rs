@@ -0,0 +1,150 @@
+//! Server instructions for MCP clients.
+//!
+//! The `instructions` of the server info are generated from the registered
+//! tools and the configuration instead of a fixed sentence: the connected
+//! tenant, the API areas that are available or missing from this build,
+//! whether write tools are offered, and optional features such as tenant
+//! profiles and the offline write queue.
+
+use rmcp::model::Tool;
+
+use crate::config::Config;
+use crate::dedup::WRITE_TOOL_MARKER;
+
+/// API areas with a tool that is registered whenever the area is available.
+const API_AREAS: &[(&str, &str)] = &[
+    ("Features", "list_features"),
+    ("Documents", "list_documents"),
+    ("Tasks", "list_tasks"),
+    ("Projects", "list_projects"),
+    ("Test Management", "list_testcases"),
+    ("Process Hierarchy", "list_hierarchy_nodes"),
+    ("Analytics", "query_analytics_dataset"),
+    ("Process Monitoring", "list_business_processes"),
+    ("Logs", "get_logs"),
+];
+
+/// Check if a tool writes data.
+pub fn is_write_tool(tool: &Tool) -> bool {
+    tool.description
+        .as_deref()
+        .is_some_and(|d| d.contains(WRITE_TOOL_MARKER))
+}
+
+/// Describe the tenant the server is connected to.
+fn tenant_label(config: &Config) -> String {
+    if config.sandbox {
+        "the SAP Business Accelerator Hub sandbox (sample data; write calls may be rejected)"
+            .to_string()
+    } else {
+        match (&config.tenant, &config.region) {
+            (Some(tenant), Some(region)) => format!("tenant '{}' ({})", tenant, region),
+            _ => config.api_base_url(),
+        }
+    }
+}
+
+/// Generate the server instructions.
+pub fn build(tools: &[Tool], config: &Config) -> String {
+    let mut available = Vec::new();
+    let mut missing = Vec::new();
+    for (area, tool) in API_AREAS {
+        if tools.iter().any(|t| t.name == *tool) {
+            available.push(*area);
+        } else {
+            missing.push(*area);
+        }
+    }
+
+    let mut lines = vec![format!(
+        "SAP Cloud ALM MCP Server connected to {}.",
+        tenant_label(config)
+    )];
+    lines.push(format!("Available API areas: {}.", available.join(", ")));
+    if !missing.is_empty() {
+        lines.push(format!(
+            "Not available in this build: {}.",
+            missing.join(", ")
+        ));
+    }
+
+    let write_tools = tools.iter().filter(|t| is_write_tool(t)).count();
+    if config.read_only {
+        lines.push(format!(
+            "Read-only mode: {} tools are available; create, update and delete tools are disabled.",
+            tools.len() - write_tools
+        ));
+    } else {
+        lines.push(format!(
+            "{} tools; {} of them change data in SAP Cloud ALM and require user confirmation before execution.",
+            tools.len(),
+            write_tools
+        ));
+    }
+
+    if !config.profiles.is_empty() {
+        let mut profiles: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        profiles.sort();
+        lines.push(format!(
+            "Additional tenant profiles: {} (pass profile to tools that support it).",
+            profiles.join(", ")
+        ));
+    }
+    if config.write_queue.is_some() && !config.read_only {
+        lines.push(
+            "Create and update calls are queued while the API is unreachable; see list_pending_operations and replay them with flush_pending_operations."
+                .to_string(),
+        );
+    }
+    lines.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn tool(name: &str, description: &str) -> Tool {
+        Tool::new(
+            name.to_string(),
+            description.to_string(),
+            Arc::new(serde_json::Map::new()),
+        )
+    }
+
+    fn config(value: serde_json::Value) -> Config {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_instructions_describe_tenant_areas_and_write_tools() {
+        let tools = vec![
+            tool("list_features", "List features."),
+            tool(
+                "create_feature",
+                "Create a feature. Requires user confirmation before execution.",
+            ),
+            tool("get_logs", "Get logs."),
+        ];
+        let text = build(
+            &tools,
+            &config(serde_json::json!({
+                "tenant": "acme",
+                "region": "eu10",
+                "client_id": "id",
+                "client_secret": "secret"
+            })),
+        );
+        assert!(text.contains("tenant 'acme' (eu10)"));
+        assert!(text.contains("Available API areas: Features, Logs."));
+        assert!(text.contains("Not available in this build: Documents, Tasks,"));
+        assert!(text.contains("3 tools; 1 of them change data"));
+
+        let text = build(
+            &tools,
+            &config(serde_json::json!({"sandbox": true, "api_key": "key", "read_only": true})),
+        );
+        assert!(text.contains("sandbox"));
+        assert!(text.contains("Read-only mode: 2 tools are available"));
+    }
+}
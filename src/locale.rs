@@ -0,0 +1,240 @@
+//! Date and number formatting of rendered reports.
+//!
+//! Reports default to ISO dates and plain numbers. A locale preset (e.g.
+//! `de-DE`) switches to the formats stakeholders expect, and individual
+//! settings can still be overridden in the configuration.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Report locale settings as configured. Unset fields come from the preset.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LocaleConfig {
+    /// Locale preset: iso, en-US, en-GB, de-DE, de-AT, de-CH, fr-FR (default: iso)
+    pub locale: Option<String>,
+    /// chrono format string for dates (e.g. "%d.%m.%Y")
+    pub date_format: Option<String>,
+    /// chrono format string for timestamps (e.g. "%d.%m.%Y %H:%M")
+    pub datetime_format: Option<String>,
+    pub decimal_separator: Option<char>,
+    pub thousands_separator: Option<char>,
+}
+
+/// Resolved report locale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    pub date_format: String,
+    pub datetime_format: String,
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            date_format: "%Y-%m-%d".to_string(),
+            datetime_format: "%Y-%m-%d %H:%M UTC".to_string(),
+            decimal_separator: '.',
+            thousands_separator: None,
+        }
+    }
+}
+
+impl Locale {
+    /// Names of the built-in presets.
+    pub const PRESETS: &'static [&'static str] =
+        &["iso", "en-US", "en-GB", "de-DE", "de-AT", "de-CH", "fr-FR"];
+
+    /// Built-in preset by locale tag (case-insensitive, `_` accepted for `-`).
+    pub fn preset(tag: &str) -> Option<Self> {
+        let (date, datetime, decimal, thousands) =
+            match tag.replace('_', "-").to_lowercase().as_str() {
+                "iso" => return Some(Self::default()),
+                "en-us" => ("%m/%d/%Y", "%m/%d/%Y %I:%M %p UTC", '.', Some(',')),
+                "en-gb" => ("%d/%m/%Y", "%d/%m/%Y %H:%M UTC", '.', Some(',')),
+                "de-de" | "de-at" => ("%d.%m.%Y", "%d.%m.%Y %H:%M UTC", ',', Some('.')),
+                "de-ch" => ("%d.%m.%Y", "%d.%m.%Y %H:%M UTC", '.', Some('\'')),
+                "fr-fr" => ("%d/%m/%Y", "%d/%m/%Y %H:%M UTC", ',', Some('\u{202f}')),
+                _ => return None,
+            };
+        Some(Self {
+            date_format: date.to_string(),
+            datetime_format: datetime.to_string(),
+            decimal_separator: decimal,
+            thousands_separator: thousands,
+        })
+    }
+
+    /// Resolve configured settings on top of their preset.
+    pub fn from_config(config: &LocaleConfig) -> Result<Self, String> {
+        let tag = config.locale.as_deref().unwrap_or("iso");
+        let mut locale = Self::preset(tag).ok_or_else(|| {
+            format!(
+                "Unknown report locale '{}'. Valid locales: {:?}",
+                tag,
+                Self::PRESETS
+            )
+        })?;
+        for (field, format) in [
+            (&mut locale.date_format, &config.date_format),
+            (&mut locale.datetime_format, &config.datetime_format),
+        ] {
+            if let Some(format) = format {
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("Invalid date format '{}'", format));
+                }
+                field.clone_from(format);
+            }
+        }
+        if let Some(separator) = config.decimal_separator {
+            locale.decimal_separator = separator;
+        }
+        if config.thousands_separator.is_some() {
+            locale.thousands_separator = config.thousands_separator;
+        }
+        Ok(locale)
+    }
+
+    /// Locale requested by a tool parameter, or `default` when none was given.
+    pub fn resolve(default: &Locale, param: Option<&str>) -> Result<Self, String> {
+        match param {
+            Some(tag) => Self::preset(tag).ok_or_else(|| {
+                format!(
+                    "Unknown locale '{}'. Valid locales: {:?}",
+                    tag,
+                    Self::PRESETS
+                )
+            }),
+            None => Ok(default.clone()),
+        }
+    }
+
+    /// Format an ISO 8601 timestamp or date. Other values are returned unchanged.
+    pub fn format_timestamp(&self, value: &str) -> String {
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+            timestamp
+                .naive_utc()
+                .format(&self.datetime_format)
+                .to_string()
+        } else if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            date.format(&self.date_format).to_string()
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Format an integer with the thousands separator.
+    pub fn format_integer(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let grouped = self.group_thousands(&digits);
+        if value < 0 {
+            format!("-{}", grouped)
+        } else {
+            grouped
+        }
+    }
+
+    /// Format a number with a fixed number of decimals.
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (int_part, fraction) = match formatted.split_once('.') {
+            Some((int_part, fraction)) => (int_part, Some(fraction)),
+            None => (formatted.as_str(), None),
+        };
+        let mut result = String::new();
+        if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        result.push_str(&self.group_thousands(int_part));
+        if let Some(fraction) = fraction {
+            result.push(self.decimal_separator);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    fn group_thousands(&self, digits: &str) -> String {
+        let Some(separator) = self.thousands_separator else {
+            return digits.to_string();
+        };
+        let mut grouped = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(c);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_format_dates_and_numbers() {
+        let iso = Locale::default();
+        assert_eq!(
+            iso.format_timestamp("2024-06-01T14:30:00+02:00"),
+            "2024-06-01 12:30 UTC"
+        );
+        assert_eq!(iso.format_number(1234567.891, 2), "1234567.89");
+
+        let de = Locale::preset("de_DE").unwrap();
+        assert_eq!(de.format_timestamp("2024-06-01"), "01.06.2024");
+        assert_eq!(
+            de.format_timestamp("2024-06-01T12:30:00Z"),
+            "01.06.2024 12:30 UTC"
+        );
+        assert_eq!(de.format_number(1234567.891, 2), "1.234.567,89");
+        assert_eq!(de.format_integer(-1234), "-1.234");
+        assert_eq!(de.format_number(-0.001, 1), "0,0");
+
+        let us = Locale::preset("en-US").unwrap();
+        assert_eq!(
+            us.format_timestamp("2024-06-01T15:05:00Z"),
+            "06/01/2024 03:05 PM UTC"
+        );
+        assert_eq!(us.format_integer(999), "999");
+        assert_eq!(us.format_timestamp("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn test_resolve_tool_parameter() {
+        let default = Locale::preset("de-CH").unwrap();
+        assert_eq!(Locale::resolve(&default, None).unwrap(), default);
+        assert_eq!(
+            Locale::resolve(&default, Some("en_gb")).unwrap(),
+            Locale::preset("en-GB").unwrap()
+        );
+        let err = Locale::resolve(&default, Some("xx-YY")).unwrap_err();
+        assert!(err.contains("Unknown locale 'xx-YY'"));
+    }
+
+    #[test]
+    fn test_config_overrides_preset() {
+        let config = LocaleConfig {
+            locale: Some("de-DE".to_string()),
+            date_format: Some("%Y/%m/%d".to_string()),
+            thousands_separator: Some(' '),
+            ..Default::default()
+        };
+        let locale = Locale::from_config(&config).unwrap();
+        assert_eq!(locale.format_timestamp("2024-06-01"), "2024/06/01");
+        assert_eq!(locale.format_number(12345.5, 1), "12 345,5");
+
+        let unknown = LocaleConfig {
+            locale: Some("xx-YY".to_string()),
+            ..Default::default()
+        };
+        assert!(Locale::from_config(&unknown).is_err());
+
+        let invalid = LocaleConfig {
+            date_format: Some("%Q".to_string()),
+            ..Default::default()
+        };
+        assert!(Locale::from_config(&invalid).is_err());
+    }
+}
@@ -2,28 +2,86 @@
 //!
 //! Bridges SAP Cloud ALM APIs to the Model Context Protocol.
 
+mod accesslog;
+mod anonymize;
 mod api;
+mod archive;
 mod auth;
+mod budget;
+mod calendar;
+mod charm;
+mod checklist;
+#[cfg(feature = "analytics")]
+mod compat;
+mod composite;
 mod config;
 mod debug;
+mod dedup;
+mod defaults;
+mod diff;
 mod error;
+mod error_catalog;
+mod export;
+mod fields;
+mod health;
+mod hierarchy;
+#[cfg(feature = "http-transport")]
+mod httpauth;
+mod instructions;
+mod locale;
+mod membership;
+mod metadata;
 mod odata;
+mod pagination;
+mod pins;
+mod queue;
+mod readiness;
+mod refmap;
+mod reports;
+mod sanitize;
 mod server;
+mod template;
+mod transform;
+mod translate;
+mod traversal;
+mod verify;
+mod watchlist;
+mod webhook;
+mod workflow;
+mod workshop;
+mod workspace;
 
 use std::sync::Arc;
 
-use clap::Parser;
-use rmcp::{transport::stdio, ServiceExt};
+use clap::{Parser, Subcommand};
+use rmcp::model::{CallToolRequestParam, RawContent};
+#[cfg(feature = "http-transport")]
+use rmcp::transport::streamable_http_server::{
+    session::local::LocalSessionManager, StreamableHttpService,
+};
+use rmcp::{transport::stdio, ServiceError, ServiceExt};
 
+#[cfg(feature = "analytics")]
+use crate::api::AnalyticsClient;
+#[cfg(feature = "logs")]
+use crate::api::LogsClient;
+#[cfg(feature = "processmonitoring")]
+use crate::api::ProcessMonitoringClient;
 use crate::api::{
-    AnalyticsClient, DocumentsClient, FeaturesClient, LogsClient, ProcessHierarchyClient,
-    ProcessMonitoringClient, ProjectsClient, TasksClient, TestManagementClient,
+    DocumentsClient, FeaturesClient, ProcessHierarchyClient, ProjectsClient, TasksClient,
+    TestManagementClient,
 };
 use crate::auth::OAuth2Client;
 use crate::config::Config;
+#[cfg(feature = "http-transport")]
+use crate::config::WebhookConfig;
 use crate::debug::DebugLogger;
+use crate::membership::ProjectAccess;
 use crate::odata::ODataClient;
+use crate::queue::WriteQueue;
 use crate::server::{ApiClients, SapCloudAlmServer};
+#[cfg(feature = "http-transport")]
+use crate::webhook::WebhookInbox;
 
 #[derive(Parser, Debug)]
 #[command(name = "sap-cloud-alm-mcp")]
@@ -36,16 +94,95 @@ struct Args {
     /// Enable debug mode (logs all MCP messages)
     #[arg(short, long)]
     debug: bool,
+
+    /// Serve MCP over streamable HTTP on this address (e.g. 127.0.0.1:8080)
+    /// instead of stdio. Also exposes /healthz and /readyz.
+    #[arg(long, value_name = "ADDR")]
+    http: Option<String>,
+
+    /// Compare the configuration (a sandbox configuration) with this
+    /// production configuration, check scopes and API access on the
+    /// production tenant, print a migration checklist and exit.
+    #[arg(long, value_name = "PRODUCTION_CONFIG")]
+    check_production_readiness: Option<String>,
+
+    /// Replay the recorded API response fixtures against the data models,
+    /// print a compatibility report and exit.
+    #[cfg(feature = "analytics")]
+    #[arg(long)]
+    check_api_compatibility: bool,
+
+    /// With --check-api-compatibility: also compare against the live
+    /// $metadata of the configured tenant.
+    #[cfg(feature = "analytics")]
+    #[arg(long, requires = "check_api_compatibility")]
+    live: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Call one tool, print its result and exit. The call goes through the
+    /// MCP server like a call from a client; the process exits with status 1
+    /// if the tool reports an error.
+    Call {
+        /// Tool name (e.g. list_features)
+        tool: String,
+
+        /// Tool arguments as a JSON object
+        #[arg(long, default_value = "{}")]
+        params: String,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
+    #[cfg(feature = "analytics")]
+    if args.check_api_compatibility {
+        let live_metadata = if args.live {
+            let config = Config::load(&args.config)?;
+            let auth_client = OAuth2Client::new(config.clone())?;
+            let clients = build_clients(&config, &auth_client, args.debug)?;
+            Some(clients.analytics.metadata().await?)
+        } else {
+            None
+        };
+        let findings = compat::check(live_metadata.as_deref());
+        print!("{}", compat::render(&findings, args.live));
+        let breaking = findings
+            .iter()
+            .any(|f| f.severity == compat::Severity::Breaking);
+        std::process::exit(if breaking { 1 } else { 0 });
+    }
+
     // Load configuration
     let config = Config::load(&args.config)?;
+    // stdout carries the protocol on stdio and the result of `call`
+    if config.access_log.as_deref() == Some("stdout")
+        && (args.http.is_none() || args.command.is_some())
+    {
+        return Err(
+            "access_log \"stdout\" is only available with --http; use \"stderr\" or a file".into(),
+        );
+    }
     let debug_enabled = args.debug || config.debug;
 
+    if let Some(production_path) = &args.check_production_readiness {
+        let production = Config::load(production_path)?;
+        let auth_client = OAuth2Client::new(production.clone())?;
+        let clients = build_clients(&production, &auth_client, debug_enabled)?;
+        let items = readiness::run(&config, &production, &auth_client, &clients).await;
+        print!("{}", readiness::render(&items));
+        let failed = items
+            .iter()
+            .any(|i| i.status == readiness::CheckStatus::Fail);
+        std::process::exit(if failed { 1 } else { 0 });
+    }
+
     // Initialize debug logger
     let debug = Arc::new(DebugLogger::new(debug_enabled));
 
@@ -65,6 +202,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "Region: {}",
                 config.region.as_deref().unwrap_or("N/A")
             ));
+            if config.api_base_url.is_some() {
+                debug.log(&format!("Base URL: {}", config.api_base_url()));
+            }
+            if let Some(url) = &config.token_url {
+                debug.log(&format!("Token URL override: {}", url));
+            }
         }
         if let Some(path) = debug.trace_path() {
             tracing::info!(path = %path.display(), "Trace file created");
@@ -75,47 +218,155 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let auth_client = OAuth2Client::new(config.clone())?;
 
     // Create API clients
+    let clients = build_clients(&config, &auth_client, debug_enabled)?;
+    #[cfg(feature = "http-transport")]
+    let readiness_probe = clients.features.clone();
+
+    // Clients for additional tenant profiles
+    let mut profiles = std::collections::HashMap::new();
+    for name in config.profiles.keys() {
+        let profile_config = config.profile(name)?;
+        let profile_auth = OAuth2Client::new(profile_config.clone())?;
+        profiles.insert(
+            name.clone(),
+            build_clients(&profile_config, &profile_auth, debug_enabled)?,
+        );
+        if debug_enabled {
+            debug.log(&format!(
+                "Profile '{}': {}",
+                name,
+                profile_config.api_base_url()
+            ));
+        }
+    }
+
+    let mut server =
+        SapCloudAlmServer::new(clients, debug.clone(), &config).with_profiles(profiles);
+    if config.verify_project_membership {
+        server = server.with_project_access(ProjectAccess::new(auth_client.clone()));
+    }
+    if let Some(queue_config) = &config.write_queue {
+        server = server.with_write_queue(WriteQueue::load(queue_config, config.read_only)?);
+    }
+
+    if let Some(Command::Call { tool, params }) = &args.command {
+        let failed = call_tool(server, tool, params).await?;
+        std::process::exit(if failed { 1 } else { 0 });
+    }
+
+    // Webhook relay (HTTP transport only)
+    #[cfg(feature = "http-transport")]
+    let webhook = match (&config.webhook, &args.http) {
+        (Some(webhook), Some(_)) => {
+            let inbox = WebhookInbox::new(webhook.max_events);
+            server = server.with_webhook_inbox(inbox.clone());
+            Some((webhook.clone(), inbox))
+        }
+        (Some(_), None) => {
+            tracing::warn!("Webhook relay is configured but only available with --http");
+            None
+        }
+        _ => None,
+    };
+    #[cfg(not(feature = "http-transport"))]
+    if config.webhook.is_some() {
+        tracing::warn!("Webhook relay is configured but this build has no HTTP transport");
+    }
+
+    if debug_enabled {
+        debug.log("All API clients initialized");
+    }
+
+    match args.http {
+        #[cfg(feature = "http-transport")]
+        Some(addr) => {
+            let token = config.http_token.as_deref();
+            serve_http(
+                server,
+                auth_client,
+                readiness_probe,
+                token,
+                webhook,
+                &addr,
+                &debug,
+            )
+            .await?
+        }
+        #[cfg(not(feature = "http-transport"))]
+        Some(_) => return Err("--http requires a build with the http-transport feature".into()),
+        None => serve_stdio(server, &debug).await?,
+    }
+
+    if debug_enabled {
+        debug.log("MCP server shutting down");
+    }
+
+    Ok(())
+}
+
+/// Create the API clients for one tenant configuration.
+fn build_clients(
+    config: &Config,
+    auth_client: &OAuth2Client,
+    debug_enabled: bool,
+) -> Result<ApiClients, Box<dyn std::error::Error>> {
     // OData-based clients
     let features_odata = ODataClient::new(
         config.features_api_url(),
         auth_client.clone(),
         debug_enabled,
-    )?;
+    )?
+    .with_version(config.odata_version("features"))
+    .with_typed_literals(config.typed_filter_literals);
     let features_client = FeaturesClient::new(features_odata);
 
     let documents_odata = ODataClient::new(
         config.documents_api_url(),
         auth_client.clone(),
         debug_enabled,
-    )?;
+    )?
+    .with_version(config.odata_version("documents"))
+    .with_typed_literals(config.typed_filter_literals);
     let documents_client = DocumentsClient::new(documents_odata);
 
     let testmanagement_odata = ODataClient::new(
         config.testmanagement_api_url(),
         auth_client.clone(),
         debug_enabled,
-    )?;
+    )?
+    .with_version(config.odata_version("testmanagement"))
+    .with_typed_literals(config.typed_filter_literals);
     let testmanagement_client = TestManagementClient::new(testmanagement_odata);
 
     let processhierarchy_odata = ODataClient::new(
         config.processhierarchy_api_url(),
         auth_client.clone(),
         debug_enabled,
-    )?;
+    )?
+    .with_version(config.odata_version("processhierarchy"))
+    .with_typed_literals(config.typed_filter_literals);
     let processhierarchy_client = ProcessHierarchyClient::new(processhierarchy_odata);
 
+    #[cfg(feature = "analytics")]
     let analytics_odata = ODataClient::new(
         config.analytics_api_url(),
         auth_client.clone(),
         debug_enabled,
-    )?;
+    )?
+    .with_version(config.odata_version("analytics"))
+    .with_typed_literals(config.typed_filter_literals);
+    #[cfg(feature = "analytics")]
     let analytics_client = AnalyticsClient::new(analytics_odata);
 
+    #[cfg(feature = "processmonitoring")]
     let processmonitoring_odata = ODataClient::new(
         config.processmonitoring_api_url(),
         auth_client.clone(),
         debug_enabled,
-    )?;
+    )?
+    .with_version(config.odata_version("processmonitoring"))
+    .with_typed_literals(config.typed_filter_literals);
+    #[cfg(feature = "processmonitoring")]
     let processmonitoring_client = ProcessMonitoringClient::new(processmonitoring_odata);
 
     // REST-based clients
@@ -128,41 +379,131 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         debug_enabled,
     )?;
 
+    #[cfg(feature = "logs")]
     let logs_client = LogsClient::new(config.logs_api_url(), auth_client.clone(), debug_enabled)?;
 
-    // Create MCP server
-    let clients = ApiClients {
+    Ok(ApiClients {
         features: features_client,
         documents: documents_client,
         tasks: tasks_client,
         projects: projects_client,
         testmanagement: testmanagement_client,
         processhierarchy: processhierarchy_client,
+        #[cfg(feature = "analytics")]
         analytics: analytics_client,
+        #[cfg(feature = "processmonitoring")]
         processmonitoring: processmonitoring_client,
+        #[cfg(feature = "logs")]
         logs: logs_client,
-    };
+    })
+}
 
-    let server = SapCloudAlmServer::new(clients, debug.clone());
+/// Call one tool on the server through an in-process MCP client and print
+/// the text content of the result. Returns whether the tool reported an
+/// error; errors are printed to stderr.
+async fn call_tool(
+    server: SapCloudAlmServer,
+    tool: &str,
+    params: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let arguments: serde_json::Map<String, serde_json::Value> = serde_json::from_str(params)
+        .map_err(|e| format!("Invalid --params: expected a JSON object ({})", e))?;
 
-    if debug_enabled {
-        debug.log("All API clients initialized");
-        debug.log("Starting MCP server on stdio transport...");
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    let server_task = tokio::spawn(async move {
+        if let Ok(service) = server.serve(server_io).await {
+            let _ = service.waiting().await;
+        }
+    });
+    let client = ().serve(client_io).await?;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: tool.to_string().into(),
+            arguments: Some(arguments),
+            task: None,
+        })
+        .await;
+    client.cancel().await?;
+    server_task.await?;
+
+    let result = match result {
+        Ok(result) => result,
+        Err(ServiceError::McpError(e)) => {
+            eprintln!("{}: {}", tool, e.message);
+            return Ok(true);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let failed = result.is_error == Some(true);
+    for content in &result.content {
+        if let RawContent::Text(text) = &content.raw {
+            if failed {
+                eprintln!("{}", text.text);
+            } else {
+                println!("{}", text.text);
+            }
+        }
     }
+    Ok(failed)
+}
+
+/// Run the MCP server on the stdio transport.
+async fn serve_stdio(
+    server: SapCloudAlmServer,
+    debug: &DebugLogger,
+) -> Result<(), Box<dyn std::error::Error>> {
+    debug.log("Starting MCP server on stdio transport...");
 
-    // Run MCP server on stdio transport
     let service = server.serve(stdio()).await?;
 
-    if debug_enabled {
-        debug.log("MCP server started, waiting for messages...");
-    }
+    debug.log("MCP server started, waiting for messages...");
 
     // Wait for the service to complete
     service.waiting().await?;
 
-    if debug_enabled {
-        debug.log("MCP server shutting down");
+    Ok(())
+}
+
+/// Run the MCP server on the streamable HTTP transport at `/mcp`,
+/// together with the `/healthz` and `/readyz` endpoints. `/mcp` requires
+/// `http_token` as bearer token if one is configured.
+#[cfg(feature = "http-transport")]
+async fn serve_http(
+    server: SapCloudAlmServer,
+    auth_client: OAuth2Client,
+    readiness_probe: FeaturesClient,
+    http_token: Option<&str>,
+    webhook: Option<(WebhookConfig, WebhookInbox)>,
+    addr: &str,
+    debug: &DebugLogger,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mcp_service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+    let mut router = axum::Router::new().nest_service("/mcp", mcp_service);
+    if let Some(token) = http_token {
+        router = httpauth::require_token(router, token);
+    }
+    router = router.merge(health::routes(auth_client, readiness_probe));
+    if let Some((config, inbox)) = webhook {
+        router = router.merge(webhook::routes(&config.path, &config.secret, inbox));
+        debug.log(&format!(
+            "Webhook receiver listening on http://{}{}",
+            addr, config.path
+        ));
     }
 
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    httpauth::check_exposure(listener.local_addr()?, http_token)?;
+    debug.log(&format!("MCP server listening on http://{}/mcp", addr));
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
     Ok(())
 }
@@ -0,0 +1,158 @@
+//! Pre-flight project membership check of write tools.
+//!
+//! When enabled, create and update tools first verify that the target project
+//! exists and that the authenticated user is a member of its team. This turns
+//! the 403/404 responses the services return for inaccessible projects into
+//! an explicit error before anything is written. The user is read from the
+//! claims of the access token; technical clients (client credentials tokens
+//! and sandbox API keys) carry no user, so only the project's existence is
+//! verified for them.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine as _};
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::api::projects::TeamMember;
+use crate::api::ProjectsClient;
+use crate::auth::OAuth2Client;
+use crate::error::ApiError;
+
+/// How long a successful check of a project is reused.
+const VERIFIED_TTL: Duration = Duration::from_secs(600);
+
+/// Token claims identifying a user, in order of preference for messages.
+const USER_CLAIMS: &[&str] = &["user_name", "email", "user_id", "user_uuid"];
+
+/// Verifies project access before write operations.
+pub struct ProjectAccess {
+    auth_client: OAuth2Client,
+    verified: Mutex<HashMap<String, Instant>>,
+}
+
+impl ProjectAccess {
+    pub fn new(auth_client: OAuth2Client) -> Self {
+        Self {
+            auth_client,
+            verified: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check that the project exists and the authenticated user has a role in it.
+    pub async fn verify(&self, projects: &ProjectsClient, project_id: &str) -> Result<(), String> {
+        if let Some(at) = self.verified.lock().await.get(project_id) {
+            if at.elapsed() < VERIFIED_TTL {
+                return Ok(());
+            }
+        }
+
+        match projects.get_project(project_id).await {
+            Ok(_) => {}
+            Err(ApiError::HttpError { status, .. })
+                if status == StatusCode::NOT_FOUND || status == StatusCode::FORBIDDEN =>
+            {
+                return Err(format!(
+                    "Project '{}' does not exist or is not accessible",
+                    project_id
+                ));
+            }
+            Err(e) => {
+                return Err(format!("Could not verify project '{}': {}", project_id, e));
+            }
+        }
+
+        let token = self
+            .auth_client
+            .get_token()
+            .await
+            .map_err(|e| format!("Could not verify project '{}': {}", project_id, e))?;
+        let user = token_user_ids(&token);
+        if !user.is_empty() {
+            let members = projects
+                .list_team_members(project_id)
+                .await
+                .map_err(|e| format!("Could not verify project '{}': {}", project_id, e))?;
+            if !has_role(&members, &user) {
+                return Err(format!(
+                    "User '{}' has no role in project '{}'. Ask a project lead to add you to the project team.",
+                    user[0], project_id
+                ));
+            }
+        }
+
+        self.verified
+            .lock()
+            .await
+            .insert(project_id.to_string(), Instant::now());
+        Ok(())
+    }
+}
+
+/// User identifiers from the claims of a JWT access token. Empty if the token
+/// is not a JWT or was issued to a technical client.
+fn token_user_ids(token: &str) -> Vec<String> {
+    let claims: Option<Value> = token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| BASE64.decode(payload.trim_end_matches('=')).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+    let Some(claims) = claims else {
+        return Vec::new();
+    };
+    USER_CLAIMS
+        .iter()
+        .filter_map(|claim| claims.get(*claim).and_then(Value::as_str))
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Check if any team member with a role matches one of the user identifiers.
+fn has_role(members: &[TeamMember], user: &[String]) -> bool {
+    members.iter().any(|member| {
+        let has_role = member.role.as_deref().is_some_and(|r| !r.is_empty());
+        let matches = [&member.user_id, &member.user_name, &member.email]
+            .into_iter()
+            .flatten()
+            .any(|id| user.iter().any(|u| u.eq_ignore_ascii_case(id)));
+        has_role && matches
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn member(user_name: &str, role: Option<&str>) -> TeamMember {
+        TeamMember {
+            id: None,
+            user_id: None,
+            user_name: Some(user_name.to_string()),
+            email: None,
+            role: role.map(str::to_string),
+            project_id: None,
+        }
+    }
+
+    #[test]
+    fn test_token_user_ids_and_roles() {
+        let payload = BASE64.encode(
+            json!({"user_name": "ANNA", "email": "anna@example.com", "client_id": "sb-1"})
+                .to_string(),
+        );
+        let user = token_user_ids(&format!("eyJhbGciOiJSUzI1NiJ9.{}.sig", payload));
+        assert_eq!(user, vec!["ANNA", "anna@example.com"]);
+
+        let technical = BASE64.encode(json!({"client_id": "sb-1"}).to_string());
+        assert!(token_user_ids(&format!("h.{}.s", technical)).is_empty());
+        assert!(token_user_ids("sandbox-api-key").is_empty());
+
+        assert!(has_role(&[member("anna", Some("Team Member"))], &user));
+        assert!(!has_role(&[member("anna", None)], &user));
+        assert!(!has_role(&[member("ben", Some("Project Lead"))], &user));
+    }
+}
@@ -0,0 +1,332 @@
+//! OData `$metadata` property types and typed filter literals.
+//!
+//! Filters are often written by hand (or by an LLM) with inconsistent quoting,
+//! e.g. `priorityCode eq '10'` for a numeric property or `isDone eq 'true'`
+//! for a boolean. With the property types from the service's `$metadata`,
+//! literals can be rewritten to the form the service expects.
+
+use std::collections::HashMap;
+
+/// Comparison operators whose right-hand side is a literal.
+const COMPARISON_OPERATORS: &[&str] = &["eq", "ne", "gt", "ge", "lt", "le"];
+
+/// EDM property types of a service, keyed by property name.
+///
+/// Property names are merged across all entity types of the service; the first
+/// declaration of a name wins.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyTypes {
+    types: HashMap<String, String>,
+}
+
+impl PropertyTypes {
+    /// Parse property types from a CSDL `$metadata` XML document.
+    pub fn from_metadata_xml(xml: &str) -> Self {
+        let mut types = HashMap::new();
+        for element in xml.split('<').skip(1) {
+            if !element.starts_with("Property ") && !element.starts_with("Property\t") {
+                continue;
+            }
+            if let (Some(name), Some(edm_type)) =
+                (attribute(element, "Name"), attribute(element, "Type"))
+            {
+                types
+                    .entry(name.to_string())
+                    .or_insert_with(|| edm_type.to_string());
+            }
+        }
+        Self { types }
+    }
+
+    /// EDM type of a property. Navigation paths (`toStatus/code`) use the last segment.
+    pub fn get(&self, property: &str) -> Option<&str> {
+        let name = property.rsplit('/').next().unwrap_or(property);
+        self.types.get(name).map(String::as_str)
+    }
+}
+
+/// Properties of each entity type in a CSDL `$metadata` XML document:
+/// entity type name to (property name, EDM type), in declaration order.
+#[cfg(feature = "analytics")]
+pub fn entity_properties(xml: &str) -> HashMap<String, Vec<(String, String)>> {
+    let mut entity_types: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut current = None;
+    for element in xml.split('<').skip(1) {
+        if element.starts_with("EntityType ") {
+            current = attribute(element, "Name").map(str::to_string);
+            if let Some(name) = &current {
+                entity_types.entry(name.clone()).or_default();
+            }
+        } else if element.starts_with("/EntityType") {
+            current = None;
+        } else if element.starts_with("Property ") || element.starts_with("Property\t") {
+            if let (Some(entity_type), Some(name), Some(edm_type)) = (
+                &current,
+                attribute(element, "Name"),
+                attribute(element, "Type"),
+            ) {
+                entity_types
+                    .entry(entity_type.clone())
+                    .or_default()
+                    .push((name.to_string(), edm_type.to_string()));
+            }
+        }
+    }
+    entity_types
+}
+
+/// Read an XML attribute value from the text of a single element.
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let tag_end = element.find('>').unwrap_or(element.len());
+    let tag = &element[..tag_end];
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Space(String),
+    Punct(char),
+}
+
+fn tokenize(filter: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = filter.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c == '\'' {
+            let mut raw = String::from(chars.next().unwrap_or('\''));
+            while let Some(c) = chars.next() {
+                raw.push(c);
+                if c == '\'' {
+                    // Doubled quote is an escaped quote inside the literal
+                    if chars.peek() == Some(&'\'') {
+                        raw.push(chars.next().unwrap_or('\''));
+                    } else {
+                        break;
+                    }
+                }
+            }
+            tokens.push(Token::Quoted(raw));
+        } else if c.is_whitespace() {
+            let mut raw = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_whitespace()) {
+                raw.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Space(raw));
+        } else if matches!(c, '(' | ')' | ',') {
+            tokens.push(Token::Punct(c));
+            chars.next();
+        } else {
+            let mut raw = String::new();
+            while let Some(&c) = chars
+                .peek()
+                .filter(|c| !c.is_whitespace() && !matches!(c, '(' | ')' | ',' | '\''))
+            {
+                raw.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(raw));
+        }
+    }
+
+    tokens
+}
+
+/// Rewrite comparison literals in a filter according to property types.
+///
+/// - numbers, booleans, GUIDs and dates are emitted without quotes
+/// - strings are quoted (with embedded quotes escaped), except bare words
+///   that name a property or call a function, e.g. `title eq tolower(code)`
+/// - a bare date compared to a `DateTimeOffset` becomes midnight UTC
+///
+/// Literals that do not fit the property type are left unchanged so the
+/// service reports the error.
+pub fn normalize_filter_literals(filter: &str, types: &PropertyTypes) -> String {
+    let mut tokens = tokenize(filter);
+
+    let significant: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !matches!(t, Token::Space(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    for (position, window) in significant.windows(3).enumerate() {
+        let (prop, op, literal) = (window[0], window[1], window[2]);
+        let (Token::Word(prop), Token::Word(op)) = (&tokens[prop], &tokens[op]) else {
+            continue;
+        };
+        if !COMPARISON_OPERATORS.contains(&op.to_lowercase().as_str()) {
+            continue;
+        }
+        let Some(edm_type) = types.get(prop) else {
+            continue;
+        };
+        if let Token::Word(word) = &tokens[literal] {
+            let is_call = significant
+                .get(position + 3)
+                .is_some_and(|&next| tokens[next] == Token::Punct('('));
+            if is_call || types.get(word).is_some() {
+                continue;
+            }
+        }
+        if let Some(rewritten) = typed_literal(&tokens[literal], edm_type) {
+            tokens[literal] = rewritten;
+        }
+    }
+
+    tokens
+        .into_iter()
+        .map(|t| match t {
+            Token::Word(s) | Token::Quoted(s) | Token::Space(s) => s,
+            Token::Punct(c) => c.to_string(),
+        })
+        .collect()
+}
+
+/// Format a single literal token for the given EDM type.
+fn typed_literal(token: &Token, edm_type: &str) -> Option<Token> {
+    let value = match token {
+        Token::Quoted(raw) => raw[1..raw.len().saturating_sub(1).max(1)].replace("''", "'"),
+        Token::Word(word) if word == "null" => return None,
+        Token::Word(word) => word.clone(),
+        _ => return None,
+    };
+    let bare = |v: &str| Some(Token::Word(v.to_string()));
+
+    match edm_type {
+        "Edm.String" => match token {
+            Token::Word(_) => Some(Token::Quoted(format!("'{}'", value.replace('\'', "''")))),
+            _ => None,
+        },
+        "Edm.Boolean" => match value.to_lowercase().as_str() {
+            v @ ("true" | "false") => bare(v),
+            _ => None,
+        },
+        "Edm.Byte" | "Edm.SByte" | "Edm.Int16" | "Edm.Int32" | "Edm.Int64" => {
+            value.parse::<i64>().ok().and_then(|_| bare(&value))
+        }
+        "Edm.Decimal" | "Edm.Double" | "Edm.Single" => {
+            value.parse::<f64>().ok().and_then(|_| bare(&value))
+        }
+        "Edm.Guid" => (value.len() == 36
+            && value.chars().all(|c| c.is_ascii_hexdigit() || c == '-'))
+        .then(|| Token::Word(value.clone())),
+        "Edm.Date" => chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+            .ok()
+            .and_then(|_| bare(&value)),
+        "Edm.DateTimeOffset" => {
+            if chrono::DateTime::parse_from_rfc3339(&value).is_ok() {
+                bare(&value)
+            } else if chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d").is_ok() {
+                bare(&format!("{}T00:00:00Z", value))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<edmx:Edmx Version="4.0" xmlns:edmx="http://docs.oasis-open.org/odata/ns/edmx">
+  <edmx:DataServices>
+    <Schema Namespace="FeatureService" xmlns="http://docs.oasis-open.org/odata/ns/edm">
+      <EntityType Name="Features">
+        <Key><PropertyRef Name="uuid"/></Key>
+        <Property Name="uuid" Type="Edm.Guid" Nullable="false"/>
+        <Property Name="title" Type="Edm.String" MaxLength="255"/>
+        <Property Name="priorityCode" Type="Edm.Int32"/>
+        <Property Name="isDone" Type="Edm.Boolean"/>
+        <Property Name="modifiedAt" Type="Edm.DateTimeOffset"/>
+        <NavigationProperty Name="toStatus" Type="FeatureService.Status"/>
+      </EntityType>
+      <EntityType Name="Status">
+        <Property Name="code" Type="Edm.String"/>
+      </EntityType>
+    </Schema>
+  </edmx:DataServices>
+</edmx:Edmx>"#;
+
+    fn types() -> PropertyTypes {
+        PropertyTypes::from_metadata_xml(METADATA)
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn test_entity_properties() {
+        let entity_types = entity_properties(METADATA);
+        assert_eq!(entity_types.len(), 2);
+        let features = &entity_types["Features"];
+        assert_eq!(features.len(), 5);
+        assert_eq!(
+            features[2],
+            ("priorityCode".to_string(), "Edm.Int32".to_string())
+        );
+        assert_eq!(entity_types["Status"][0].0, "code");
+    }
+
+    #[test]
+    fn test_parse_metadata_property_types() {
+        let types = types();
+        assert_eq!(types.get("uuid"), Some("Edm.Guid"));
+        assert_eq!(types.get("priorityCode"), Some("Edm.Int32"));
+        assert_eq!(types.get("toStatus/code"), Some("Edm.String"));
+        assert_eq!(types.get("toStatus"), None);
+    }
+
+    #[test]
+    fn test_normalize_numbers_booleans_guids() {
+        assert_eq!(
+            normalize_filter_literals(
+                "priorityCode eq '10' and isDone eq 'TRUE' and uuid eq '0f8fad5b-d9cb-469f-a165-70867728950e'",
+                &types()
+            ),
+            "priorityCode eq 10 and isDone eq true and uuid eq 0f8fad5b-d9cb-469f-a165-70867728950e"
+        );
+    }
+
+    #[test]
+    fn test_normalize_strings_and_dates() {
+        assert_eq!(
+            normalize_filter_literals(
+                "(title eq Invoice) and toStatus/code ne 'CIPDONE' and modifiedAt ge '2024-01-01'",
+                &types()
+            ),
+            "(title eq 'Invoice') and toStatus/code ne 'CIPDONE' and modifiedAt ge 2024-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_normalize_leaves_unknown_and_mismatched_literals() {
+        let filter = "priorityCode eq 'high' and unknownField eq '1' and contains(title, 'x') and title eq null";
+        assert_eq!(normalize_filter_literals(filter, &types()), filter);
+    }
+
+    #[test]
+    fn test_normalize_keeps_property_and_function_operands() {
+        let filter = "title eq toStatus/code and title ne code";
+        assert_eq!(normalize_filter_literals(filter, &types()), filter);
+
+        let filter = "title eq tolower('Invoice') and toStatus/code eq concat(title, 'X')";
+        assert_eq!(normalize_filter_literals(filter, &types()), filter);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_escaped_quotes() {
+        assert_eq!(
+            normalize_filter_literals("title eq 'O''Brien''s'", &types()),
+            "title eq 'O''Brien''s'"
+        );
+    }
+}
@@ -1,12 +1,47 @@
-//! Generic OData v4 client with query builder.
+//! Generic OData client with query builder.
+//!
+//! Speaks OData v4 by default. Services configured as OData v2 get v2 query
+//! options and literals, and their `d`/`d.results` envelopes are unwrapped into
+//! the v4 shape so callers are version-agnostic.
 
 use reqwest::{Client, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::OnceCell;
+
 use crate::auth::OAuth2Client;
+use crate::budget;
 use crate::error::ApiError;
+use crate::metadata::{normalize_filter_literals, PropertyTypes};
+
+/// Time before a failed `$metadata` fetch is retried.
+const METADATA_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Result of probing an API resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Probe {
+    /// Number of matching entities, if the service returned a count
+    pub count: Option<i64>,
+    /// Round-trip time of the request
+    pub latency_ms: u64,
+}
+
+impl Probe {
+    /// The count, or an error if the service of `endpoint` returned none.
+    pub fn required_count(self, endpoint: &str) -> Result<i64, ApiError> {
+        self.count.ok_or_else(|| ApiError::ODataError {
+            status: reqwest::StatusCode::OK,
+            code: "NoCount".to_string(),
+            message: format!("Service did not return a count for {}", endpoint),
+        })
+    }
+}
 
 /// OData query builder for constructing query parameters.
 #[derive(Debug, Default, Clone)]
@@ -19,6 +54,17 @@ pub struct ODataQuery {
     skip: Option<u32>,
     count: bool,
     search: Option<String>,
+    apply: Option<String>,
+    custom: Vec<(String, String)>,
+}
+
+/// OData protocol version spoken by a service.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ODataVersion {
+    V2,
+    #[default]
+    V4,
 }
 
 /// Sort order for $orderby.
@@ -71,12 +117,75 @@ impl ODataQuery {
         self
     }
 
-    /// Build query string for URL.
-    pub fn to_query_string(&self) -> String {
+    /// Add an $apply transformation, e.g. `groupby((status),aggregate($count as count))`.
+    pub fn apply(mut self, transformation: impl Into<String>) -> Self {
+        self.apply = Some(transformation.into());
+        self
+    }
+
+    /// Request the total count of matching entities ($count).
+    pub fn count(mut self) -> Self {
+        self.count = true;
+        self
+    }
+
+    /// Add a custom query option (e.g. `sap-language`), passed through
+    /// unchanged after the system query options. `$`-prefixed names are
+    /// rejected: system query options have their own builder methods, and a
+    /// second `$filter` or `$top` would conflict with them.
+    pub fn custom(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Self, ApiError> {
+        let name = name.into();
+        if name.starts_with('$') {
+            return Err(ApiError::InvalidQuery(format!(
+                "'{}' is a system query option, not a custom query option",
+                name
+            )));
+        }
+        self.custom.push((name, value.into()));
+        Ok(self)
+    }
+
+    /// Add the `custom_params` of a tool call. `$count` is accepted as the
+    /// one system query option without a parameter of its own; other
+    /// `$`-prefixed names are rejected as in [`ODataQuery::custom`].
+    pub fn custom_params(
+        mut self,
+        params: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ApiError> {
+        for (name, value) in params {
+            self = match (name.as_str(), value.as_str()) {
+                ("$count", "true") => self.count(),
+                ("$count", "false") => self,
+                ("$count", _) => {
+                    return Err(ApiError::InvalidQuery(format!(
+                        "$count must be true or false, got '{}'",
+                        value
+                    )))
+                }
+                _ => self.custom(name, value)?,
+            };
+        }
+        Ok(self)
+    }
+
+    /// Build query string for URL using the conventions of the given OData version.
+    pub fn to_query_string_for(&self, version: ODataVersion) -> String {
         let mut params = Vec::new();
 
         if let Some(ref filter) = self.filter {
-            params.push(format!("$filter={}", urlencoding::encode(filter)));
+            let filter = match version {
+                ODataVersion::V4 => filter.clone(),
+                ODataVersion::V2 => v2_filter_literals(filter),
+            };
+            params.push(format!("$filter={}", urlencoding::encode(&filter)));
+        }
+
+        if let Some(ref apply) = self.apply {
+            params.push(format!("$apply={}", urlencoding::encode(apply)));
         }
 
         if let Some(ref select) = self.select {
@@ -110,13 +219,25 @@ impl ODataQuery {
         }
 
         if self.count {
-            params.push("$count=true".to_string());
+            params.push(match version {
+                ODataVersion::V4 => "$count=true".to_string(),
+                ODataVersion::V2 => "$inlinecount=allpages".to_string(),
+            });
         }
 
-        if let Some(ref search) = self.search {
+        // $search has no OData v2 equivalent
+        if let Some(search) = self.search.as_ref().filter(|_| version == ODataVersion::V4) {
             params.push(format!("$search={}", urlencoding::encode(search)));
         }
 
+        for (name, value) in &self.custom {
+            params.push(format!(
+                "{}={}",
+                urlencoding::encode(name),
+                urlencoding::encode(value)
+            ));
+        }
+
         if params.is_empty() {
             String::new()
         } else {
@@ -125,6 +246,89 @@ impl ODataQuery {
     }
 }
 
+/// Rewrite bare v4 date/time literals in a filter into v2 typed literals
+/// (`2024-01-01T00:00:00Z` becomes `datetimeoffset'2024-01-01T00:00:00Z'`).
+/// Quoted string literals are left untouched.
+fn v2_filter_literals(filter: &str) -> String {
+    let mut out = String::with_capacity(filter.len());
+    let mut token = String::new();
+    let mut in_string = false;
+
+    let flush = |token: &mut String, out: &mut String| {
+        if is_datetime_literal(token) {
+            out.push_str(&format!("datetimeoffset'{}'", token));
+        } else {
+            out.push_str(token);
+        }
+        token.clear();
+    };
+
+    for c in filter.chars() {
+        if in_string {
+            out.push(c);
+            if c == '\'' {
+                in_string = false;
+            }
+        } else if c.is_whitespace() || c == '(' || c == ')' || c == ',' || c == '\'' {
+            flush(&mut token, &mut out);
+            in_string = c == '\'';
+            out.push(c);
+        } else {
+            token.push(c);
+        }
+    }
+    flush(&mut token, &mut out);
+
+    out
+}
+
+/// Check whether a token is an ISO 8601 date/time literal like `2024-01-01T10:00:00Z`.
+fn is_datetime_literal(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() >= 19
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes[10] == b'T'
+        && bytes[13] == b':'
+}
+
+/// Unwrap an OData v2 JSON envelope into the v4 shape.
+///
+/// `{"d": {"results": [...], "__count": "5", "__next": "..."}}` becomes
+/// `{"value": [...], "@odata.count": 5, "@odata.nextLink": "..."}` and
+/// `{"d": {...}}` becomes the bare entity.
+fn unwrap_v2_envelope(body: Value) -> Value {
+    let Value::Object(mut root) = body else {
+        return body;
+    };
+    let Some(d) = root.remove("d") else {
+        return Value::Object(root);
+    };
+
+    match d {
+        Value::Array(items) => serde_json::json!({ "value": items }),
+        Value::Object(mut inner) => match inner.remove("results") {
+            Some(results) => {
+                let mut collection = serde_json::Map::new();
+                collection.insert("value".to_string(), results);
+                if let Some(count) = inner.get("__count").and_then(|c| match c {
+                    Value::String(s) => s.parse::<i64>().ok(),
+                    other => other.as_i64(),
+                }) {
+                    collection.insert("@odata.count".to_string(), count.into());
+                }
+                if let Some(next) = inner.remove("__next") {
+                    collection.insert("@odata.nextLink".to_string(), next);
+                }
+                Value::Object(collection)
+            }
+            None => Value::Object(inner),
+        },
+        other => other,
+    }
+}
+
 /// OData v4 collection response wrapper.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ODataCollection<T> {
@@ -140,6 +344,91 @@ pub struct ODataCollection<T> {
     pub value: Vec<T>,
 }
 
+/// Page size of [`read_all`].
+pub const READ_ALL_PAGE_SIZE: u32 = 1000;
+
+/// Number of records after which [`read_all`] stops.
+pub const READ_ALL_LIMIT: usize = 10_000;
+
+/// One page of a collection response.
+pub trait Page {
+    type Item;
+
+    /// Records of the page, and whether the service links a next page.
+    fn into_records(self) -> (Vec<Self::Item>, bool);
+}
+
+impl<T> Page for ODataCollection<T> {
+    type Item = T;
+
+    fn into_records(self) -> (Vec<T>, bool) {
+        (self.value, self.next_link.is_some())
+    }
+}
+
+/// Raw collections, as returned by the analytics clients.
+impl Page for Value {
+    type Item = Value;
+
+    fn into_records(self) -> (Vec<Value>, bool) {
+        match self {
+            Value::Array(items) => (items, false),
+            Value::Object(mut map) => {
+                let has_next = map.get("@odata.nextLink").is_some_and(|l| !l.is_null());
+                match map.remove("value") {
+                    Some(Value::Array(items)) => (items, has_next),
+                    _ => (Vec::new(), false),
+                }
+            }
+            _ => (Vec::new(), false),
+        }
+    }
+}
+
+/// Records read from all pages of a collection.
+#[derive(Debug)]
+pub struct AllPages<T> {
+    pub items: Vec<T>,
+    /// Reading stopped before the last page
+    pub truncated: bool,
+}
+
+/// Read a collection page by page with `$top`/`$skip` until the last page.
+/// Stops early, with `truncated` set, after [`READ_ALL_LIMIT`] records or when
+/// the time budget of the tool call is used up.
+pub async fn read_all<P, F, Fut>(
+    query: ODataQuery,
+    mut fetch: F,
+) -> Result<AllPages<P::Item>, ApiError>
+where
+    P: Page,
+    F: FnMut(ODataQuery) -> Fut,
+    Fut: Future<Output = Result<P, ApiError>>,
+{
+    let mut items = Vec::new();
+    loop {
+        let page_query = query
+            .clone()
+            .top(READ_ALL_PAGE_SIZE)
+            .skip(items.len() as u32);
+        let (records, has_next) = fetch(page_query).await?.into_records();
+        let last = records.is_empty() || (!has_next && records.len() < READ_ALL_PAGE_SIZE as usize);
+        items.extend(records);
+        if last {
+            return Ok(AllPages {
+                items,
+                truncated: false,
+            });
+        }
+        if items.len() >= READ_ALL_LIMIT || budget::exhausted() {
+            return Ok(AllPages {
+                items,
+                truncated: true,
+            });
+        }
+    }
+}
+
 /// OData v4 error response.
 #[derive(Debug, Deserialize)]
 pub struct ODataErrorResponse {
@@ -149,12 +438,31 @@ pub struct ODataErrorResponse {
 #[derive(Debug, Deserialize)]
 pub struct ODataErrorDetail {
     pub code: String,
+    #[serde(deserialize_with = "deserialize_error_message")]
     pub message: String,
     #[serde(default)]
     #[allow(dead_code)]
     details: Vec<ODataErrorItem>,
 }
 
+/// Accept both the v4 string message and the v2 `{"lang": ..., "value": ...}` form.
+fn deserialize_error_message<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Message {
+        Text(String),
+        Localized { value: String },
+    }
+
+    Ok(match Message::deserialize(deserializer)? {
+        Message::Text(text) => text,
+        Message::Localized { value } => value,
+    })
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 struct ODataErrorItem {
@@ -163,7 +471,7 @@ struct ODataErrorItem {
     target: Option<String>,
 }
 
-/// OData v4 client for SAP Cloud ALM APIs.
+/// OData client for SAP Cloud ALM APIs.
 #[derive(Clone)]
 pub struct ODataClient {
     base_url: String,
@@ -171,6 +479,11 @@ pub struct ODataClient {
     auth_client: OAuth2Client,
     debug: bool,
     is_sandbox: bool,
+    version: ODataVersion,
+    typed_literals: bool,
+    property_types: Arc<OnceCell<PropertyTypes>>,
+    /// Time of the last failed `$metadata` fetch
+    metadata_failed_at: Arc<Mutex<Option<Instant>>>,
 }
 
 impl ODataClient {
@@ -191,9 +504,19 @@ impl ODataClient {
             auth_client,
             debug,
             is_sandbox,
+            version: ODataVersion::V4,
+            typed_literals: false,
+            property_types: Arc::new(OnceCell::new()),
+            metadata_failed_at: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Set the OData protocol version spoken by the service.
+    pub fn with_version(mut self, version: ODataVersion) -> Self {
+        self.version = version;
+        self
+    }
+
     /// Get the appropriate auth header name and value.
     /// Returns ("APIKey", token) for sandbox mode, ("Authorization", "Bearer {token}") for OAuth2.
     fn auth_header(&self, token: &str) -> (&'static str, String) {
@@ -204,45 +527,128 @@ impl ODataClient {
         }
     }
 
+    /// Rewrite filter literals using `$metadata` property types.
+    pub fn with_typed_literals(mut self, enabled: bool) -> Self {
+        self.typed_literals = enabled;
+        self
+    }
+
+    /// Property types from the service's `$metadata`, fetched once and cached.
+    /// Returns None if the metadata cannot be retrieved; a failed fetch is
+    /// retried after `METADATA_RETRY_AFTER`.
+    pub async fn property_types(&self) -> Option<&PropertyTypes> {
+        if let Some(types) = self.property_types.get() {
+            return Some(types);
+        }
+        let failed_at = *self
+            .metadata_failed_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if failed_at.is_some_and(|at| at.elapsed() < METADATA_RETRY_AFTER) {
+            return None;
+        }
+        let result = self
+            .property_types
+            .get_or_try_init(|| async {
+                let xml = self.metadata().await?;
+                Ok::<_, ApiError>(PropertyTypes::from_metadata_xml(&xml))
+            })
+            .await;
+        match result {
+            Ok(types) => Some(types),
+            Err(e) => {
+                tracing::warn!(base_url = %self.base_url, error = %e, "Failed to load OData $metadata");
+                *self
+                    .metadata_failed_at
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+                None
+            }
+        }
+    }
+
+    /// The service's `$metadata` document (CSDL XML).
+    pub async fn metadata(&self) -> Result<String, ApiError> {
+        let url = format!("{}/$metadata", self.base_url);
+        self.execute_get_text(&url, "application/xml").await
+    }
+
+    /// Apply typed literal normalization to the query filter, if enabled.
+    /// Only OData v4 services are normalized; v2 literal syntax differs.
+    async fn prepare_query(&self, query: Option<ODataQuery>) -> Option<ODataQuery> {
+        let mut query = query?;
+        if self.typed_literals && self.version == ODataVersion::V4 && query.filter.is_some() {
+            if let Some(types) = self.property_types().await {
+                query.filter = query.filter.map(|f| normalize_filter_literals(&f, types));
+            }
+        }
+        Some(query)
+    }
+
     /// GET collection with OData query.
     pub async fn get_collection<T: DeserializeOwned>(
         &self,
         endpoint: &str,
         query: Option<ODataQuery>,
     ) -> Result<ODataCollection<T>, ApiError> {
+        let query = self.prepare_query(query).await;
         let url = format!(
             "{}{}{}",
             self.base_url,
             endpoint,
-            query.map(|q| q.to_query_string()).unwrap_or_default()
+            query
+                .map(|q| q.to_query_string_for(self.version))
+                .unwrap_or_default()
         );
 
         self.execute_get(&url).await
     }
 
     /// GET collection as raw JSON value.
+    #[cfg(any(feature = "analytics", feature = "processmonitoring"))]
     pub async fn get_collection_raw(
         &self,
         endpoint: &str,
         query: Option<ODataQuery>,
     ) -> Result<Value, ApiError> {
+        let query = self.prepare_query(query).await;
         let url = format!(
             "{}{}{}",
             self.base_url,
             endpoint,
-            query.map(|q| q.to_query_string()).unwrap_or_default()
+            query
+                .map(|q| q.to_query_string_for(self.version))
+                .unwrap_or_default()
         );
 
         self.execute_get(&url).await
     }
 
-    /// GET single entity by UUID key.
-    pub async fn get_entity_by_uuid<T: DeserializeOwned>(
-        &self,
-        endpoint: &str,
-        uuid: &str,
-    ) -> Result<T, ApiError> {
-        let url = format!("{}{}/{}", self.base_url, endpoint, uuid);
+    /// Probe an entity set with `$top=0` and `$count=true`: verifies that
+    /// the client may read it and returns the number of matching entities
+    /// without transferring any. A GET of an empty page is used instead of
+    /// HEAD, which OData services do not reliably support.
+    pub async fn probe(&self, endpoint: &str, filter: Option<String>) -> Result<Probe, ApiError> {
+        let mut query = ODataQuery::new().top(0).count();
+        if let Some(filter) = filter {
+            query = query.filter(filter);
+        }
+        let started = Instant::now();
+        let collection: ODataCollection<Value> = self.get_collection(endpoint, Some(query)).await?;
+        Ok(Probe {
+            count: collection.count,
+            latency_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Count entities matching an optional filter without transferring them.
+    pub async fn count(&self, endpoint: &str, filter: Option<String>) -> Result<i64, ApiError> {
+        self.probe(endpoint, filter).await?.required_count(endpoint)
+    }
+
+    /// GET the entity at a path that includes its key.
+    pub async fn get_entity<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        let url = format!("{}{}", self.base_url, path);
         self.execute_get(&url).await
     }
 
@@ -263,7 +669,7 @@ impl ODataClient {
     }
 
     /// POST create entity.
-    pub async fn create_entity<T: DeserializeOwned, B: Serialize>(
+    pub async fn create_entity<T: DeserializeOwned, B: Serialize + ?Sized>(
         &self,
         endpoint: &str,
         body: &B,
@@ -272,20 +678,19 @@ impl ODataClient {
         self.execute_post(&url, body).await
     }
 
-    /// PATCH update entity by UUID.
-    pub async fn update_entity_by_uuid<T: DeserializeOwned, B: Serialize>(
+    /// PATCH update the entity at a path that includes its key.
+    pub async fn update_entity<T: DeserializeOwned, B: Serialize + ?Sized>(
         &self,
-        endpoint: &str,
-        uuid: &str,
+        path: &str,
         body: &B,
     ) -> Result<T, ApiError> {
-        let url = format!("{}{}/{}", self.base_url, endpoint, uuid);
+        let url = format!("{}{}", self.base_url, path);
         self.execute_patch(&url, body).await
     }
 
-    /// DELETE entity by UUID.
-    pub async fn delete_entity_by_uuid(&self, endpoint: &str, uuid: &str) -> Result<(), ApiError> {
-        let url = format!("{}{}/{}", self.base_url, endpoint, uuid);
+    /// DELETE the entity at a path that includes its key.
+    pub async fn delete_entity(&self, path: &str) -> Result<(), ApiError> {
+        let url = format!("{}{}", self.base_url, path);
         self.execute_delete(&url).await
     }
 
@@ -309,8 +714,35 @@ impl ODataClient {
         self.handle_response(response).await
     }
 
+    /// Execute GET request returning the raw response body.
+    async fn execute_get_text(&self, url: &str, accept: &str) -> Result<String, ApiError> {
+        if self.debug {
+            tracing::debug!(url = %url, "OData GET request");
+        }
+
+        let token = self.auth_client.get_token().await?;
+        let (header_name, header_value) = self.auth_header(&token);
+
+        let response = self
+            .http_client
+            .get(url)
+            .header(header_name, header_value)
+            .header("Accept", accept)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if status.is_success() {
+            self.auth_client.health().record_api_success();
+            Ok(body)
+        } else {
+            self.parse_error_response(status, &body)
+        }
+    }
+
     /// Execute POST request.
-    async fn execute_post<T: DeserializeOwned, B: Serialize>(
+    async fn execute_post<T: DeserializeOwned, B: Serialize + ?Sized>(
         &self,
         url: &str,
         body: &B,
@@ -336,7 +768,7 @@ impl ODataClient {
     }
 
     /// Execute PATCH request.
-    async fn execute_patch<T: DeserializeOwned, B: Serialize>(
+    async fn execute_patch<T: DeserializeOwned, B: Serialize + ?Sized>(
         &self,
         url: &str,
         body: &B,
@@ -380,6 +812,7 @@ impl ODataClient {
 
         let status = response.status();
         if status.is_success() || status == StatusCode::NO_CONTENT {
+            self.auth_client.health().record_api_success();
             Ok(())
         } else {
             let body = response.text().await.unwrap_or_default();
@@ -395,6 +828,7 @@ impl ODataClient {
         let status = response.status();
 
         if status.is_success() {
+            self.auth_client.health().record_api_success();
             let body = response.text().await?;
             if self.debug {
                 let truncated = if body.len() > 500 {
@@ -404,7 +838,13 @@ impl ODataClient {
                 };
                 tracing::debug!(response = %truncated, "OData response received");
             }
-            serde_json::from_str(&body).map_err(|e| {
+            let parsed = match self.version {
+                ODataVersion::V4 => serde_json::from_str(&body),
+                ODataVersion::V2 => serde_json::from_str::<Value>(&body)
+                    .map(unwrap_v2_envelope)
+                    .and_then(serde_json::from_value),
+            };
+            parsed.map_err(|e| {
                 ApiError::JsonParse(serde_json::Error::io(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
                     format!(
@@ -454,16 +894,66 @@ impl std::fmt::Debug for ODataClient {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_read_all_pages_until_short_page() {
+        let total = READ_ALL_PAGE_SIZE as usize + 5;
+        let mut skips = Vec::new();
+        let all = read_all(ODataQuery::new().filter("a eq 1"), |query| {
+            let skip = query.skip.unwrap_or(0) as usize;
+            skips.push(skip);
+            let end = total.min(skip + query.top.unwrap() as usize);
+            async move { Ok(serde_json::json!({"value": (skip..end).collect::<Vec<_>>()})) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(all.items.len(), total);
+        assert!(!all.truncated);
+        assert_eq!(skips, vec![0, READ_ALL_PAGE_SIZE as usize]);
+    }
+
+    #[tokio::test]
+    async fn test_read_all_truncates_at_limit() {
+        let all = read_all(ODataQuery::new(), |query| async move {
+            Ok(ODataCollection {
+                context: None,
+                count: None,
+                next_link: None,
+                value: vec![0; query.top.unwrap() as usize],
+            })
+        })
+        .await
+        .unwrap();
+        assert_eq!(all.items.len(), READ_ALL_LIMIT);
+        assert!(all.truncated);
+    }
+
+    #[test]
+    fn test_probe_required_count() {
+        let probe = Probe {
+            count: Some(7),
+            latency_ms: 3,
+        };
+        assert_eq!(probe.required_count("/Features").unwrap(), 7);
+        let uncounted = Probe {
+            count: None,
+            ..probe
+        };
+        assert!(matches!(
+            uncounted.required_count("/Features"),
+            Err(ApiError::ODataError { code, .. }) if code == "NoCount"
+        ));
+    }
+
     #[test]
     fn test_empty_query_returns_empty_string() {
         let query = ODataQuery::new();
-        assert_eq!(query.to_query_string(), "");
+        assert_eq!(query.to_query_string_for(ODataVersion::V4), "");
     }
 
     #[test]
     fn test_query_with_filter() {
         let query = ODataQuery::new().filter("name eq 'test'");
-        let result = query.to_query_string();
+        let result = query.to_query_string_for(ODataVersion::V4);
         assert!(result.starts_with("?$filter="));
         assert!(result.contains("name%20eq%20%27test%27"));
     }
@@ -471,25 +961,37 @@ mod tests {
     #[test]
     fn test_query_with_select() {
         let query = ODataQuery::new().select(vec!["name".to_string(), "id".to_string()]);
-        assert_eq!(query.to_query_string(), "?$select=name,id");
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$select=name,id"
+        );
     }
 
     #[test]
     fn test_query_with_expand() {
         let query = ODataQuery::new().expand(vec!["toProject".to_string(), "toStatus".to_string()]);
-        assert_eq!(query.to_query_string(), "?$expand=toProject,toStatus");
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$expand=toProject,toStatus"
+        );
     }
 
     #[test]
     fn test_query_with_orderby_asc() {
         let query = ODataQuery::new().orderby("modifiedAt", SortOrder::Asc);
-        assert_eq!(query.to_query_string(), "?$orderby=modifiedAt asc");
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$orderby=modifiedAt asc"
+        );
     }
 
     #[test]
     fn test_query_with_orderby_desc() {
         let query = ODataQuery::new().orderby("createdAt", SortOrder::Desc);
-        assert_eq!(query.to_query_string(), "?$orderby=createdAt desc");
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$orderby=createdAt desc"
+        );
     }
 
     #[test]
@@ -498,7 +1000,7 @@ mod tests {
             .orderby("status", SortOrder::Asc)
             .orderby("modifiedAt", SortOrder::Desc);
         assert_eq!(
-            query.to_query_string(),
+            query.to_query_string_for(ODataVersion::V4),
             "?$orderby=status asc,modifiedAt desc"
         );
     }
@@ -506,19 +1008,49 @@ mod tests {
     #[test]
     fn test_query_with_top() {
         let query = ODataQuery::new().top(10);
-        assert_eq!(query.to_query_string(), "?$top=10");
+        assert_eq!(query.to_query_string_for(ODataVersion::V4), "?$top=10");
     }
 
     #[test]
     fn test_query_with_skip() {
         let query = ODataQuery::new().skip(20);
-        assert_eq!(query.to_query_string(), "?$skip=20");
+        assert_eq!(query.to_query_string_for(ODataVersion::V4), "?$skip=20");
     }
 
     #[test]
     fn test_query_with_pagination() {
         let query = ODataQuery::new().top(10).skip(20);
-        assert_eq!(query.to_query_string(), "?$top=10&$skip=20");
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$top=10&$skip=20"
+        );
+    }
+
+    #[test]
+    fn test_query_with_count() {
+        let query = ODataQuery::new().top(0).count();
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$top=0&$count=true"
+        );
+    }
+
+    #[test]
+    fn test_query_with_custom_options() {
+        let query = ODataQuery::new()
+            .top(10)
+            .custom("sap-language", "DE")
+            .and_then(|q| q.custom("note", "a b&c"))
+            .unwrap();
+        assert_eq!(
+            query.to_query_string_for(ODataVersion::V4),
+            "?$top=10&sap-language=DE&note=a%20b%26c"
+        );
+        assert!(ODataQuery::new().custom("$filter", "a eq 1").is_err());
+
+        let params = [("$count".to_string(), "true".to_string())];
+        let query = ODataQuery::new().custom_params(params).unwrap();
+        assert_eq!(query.to_query_string_for(ODataVersion::V4), "?$count=true");
     }
 
     #[test]
@@ -528,7 +1060,7 @@ mod tests {
             .select(vec!["id".to_string(), "title".to_string()])
             .orderby("modifiedAt", SortOrder::Desc)
             .top(50);
-        let result = query.to_query_string();
+        let result = query.to_query_string_for(ODataVersion::V4);
         assert!(result.contains("$filter="));
         assert!(result.contains("$select=id,title"));
         assert!(
@@ -541,7 +1073,7 @@ mod tests {
     #[test]
     fn test_filter_url_encoding_special_characters() {
         let query = ODataQuery::new().filter("name eq 'O'Reilly & Sons'");
-        let result = query.to_query_string();
+        let result = query.to_query_string_for(ODataVersion::V4);
         // Should encode quotes and ampersand
         assert!(result.contains("%27"));
         assert!(result.contains("%26"));
@@ -616,6 +1148,73 @@ mod tests {
     fn test_odata_query_clone() {
         let query = ODataQuery::new().filter("test").top(10);
         let cloned = query.clone();
-        assert_eq!(cloned.to_query_string(), query.to_query_string());
+        assert_eq!(
+            cloned.to_query_string_for(ODataVersion::V4),
+            query.to_query_string_for(ODataVersion::V4)
+        );
+    }
+
+    #[test]
+    fn test_v2_query_string() {
+        let query = ODataQuery {
+            count: true,
+            search: Some("invoice".to_string()),
+            ..ODataQuery::new()
+                .filter("modifiedAt gt 2024-01-01T00:00:00Z")
+                .top(5)
+        };
+        let result = query.to_query_string_for(ODataVersion::V2);
+        assert!(result.contains("$inlinecount=allpages"));
+        assert!(!result.contains("$count"));
+        assert!(!result.contains("$search"));
+        assert!(result.contains(&*urlencoding::encode(
+            "modifiedAt gt datetimeoffset'2024-01-01T00:00:00Z'"
+        )));
+    }
+
+    #[test]
+    fn test_v2_filter_literals_skip_strings() {
+        assert_eq!(
+            v2_filter_literals(
+                "title eq '2024-01-01T00:00:00Z' or (dueDate lt 2024-06-30T12:00:00)"
+            ),
+            "title eq '2024-01-01T00:00:00Z' or (dueDate lt datetimeoffset'2024-06-30T12:00:00')"
+        );
+    }
+
+    #[test]
+    fn test_unwrap_v2_collection_envelope() {
+        let body = serde_json::json!({
+            "d": {
+                "results": [{"uuid": "1"}],
+                "__count": "12",
+                "__next": "Features?$skiptoken=1"
+            }
+        });
+        let collection: ODataCollection<Value> =
+            serde_json::from_value(unwrap_v2_envelope(body)).unwrap();
+        assert_eq!(collection.value.len(), 1);
+        assert_eq!(collection.count, Some(12));
+        assert_eq!(
+            collection.next_link.as_deref(),
+            Some("Features?$skiptoken=1")
+        );
+    }
+
+    #[test]
+    fn test_unwrap_v2_entity_envelope() {
+        let body = serde_json::json!({"d": {"uuid": "1", "title": "x"}});
+        assert_eq!(
+            unwrap_v2_envelope(body),
+            serde_json::json!({"uuid": "1", "title": "x"})
+        );
+    }
+
+    #[test]
+    fn test_v2_error_response_deserialization() {
+        let body =
+            r#"{"error": {"code": "SY/530", "message": {"lang": "en", "value": "Not found"}}}"#;
+        let error: ODataErrorResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(error.error.message, "Not found");
     }
 }
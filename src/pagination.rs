@@ -0,0 +1,289 @@
+//! Pagination envelope of list results.
+//!
+//! List tools return OData collections or bare arrays, so an agent cannot
+//! always tell whether it sees all records. The result of every list tool is
+//! wrapped in one envelope: the records under `value`, `returned_count`,
+//! `has_more`, `next_cursor` and, when `$count` was requested and reported,
+//! `total_count`. Passing `next_cursor` as `cursor` to the same tool with
+//! the same arguments fetches the next page.
+//!
+//! `has_more` is exact when a total count or an OData next link is known;
+//! otherwise a full page (as many records as `top` / `limit` asked for) is
+//! taken to mean that more records may follow.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine as _};
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::composite::fingerprint;
+
+/// Argument continuing a list at a cursor.
+pub const CURSOR_ARGUMENT: &str = "cursor";
+
+/// Paging arguments of a list tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paging {
+    /// OData `top` / `skip`
+    TopSkip,
+    /// REST `limit` / `offset`
+    LimitOffset,
+    /// The tool returns all records at once
+    Unpaged,
+}
+
+impl Paging {
+    /// Names of the page size and offset arguments.
+    fn arguments(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Paging::TopSkip => Some(("top", "skip")),
+            Paging::LimitOffset => Some(("limit", "offset")),
+            Paging::Unpaged => None,
+        }
+    }
+}
+
+/// Check if a tool lists records, and how it pages.
+pub fn paging_of(tool: &Tool) -> Option<Paging> {
+    let name = tool.name.as_ref();
+    let is_list = name.starts_with("list_")
+        || name.starts_with("get_analytics_")
+        || name == "query_analytics_dataset";
+    if !is_list {
+        return None;
+    }
+    let properties = tool
+        .input_schema
+        .get("properties")
+        .and_then(Value::as_object);
+    let has = |argument: &str| properties.is_some_and(|p| p.contains_key(argument));
+    Some(if has("top") && has("skip") {
+        Paging::TopSkip
+    } else if has("limit") && has("offset") {
+        Paging::LimitOffset
+    } else {
+        Paging::Unpaged
+    })
+}
+
+/// Decoded cursor: the tool and the arguments of the next page.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    tool: String,
+    /// Fingerprint of the other arguments of the call
+    arguments: String,
+    offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_size: Option<u64>,
+}
+
+fn encode_cursor(cursor: &Cursor) -> String {
+    BASE64.encode(serde_json::to_vec(cursor).unwrap_or_default())
+}
+
+/// Fingerprint of the arguments apart from the cursor and the paging
+/// arguments, which the cursor sets.
+fn arguments_fingerprint(paging: Paging, arguments: &Map<String, Value>) -> String {
+    let mut ignored = vec![CURSOR_ARGUMENT];
+    if let Some((size_argument, offset_argument)) = paging.arguments() {
+        ignored.extend([size_argument, offset_argument]);
+    }
+    fingerprint(&Value::Object(arguments.clone()), &ignored)
+}
+
+/// Remove the `cursor` argument and apply it to the paging arguments.
+///
+/// Fails if the cursor is malformed or was issued by another tool or for
+/// other arguments.
+pub fn take_cursor(
+    tool: &str,
+    paging: Paging,
+    arguments: Option<&mut Map<String, Value>>,
+) -> Result<(), String> {
+    let Some(arguments) = arguments else {
+        return Ok(());
+    };
+    let Some(token) = arguments.remove(CURSOR_ARGUMENT) else {
+        return Ok(());
+    };
+    let Some((size_argument, offset_argument)) = paging.arguments() else {
+        return Err(format!(
+            "{} returns all records and does not accept a cursor",
+            tool
+        ));
+    };
+    let cursor: Cursor = token
+        .as_str()
+        .and_then(|t| BASE64.decode(t).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| "Invalid cursor".to_string())?;
+    if cursor.tool != tool {
+        return Err(format!(
+            "The cursor was issued by {}, not {}",
+            cursor.tool, tool
+        ));
+    }
+    if cursor.arguments != arguments_fingerprint(paging, arguments) {
+        return Err(format!(
+            "The cursor was issued for {} with different arguments",
+            tool
+        ));
+    }
+    arguments.insert(offset_argument.to_string(), json!(cursor.offset));
+    if let Some(page_size) = cursor.page_size {
+        arguments.insert(size_argument.to_string(), json!(page_size));
+    }
+    Ok(())
+}
+
+/// Wrap a list result in the pagination envelope. Results that are neither
+/// an array nor an object with a `value` array are returned unchanged.
+pub fn envelope(
+    tool: &str,
+    paging: Paging,
+    arguments: &Map<String, Value>,
+    result: Value,
+) -> Value {
+    let (items, mut fields) = match result {
+        Value::Array(items) => (items, Map::new()),
+        Value::Object(mut map) => match map.remove("value") {
+            Some(Value::Array(items)) => (items, map),
+            Some(other) => {
+                map.insert("value".to_string(), other);
+                return Value::Object(map);
+            }
+            None => return Value::Object(map),
+        },
+        other => return other,
+    };
+
+    let total_count = fields.remove("@odata.count").and_then(|c| c.as_u64());
+    let next_link = fields.remove("@odata.nextLink").filter(|l| !l.is_null());
+    fields.remove("@odata.context");
+
+    let argument = |name: &str| arguments.get(name).and_then(Value::as_u64);
+    let (page_size, offset) = match paging.arguments() {
+        Some((size_argument, offset_argument)) => (
+            argument(size_argument),
+            argument(offset_argument).unwrap_or(0),
+        ),
+        None => (None, 0),
+    };
+    let returned = items.len() as u64;
+    let has_more = match (total_count, &next_link, page_size) {
+        (Some(total), _, _) => offset + returned < total,
+        (None, Some(_), _) => true,
+        (None, None, Some(page_size)) => returned > 0 && returned >= page_size,
+        (None, None, None) => false,
+    };
+    let next_cursor = (has_more && paging != Paging::Unpaged).then(|| {
+        encode_cursor(&Cursor {
+            tool: tool.to_string(),
+            arguments: arguments_fingerprint(paging, arguments),
+            offset: offset + returned,
+            page_size,
+        })
+    });
+
+    let mut envelope = Map::new();
+    envelope.insert("value".to_string(), Value::Array(items));
+    if let Some(total) = total_count {
+        envelope.insert("total_count".to_string(), json!(total));
+    }
+    envelope.insert("returned_count".to_string(), json!(returned));
+    envelope.insert("has_more".to_string(), json!(has_more));
+    envelope.insert("next_cursor".to_string(), json!(next_cursor));
+    envelope.extend(fields);
+    Value::Object(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn arguments(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_envelope_of_odata_collection_and_array() {
+        let collection = json!({
+            "@odata.context": "$metadata#Features",
+            "@odata.count": 120,
+            "value": [{"uuid": "a"}, {"uuid": "b"}]
+        });
+        let wrapped = envelope(
+            "list_features",
+            Paging::TopSkip,
+            &arguments(json!({"top": 2, "skip": 100})),
+            collection,
+        );
+        assert_eq!(wrapped["total_count"], 120);
+        assert_eq!(wrapped["returned_count"], 2);
+        assert_eq!(wrapped["has_more"], true);
+        assert!(wrapped.get("@odata.context").is_none());
+
+        let mut next = arguments(json!({
+            "top": 5,
+            "cursor": wrapped["next_cursor"].clone()
+        }));
+        take_cursor("list_features", Paging::TopSkip, Some(&mut next)).unwrap();
+        assert_eq!(next, arguments(json!({"top": 2, "skip": 102})));
+
+        // The cursor continues the same query only
+        let mut other = arguments(json!({
+            "filter": "status eq 'OPEN'",
+            "cursor": wrapped["next_cursor"].clone()
+        }));
+        let err = take_cursor("list_features", Paging::TopSkip, Some(&mut other)).unwrap_err();
+        assert!(err.contains("different arguments"));
+
+        let tasks = json!([{"id": "t1"}]);
+        let wrapped = envelope(
+            "list_tasks",
+            Paging::LimitOffset,
+            &arguments(json!({"limit": 50})),
+            tasks,
+        );
+        assert_eq!(wrapped["value"][0]["id"], "t1");
+        assert_eq!(wrapped["has_more"], false);
+        assert_eq!(wrapped["next_cursor"], Value::Null);
+        assert!(wrapped.get("total_count").is_none());
+
+        let summary = json!({"pending": 3});
+        assert_eq!(
+            envelope(
+                "list_pending_operations",
+                Paging::Unpaged,
+                &Map::new(),
+                summary.clone()
+            ),
+            summary
+        );
+    }
+
+    #[test]
+    fn test_cursor_is_bound_to_tool_and_paging_is_detected() {
+        let cursor = encode_cursor(&Cursor {
+            tool: "list_documents".to_string(),
+            arguments: arguments_fingerprint(Paging::TopSkip, &Map::new()),
+            offset: 10,
+            page_size: None,
+        });
+        let mut next = arguments(json!({"cursor": cursor}));
+        assert!(take_cursor("list_features", Paging::TopSkip, Some(&mut next)).is_err());
+        let mut next = arguments(json!({"cursor": "not a cursor"}));
+        assert!(take_cursor("list_features", Paging::TopSkip, Some(&mut next)).is_err());
+
+        let schema = json!({"type": "object", "properties": {"limit": {}, "offset": {}}});
+        let tool = Tool::new(
+            "list_tasks",
+            "List tasks.",
+            Arc::new(schema.as_object().unwrap().clone()),
+        );
+        assert_eq!(paging_of(&tool), Some(Paging::LimitOffset));
+        let tool = Tool::new("get_task", "Get a task.", Arc::new(Map::new()));
+        assert_eq!(paging_of(&tool), None);
+    }
+}
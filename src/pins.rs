@@ -0,0 +1,282 @@
+//! Entities pinned in a session.
+//!
+//! `pin_entity` keeps a compact digest of an entity under discussion (IDs,
+//! title, status, assignee, project, dates and an optional note) instead of
+//! its full payload. `get_session_context` renders all pins as a short text
+//! the agent can call to refresh its memory without fetching the entities
+//! again; pinning an entity again refreshes its digest. Pins belong to the
+//! session: every clone of the server (one per HTTP session) starts with an
+//! empty board.
+
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Maximum number of pinned entities per session.
+pub const MAX_PINS: usize = 25;
+
+/// Entity types that can be pinned: (type, get tool).
+const PIN_TYPES: &[(&str, &str)] = &[
+    ("feature", "get_feature"),
+    ("document", "get_document"),
+    ("task", "get_task"),
+    ("testcase", "get_testcase"),
+    ("hierarchy_node", "get_hierarchy_node"),
+    ("project", "get_project"),
+];
+
+/// Digest fields: (digest field, entity keys in order of preference).
+const DIGEST_FIELDS: &[(&str, &[&str])] = &[
+    ("display_id", &["displayId", "display_id", "externalId"]),
+    ("title", &["title", "name"]),
+    ("status", &["status", "statusCode", "status_code"]),
+    (
+        "assignee",
+        &["assigneeName", "assigneeId", "responsibleId", "responsible"],
+    ),
+    ("project_id", &["projectId", "project_id"]),
+    ("due_date", &["dueDate", "due_date", "plannedDate"]),
+    (
+        "modified_at",
+        &["modifiedAt", "lastChangedDate", "lastChangedAt"],
+    ),
+];
+
+/// Type of a pinned entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinType(&'static str);
+
+impl PinType {
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+
+    /// Get tool reading the entity, and its arguments for an ID. Features
+    /// can be given by display ID.
+    pub fn read_call(self, id: &str) -> (&'static str, &'static str) {
+        let get_tool = PIN_TYPES
+            .iter()
+            .find(|(name, _)| *name == self.0)
+            .map(|(_, tool)| *tool)
+            .unwrap_or_default();
+        let id_argument = match self.0 {
+            "project" => "id",
+            "feature" if !looks_like_uuid(id) => "display_id",
+            _ => "uuid",
+        };
+        (get_tool, id_argument)
+    }
+}
+
+impl FromStr for PinType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.to_lowercase().replace(['-', ' '], "_");
+        let normalized = normalized.trim_end_matches('s');
+        let normalized = if normalized == "test_case" {
+            "testcase"
+        } else {
+            normalized
+        };
+        PIN_TYPES
+            .iter()
+            .find(|(name, _)| *name == normalized)
+            .map(|(name, _)| PinType(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = PIN_TYPES.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "Unknown entity type '{}'. Expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+fn looks_like_uuid(id: &str) -> bool {
+    id.len() == 36 && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// Compact digest of a pinned entity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PinnedEntity {
+    pub entity_type: &'static str,
+    pub id: String,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub pinned_at: String,
+}
+
+/// Build the digest of an entity.
+pub fn digest(
+    entity_type: PinType,
+    id: &str,
+    entity: &Value,
+    note: Option<String>,
+) -> PinnedEntity {
+    let mut fields = serde_json::Map::new();
+    for (field, keys) in DIGEST_FIELDS {
+        let value = keys
+            .iter()
+            .filter_map(|k| entity.get(*k))
+            .find(|v| !v.is_null() && v.as_str() != Some(""));
+        if let Some(value) = value {
+            fields.insert(field.to_string(), value.clone());
+        }
+    }
+    PinnedEntity {
+        entity_type: entity_type.name(),
+        id: id.to_string(),
+        fields,
+        note,
+        pinned_at: Utc::now().to_rfc3339(),
+    }
+}
+
+/// Render pins as a compact text summary, one line per entity.
+pub fn render_context(pins: &[PinnedEntity]) -> String {
+    if pins.is_empty() {
+        return "No entities are pinned in this session.".to_string();
+    }
+    let mut lines = vec![format!("Pinned entities ({}):", pins.len())];
+    for pin in pins {
+        let text = |field: &str| {
+            pin.fields.get(field).map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        };
+        let mut line = format!(
+            "- {} {}",
+            pin.entity_type,
+            text("display_id").unwrap_or_else(|| pin.id.clone())
+        );
+        if let Some(title) = text("title") {
+            line.push_str(&format!(" \"{}\"", title));
+        }
+        let details: Vec<String> = ["status", "assignee", "due_date", "project_id"]
+            .iter()
+            .filter_map(|f| Some(format!("{}: {}", f.replace('_', " "), text(f)?)))
+            .collect();
+        if !details.is_empty() {
+            line.push_str(&format!(" [{}]", details.join(", ")));
+        }
+        if let Some(note) = &pin.note {
+            line.push_str(&format!(" - note: {}", note));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Pinned entities of a session, in the order they were pinned.
+#[derive(Default)]
+pub struct PinBoard {
+    pins: Mutex<Vec<PinnedEntity>>,
+}
+
+/// A cloned server serves a new session, which starts without pins.
+impl Clone for PinBoard {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PinBoard {
+    /// Pin an entity, replacing an earlier digest of the same entity.
+    /// Fails if the board is full.
+    pub fn pin(&self, entity: PinnedEntity) -> Result<(), String> {
+        let mut pins = self.pins.lock().unwrap_or_else(|e| e.into_inner());
+        let existing = pins
+            .iter()
+            .position(|p| p.entity_type == entity.entity_type && p.id == entity.id);
+        match existing {
+            Some(index) => pins[index] = entity,
+            None if pins.len() >= MAX_PINS => {
+                return Err(format!(
+                    "At most {} entities can be pinned; unpin some first",
+                    MAX_PINS
+                ))
+            }
+            None => pins.push(entity),
+        }
+        Ok(())
+    }
+
+    /// Unpin an entity. Returns false if it was not pinned.
+    pub fn unpin(&self, entity_type: PinType, id: &str) -> bool {
+        let mut pins = self.pins.lock().unwrap_or_else(|e| e.into_inner());
+        let before = pins.len();
+        pins.retain(|p| !(p.entity_type == entity_type.name() && p.id == id));
+        pins.len() < before
+    }
+
+    /// Unpin all entities, returning how many were pinned.
+    pub fn clear(&self) -> usize {
+        let mut pins = self.pins.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *pins).len()
+    }
+
+    pub fn list(&self) -> Vec<PinnedEntity> {
+        self.pins.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_digest_and_context() {
+        let feature: PinType = "Features".parse().unwrap();
+        assert_eq!(feature.read_call("6-12"), ("get_feature", "display_id"));
+        assert_eq!(
+            "project".parse::<PinType>().unwrap().read_call("p1"),
+            ("get_project", "id")
+        );
+        assert!("release".parse::<PinType>().is_err());
+
+        let entity = json!({
+            "uuid": "f1",
+            "displayId": "6-12",
+            "title": "Invoice check",
+            "description": "A long description that is not kept",
+            "statusCode": "CIPINPROGRESS",
+            "responsibleId": null,
+            "projectId": "p1"
+        });
+        let pin = digest(feature, "6-12", &entity, Some("blocked by test".into()));
+        assert_eq!(pin.fields.len(), 4);
+        assert!(!pin.fields.contains_key("assignee"));
+
+        let context = render_context(&[pin]);
+        assert_eq!(
+            context,
+            "Pinned entities (1):\n- feature 6-12 \"Invoice check\" [status: CIPINPROGRESS, project id: p1] - note: blocked by test"
+        );
+    }
+
+    #[test]
+    fn test_board_replaces_and_limits_pins() {
+        let board = PinBoard::default();
+        let task: PinType = "task".parse().unwrap();
+        let pin = |id: &str| digest(task, id, &json!({"title": id}), None);
+        board.pin(pin("t1")).unwrap();
+        board.pin(pin("t1")).unwrap();
+        assert_eq!(board.list().len(), 1);
+        for i in 1..MAX_PINS {
+            board.pin(pin(&format!("t{}", i + 1))).unwrap();
+        }
+        assert!(board.pin(pin("one-too-many")).is_err());
+        assert!(board.unpin(task, "t1"));
+        assert_eq!(board.clone().list().len(), 0);
+        assert_eq!(board.clear(), MAX_PINS - 1);
+    }
+}
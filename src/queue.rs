@@ -0,0 +1,285 @@
+//! Offline buffering of write operations.
+//!
+//! When enabled, create and update tool calls that fail because the SAP Cloud
+//! ALM API is unreachable (connection and DNS errors, 502/503 during
+//! maintenance) are queued instead of failing. Timeouts and responses that
+//! could not be read are not queued: the write may have been applied, and a
+//! replay would duplicate it. Queued calls are replayed in
+//! order only when `flush_pending_operations` is called, so every replayed
+//! write is confirmed like the original call. A call that fails for another
+//! reason during replay is marked as failed and kept until it is flushed or
+//! discarded. With a `path`, the queue is persisted so pending operations
+//! survive a restart; a persisted queue with operations is refused in
+//! read-only mode, where they could not be replayed.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use rmcp::model::ErrorData;
+
+use crate::config::WriteQueueConfig;
+use crate::error::ApiError;
+
+/// Prefixes of tools whose calls can be queued.
+const QUEUEABLE_PREFIXES: &[&str] = &["create_", "update_"];
+
+/// Key of the MCP error data marking errors of an unreachable API.
+const UNREACHABLE_KEY: &str = "unreachable";
+
+/// State of a queued operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationStatus {
+    /// Waiting for the API to become reachable
+    Pending,
+    /// Replayed, but rejected by the API; kept for review
+    Failed,
+}
+
+/// A buffered tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOperation {
+    pub id: u64,
+    pub tool: String,
+    pub arguments: Map<String, Value>,
+    pub queued_at: String,
+    pub status: OperationStatus,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueState {
+    next_id: u64,
+    operations: Vec<PendingOperation>,
+}
+
+/// Queue of write operations waiting for the API.
+pub struct WriteQueue {
+    path: Option<PathBuf>,
+    max_operations: usize,
+    state: Mutex<QueueState>,
+    replay: tokio::sync::Mutex<()>,
+}
+
+impl WriteQueue {
+    /// Create a queue, loading operations persisted by a previous run.
+    /// Fails in read-only mode if the persisted queue holds operations.
+    pub fn load(config: &WriteQueueConfig, read_only: bool) -> Result<Self, String> {
+        let path = config.path.as_ref().map(PathBuf::from);
+        let state = match &path {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Cannot read write queue '{}': {}", path.display(), e))?;
+                serde_json::from_str(&text)
+                    .map_err(|e| format!("Invalid write queue '{}': {}", path.display(), e))?
+            }
+            _ => QueueState::default(),
+        };
+        if read_only && !state.operations.is_empty() {
+            return Err(format!(
+                "The write queue '{}' holds {} operations, which cannot be replayed in read-only mode. Flush or discard them with read_only disabled, or remove the file.",
+                path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+                state.operations.len()
+            ));
+        }
+        Ok(Self {
+            path,
+            max_operations: config.max_operations,
+            state: Mutex::new(state),
+            replay: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Queue a tool call. Fails if the queue is full.
+    pub fn enqueue(
+        &self,
+        tool: &str,
+        arguments: Map<String, Value>,
+        error: &str,
+    ) -> Result<u64, String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.operations.len() >= self.max_operations {
+            return Err(format!(
+                "Write queue is full ({} operations)",
+                self.max_operations
+            ));
+        }
+        state.next_id += 1;
+        let id = state.next_id;
+        state.operations.push(PendingOperation {
+            id,
+            tool: tool.to_string(),
+            arguments,
+            queued_at: chrono::Utc::now().to_rfc3339(),
+            status: OperationStatus::Pending,
+            attempts: 0,
+            last_error: error.to_string(),
+        });
+        self.persist(&state);
+        Ok(id)
+    }
+
+    /// All queued operations in queue order.
+    pub fn list(&self) -> Vec<PendingOperation> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .operations
+            .clone()
+    }
+
+    /// Remove an operation that was replayed successfully.
+    pub fn complete(&self, id: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.operations.retain(|op| op.id != id);
+        self.persist(&state);
+    }
+
+    /// Record a failed replay. Operations rejected by the API (as opposed to
+    /// an unreachable API) are marked as failed and no longer replayed
+    /// automatically.
+    pub fn record_failure(&self, id: u64, error: &str, rejected: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(op) = state.operations.iter_mut().find(|op| op.id == id) {
+            op.attempts += 1;
+            op.last_error = error.to_string();
+            if rejected {
+                op.status = OperationStatus::Failed;
+            }
+        }
+        self.persist(&state);
+    }
+
+    /// Discard the given operations, or all if `ids` is None. Returns the
+    /// number of discarded operations.
+    pub fn discard(&self, ids: Option<&[u64]>) -> usize {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let before = state.operations.len();
+        match ids {
+            Some(ids) => state.operations.retain(|op| !ids.contains(&op.id)),
+            None => state.operations.clear(),
+        }
+        self.persist(&state);
+        before - state.operations.len()
+    }
+
+    /// Serialize replays: returns None if another replay is running.
+    pub fn try_begin_replay(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        self.replay.try_lock().ok()
+    }
+
+    fn persist(&self, state: &QueueState) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(state)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            tracing::warn!(path = %path.display(), error = %e, "Failed to persist write queue");
+        }
+    }
+}
+
+/// Check if calls of a tool can be queued.
+pub fn is_queueable(tool: &str) -> bool {
+    QUEUEABLE_PREFIXES.iter().any(|p| tool.starts_with(p))
+}
+
+/// Check if a tool error means the API could not be reached.
+pub fn is_unreachable(error: &ErrorData) -> bool {
+    error
+        .data
+        .as_ref()
+        .and_then(|data| data.get(UNREACHABLE_KEY))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// MCP error data of an API error, marking errors of an unreachable API.
+pub fn error_data(error: &ApiError) -> Option<Value> {
+    error
+        .is_unreachable()
+        .then(|| serde_json::json!({ UNREACHABLE_KEY: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_queue_persists_and_discards() {
+        let path = std::env::temp_dir().join(format!("calm-queue-{}.json", std::process::id()));
+        let config = WriteQueueConfig {
+            path: Some(path.to_string_lossy().into_owned()),
+            max_operations: 2,
+        };
+        let queue = WriteQueue::load(&config, false).unwrap();
+        let first = queue
+            .enqueue("create_task", args(json!({"title": "A"})), "HTTP error 503")
+            .unwrap();
+        let second = queue
+            .enqueue("update_task", args(json!({"uuid": "t1"})), "HTTP error 503")
+            .unwrap();
+        assert!(queue.enqueue("create_task", Map::new(), "").is_err());
+
+        queue.record_failure(second, "HTTP error 400 Bad Request", true);
+        let reloaded = WriteQueue::load(&config, false).unwrap();
+        let operations = reloaded.list();
+        assert_eq!(operations[0].id, first);
+        assert_eq!(operations[0].status, OperationStatus::Pending);
+        assert_eq!(operations[1].status, OperationStatus::Failed);
+
+        reloaded.complete(first);
+        assert_eq!(reloaded.discard(None), 1);
+        assert!(WriteQueue::load(&config, false).unwrap().list().is_empty());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_read_only_refuses_persisted_operations() {
+        let path = std::env::temp_dir().join(format!("calm-queue-ro-{}.json", std::process::id()));
+        let config = WriteQueueConfig {
+            path: Some(path.to_string_lossy().into_owned()),
+            max_operations: 10,
+        };
+        assert!(WriteQueue::load(&config, true).is_ok());
+        let queue = WriteQueue::load(&config, false).unwrap();
+        queue
+            .enqueue("create_task", args(json!({"title": "A"})), "HTTP error 503")
+            .unwrap();
+        let error = WriteQueue::load(&config, true).err().unwrap();
+        assert!(error.contains("read-only"), "{}", error);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_queueable_tools_and_unreachable_errors() {
+        assert!(is_queueable("create_feature"));
+        assert!(is_queueable("update_document"));
+        assert!(!is_queueable("delete_feature"));
+
+        let error = |api: ApiError| ErrorData::internal_error(api.to_string(), error_data(&api));
+        assert!(is_unreachable(&error(ApiError::HttpError {
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            body: "maintenance".to_string(),
+        })));
+        assert!(!is_unreachable(&error(ApiError::HttpError {
+            status: reqwest::StatusCode::GATEWAY_TIMEOUT,
+            body: String::new(),
+        })));
+        assert!(!is_unreachable(&ErrorData::internal_error(
+            "HTTP error 503 Service Unavailable",
+            None
+        )));
+    }
+}
@@ -0,0 +1,452 @@
+//! Production readiness check for moving from the sandbox to a tenant.
+//!
+//! `--check-production-readiness <production config>` compares the sandbox
+//! configuration (`--config`) with a production OAuth configuration, obtains
+//! a token and checks the granted scopes, probes every API area with an
+//! empty-page read (permission discovery) and prints a migration checklist.
+//! The process exits with status 1 if any check failed.
+
+use serde::Serialize;
+
+use crate::api::tasks::ListTasksParams;
+use crate::auth::OAuth2Client;
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::odata::Probe;
+use crate::server::ApiClients;
+
+/// API areas and the scope names granting access, as `calm-api.<scope>.read`
+/// and `calm-api.<scope>.write`. Areas without write tools have no write scope.
+const AREA_SCOPES: &[(&str, &str, bool)] = &[
+    ("Features", "features", true),
+    ("Documents", "documents", true),
+    ("Tasks", "tasks", true),
+    ("Projects", "projects", true),
+    ("Test Management", "testmanagement", true),
+    ("Process Hierarchy", "processhierarchy", true),
+    ("Analytics", "analytics", false),
+    ("Process Monitoring", "processmonitoring", false),
+    ("Logs", "logs", true),
+];
+
+/// API areas whose tools are optional Cargo features, and whether they are
+/// part of this build.
+const OPTIONAL_AREAS: &[(&str, bool)] = &[
+    ("Analytics", cfg!(feature = "analytics")),
+    ("Process Monitoring", cfg!(feature = "processmonitoring")),
+    ("Logs", cfg!(feature = "logs")),
+];
+
+/// Check if the tools of an API area are part of this build.
+fn in_build(area: &str) -> bool {
+    OPTIONAL_AREAS
+        .iter()
+        .find(|(name, _)| *name == area)
+        .is_none_or(|(_, built)| *built)
+}
+
+/// Outcome of a checklist item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One item of the migration checklist.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChecklistItem {
+    pub status: CheckStatus,
+    pub check: String,
+    pub detail: String,
+}
+
+impl ChecklistItem {
+    fn new(status: CheckStatus, check: &str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            check: check.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Compare the sandbox configuration with the production configuration.
+pub fn compare_configs(sandbox: &Config, production: &Config) -> Vec<ChecklistItem> {
+    use CheckStatus::*;
+
+    let mut items = Vec::new();
+    if !sandbox.sandbox {
+        items.push(ChecklistItem::new(
+            Warn,
+            "Sandbox configuration",
+            "The configuration given with --config is not a sandbox configuration",
+        ));
+    }
+    items.push(if production.sandbox {
+        ChecklistItem::new(
+            Fail,
+            "Production mode",
+            "The production configuration still sets sandbox: true",
+        )
+    } else {
+        ChecklistItem::new(
+            Pass,
+            "Production mode",
+            format!("OAuth2 against {}", production.api_base_url()),
+        )
+    });
+    if production.api_key.is_some() && !production.sandbox {
+        items.push(ChecklistItem::new(
+            Warn,
+            "Sandbox API key",
+            "api_key is ignored outside sandbox mode; remove it from the production configuration",
+        ));
+    }
+
+    let carried_over = [
+        (
+            "field_aliases",
+            !sandbox.field_aliases.is_empty(),
+            !production.field_aliases.is_empty(),
+        ),
+        (
+            "output_transformers",
+            !sandbox.output_transformers.is_empty(),
+            !production.output_transformers.is_empty(),
+        ),
+        (
+            "odata_versions",
+            !sandbox.odata_versions.is_empty(),
+            !production.odata_versions.is_empty(),
+        ),
+        (
+            "translation",
+            sandbox.translation.is_some(),
+            production.translation.is_some(),
+        ),
+    ];
+    for (setting, in_sandbox, in_production) in carried_over {
+        if in_sandbox && !in_production {
+            items.push(ChecklistItem::new(
+                Warn,
+                setting,
+                format!(
+                    "{} is configured for the sandbox but not for production",
+                    setting
+                ),
+            ));
+        }
+    }
+
+    let safeguards = [
+        (
+            "read_only",
+            production.read_only,
+            "Consider read_only: true for the first production sessions",
+        ),
+        (
+            "verify_project_membership",
+            production.verify_project_membership || production.read_only,
+            "Writes are not restricted to projects the client is a member of",
+        ),
+        (
+            "duplicate_call_window_seconds",
+            production.duplicate_call_window_seconds.is_some() || production.read_only,
+            "Repeated create calls of a retrying agent are not suppressed",
+        ),
+        (
+            "access_log",
+            production.access_log.is_some(),
+            "No access log: tool calls against the tenant are not recorded",
+        ),
+    ];
+    for (setting, enabled, risk) in safeguards {
+        items.push(if enabled {
+            ChecklistItem::new(Pass, setting, "enabled")
+        } else {
+            ChecklistItem::new(Warn, setting, risk)
+        });
+    }
+    items
+}
+
+/// Normalize a scope for matching: lowercase without `-` and `_`.
+fn fold_scope(scope: &str) -> String {
+    scope
+        .chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Check the granted scopes against the scopes of each API area. Scopes
+/// may carry an application prefix (`<xsappname>.calm-api.features.read`).
+pub fn check_scopes(granted: &[String], read_only: bool) -> Vec<ChecklistItem> {
+    if granted.is_empty() {
+        return vec![ChecklistItem::new(
+            CheckStatus::Warn,
+            "Scopes",
+            "The token response lists no scopes; see the permission probes",
+        )];
+    }
+    let granted: Vec<String> = granted.iter().map(|s| fold_scope(s)).collect();
+    let has = |scope: &str, access: &str| {
+        let suffix = format!("calmapi.{}.{}", scope, access);
+        granted.iter().any(|g| g.ends_with(&suffix))
+    };
+
+    let mut items = Vec::new();
+    for (area, scope, writable) in AREA_SCOPES.iter().filter(|(area, ..)| in_build(area)) {
+        let check = format!("Scopes: {}", area);
+        let item = if !has(scope, "read") {
+            ChecklistItem::new(
+                CheckStatus::Fail,
+                &check,
+                format!("calm-api.{}.read is not granted", scope),
+            )
+        } else if *writable && !read_only && !has(scope, "write") {
+            ChecklistItem::new(
+                CheckStatus::Warn,
+                &check,
+                format!(
+                    "calm-api.{}.write is not granted; write tools of this area will fail",
+                    scope
+                ),
+            )
+        } else {
+            ChecklistItem::new(CheckStatus::Pass, &check, "granted")
+        };
+        items.push(item);
+    }
+    items
+}
+
+/// Checklist item of a permission probe, with the record count if the
+/// probe returned one.
+fn probe_item(area: &str, result: Result<Option<i64>, ApiError>) -> ChecklistItem {
+    let check = format!("Access: {}", area);
+    let status = match &result {
+        Err(ApiError::HttpError { status, .. }) | Err(ApiError::ODataError { status, .. }) => {
+            Some(*status)
+        }
+        _ => None,
+    };
+    match (result, status) {
+        (Ok(Some(count)), _) => ChecklistItem::new(
+            CheckStatus::Pass,
+            &check,
+            format!("read succeeded, {} records", count),
+        ),
+        (Ok(None), _) => ChecklistItem::new(CheckStatus::Pass, &check, "read succeeded"),
+        (Err(_), Some(status)) if status.as_u16() == 401 || status.as_u16() == 403 => {
+            ChecklistItem::new(
+                CheckStatus::Fail,
+                &check,
+                format!(
+                    "HTTP {}: the client lacks the authorization for this API",
+                    status.as_u16()
+                ),
+            )
+        }
+        (Err(_), Some(status)) if status.as_u16() == 404 => ChecklistItem::new(
+            CheckStatus::Warn,
+            &check,
+            "HTTP 404: the API is not available on this tenant or region",
+        ),
+        (Err(e), _) => ChecklistItem::new(CheckStatus::Fail, &check, e.to_string()),
+    }
+}
+
+/// Probe every API area without transferring records. Projects and tasks
+/// are read, as the tasks probe needs a project ID.
+pub async fn discover_permissions(clients: &ApiClients) -> Vec<ChecklistItem> {
+    let count = |probe: Result<Probe, ApiError>| probe.map(|p| p.count);
+    let (features, documents, projects, testcases, hierarchy) = tokio::join!(
+        clients.features.api().probe("/Features"),
+        clients.documents.api().probe("/Documents"),
+        clients.projects.list_projects(),
+        clients.testmanagement.api().probe("/ManualTestCases"),
+        clients.processhierarchy.api().probe("/HierarchyNodes"),
+    );
+
+    let mut items = vec![
+        probe_item("Features", count(features)),
+        probe_item("Documents", count(documents)),
+    ];
+    // Tasks are listed per project
+    let first_project = projects
+        .as_ref()
+        .ok()
+        .and_then(|p| p.iter().find_map(|p| p.id.clone()));
+    items.push(probe_item(
+        "Projects",
+        projects.map(|p| Some(p.len() as i64)),
+    ));
+    items.push(match first_project {
+        Some(project_id) => {
+            let request = ListTasksParams {
+                project_id,
+                limit: Some(1),
+                ..Default::default()
+            };
+            probe_item(
+                "Tasks",
+                clients.tasks.list_tasks(&request).await.map(|_| None),
+            )
+        }
+        None => ChecklistItem::new(
+            CheckStatus::Warn,
+            "Access: Tasks",
+            "Not probed: no project is readable",
+        ),
+    });
+    items.push(probe_item("Test Management", count(testcases)));
+    items.push(probe_item("Process Hierarchy", count(hierarchy)));
+    #[cfg(feature = "analytics")]
+    items.push(probe_item(
+        "Analytics",
+        count(clients.analytics.api().probe("/Requirements").await),
+    ));
+    #[cfg(feature = "processmonitoring")]
+    items.push(probe_item(
+        "Process Monitoring",
+        count(
+            clients
+                .processmonitoring
+                .api()
+                .probe("/businessProcesses")
+                .await,
+        ),
+    ));
+    items
+}
+
+/// Run all checks against the production tenant.
+pub async fn run(
+    sandbox: &Config,
+    production: &Config,
+    auth: &OAuth2Client,
+    clients: &ApiClients,
+) -> Vec<ChecklistItem> {
+    let mut items = compare_configs(sandbox, production);
+    match auth.granted_scopes().await {
+        Ok(scopes) => {
+            items.push(ChecklistItem::new(
+                CheckStatus::Pass,
+                "Token",
+                "Access token obtained",
+            ));
+            items.extend(check_scopes(&scopes, production.read_only));
+            items.extend(discover_permissions(clients).await);
+        }
+        Err(e) => items.push(ChecklistItem::new(
+            CheckStatus::Fail,
+            "Token",
+            format!("No access token: {}", e),
+        )),
+    }
+    items
+}
+
+/// Render the checklist as markdown; failed items are unchecked.
+pub fn render(items: &[ChecklistItem]) -> String {
+    let failed = items
+        .iter()
+        .filter(|i| i.status == CheckStatus::Fail)
+        .count();
+    let warnings = items
+        .iter()
+        .filter(|i| i.status == CheckStatus::Warn)
+        .count();
+    let mut out = String::from("# Production readiness\n\n");
+    for item in items {
+        let (mark, label) = match item.status {
+            CheckStatus::Pass => ("x", "ok"),
+            CheckStatus::Warn => ("x", "warning"),
+            CheckStatus::Fail => (" ", "FAILED"),
+        };
+        out.push_str(&format!(
+            "- [{}] {} ({}): {}\n",
+            mark, item.check, label, item.detail
+        ));
+    }
+    out.push_str(&format!(
+        "\n{} checks, {} failed, {} warnings\n",
+        items.len(),
+        failed,
+        warnings
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn config(value: serde_json::Value) -> Config {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_compare_configs() {
+        let sandbox = config(serde_json::json!({
+            "sandbox": true,
+            "api_key": "key",
+            "field_aliases": {"status": "statusCode"}
+        }));
+        let production = config(serde_json::json!({
+            "tenant": "acme",
+            "region": "eu10",
+            "client_id": "id",
+            "client_secret": "secret",
+            "read_only": true
+        }));
+        let items = compare_configs(&sandbox, &production);
+        let status = |check: &str| items.iter().find(|i| i.check == check).map(|i| i.status);
+        assert_eq!(status("Production mode"), Some(CheckStatus::Pass));
+        assert_eq!(status("field_aliases"), Some(CheckStatus::Warn));
+        assert_eq!(status("read_only"), Some(CheckStatus::Pass));
+        assert_eq!(
+            status("duplicate_call_window_seconds"),
+            Some(CheckStatus::Pass)
+        );
+        assert_eq!(status("access_log"), Some(CheckStatus::Warn));
+        assert!(items.iter().all(|i| i.status != CheckStatus::Fail));
+    }
+
+    #[test]
+    fn test_scopes_and_probes() {
+        let granted: Vec<String> = AREA_SCOPES
+            .iter()
+            .map(|(_, scope, _)| format!("calm-api!b42.calm-api.{}.read", scope))
+            .chain(["calm-api!b42.calm-api.features.write".to_string()])
+            .collect();
+        let items = check_scopes(&granted, false);
+        assert_eq!(items[0].status, CheckStatus::Pass);
+        assert_eq!(items[1].status, CheckStatus::Warn);
+        assert!(check_scopes(&granted, true)
+            .iter()
+            .all(|i| i.status == CheckStatus::Pass));
+        assert_eq!(
+            check_scopes(&granted[..1], true)[1].status,
+            CheckStatus::Fail
+        );
+
+        let forbidden = probe_item(
+            "Features",
+            Err(ApiError::HttpError {
+                status: StatusCode::FORBIDDEN,
+                body: String::new(),
+            }),
+        );
+        assert_eq!(forbidden.status, CheckStatus::Fail);
+        let documents = probe_item("Documents", Ok(Some(42)));
+        assert_eq!(documents.detail, "read succeeded, 42 records");
+        let report = render(&[forbidden, documents]);
+        assert!(report.contains("- [ ] Access: Features (FAILED)"));
+        assert!(report.contains("2 checks, 1 failed, 0 warnings"));
+    }
+}
@@ -0,0 +1,241 @@
+//! External reference mapping files.
+//!
+//! A mapping file is a CSV file linking SAP Cloud ALM entities to objects in
+//! external systems (Jira issues, Solution Manager documents, ...), one
+//! reference per row:
+//!
+//! ```text
+//! entity_type,entity_id,external_system,external_id,url
+//! feature,3-17,Jira,PROJ-123,https://jira.example.com/browse/PROJ-123
+//! task,6f1c...,Solution Manager,8000001234,
+//! ```
+//!
+//! `entity_type` is `feature` or `task`. Features are identified by UUID or
+//! display ID. Feature references store the external system as their name.
+
+use serde::Serialize;
+
+use crate::export::csv_field;
+
+/// Columns of a mapping file, in export order.
+pub const COLUMNS: [&str; 5] = [
+    "entity_type",
+    "entity_id",
+    "external_system",
+    "external_id",
+    "url",
+];
+
+/// Entity types that carry external references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityType {
+    Feature,
+    Task,
+}
+
+impl EntityType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Feature => "feature",
+            Self::Task => "task",
+        }
+    }
+}
+
+/// One row of a mapping file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReferenceRow {
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    pub external_system: String,
+    pub external_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Render rows as CSV with a header line.
+pub fn to_csv(rows: &[ReferenceRow]) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields = [
+            row.entity_type.as_str(),
+            &row.entity_id,
+            &row.external_system,
+            &row.external_id,
+            row.url.as_deref().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Line number of a mapping file row with the row or the reason it is invalid.
+pub type ParsedRow = (usize, Result<ReferenceRow, String>);
+
+/// Parse a mapping file. Columns are matched by header name, so their order
+/// is free and extra columns are ignored.
+pub fn parse_csv(text: &str) -> Result<Vec<ParsedRow>, String> {
+    let mut records = csv_records(text).into_iter();
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| "Mapping file is empty".to_string())?;
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let mut positions = [0; 5];
+    for (position, column) in positions.iter_mut().zip(COLUMNS) {
+        *position = match header.iter().position(|h| h == column) {
+            Some(index) => index,
+            None if column == "url" => usize::MAX,
+            None => return Err(format!("Mapping file has no '{}' column", column)),
+        };
+    }
+
+    Ok(records
+        .filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()))
+        .map(|(line, fields)| {
+            let field = |i: usize| fields.get(positions[i]).map(|f| f.trim()).unwrap_or("");
+            (
+                line,
+                parse_row(field(0), field(1), field(2), field(3), field(4)),
+            )
+        })
+        .collect())
+}
+
+fn parse_row(
+    entity_type: &str,
+    entity_id: &str,
+    external_system: &str,
+    external_id: &str,
+    url: &str,
+) -> Result<ReferenceRow, String> {
+    let entity_type = match entity_type.to_lowercase().as_str() {
+        "feature" => EntityType::Feature,
+        "task" => EntityType::Task,
+        other => {
+            return Err(format!(
+                "Unknown entity_type '{}' (expected feature or task)",
+                other
+            ))
+        }
+    };
+    for (name, value) in [
+        ("entity_id", entity_id),
+        ("external_system", external_system),
+        ("external_id", external_id),
+    ] {
+        if value.is_empty() {
+            return Err(format!("Missing {}", name));
+        }
+    }
+    Ok(ReferenceRow {
+        entity_type,
+        entity_id: entity_id.to_string(),
+        external_system: external_system.to_string(),
+        external_id: external_id.to_string(),
+        url: (!url.is_empty()).then(|| url.to_string()),
+    })
+}
+
+/// Split CSV text into records with the line number they start on.
+/// Quoted fields may contain commas, doubled quotes and line breaks.
+fn csv_records(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+    records
+}
+
+/// Check if an entity ID is a UUID rather than a display ID.
+pub fn is_uuid(id: &str) -> bool {
+    id.len() == 36
+        && id.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_round_trip() {
+        let rows = vec![
+            ReferenceRow {
+                entity_type: EntityType::Feature,
+                entity_id: "3-17".to_string(),
+                external_system: "Jira".to_string(),
+                external_id: "PROJ-1".to_string(),
+                url: Some("https://jira.example.com/browse/PROJ-1".to_string()),
+            },
+            ReferenceRow {
+                entity_type: EntityType::Task,
+                entity_id: "t1".to_string(),
+                external_system: "Solution Manager, ChaRM".to_string(),
+                external_id: "say \"hi\"".to_string(),
+                url: None,
+            },
+        ];
+        let parsed: Vec<ReferenceRow> = parse_csv(&to_csv(&rows))
+            .unwrap()
+            .into_iter()
+            .map(|(_, row)| row.unwrap())
+            .collect();
+        assert_eq!(parsed, rows);
+    }
+
+    #[test]
+    fn test_parse_reports_invalid_rows_with_line_numbers() {
+        let csv = "External_ID,entity_type,entity_id,external_system,comment\n\
+                   J-1,feature,3-1,Jira,\"multi\nline\"\n\
+                   \n\
+                   J-2,epic,3-2,Jira,\n\
+                   ,task,t1,Jira,\n";
+        let rows = parse_csv(csv).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].0, 2);
+        assert_eq!(rows[0].1.as_ref().unwrap().external_id, "J-1");
+        assert_eq!(rows[1].0, 5);
+        assert!(rows[1].1.as_ref().unwrap_err().contains("epic"));
+        assert_eq!(rows[2].1, Err("Missing external_id".to_string()));
+
+        assert!(parse_csv("entity_type,entity_id\n").is_err());
+        assert!(is_uuid("1b4e28ba-2fa1-11d2-883f-0016d3cca427"));
+        assert!(!is_uuid("3-17"));
+    }
+}
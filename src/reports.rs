@@ -0,0 +1,1292 @@
+//! Client-side aggregation helpers for report and analysis tools.
+//!
+//! Analytics payloads are returned as raw JSON whose field names vary between
+//! providers and tenants, so lookups here accept a list of candidate keys.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::locale::Locale;
+
+/// Candidate keys for the execution status of a test run.
+#[cfg(feature = "analytics")]
+pub const RUN_STATUS_KEYS: &[&str] = &["status", "executionStatus", "result", "statusName"];
+
+/// Candidate keys for the test plan a test run belongs to.
+#[cfg(feature = "analytics")]
+pub const TEST_PLAN_KEYS: &[&str] = &["testPlanId", "testPlanUuid", "planId", "testPlan"];
+
+/// Candidate keys for the test case a test run executed.
+#[cfg(feature = "analytics")]
+pub const TEST_CASE_KEYS: &[&str] = &[
+    "testCaseId",
+    "testCaseUuid",
+    "testCase",
+    "testCaseDisplayId",
+];
+
+/// Read the first present field from a JSON object as a string.
+/// Numbers and booleans are converted to their string form.
+pub fn field_str(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| match value.get(*key) {
+        Some(Value::String(s)) if !s.is_empty() => Some(s.clone()),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        Some(Value::Bool(b)) => Some(b.to_string()),
+        _ => None,
+    })
+}
+
+/// Return the records of a collection response (`{"value": [...]}`) or a bare array.
+pub fn collection_items(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(items) => items,
+        Value::Object(map) => map
+            .get("value")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]),
+        _ => &[],
+    }
+}
+
+/// Check whether any string anywhere within `value` equals one of `needles`.
+pub fn mentions_any(value: &Value, needles: &[&str]) -> bool {
+    match value {
+        Value::String(s) => needles.iter().any(|n| !n.is_empty() && s == n),
+        Value::Array(items) => items.iter().any(|v| mentions_any(v, needles)),
+        Value::Object(map) => map.values().any(|v| mentions_any(v, needles)),
+        _ => false,
+    }
+}
+
+/// Check whether a status name or code denotes a closed item
+/// (e.g. `DONE`, `CIPDONE`, `Closed`, `Cancelled`).
+pub fn is_closed_status(status: &str) -> bool {
+    let status = status.to_uppercase();
+    ["DONE", "CLOSED", "CANCEL", "COMPLETED", "REJECTED"]
+        .iter()
+        .any(|s| status.contains(s))
+}
+
+/// Test coverage state of a feature or requirement.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coverage {
+    /// No test case or test run references the item.
+    Untested,
+    /// Test runs exist but every one of them failed.
+    OnlyFailed,
+    /// At least one test case or non-failed run references the item.
+    Covered,
+}
+
+/// Classify coverage of an item identified by `ids` against test cases and test runs.
+#[cfg(feature = "analytics")]
+pub fn classify_coverage(ids: &[&str], testcases: &[Value], runs: &[Value]) -> Coverage {
+    let linked_runs: Vec<&Value> = runs.iter().filter(|r| mentions_any(r, ids)).collect();
+    let has_testcase = testcases.iter().any(|t| mentions_any(t, ids));
+
+    if linked_runs.is_empty() {
+        return if has_testcase {
+            Coverage::Covered
+        } else {
+            Coverage::Untested
+        };
+    }
+
+    let all_failed = linked_runs
+        .iter()
+        .all(|run| run_status(run) == RunStatus::Failed);
+    if all_failed {
+        Coverage::OnlyFailed
+    } else {
+        Coverage::Covered
+    }
+}
+
+/// Execution outcome of a test run.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Passed,
+    Failed,
+    Blocked,
+    NotExecuted,
+}
+
+/// Classify a test run record by its status field.
+#[cfg(feature = "analytics")]
+pub fn run_status(run: &Value) -> RunStatus {
+    let status = field_str(run, RUN_STATUS_KEYS)
+        .unwrap_or_default()
+        .to_lowercase();
+    if status.contains("fail") {
+        RunStatus::Failed
+    } else if status.contains("block") {
+        RunStatus::Blocked
+    } else if status.contains("pass") || status.contains("success") || status == "ok" {
+        RunStatus::Passed
+    } else {
+        RunStatus::NotExecuted
+    }
+}
+
+/// Execution progress of one test plan.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Default, Serialize)]
+pub struct PlanProgress {
+    pub plan_id: String,
+    pub plan_name: Option<String>,
+    pub total: usize,
+    pub executed: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub blocked: usize,
+    pub not_executed: usize,
+}
+
+/// Aggregate test runs into per-plan progress counts.
+/// Runs without a plan reference are grouped under "unassigned".
+#[cfg(feature = "analytics")]
+pub fn summarize_test_progress(runs: &[Value]) -> Vec<PlanProgress> {
+    let mut plans: BTreeMap<String, PlanProgress> = BTreeMap::new();
+
+    for run in runs {
+        let plan_id = field_str(run, TEST_PLAN_KEYS).unwrap_or_else(|| "unassigned".to_string());
+        let plan = plans
+            .entry(plan_id.clone())
+            .or_insert_with(|| PlanProgress {
+                plan_id,
+                ..Default::default()
+            });
+        if plan.plan_name.is_none() {
+            plan.plan_name = field_str(run, &["testPlanName", "testPlanTitle", "planName"]);
+        }
+
+        plan.total += 1;
+        match run_status(run) {
+            RunStatus::Passed => plan.passed += 1,
+            RunStatus::Failed => plan.failed += 1,
+            RunStatus::Blocked => plan.blocked += 1,
+            RunStatus::NotExecuted => plan.not_executed += 1,
+        }
+        plan.executed = plan.total - plan.not_executed;
+    }
+
+    plans.into_values().collect()
+}
+
+/// Test cases with the most failed runs, most failures first.
+#[cfg(feature = "analytics")]
+pub fn top_failing_test_cases(runs: &[Value], limit: usize) -> Vec<(String, usize)> {
+    let mut failures: BTreeMap<String, usize> = BTreeMap::new();
+    for run in runs.iter().filter(|r| run_status(r) == RunStatus::Failed) {
+        if let Some(test_case) = field_str(run, TEST_CASE_KEYS) {
+            *failures.entry(test_case).or_default() += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = failures.into_iter().collect();
+    ranked.sort_by_key(|(_, failures)| std::cmp::Reverse(*failures));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Age buckets for open defects, in days since creation.
+#[cfg(feature = "analytics")]
+const AGE_BUCKETS: &[(&str, i64)] = &[("0-7d", 7), ("8-30d", 30), ("31-90d", 90)];
+
+/// Defect counts grouped for triage.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Default, Serialize)]
+pub struct DefectSummary {
+    pub total: usize,
+    pub by_status: BTreeMap<String, usize>,
+    pub by_severity: BTreeMap<String, usize>,
+    pub by_age: BTreeMap<String, usize>,
+    pub top_assignees: Vec<(String, usize)>,
+}
+
+/// Group defect records by status, severity, age bucket and assignee.
+/// Age is measured from the creation timestamp to `now`.
+#[cfg(feature = "analytics")]
+pub fn summarize_defects(
+    defects: &[Value],
+    now: chrono::DateTime<chrono::Utc>,
+    top_assignees: usize,
+) -> DefectSummary {
+    let mut summary = DefectSummary {
+        total: defects.len(),
+        ..Default::default()
+    };
+    let mut assignees: BTreeMap<String, usize> = BTreeMap::new();
+
+    for defect in defects {
+        let status = field_str(defect, &["status", "statusName", "statusCode"])
+            .unwrap_or_else(|| "unknown".to_string());
+        *summary.by_status.entry(status).or_default() += 1;
+
+        let severity = field_str(
+            defect,
+            &["severity", "severityName", "priority", "priorityName"],
+        )
+        .unwrap_or_else(|| "unknown".to_string());
+        *summary.by_severity.entry(severity).or_default() += 1;
+
+        let age = field_str(defect, &["createdAt", "creationDate", "createdOn"])
+            .and_then(|created| chrono::DateTime::parse_from_rfc3339(&created).ok())
+            .map(|created| (now - created.with_timezone(&chrono::Utc)).num_days());
+        let bucket = match age {
+            Some(days) => AGE_BUCKETS
+                .iter()
+                .find(|(_, max)| days <= *max)
+                .map(|(label, _)| label.to_string())
+                .unwrap_or_else(|| ">90d".to_string()),
+            None => "unknown".to_string(),
+        };
+        *summary.by_age.entry(bucket).or_default() += 1;
+
+        if let Some(assignee) = field_str(
+            defect,
+            &["assignee", "assigneeName", "assignedTo", "processor"],
+        ) {
+            *assignees.entry(assignee).or_default() += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = assignees.into_iter().collect();
+    ranked.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    ranked.truncate(top_assignees);
+    summary.top_assignees = ranked;
+
+    summary
+}
+
+/// Candidate keys for the timestamp of an analytics record.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub const TIMESTAMP_KEYS: &[&str] = &["timestamp", "eventTime", "createdAt", "startTime", "time"];
+
+/// Filter for records whose `property` lies within `[from, to]`.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub fn window_filter(
+    property: &str,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let literal =
+        |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true);
+    format!(
+        "{} ge {} and {} le {}",
+        property,
+        literal(from),
+        property,
+        literal(to)
+    )
+}
+
+/// Keep records whose timestamp lies within `[from, to]`.
+/// Records without a parseable timestamp are excluded.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub fn records_in_window(
+    records: &[Value],
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> Vec<Value> {
+    records
+        .iter()
+        .filter(|r| {
+            field_str(r, TIMESTAMP_KEYS)
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+                .is_some_and(|t| t >= from && t <= to)
+        })
+        .cloned()
+        .collect()
+}
+
+/// A single log record extracted from an OpenTelemetry logs payload.
+#[cfg(feature = "logs")]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLine {
+    pub time: Option<String>,
+    pub severity: Option<String>,
+    pub service: Option<String>,
+    pub body: String,
+}
+
+/// Flatten an OpenTelemetry logs payload (`resourceLogs[].scopeLogs[].logRecords[]`).
+#[cfg(feature = "logs")]
+pub fn otel_log_lines(payload: &Value) -> Vec<LogLine> {
+    let mut lines = Vec::new();
+    let resource_logs = payload
+        .get("resourceLogs")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+
+    for resource in resource_logs {
+        let service = resource
+            .pointer("/resource/attributes")
+            .and_then(Value::as_array)
+            .and_then(|attrs| {
+                attrs
+                    .iter()
+                    .find(|a| a.get("key").and_then(Value::as_str) == Some("service.name"))
+            })
+            .and_then(|a| a.pointer("/value/stringValue"))
+            .and_then(Value::as_str)
+            .map(String::from);
+
+        let scope_logs = resource
+            .get("scopeLogs")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        for record in scope_logs
+            .iter()
+            .filter_map(|s| s.get("logRecords").and_then(Value::as_array))
+            .flatten()
+        {
+            let time = field_str(record, &["timeUnixNano", "observedTimeUnixNano"])
+                .and_then(|nanos| nanos.parse::<i64>().ok())
+                .map(|nanos| chrono::DateTime::from_timestamp_nanos(nanos).to_rfc3339());
+            let body = match record.pointer("/body/stringValue") {
+                Some(Value::String(text)) => text.clone(),
+                _ => record.get("body").map(Value::to_string).unwrap_or_default(),
+            };
+            lines.push(LogLine {
+                time,
+                severity: field_str(record, &["severityText", "severityNumber"]),
+                service: service.clone(),
+                body,
+            });
+        }
+    }
+
+    lines
+}
+
+/// Render log lines as compact text, one record per line:
+/// `2024-06-01 10:00:00.000 ERROR [billing] Connection refused`.
+/// Multi-line bodies are joined with ` | `.
+#[cfg(feature = "logs")]
+pub fn render_log_lines(lines: &[LogLine]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        let time = line
+            .time
+            .as_deref()
+            .map(|t| match chrono::DateTime::parse_from_rfc3339(t) {
+                Ok(time) => time.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                Err(_) => t.to_string(),
+            })
+            .unwrap_or_else(|| "-".to_string());
+        let body: Vec<&str> = line
+            .body
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        text.push_str(&format!(
+            "{} {:<5}",
+            time,
+            line.severity.as_deref().unwrap_or("-")
+        ));
+        if let Some(service) = &line.service {
+            text.push_str(&format!(" [{}]", service));
+        }
+        text.push(' ');
+        text.push_str(&body.join(" | "));
+        text.push('\n');
+    }
+    text
+}
+
+/// Result sizes up to which a query can be run directly.
+#[cfg(feature = "analytics")]
+const DIRECT_QUERY_MAX_ROWS: i64 = 200;
+#[cfg(feature = "analytics")]
+const DIRECT_QUERY_MAX_BYTES: u64 = 100_000;
+
+/// Result sizes above which only aggregation or export are sensible.
+#[cfg(feature = "analytics")]
+const PAGED_QUERY_MAX_ROWS: i64 = 5_000;
+
+/// Estimated size of a query result.
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryEstimate {
+    pub rows: i64,
+    pub sampled_rows: usize,
+    pub average_row_bytes: u64,
+    pub estimated_bytes: u64,
+    /// Rough token count (4 bytes per token)
+    pub estimated_tokens: u64,
+    pub recommendation: String,
+}
+
+/// Estimate the payload of a query from its row count and a sample of rows.
+#[cfg(feature = "analytics")]
+pub fn estimate_query_size(rows: i64, sample: &[Value]) -> QueryEstimate {
+    let sample_bytes: usize = sample.iter().map(|r| r.to_string().len()).sum();
+    let average_row_bytes = if sample.is_empty() {
+        0
+    } else {
+        (sample_bytes / sample.len()) as u64
+    };
+    let estimated_bytes = average_row_bytes * rows.max(0) as u64;
+    let recommendation = if rows == 0 {
+        "The query returns no rows; check the provider and filter."
+    } else if rows <= DIRECT_QUERY_MAX_ROWS && estimated_bytes <= DIRECT_QUERY_MAX_BYTES {
+        "Small result: query it directly."
+    } else if rows <= PAGED_QUERY_MAX_ROWS {
+        "Large result: narrow the filter (e.g. a shorter time range), select fewer fields or page with top/skip."
+    } else {
+        "Very large result: aggregate server-side with $apply (e.g. groupby with aggregate) or write it to a file with export_analytics_dataset instead of reading the rows."
+    };
+    QueryEstimate {
+        rows,
+        sampled_rows: sample.len(),
+        average_row_bytes,
+        estimated_bytes,
+        estimated_tokens: estimated_bytes / 4,
+        recommendation: recommendation.to_string(),
+    }
+}
+
+/// Read the first present numeric field from a JSON object.
+/// Numeric strings (e.g. `"99.5"`) are accepted.
+pub fn field_f64(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| match value.get(*key) {
+        Some(Value::Number(n)) => n.as_f64(),
+        Some(Value::String(s)) => s.trim().trim_end_matches('%').parse().ok(),
+        _ => None,
+    })
+}
+
+/// Candidate keys for the measured value of a service level record.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub const SLA_VALUE_KEYS: &[&str] = &["availability", "value", "slaValue", "percentage"];
+
+/// Candidate keys for the target of a service level record.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub const SLA_TARGET_KEYS: &[&str] = &["target", "threshold", "slaTarget", "targetValue"];
+
+/// Candidate keys for the service a record belongs to.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub const SERVICE_KEYS: &[&str] = &[
+    "serviceName",
+    "serviceId",
+    "managedObjectName",
+    "systemName",
+];
+
+/// Service whose measured service level is below its target.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlaBreach {
+    pub service: String,
+    pub target: f64,
+    pub average: f64,
+    pub minimum: f64,
+    pub samples: usize,
+    pub samples_below_target: usize,
+    pub error_log_lines: usize,
+}
+
+/// Find services whose average service level is below target.
+///
+/// The target is read from each record, falling back to `default_target`.
+/// `error_logs` counts error log lines per service as supporting evidence.
+/// Breaches are sorted by the largest shortfall first.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub fn sla_breaches(
+    records: &[Value],
+    default_target: f64,
+    error_logs: &BTreeMap<String, usize>,
+) -> Vec<SlaBreach> {
+    let mut samples: BTreeMap<String, Vec<(f64, f64)>> = BTreeMap::new();
+    for record in records {
+        let Some(value) = field_f64(record, SLA_VALUE_KEYS) else {
+            continue;
+        };
+        let service = field_str(record, SERVICE_KEYS).unwrap_or_else(|| "unknown".to_string());
+        let target = field_f64(record, SLA_TARGET_KEYS).unwrap_or(default_target);
+        samples.entry(service).or_default().push((value, target));
+    }
+
+    let mut breaches: Vec<SlaBreach> = samples
+        .into_iter()
+        .filter_map(|(service, values)| {
+            let count = values.len() as f64;
+            let average = values.iter().map(|(v, _)| v).sum::<f64>() / count;
+            let target = values.iter().map(|(_, t)| t).sum::<f64>() / count;
+            if average >= target {
+                return None;
+            }
+            Some(SlaBreach {
+                error_log_lines: error_logs.get(&service).copied().unwrap_or(0),
+                target,
+                average,
+                minimum: values.iter().map(|(v, _)| *v).fold(f64::INFINITY, f64::min),
+                samples: values.len(),
+                samples_below_target: values.iter().filter(|(v, t)| v < t).count(),
+                service,
+            })
+        })
+        .collect();
+    breaches.sort_by(|a, b| (a.average - a.target).total_cmp(&(b.average - b.target)));
+    breaches
+}
+
+/// Count error-level log lines per service.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+pub fn error_log_counts(lines: &[LogLine]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for line in lines {
+        let is_error = line.severity.as_deref().is_some_and(|s| {
+            let s = s.to_uppercase();
+            s.starts_with("ERROR") || s.starts_with("FATAL")
+        });
+        if let (true, Some(service)) = (is_error, &line.service) {
+            *counts.entry(service.clone()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Label for records without a value in a counted field.
+pub const NO_VALUE: &str = "(none)";
+
+/// Count records by the value of a field.
+pub fn count_by_field(records: &[Value], field: &str) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for record in records {
+        let value = field_str(record, &[field]).unwrap_or_else(|| NO_VALUE.to_string());
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+}
+
+/// Read the counts of an `$apply=groupby((field),aggregate($count as count))`
+/// response. Returns None if a row has no count, i.e. the service ignored
+/// `$apply` and returned plain records.
+pub fn grouped_counts(rows: &[Value], field: &str) -> Option<BTreeMap<String, usize>> {
+    let mut counts = BTreeMap::new();
+    for row in rows {
+        let count = match row.get("count")? {
+            Value::Number(n) => n.as_u64()?,
+            Value::String(s) => s.parse().ok()?,
+            _ => return None,
+        };
+        let value = field_str(row, &[field]).unwrap_or_else(|| NO_VALUE.to_string());
+        *counts.entry(value).or_default() += count as usize;
+    }
+    Some(counts)
+}
+
+/// Due-date buckets of open work, in days from today.
+const DUE_BUCKETS: &[(&str, i64)] = &[("due_7d", 7), ("due_30d", 30)];
+
+/// Kind of an assigned work item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkKind {
+    Task,
+    Feature,
+}
+
+/// An open task or feature with its assignee.
+#[derive(Debug, Clone)]
+pub struct WorkItem {
+    pub kind: WorkKind,
+    pub assignee_id: Option<String>,
+    pub assignee_name: Option<String>,
+    pub due_date: Option<chrono::NaiveDate>,
+}
+
+/// Open work of one assignee.
+#[derive(Debug, Default, Serialize)]
+pub struct AssigneeWorkload {
+    pub assignee: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub total: usize,
+    pub tasks: usize,
+    pub features: usize,
+    /// Counts by due-date bucket: overdue, due_7d, due_30d, later, no_due_date
+    pub by_due: BTreeMap<String, usize>,
+}
+
+/// Group open work items by assignee, busiest first. Items without an
+/// assignee are grouped as `unassigned`.
+pub fn summarize_workload(items: &[WorkItem], today: chrono::NaiveDate) -> Vec<AssigneeWorkload> {
+    let mut workloads: BTreeMap<String, AssigneeWorkload> = BTreeMap::new();
+    for item in items {
+        let assignee = item
+            .assignee_id
+            .clone()
+            .or_else(|| item.assignee_name.clone())
+            .unwrap_or_else(|| "unassigned".to_string());
+        let workload = workloads
+            .entry(assignee.clone())
+            .or_insert_with(|| AssigneeWorkload {
+                assignee,
+                ..Default::default()
+            });
+        if workload.name.is_none() {
+            workload.name = item.assignee_name.clone();
+        }
+        workload.total += 1;
+        match item.kind {
+            WorkKind::Task => workload.tasks += 1,
+            WorkKind::Feature => workload.features += 1,
+        }
+        let bucket = match item.due_date.map(|due| (due - today).num_days()) {
+            Some(days) if days < 0 => "overdue",
+            Some(days) => DUE_BUCKETS
+                .iter()
+                .find(|(_, max)| days <= *max)
+                .map(|(label, _)| *label)
+                .unwrap_or("later"),
+            None => "no_due_date",
+        };
+        *workload.by_due.entry(bucket.to_string()).or_default() += 1;
+    }
+
+    let mut workloads: Vec<AssigneeWorkload> = workloads.into_values().collect();
+    workloads.sort_by_key(|w| std::cmp::Reverse(w.total));
+    workloads
+}
+
+/// An open task, feature or document and when it was last modified.
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleItem {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_id: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    #[serde(skip)]
+    pub owner: Option<String>,
+    pub last_modified: Option<String>,
+    /// Days since the last modification, set by `stale_items_by_owner`
+    pub idle_days: i64,
+}
+
+/// Stale items of one owner, oldest first.
+#[derive(Debug, Serialize)]
+pub struct OwnerStaleItems {
+    pub owner: String,
+    pub count: usize,
+    pub items: Vec<StaleItem>,
+}
+
+/// Keep the items not modified for at least `idle_days` days and group
+/// them by owner, owners with the most stale items first. Items without an
+/// owner are grouped as `unassigned`; items without a modification date
+/// are dropped.
+pub fn stale_items_by_owner(
+    items: Vec<StaleItem>,
+    today: chrono::NaiveDate,
+    idle_days: i64,
+) -> Vec<OwnerStaleItems> {
+    let mut owners: BTreeMap<String, Vec<StaleItem>> = BTreeMap::new();
+    for mut item in items {
+        let Some(modified) = item
+            .last_modified
+            .as_deref()
+            .and_then(crate::calendar::parse_date)
+        else {
+            continue;
+        };
+        item.idle_days = (today - modified).num_days();
+        if item.idle_days < idle_days {
+            continue;
+        }
+        let owner = item
+            .owner
+            .clone()
+            .unwrap_or_else(|| "unassigned".to_string());
+        owners.entry(owner).or_default().push(item);
+    }
+
+    let mut owners: Vec<OwnerStaleItems> = owners
+        .into_iter()
+        .map(|(owner, mut items)| {
+            items.sort_by_key(|i| std::cmp::Reverse(i.idle_days));
+            OwnerStaleItems {
+                owner,
+                count: items.len(),
+                items,
+            }
+        })
+        .collect();
+    owners.sort_by_key(|o| std::cmp::Reverse(o.count));
+    owners
+}
+
+/// Candidate keys for the story points of a task.
+pub const STORY_POINT_KEYS: &[&str] = &["storyPoints", "points", "effort", "estimatedEffort"];
+
+/// Check whether a status denotes completed work (closed, but not
+/// cancelled or rejected).
+pub fn is_completed_status(status: &str) -> bool {
+    let upper = status.to_uppercase();
+    is_closed_status(status) && !upper.contains("CANCEL") && !upper.contains("REJECT")
+}
+
+/// A task assigned to a sprint.
+#[derive(Debug, Clone)]
+pub struct SprintTask {
+    pub id: String,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub is_defect: bool,
+    pub points: Option<f64>,
+}
+
+impl SprintTask {
+    fn completed(&self) -> bool {
+        self.status.as_deref().is_some_and(is_completed_status)
+    }
+
+    fn open(&self) -> bool {
+        !self.status.as_deref().is_some_and(is_closed_status)
+    }
+}
+
+/// Totals of one sprint.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct SprintSummary {
+    pub tasks: usize,
+    pub completed_tasks: usize,
+    pub open_tasks: usize,
+    pub points: f64,
+    pub completed_points: f64,
+    pub defects: usize,
+    pub open_defects: usize,
+}
+
+/// Sum up the tasks of a sprint.
+pub fn summarize_sprint(tasks: &[SprintTask]) -> SprintSummary {
+    let mut summary = SprintSummary::default();
+    for task in tasks {
+        let points = task.points.unwrap_or(0.0);
+        summary.tasks += 1;
+        summary.points += points;
+        if task.completed() {
+            summary.completed_tasks += 1;
+            summary.completed_points += points;
+        }
+        if task.open() {
+            summary.open_tasks += 1;
+        }
+        if task.is_defect {
+            summary.defects += 1;
+            if task.open() {
+                summary.open_defects += 1;
+            }
+        }
+    }
+    summary
+}
+
+/// A sprint of a comparison.
+#[derive(Debug, Clone)]
+pub struct Sprint<'a> {
+    pub name: &'a str,
+    pub start: Option<&'a str>,
+    pub end: Option<&'a str>,
+    pub tasks: &'a [SprintTask],
+}
+
+/// Render two sprints as a markdown comparison for retrospectives: totals
+/// with deltas (second minus first) and the items carried over, i.e. still
+/// open, from the first sprint.
+pub fn render_sprint_comparison(a: &Sprint, b: &Sprint, locale: &Locale) -> String {
+    let (sa, sb) = (summarize_sprint(a.tasks), summarize_sprint(b.tasks));
+    let count = |n: usize| locale.format_integer(n as i64);
+    let count_delta = |x: usize, y: usize| signed(locale.format_integer(y as i64 - x as i64));
+    let points = |p: f64| locale.format_number(p, 1);
+    let points_delta = |x: f64, y: f64| signed(locale.format_number(y - x, 1));
+    let rate = |s: &SprintSummary| {
+        if s.tasks == 0 {
+            0.0
+        } else {
+            s.completed_tasks as f64 * 100.0 / s.tasks as f64
+        }
+    };
+    let dates = |s: &Sprint| match (s.start, s.end) {
+        (Some(start), Some(end)) => format!(
+            " ({} - {})",
+            locale.format_timestamp(start),
+            locale.format_timestamp(end)
+        ),
+        _ => String::new(),
+    };
+
+    let mut lines = vec![
+        format!("# Sprint Comparison: {} vs. {}", a.name, b.name),
+        String::new(),
+        format!("- **A:** {}{}", a.name, dates(a)),
+        format!("- **B:** {}{}", b.name, dates(b)),
+        String::new(),
+        "| Metric | A | B | Delta |".to_string(),
+        "|--------|--:|--:|------:|".to_string(),
+    ];
+    let rows = [
+        (
+            "Tasks",
+            count(sa.tasks),
+            count(sb.tasks),
+            count_delta(sa.tasks, sb.tasks),
+        ),
+        (
+            "Completed tasks",
+            count(sa.completed_tasks),
+            count(sb.completed_tasks),
+            count_delta(sa.completed_tasks, sb.completed_tasks),
+        ),
+        (
+            "Completion rate (%)",
+            locale.format_number(rate(&sa), 1),
+            locale.format_number(rate(&sb), 1),
+            signed(locale.format_number(rate(&sb) - rate(&sa), 1)),
+        ),
+        (
+            "Story points",
+            points(sa.points),
+            points(sb.points),
+            points_delta(sa.points, sb.points),
+        ),
+        (
+            "Completed points",
+            points(sa.completed_points),
+            points(sb.completed_points),
+            points_delta(sa.completed_points, sb.completed_points),
+        ),
+        (
+            "Open tasks",
+            count(sa.open_tasks),
+            count(sb.open_tasks),
+            count_delta(sa.open_tasks, sb.open_tasks),
+        ),
+        (
+            "Defects",
+            count(sa.defects),
+            count(sb.defects),
+            count_delta(sa.defects, sb.defects),
+        ),
+        (
+            "Open defects",
+            count(sa.open_defects),
+            count(sb.open_defects),
+            count_delta(sa.open_defects, sb.open_defects),
+        ),
+    ];
+    for (metric, va, vb, delta) in rows {
+        lines.push(format!("| {} | {} | {} | {} |", metric, va, vb, delta));
+    }
+
+    let carry_over: Vec<&SprintTask> = a.tasks.iter().filter(|t| t.open()).collect();
+    lines.push(String::new());
+    lines.push(format!(
+        "## Carry-over from {} ({})",
+        a.name,
+        count(carry_over.len())
+    ));
+    lines.push(String::new());
+    if carry_over.is_empty() {
+        lines.push("No open items.".to_string());
+    }
+    for task in carry_over {
+        let mut line = format!("- {}", task.id);
+        if let Some(title) = &task.title {
+            line.push_str(&format!(": {}", title));
+        }
+        line.push_str(&format!(
+            " ({})",
+            task.status.as_deref().unwrap_or("no status")
+        ));
+        if let Some(points) = task.points {
+            line.push_str(&format!(", {} points", locale.format_number(points, 1)));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Prefix positive numbers with a plus sign.
+fn signed(number: String) -> String {
+    if number.starts_with('-') || number.chars().all(|c| !c.is_ascii_digit() || c == '0') {
+        number
+    } else {
+        format!("+{}", number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_str_uses_first_present_key() {
+        let value = json!({"status": "", "executionStatus": "Passed", "count": 3});
+        assert_eq!(
+            field_str(&value, &["status", "executionStatus"]),
+            Some("Passed".to_string())
+        );
+        assert_eq!(field_str(&value, &["count"]), Some("3".to_string()));
+        assert_eq!(field_str(&value, &["missing"]), None);
+    }
+
+    #[test]
+    fn test_collection_items_handles_envelope_and_array() {
+        assert_eq!(collection_items(&json!({"value": [1, 2]})).len(), 2);
+        assert_eq!(collection_items(&json!([1])).len(), 1);
+        assert!(collection_items(&json!({"other": 1})).is_empty());
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn test_classify_coverage() {
+        let testcases = vec![json!({"uuid": "tc-1", "links": ["feat-1"]})];
+        let runs = vec![
+            json!({"featureId": "feat-2", "status": "Failed"}),
+            json!({"featureId": "feat-3", "status": "Passed"}),
+        ];
+
+        assert_eq!(
+            classify_coverage(&["feat-1"], &testcases, &runs),
+            Coverage::Covered
+        );
+        assert_eq!(
+            classify_coverage(&["feat-2"], &testcases, &runs),
+            Coverage::OnlyFailed
+        );
+        assert_eq!(
+            classify_coverage(&["feat-3"], &testcases, &runs),
+            Coverage::Covered
+        );
+        assert_eq!(
+            classify_coverage(&["feat-4"], &testcases, &runs),
+            Coverage::Untested
+        );
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn test_summarize_test_progress_per_plan() {
+        let runs = vec![
+            json!({"testPlanId": "P1", "testPlanName": "SIT", "status": "Passed"}),
+            json!({"testPlanId": "P1", "status": "Failed"}),
+            json!({"testPlanId": "P1", "status": "Blocked"}),
+            json!({"testPlanId": "P1", "status": "Not Started"}),
+            json!({"status": "Passed"}),
+        ];
+
+        let progress = summarize_test_progress(&runs);
+        assert_eq!(progress.len(), 2);
+        let p1 = &progress[0];
+        assert_eq!(p1.plan_id, "P1");
+        assert_eq!(p1.plan_name.as_deref(), Some("SIT"));
+        assert_eq!(
+            (p1.total, p1.executed, p1.passed, p1.failed, p1.blocked),
+            (4, 3, 1, 1, 1)
+        );
+        assert_eq!(progress[1].plan_id, "unassigned");
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn test_top_failing_test_cases() {
+        let runs = vec![
+            json!({"testCaseId": "TC1", "status": "Failed"}),
+            json!({"testCaseId": "TC2", "status": "Failed"}),
+            json!({"testCaseId": "TC2", "status": "Failed"}),
+            json!({"testCaseId": "TC3", "status": "Passed"}),
+        ];
+        assert_eq!(
+            top_failing_test_cases(&runs, 1),
+            vec![("TC2".to_string(), 2)]
+        );
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn test_summarize_defects() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-06-30T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let defects = vec![
+            json!({"status": "Open", "severity": "High", "createdAt": "2024-06-28T00:00:00Z", "assignee": "anna"}),
+            json!({"status": "Open", "severity": "Low", "createdAt": "2024-05-15T00:00:00Z", "assignee": "anna"}),
+            json!({"status": "In Process", "createdAt": "2023-01-01T00:00:00Z", "assignee": "ben"}),
+            json!({"status": "Open"}),
+        ];
+
+        let summary = summarize_defects(&defects, now, 1);
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.by_status["Open"], 3);
+        assert_eq!(summary.by_severity["unknown"], 2);
+        assert_eq!(summary.by_age["0-7d"], 1);
+        assert_eq!(summary.by_age["31-90d"], 1);
+        assert_eq!(summary.by_age[">90d"], 1);
+        assert_eq!(summary.by_age["unknown"], 1);
+        assert_eq!(summary.top_assignees, vec![("anna".to_string(), 2)]);
+    }
+
+    #[cfg(all(feature = "analytics", feature = "logs"))]
+    #[test]
+    fn test_records_in_window() {
+        let from = chrono::DateTime::parse_from_rfc3339("2024-06-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let to = from + chrono::Duration::hours(1);
+        let records = vec![
+            json!({"id": 1, "timestamp": "2024-06-01T10:30:00Z"}),
+            json!({"id": 2, "timestamp": "2024-06-01T12:00:00Z"}),
+            json!({"id": 3}),
+        ];
+        let inside = records_in_window(&records, from, to);
+        assert_eq!(inside, vec![records[0].clone()]);
+        assert_eq!(
+            window_filter("timestamp", from, to),
+            "timestamp ge 2024-06-01T10:00:00Z and timestamp le 2024-06-01T11:00:00Z"
+        );
+    }
+
+    #[cfg(feature = "logs")]
+    #[test]
+    fn test_otel_log_lines() {
+        let payload = json!({
+            "resourceLogs": [{
+                "resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "billing"}}]},
+                "scopeLogs": [{"logRecords": [{
+                    "timeUnixNano": "1717236000000000000",
+                    "severityText": "ERROR",
+                    "body": {"stringValue": "Connection refused"}
+                }]}]
+            }]
+        });
+        let lines = otel_log_lines(&payload);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].service.as_deref(), Some("billing"));
+        assert_eq!(lines[0].severity.as_deref(), Some("ERROR"));
+        assert_eq!(lines[0].body, "Connection refused");
+        assert_eq!(lines[0].time.as_deref(), Some("2024-06-01T10:00:00+00:00"));
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn test_estimate_query_size() {
+        let sample = vec![json!({"id": 1, "name": "abcdef"}); 4];
+        let row_bytes = sample[0].to_string().len() as u64;
+
+        let small = estimate_query_size(10, &sample);
+        assert_eq!(small.average_row_bytes, row_bytes);
+        assert_eq!(small.estimated_bytes, row_bytes * 10);
+        assert!(small.recommendation.starts_with("Small"));
+
+        assert!(estimate_query_size(1_000, &sample)
+            .recommendation
+            .starts_with("Large"));
+        let huge = estimate_query_size(50_000, &sample);
+        assert!(huge.recommendation.contains("$apply"));
+        assert_eq!(huge.estimated_tokens, huge.estimated_bytes / 4);
+        assert_eq!(estimate_query_size(0, &[]).estimated_bytes, 0);
+    }
+
+    #[cfg(feature = "logs")]
+    #[test]
+    fn test_render_log_lines() {
+        let lines = vec![
+            LogLine {
+                time: Some("2024-06-01T10:00:00+00:00".to_string()),
+                severity: Some("WARN".to_string()),
+                service: Some("billing".to_string()),
+                body: "Retry failed\n  attempt 3\n".to_string(),
+            },
+            LogLine {
+                time: None,
+                severity: None,
+                service: None,
+                body: "started".to_string(),
+            },
+        ];
+        assert_eq!(
+            render_log_lines(&lines),
+            "2024-06-01 10:00:00.000 WARN  [billing] Retry failed | attempt 3\n- -     started\n"
+        );
+    }
+
+    #[cfg(all(feature = "analytics", feature = "logs"))]
+    #[test]
+    fn test_sla_breaches() {
+        let records = vec![
+            json!({"serviceName": "billing", "availability": 98.0, "target": 99.5}),
+            json!({"serviceName": "billing", "availability": "99.9"}),
+            json!({"serviceName": "orders", "availability": 99.95}),
+            json!({"serviceName": "orders", "availability": 99.9}),
+        ];
+        let logs = BTreeMap::from([("billing".to_string(), 3)]);
+        let breaches = sla_breaches(&records, 99.5, &logs);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].service, "billing");
+        assert_eq!(breaches[0].samples, 2);
+        assert_eq!(breaches[0].samples_below_target, 1);
+        assert_eq!(breaches[0].minimum, 98.0);
+        assert_eq!(breaches[0].error_log_lines, 3);
+    }
+
+    #[test]
+    fn test_count_by_field_and_grouped_counts() {
+        let records = vec![
+            json!({"statusCode": "OPEN"}),
+            json!({"statusCode": "OPEN"}),
+            json!({"statusCode": 20}),
+            json!({"title": "no status"}),
+        ];
+        let counts = count_by_field(&records, "statusCode");
+        assert_eq!(counts["OPEN"], 2);
+        assert_eq!(counts["20"], 1);
+        assert_eq!(counts[NO_VALUE], 1);
+
+        let rows = vec![
+            json!({"statusCode": "OPEN", "count": 12}),
+            json!({"statusCode": null, "count": "3"}),
+        ];
+        let counts = grouped_counts(&rows, "statusCode").unwrap();
+        assert_eq!(counts["OPEN"], 12);
+        assert_eq!(counts[NO_VALUE], 3);
+        assert!(grouped_counts(&records, "statusCode").is_none());
+    }
+
+    #[test]
+    fn test_summarize_workload() {
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let item = |kind, id: Option<&str>, due: Option<(u32, u32)>| WorkItem {
+            kind,
+            assignee_id: id.map(str::to_string),
+            assignee_name: id.map(|id| format!("Name of {}", id)),
+            due_date: due.and_then(|(m, d)| chrono::NaiveDate::from_ymd_opt(2024, m, d)),
+        };
+        let items = vec![
+            item(WorkKind::Task, Some("u1"), Some((6, 1))),
+            item(WorkKind::Task, Some("u1"), Some((6, 12))),
+            item(WorkKind::Feature, Some("u1"), None),
+            item(WorkKind::Task, Some("u2"), Some((9, 1))),
+            item(WorkKind::Task, None, Some((7, 1))),
+        ];
+
+        let workload = summarize_workload(&items, today);
+        assert_eq!(workload.len(), 3);
+        assert_eq!(workload[0].assignee, "u1");
+        assert_eq!(workload[0].name.as_deref(), Some("Name of u1"));
+        assert_eq!((workload[0].tasks, workload[0].features), (2, 1));
+        assert_eq!(workload[0].by_due["overdue"], 1);
+        assert_eq!(workload[0].by_due["due_7d"], 1);
+        assert_eq!(workload[0].by_due["no_due_date"], 1);
+        let unassigned = workload
+            .iter()
+            .find(|w| w.assignee == "unassigned")
+            .unwrap();
+        assert_eq!(unassigned.by_due["due_30d"], 1);
+        let u2 = workload.iter().find(|w| w.assignee == "u2").unwrap();
+        assert_eq!(u2.by_due["later"], 1);
+    }
+
+    #[test]
+    fn test_stale_items_by_owner() {
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+        let item = |id: &str, owner: Option<&str>, modified: Option<&str>| StaleItem {
+            kind: "task",
+            id: id.to_string(),
+            display_id: None,
+            title: None,
+            status: Some("CIPINPROGRESS".to_string()),
+            owner: owner.map(str::to_string),
+            last_modified: modified.map(str::to_string),
+            idle_days: 0,
+        };
+        let items = vec![
+            item("t1", Some("u1"), Some("2024-05-01T08:00:00Z")),
+            item("t2", Some("u1"), Some("2024-03-01")),
+            item("t3", Some("u2"), Some("2024-06-20T08:00:00Z")),
+            item("t4", None, Some("2024-01-15")),
+            item("t5", Some("u2"), None),
+        ];
+
+        let stale = stale_items_by_owner(items, today, 30);
+        assert_eq!(stale.len(), 2);
+        assert_eq!((stale[0].owner.as_str(), stale[0].count), ("u1", 2));
+        assert_eq!(stale[0].items[0].id, "t2");
+        assert_eq!(stale[0].items[0].idle_days, 121);
+        assert_eq!(stale[1].owner, "unassigned");
+    }
+
+    #[test]
+    fn test_render_sprint_comparison() {
+        let task = |id: &str, status: &str, is_defect: bool, points: Option<f64>| SprintTask {
+            id: id.to_string(),
+            title: Some(format!("Task {}", id)),
+            status: Some(status.to_string()),
+            is_defect,
+            points,
+        };
+        let a_tasks = vec![
+            task("t1", "CIPDONE", false, Some(3.0)),
+            task("t2", "CIPINPROGRESS", false, Some(5.0)),
+            task("d1", "CIPCANCELED", true, None),
+        ];
+        let b_tasks = vec![
+            task("t3", "CIPDONE", false, Some(8.0)),
+            task("t4", "CIPDONE", false, Some(2.5)),
+        ];
+        assert_eq!(
+            summarize_sprint(&a_tasks),
+            SprintSummary {
+                tasks: 3,
+                completed_tasks: 1,
+                open_tasks: 1,
+                points: 8.0,
+                completed_points: 3.0,
+                defects: 1,
+                open_defects: 0,
+            }
+        );
+
+        let sprint = |name, tasks| Sprint {
+            name,
+            start: Some("2024-06-03"),
+            end: Some("2024-06-14"),
+            tasks,
+        };
+        let locale = Locale::preset("de-DE").unwrap();
+        let report = render_sprint_comparison(
+            &sprint("Sprint 1", &a_tasks),
+            &sprint("Sprint 2", &b_tasks),
+            &locale,
+        );
+        assert!(report.starts_with("# Sprint Comparison: Sprint 1 vs. Sprint 2"));
+        assert!(report.contains("| Completed tasks | 1 | 2 | +1 |"));
+        assert!(report.contains("| Completed points | 3,0 | 10,5 | +7,5 |"));
+        assert!(report.contains("| Defects | 1 | 0 | -1 |"));
+        assert!(report.contains("| Open tasks | 1 | 0 | -1 |"));
+        assert!(report.contains(
+            "## Carry-over from Sprint 1 (1)\n\n- t2: Task t2 (CIPINPROGRESS), 5,0 points"
+        ));
+    }
+
+    #[test]
+    fn test_is_closed_status() {
+        assert!(is_closed_status("CIPDONE"));
+        assert!(is_closed_status("Cancelled"));
+        assert!(!is_closed_status("CIPINPROGRESS"));
+        assert!(!is_closed_status("Open"));
+    }
+}
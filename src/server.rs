@@ -1,40 +1,107 @@
 //! MCP Server implementation with SAP Cloud ALM tools.
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    handler::server::{router::tool::ToolRouter, tool::ToolCallContext, wrapper::Parameters},
     model::{
-        CallToolResult, Content, ErrorCode, ErrorData as McpError, Implementation, ProtocolVersion,
-        ServerCapabilities, ServerInfo,
+        AnnotateAble, CallToolRequestParam, CallToolResult, Content, ErrorCode,
+        ErrorData as McpError, Implementation, ListResourcesResult, ListToolsResult,
+        PaginatedRequestParam, ProtocolVersion, RawContent, RawResource, ReadResourceRequestParam,
+        ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo,
+        SubscribeRequestParam, UnsubscribeRequestParam,
     },
     schemars::{self, JsonSchema},
-    tool, tool_handler, tool_router, ServerHandler,
+    service::{NotificationContext, RequestContext},
+    tool, tool_router, Peer, RoleServer, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use crate::api::documents::{CreateDocumentRequest, UpdateDocumentRequest};
+use crate::accesslog::{AccessLog, AccessLogEntry, CallStatus};
+use crate::anonymize::Anonymizer;
+use crate::api::documents::{ApprovalAction, CreateDocumentRequest, UpdateDocumentRequest};
 use crate::api::features::{
     CreateExternalReferenceRequest, CreateFeatureRequest, UpdateFeatureRequest,
 };
-use crate::api::logs::{GetLogsParams, PostLogsParams};
+#[cfg(feature = "logs")]
+use crate::api::logs::{GetLogsParams, PostLogsParams, MAX_POST_BYTES};
 use crate::api::processhierarchy::{CreateHierarchyNodeRequest, UpdateHierarchyNodeRequest};
 use crate::api::projects::CreateProjectRequest;
 use crate::api::tasks::{
-    CreateTaskCommentRequest, CreateTaskRequest, ListTasksParams, UpdateTaskRequest,
+    CreateTaskCommentRequest, CreateTaskReferenceRequest, CreateTaskRequest, ListTasksParams,
+    UpdateTaskRequest,
 };
 use crate::api::testmanagement::{
-    CreateTestActionRequest, CreateTestActivityRequest, CreateTestCaseRequest,
-    UpdateTestCaseRequest,
+    plan_action_sequence, CreateTestActionRequest, CreateTestActivityRequest,
+    CreateTestCaseRequest, UpdateTestActionRequest, UpdateTestCaseRequest,
 };
+#[cfg(feature = "analytics")]
+use crate::api::AnalyticsClient;
+#[cfg(feature = "logs")]
+use crate::api::LogsClient;
+#[cfg(feature = "processmonitoring")]
+use crate::api::ProcessMonitoringClient;
 use crate::api::{
-    AnalyticsClient, DocumentsClient, FeaturesClient, LogsClient, ProcessHierarchyClient,
-    ProcessMonitoringClient, ProjectsClient, TasksClient, TestManagementClient,
+    CalmApiClient, DocumentsClient, FeaturesClient, ProcessHierarchyClient, ProjectsClient,
+    TasksClient, TestManagementClient,
 };
+use crate::archive::{archive_or_delete, ArchiveError, Removal};
+use crate::budget;
+use crate::calendar::{self, CalendarEvent};
+use crate::charm;
+use crate::checklist::{self, Criterion, CriterionResult, Evidence};
+use crate::composite::CompositeResult;
+use crate::config::Config;
 use crate::debug::DebugLogger;
-use crate::odata::ODataQuery;
+use crate::dedup::{self, Duplicate, DuplicateGuard};
+use crate::defaults::CreationDefaults;
+use crate::diff::{diff_record_sets, field_changes};
+use crate::error::ApiError;
+use crate::error_catalog;
+#[cfg(feature = "analytics")]
+use crate::export::{ExportFormat, ExportWriter};
+use crate::fields::{FieldAliases, FieldFilter};
+use crate::hierarchy::{
+    diff_snapshots, split_title_path, title_matches, title_search_filter, HierarchySnapshot,
+};
+use crate::instructions;
+use crate::locale::Locale;
+use crate::membership::ProjectAccess;
+use crate::odata::{self, ODataQuery};
+use crate::pagination::{self, Paging};
+use crate::pins::{self, PinBoard, PinType};
+use crate::queue::{self, PendingOperation, WriteQueue};
+use crate::refmap::{self, EntityType, ReferenceRow};
+#[cfg(feature = "analytics")]
+use crate::reports::{
+    classify_coverage, collection_items, estimate_query_size, field_str, run_status,
+    summarize_defects, summarize_test_progress, top_failing_test_cases, Coverage, RunStatus,
+};
+use crate::reports::{
+    count_by_field, field_f64, grouped_counts, is_closed_status, mentions_any,
+    render_sprint_comparison, stale_items_by_owner, summarize_workload, Sprint, SprintTask,
+    StaleItem, WorkItem, WorkKind, STORY_POINT_KEYS,
+};
+#[cfg(all(feature = "analytics", feature = "logs"))]
+use crate::reports::{
+    error_log_counts, records_in_window, sla_breaches, window_filter, LogLine, TIMESTAMP_KEYS,
+};
+#[cfg(feature = "logs")]
+use crate::reports::{otel_log_lines, render_log_lines};
+use crate::template::{self, ProjectTemplate, TEMPLATE_VERSION};
+use crate::transform::OutputPipeline;
+use crate::translate::Translator;
+use crate::traversal::{TraversalConfig, TraversalReport};
+use crate::verify;
+use crate::watchlist::{WatchedState, WatchedType, Watchlist};
+use crate::webhook::{WebhookInbox, EVENTS_URI};
+use crate::workflow;
+use crate::workshop::{split_requirements, SplitOptions};
+use crate::workspace::{self, Workspace};
 
 /// Container for all SAP Cloud ALM API clients.
 #[derive(Clone)]
@@ -45,8 +112,11 @@ pub struct ApiClients {
     pub projects: ProjectsClient,
     pub testmanagement: TestManagementClient,
     pub processhierarchy: ProcessHierarchyClient,
+    #[cfg(feature = "analytics")]
     pub analytics: AnalyticsClient,
+    #[cfg(feature = "processmonitoring")]
     pub processmonitoring: ProcessMonitoringClient,
+    #[cfg(feature = "logs")]
     pub logs: LogsClient,
 }
 
@@ -55,16 +125,851 @@ pub struct ApiClients {
 pub struct SapCloudAlmServer {
     clients: ApiClients,
     debug: Arc<DebugLogger>,
+    field_aliases: FieldAliases,
+    locale: Locale,
+    tool_budget: Option<Duration>,
+    transformers: Arc<OutputPipeline>,
+    access_log: Arc<AccessLog>,
+    profiles: Arc<HashMap<String, ApiClients>>,
+    webhooks: Option<WebhookInbox>,
+    project_access: Option<Arc<ProjectAccess>>,
+    duplicates: Option<Arc<DuplicateGuard>>,
+    write_queue: Option<Arc<WriteQueue>>,
+    traversal: TraversalConfig,
+    read_only: bool,
+    write_tools: Arc<HashSet<String>>,
+    paged_tools: Arc<HashMap<String, Paging>>,
+    instructions: String,
+    translator: Option<Arc<Translator>>,
+    prefer_archive: bool,
+    watchlist: Arc<Watchlist>,
+    workspace: Option<Arc<Workspace>>,
+    anonymizer: Arc<Anonymizer>,
+    feature_checklist: Vec<Criterion>,
+    creation_defaults: Arc<CreationDefaults>,
+    pins: PinBoard,
     tool_router: ToolRouter<Self>,
 }
 
+/// Who issued a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallOrigin {
+    /// The MCP client
+    Client,
+    /// The server itself, on behalf of another tool
+    Internal,
+}
+
 impl SapCloudAlmServer {
-    pub fn new(clients: ApiClients, debug: Arc<DebugLogger>) -> Self {
+    pub fn new(clients: ApiClients, debug: Arc<DebugLogger>, config: &Config) -> Self {
+        #[allow(unused_mut)]
+        let mut tool_router = Self::tool_router();
+        #[cfg(feature = "analytics")]
+        tool_router.merge(Self::analytics_tool_router());
+        #[cfg(feature = "processmonitoring")]
+        tool_router.merge(Self::processmonitoring_tool_router());
+        #[cfg(feature = "logs")]
+        tool_router.merge(Self::logs_tool_router());
+        #[cfg(all(feature = "analytics", feature = "logs"))]
+        tool_router.merge(Self::incident_tool_router());
+        let tools = tool_router.list_all();
+        let write_tools: HashSet<String> = tools
+            .iter()
+            .filter(|tool| instructions::is_write_tool(tool))
+            .map(|tool| tool.name.to_string())
+            .collect();
+        let paged_tools: HashMap<String, Paging> = tools
+            .iter()
+            .filter_map(|tool| Some((tool.name.to_string(), pagination::paging_of(tool)?)))
+            .collect();
+        let creation_defaults = CreationDefaults::new(&config.creation_defaults);
+        for argument in creation_defaults.unknown_arguments(&tools) {
+            tracing::warn!(argument = %argument, "Creation default is not an argument of the create tool");
+        }
+        let duplicates = config.duplicate_call_window_seconds.map(|seconds| {
+            Arc::new(
+                DuplicateGuard::new(Duration::from_secs(seconds), write_tools.clone())
+                    .with_schema_defaults(&tools),
+            )
+        });
         Self {
             clients,
             debug,
-            tool_router: Self::tool_router(),
+            field_aliases: FieldAliases::new(&config.field_aliases),
+            locale: Locale::from_config(&config.report_locale).unwrap_or_default(),
+            tool_budget: config.tool_budget_seconds.map(Duration::from_secs),
+            transformers: Arc::new(OutputPipeline::new(&config.output_transformers)),
+            access_log: Arc::new(AccessLog::new(config.access_log.as_deref())),
+            profiles: Arc::new(HashMap::new()),
+            webhooks: None,
+            project_access: None,
+            duplicates,
+            write_queue: None,
+            traversal: config.traversal.clone(),
+            read_only: config.read_only,
+            write_tools: Arc::new(write_tools),
+            paged_tools: Arc::new(paged_tools),
+            instructions: instructions::build(&tools, config),
+            translator: config
+                .translation
+                .as_ref()
+                .map(|translation| Arc::new(Translator::new(translation, config.timeout()))),
+            prefer_archive: config.prefer_archive_over_delete,
+            watchlist: Arc::new(Watchlist::default()),
+            workspace: config
+                .workspace_dir
+                .as_ref()
+                .map(|dir| Arc::new(Workspace::new(dir))),
+            anonymizer: Arc::new(Anonymizer::new(&config.anonymization)),
+            feature_checklist: checklist::parse_criteria(&config.feature_checklist)
+                .unwrap_or_else(|_| Criterion::ALL.to_vec()),
+            creation_defaults: Arc::new(creation_defaults),
+            pins: PinBoard::default(),
+            tool_router,
+        }
+    }
+
+    /// Expose received webhook events as MCP resources.
+    #[cfg(feature = "http-transport")]
+    pub fn with_webhook_inbox(mut self, inbox: WebhookInbox) -> Self {
+        self.webhooks = Some(inbox);
+        self
+    }
+
+    /// Request of a task created internally by a composite tool, with the
+    /// creation defaults of `create_task` applied. `task_type` is used if
+    /// neither the arguments nor the defaults set one. Also returns the
+    /// arguments set by defaults.
+    fn internal_task_request(
+        &self,
+        arguments: Value,
+        task_type: &str,
+    ) -> Result<(CreateTaskRequest, Vec<String>), McpError> {
+        let arguments = arguments.as_object().cloned().unwrap_or_default();
+        let (arguments, applied) = self.creation_defaults.complete(
+            "create_task",
+            arguments,
+            &[("task_type", json!(task_type))],
+        );
+        let params: CreateTaskParams =
+            serde_json::from_value(Value::Object(arguments)).map_err(invalid_params)?;
+        Ok((task_request(params), applied))
+    }
+
+    /// Webhook inbox, or an error if the webhook relay is not enabled.
+    fn webhook_inbox(&self) -> Result<&WebhookInbox, McpError> {
+        self.webhooks.as_ref().ok_or_else(|| McpError {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from(
+                "Webhook relay is not enabled (requires HTTP transport and webhook config)",
+            ),
+            data: None,
+        })
+    }
+
+    /// Verify project membership before create and update tools write anything.
+    pub fn with_project_access(mut self, access: ProjectAccess) -> Self {
+        self.project_access = Some(Arc::new(access));
+        self
+    }
+
+    /// Run the project membership pre-flight check, if enabled.
+    /// Writes without a target project are not checked.
+    async fn check_project_access(&self, project_id: Option<&str>) -> Result<(), McpError> {
+        match (&self.project_access, project_id) {
+            (Some(access), Some(project_id)) => access
+                .verify(&self.clients.projects, project_id)
+                .await
+                .map_err(invalid_params),
+            _ => Ok(()),
+        }
+    }
+
+    /// Run the project membership check for a write below a test case: the
+    /// project is the one of the test case, found through the parent
+    /// activity for test actions.
+    async fn check_test_access(
+        &self,
+        testcase_uuid: Option<&str>,
+        activity_uuid: Option<&str>,
+    ) -> Result<(), McpError> {
+        if self.project_access.is_none() {
+            return Ok(());
+        }
+        let testmanagement = &self.clients.testmanagement;
+        let parent = match activity_uuid {
+            Some(activity_uuid) => {
+                testmanagement
+                    .get_activity(activity_uuid)
+                    .await
+                    .map_err(to_mcp_error)?
+                    .parent_id
+            }
+            None => testcase_uuid.map(str::to_string),
+        };
+        let Some(testcase_uuid) = parent else {
+            return Ok(());
+        };
+        let testcase = testmanagement
+            .get_testcase(&testcase_uuid)
+            .await
+            .map_err(to_mcp_error)?;
+        self.check_project_access(testcase.project_id.as_deref())
+            .await
+    }
+
+    /// Buffer create and update calls while the API is unreachable.
+    pub fn with_write_queue(mut self, queue: WriteQueue) -> Self {
+        self.write_queue = Some(Arc::new(queue));
+        self
+    }
+
+    /// Workspace, or an error if no workspace directory is configured.
+    fn workspace(&self) -> Result<&Workspace, McpError> {
+        self.workspace.as_deref().ok_or_else(|| McpError {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from("Workspace is not enabled (set workspace_dir in the configuration)"),
+            data: None,
+        })
+    }
+
+    /// Path of a file written by a tool: inside the workspace if one is
+    /// configured, otherwise as given.
+    fn output_path(&self, path: &str) -> Result<String, McpError> {
+        match &self.workspace {
+            Some(workspace) => workspace
+                .resolve_for_write(path)
+                .map(|p| p.to_string_lossy().into_owned())
+                .map_err(invalid_params),
+            None => Ok(path.to_string()),
+        }
+    }
+
+    /// Path of a file read by a tool: inside the workspace if one is
+    /// configured, otherwise as given.
+    fn input_path(&self, path: &str) -> Result<String, McpError> {
+        match &self.workspace {
+            Some(workspace) => workspace
+                .resolve(path)
+                .map(|p| p.to_string_lossy().into_owned())
+                .map_err(invalid_params),
+            None => Ok(path.to_string()),
+        }
+    }
+
+    /// Test runs (Tests analytics) of a project referencing one of `ids`.
+    #[cfg(feature = "analytics")]
+    async fn test_run_evidence(
+        &self,
+        project_filter: &str,
+        ids: &[&str],
+    ) -> Result<Vec<Evidence>, String> {
+        let runs = self
+            .clients
+            .analytics
+            .get_tests(Some(ODataQuery::new().filter(project_filter)))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(collection_items(&runs)
+            .iter()
+            .filter(|run| mentions_any(run, ids))
+            .map(|run| {
+                let status = match run_status(run) {
+                    RunStatus::Passed => "passed",
+                    RunStatus::Failed => "failed",
+                    RunStatus::Blocked => "blocked",
+                    RunStatus::NotExecuted => "not_executed",
+                };
+                let id = field_str(run, &["id", "uuid", "testRunId", "testCaseId"]);
+                Evidence::new("test_run", id.unwrap_or_default(), Some(status.to_string()))
+                    .title(field_str(run, &["testCaseName", "name", "title"]))
+            })
+            .collect())
+    }
+
+    /// Current status and assignment of a watched entity.
+    async fn watched_state(
+        &self,
+        entity_type: WatchedType,
+        id: &str,
+    ) -> Result<WatchedState, crate::error::ApiError> {
+        match entity_type {
+            WatchedType::Feature => {
+                let feature = self.clients.features.get_feature(id).await?;
+                Ok(WatchedState {
+                    title: feature.title,
+                    status: feature.status_code,
+                    assignee: feature.responsible_id,
+                })
+            }
+            WatchedType::Task => {
+                let task = self.clients.tasks.get_task(id).await?;
+                Ok(WatchedState {
+                    title: task.title,
+                    status: task.status,
+                    assignee: task.assignee_name.or(task.assignee_id),
+                })
+            }
+            #[cfg(feature = "analytics")]
+            WatchedType::QualityGate => {
+                let query = ODataQuery::new().filter(format!("id eq '{}'", id)).top(1);
+                let gate = self
+                    .clients
+                    .analytics
+                    .get_quality_gates(Some(query))
+                    .await?
+                    .value
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| crate::error::ApiError::HttpError {
+                        status: reqwest::StatusCode::NOT_FOUND,
+                        body: format!("Quality gate '{}' not found", id),
+                    })?;
+                Ok(WatchedState {
+                    title: gate.name,
+                    status: gate.status,
+                    assignee: None,
+                })
+            }
+            #[cfg(not(feature = "analytics"))]
+            WatchedType::QualityGate => Err(crate::error::ApiError::HttpError {
+                status: reqwest::StatusCode::NOT_IMPLEMENTED,
+                body: "Quality gates require the analytics feature".to_string(),
+            }),
+        }
+    }
+
+    /// Write queue, or an error if offline buffering is not enabled.
+    fn queue(&self) -> Result<&WriteQueue, McpError> {
+        self.write_queue.as_deref().ok_or_else(|| McpError {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from("Write queue is not enabled (set write_queue in the configuration)"),
+            data: None,
+        })
+    }
+
+    /// Queue a write call that failed because the API is unreachable.
+    /// Returns the original error if the queue is full.
+    fn queue_operation(
+        &self,
+        queue: &WriteQueue,
+        tool: &str,
+        arguments: serde_json::Map<String, Value>,
+        error: McpError,
+    ) -> Result<CallToolResult, McpError> {
+        match queue.enqueue(tool, arguments, &error.message) {
+            Ok(id) => to_json_result(&json!({
+                "queued": true,
+                "operation_id": id,
+                "tool": tool,
+                "error": error.message,
+                "note": "The API is unreachable. The call is replayed with flush_pending_operations."
+            })),
+            Err(e) => {
+                tracing::warn!(tool = %tool, error = %e, "Write call not queued");
+                Err(error)
+            }
+        }
+    }
+
+    /// Run a tool call through the per-call pipeline: read-only check, field
+    /// aliases, creation defaults, pagination, verification, offline queue,
+    /// duplicate suppression, time budget and access log. Internal calls
+    /// (workflow steps, pins, read-backs) get the raw tool output: no
+    /// pagination envelope, translation, output aliases or transformers.
+    async fn dispatch(
+        &self,
+        mut request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+        origin: CallOrigin,
+    ) -> Result<CallToolResult, McpError> {
+        let started = Instant::now();
+        let tool_name = request.name.clone();
+
+        if self.read_only && self.write_tools.contains(tool_name.as_ref()) {
+            return Err(invalid_params(format!(
+                "{} is not available: the server runs in read-only mode",
+                tool_name
+            )));
+        }
+
+        if let Some(arguments) = request.arguments.as_mut() {
+            self.field_aliases.translate_arguments(arguments);
+        }
+        let defaults_applied = match request.arguments.as_mut() {
+            Some(arguments) => self.creation_defaults.apply(&tool_name, arguments),
+            None => Vec::new(),
+        };
+
+        // Pagination: `cursor` is consumed here, list results get an envelope
+        let paging = self
+            .paged_tools
+            .get(tool_name.as_ref())
+            .copied()
+            .filter(|_| origin == CallOrigin::Client);
+        if let Some(paging) = paging {
+            pagination::take_cursor(&tool_name, paging, request.arguments.as_mut())
+                .map_err(invalid_params)?;
+        }
+        let paging_call =
+            paging.map(|paging| (paging, request.arguments.clone().unwrap_or_default()));
+
+        // Read-back verification: `verify` is consumed here, not by the tool
+        let verify = verify::is_verifiable(&tool_name)
+            && verify::take_verify(request.arguments.as_mut())
+            && request.arguments.as_ref().and_then(|a| a.get("preview"))
+                != Some(&Value::Bool(true));
+        let verify_call = verify.then(|| {
+            (
+                request.arguments.clone().unwrap_or_default(),
+                context.clone(),
+            )
+        });
+
+        // Offline buffering: keep the arguments of queueable write calls
+        let queue_arguments = match (&self.write_queue, &request.arguments) {
+            (Some(_), Some(arguments))
+                if queue::is_queueable(&tool_name)
+                    && arguments.get("preview") != Some(&Value::Bool(true)) =>
+            {
+                Some(arguments.clone())
+            }
+            _ => None,
+        };
+        let peer = context.peer.clone();
+
+        // Duplicate write call suppression: `force` is consumed here, not by the tool.
+        // Unforced calls are reserved, so a concurrent identical call is refused too.
+        let mut duplicate_key = None;
+        let mut reserved = false;
+        let mut duplicate_of = None;
+        if let Some(guard) = self
+            .duplicates
+            .as_ref()
+            .filter(|g| g.is_write_tool(&tool_name))
+        {
+            let force = dedup::take_force(request.arguments.as_mut());
+            if let Some(key) = guard.call_key(&tool_name, request.arguments.as_ref()) {
+                if force {
+                    duplicate_key = Some(key);
+                } else {
+                    match guard.reserve(&key) {
+                        Ok(()) => {
+                            duplicate_key = Some(key);
+                            reserved = true;
+                        }
+                        Err(duplicate) => duplicate_of = Some(duplicate),
+                    }
+                }
+            }
+        }
+
+        let result = if let Some(duplicate) = duplicate_of {
+            let reason = match duplicate {
+                Duplicate::Running => "is still running".to_string(),
+                Duplicate::Succeeded(age) => format!("succeeded {} s ago", age.as_secs()),
+            };
+            Ok(CallToolResult::error(vec![Content::text(format!(
+                "Duplicate call suppressed: an identical {} call {}. Pass force: true to run it again.",
+                tool_name, reason
+            ))]))
+        } else {
+            let tcc = ToolCallContext::new(self, request, context);
+            match self.tool_budget {
+                Some(budget) => budget::run(budget, self.tool_router.call(tcc))
+                    .await
+                    .unwrap_or_else(|| {
+                        Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Tool call aborted: exceeded its time budget of {} s",
+                            budget.as_secs()
+                        ))]))
+                    }),
+                None => self.tool_router.call(tcc).await,
+            }
+        };
+
+        let mut queued = false;
+        let result = match (result, &self.write_queue, queue_arguments) {
+            (Err(e), Some(queue), Some(arguments)) if queue::is_unreachable(&e) => {
+                queued = true;
+                self.queue_operation(queue, &tool_name, arguments, e)
+            }
+            (result, ..) => result,
+        };
+
+        let mut result = result;
+        if let (Some((paging, arguments)), Ok(r)) = (&paging_call, &mut result) {
+            if r.is_error != Some(true) {
+                Self::apply_pagination(&tool_name, *paging, arguments, r);
+            }
+        }
+        if let (Some((arguments, context)), Ok(r)) = (verify_call, &mut result) {
+            if !queued && r.is_error != Some(true) {
+                Box::pin(self.verify_write(&tool_name, &arguments, r, context)).await;
+            }
+        }
+        if let Ok(r) = &mut result {
+            if r.is_error != Some(true) && !defaults_applied.is_empty() {
+                add_defaults_applied(r, &defaults_applied);
+            }
+        }
+        if let Ok(r) = &mut result {
+            if origin == CallOrigin::Client && r.is_error != Some(true) {
+                self.translate_result(r, &peer).await;
+            }
+        }
+
+        let result = result.map(|mut result| {
+            if origin == CallOrigin::Client {
+                self.apply_output_aliases(&mut result);
+                self.apply_output_transformers(&mut result);
+            }
+            result
+        });
+
+        if let (Some(guard), Some(key)) = (&self.duplicates, duplicate_key) {
+            match &result {
+                Ok(r) if r.is_error != Some(true) => guard.record(key),
+                _ if reserved => guard.release(&key),
+                _ => {}
+            }
+        }
+
+        if self.access_log.is_enabled() {
+            let (status, bytes) = match &result {
+                Ok(r) if r.is_error == Some(true) => (CallStatus::ToolError, content_bytes(r)),
+                Ok(r) => (CallStatus::Ok, content_bytes(r)),
+                Err(_) => (CallStatus::Error, 0),
+            };
+            self.access_log.record(&AccessLogEntry::new(
+                &tool_name,
+                started.elapsed(),
+                status,
+                bytes,
+            ));
+        }
+
+        result
+    }
+
+    /// Replay queued operations in queue order. Stops at the first operation
+    /// that fails because the API is still unreachable.
+    async fn replay_operations(
+        &self,
+        queue: &WriteQueue,
+        operations: Vec<PendingOperation>,
+        context: &RequestContext<RoleServer>,
+    ) -> Vec<Value> {
+        let mut results = Vec::new();
+        for op in operations {
+            let request = CallToolRequestParam {
+                name: Cow::from(op.tool.clone()),
+                arguments: Some(op.arguments),
+                task: None,
+            };
+            let tcc = ToolCallContext::new(self, request, context.clone());
+            let (status, error) = match self.tool_router.call(tcc).await {
+                Ok(r) if r.is_error != Some(true) => {
+                    queue.complete(op.id);
+                    ("replayed", None)
+                }
+                Ok(r) => {
+                    let message = r
+                        .content
+                        .iter()
+                        .find_map(|c| match &c.raw {
+                            RawContent::Text(text) => Some(text.text.clone()),
+                            _ => None,
+                        })
+                        .unwrap_or_default();
+                    queue.record_failure(op.id, &message, true);
+                    ("failed", Some(message))
+                }
+                Err(e) if queue::is_unreachable(&e) => {
+                    queue.record_failure(op.id, &e.message, false);
+                    results.push(json!({"id": op.id, "tool": op.tool, "status": "pending", "error": e.message}));
+                    break;
+                }
+                Err(e) => {
+                    queue.record_failure(op.id, &e.message, true);
+                    ("failed", Some(e.message.to_string()))
+                }
+            };
+            results.push(json!({"id": op.id, "tool": op.tool, "status": status, "error": error}));
+        }
+        results
+    }
+
+    /// Set the API clients of additional tenant profiles.
+    pub fn with_profiles(mut self, profiles: HashMap<String, ApiClients>) -> Self {
+        self.profiles = Arc::new(profiles);
+        self
+    }
+
+    /// API clients of a tenant profile. `None` or "default" selects the main tenant.
+    fn profile_clients(&self, profile: Option<&str>) -> Result<&ApiClients, McpError> {
+        match profile {
+            None | Some("default") => Ok(&self.clients),
+            Some(name) => self.profiles.get(name).ok_or_else(|| {
+                let mut known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                known.sort();
+                McpError {
+                    code: ErrorCode::INVALID_PARAMS,
+                    message: Cow::from(format!(
+                        "Unknown profile '{}'. Configured profiles: default, {}",
+                        name,
+                        known.join(", ")
+                    )),
+                    data: None,
+                }
+            }),
+        }
+    }
+
+    /// Rename API field names to configured aliases in JSON text content.
+    fn apply_output_aliases(&self, result: &mut CallToolResult) {
+        if self.field_aliases.is_empty() {
+            return;
+        }
+        for content in &mut result.content {
+            if let RawContent::Text(text) = &mut content.raw {
+                if let Ok(value) = serde_json::from_str::<Value>(&text.text) {
+                    let renamed = self.field_aliases.rename_output(value);
+                    if let Ok(json_str) = serde_json::to_string_pretty(&renamed) {
+                        text.text = json_str;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Wrap the JSON result of a list tool in the pagination envelope.
+    fn apply_pagination(
+        tool: &str,
+        paging: Paging,
+        arguments: &serde_json::Map<String, Value>,
+        result: &mut CallToolResult,
+    ) {
+        let Some(RawContent::Text(text)) = result.content.first_mut().map(|c| &mut c.raw) else {
+            return;
+        };
+        if let Ok(value) = serde_json::from_str::<Value>(&text.text) {
+            let wrapped = pagination::envelope(tool, paging, arguments, value);
+            if let Ok(json_str) = serde_json::to_string_pretty(&wrapped) {
+                text.text = json_str;
+            }
+        }
+    }
+
+    /// Run text content through the configured output transformers.
+    fn apply_output_transformers(&self, result: &mut CallToolResult) {
+        if self.transformers.is_empty() {
+            return;
+        }
+        for content in &mut result.content {
+            if let RawContent::Text(text) = &mut content.raw {
+                text.text = self.transformers.apply(&text.text);
+            }
+        }
+    }
+
+    /// Translate text fields of JSON content, if translation is configured.
+    /// Failures leave the result untranslated and add a note.
+    async fn translate_result(&self, result: &mut CallToolResult, peer: &Peer<RoleServer>) {
+        let Some(translator) = &self.translator else {
+            return;
+        };
+        let mut failure = None;
+        for content in &mut result.content {
+            if let RawContent::Text(text) = &mut content.raw {
+                let Ok(mut value) = serde_json::from_str::<Value>(&text.text) else {
+                    continue;
+                };
+                match translator.translate_value(&mut value, peer).await {
+                    Ok(0) => {}
+                    Ok(_) => {
+                        text.text = serde_json::to_string_pretty(&value)
+                            .unwrap_or_else(|_| text.text.clone());
+                    }
+                    Err(e) => failure = Some(e),
+                }
+            }
+        }
+        if let Some(error) = failure {
+            tracing::warn!(error = %error, "Result translation failed");
+            result
+                .content
+                .push(Content::text(format!("Translation skipped: {}", error)));
+        }
+    }
+
+    /// Read back the entity written by a create or update call and add the
+    /// comparison with the requested values to the result.
+    async fn verify_write(
+        &self,
+        tool: &str,
+        arguments: &serde_json::Map<String, Value>,
+        result: &mut CallToolResult,
+        context: RequestContext<RoleServer>,
+    ) {
+        let Some((get_tool, id_argument)) = verify::read_back(tool) else {
+            return;
+        };
+        let Some(RawContent::Text(text)) = result.content.first_mut().map(|c| &mut c.raw) else {
+            return;
+        };
+        let Ok(mut written) = serde_json::from_str::<Value>(&text.text) else {
+            return;
+        };
+        let verification = match verify::entity_id(arguments, &written) {
+            None => {
+                json!({"verified": false, "error": "The result does not identify the written entity"})
+            }
+            Some(id) => {
+                let mut get_arguments = serde_json::Map::new();
+                get_arguments.insert(id_argument.to_string(), json!(id));
+                let request = CallToolRequestParam {
+                    name: Cow::from(get_tool),
+                    arguments: Some(get_arguments),
+                    task: None,
+                };
+                let read = self.dispatch(request, context, CallOrigin::Internal).await;
+                let persisted = read
+                    .ok()
+                    .filter(|r| r.is_error != Some(true))
+                    .and_then(|r| {
+                        r.content.first().and_then(|c| match &c.raw {
+                            RawContent::Text(text) => {
+                                serde_json::from_str::<Value>(&text.text).ok()
+                            }
+                            _ => None,
+                        })
+                    });
+                match persisted {
+                    Some(persisted) => json!(verify::compare(arguments, &persisted)),
+                    None => {
+                        json!({"verified": false, "error": format!("Read-back with {} failed", get_tool)})
+                    }
+                }
+            }
+        };
+        match written.as_object_mut() {
+            Some(object) => {
+                object.insert("verification".to_string(), verification);
+                text.text =
+                    serde_json::to_string_pretty(&written).unwrap_or_else(|_| text.text.clone());
+            }
+            None => result
+                .content
+                .push(Content::text(format!("Verification: {}", verification))),
+        }
+    }
+
+    /// Set a document to the tenant's obsolete or archived status. Returns the
+    /// status name, or why the document cannot be archived.
+    async fn archive_document(&self, uuid: &str) -> Result<String, ArchiveError> {
+        let documents = &self.clients.documents;
+        let statuses = documents.list_statuses().await?.value;
+        let status = statuses
+            .iter()
+            .find(|s| {
+                let name = s.name.to_lowercase();
+                DOCUMENT_ARCHIVE_STATUSES.iter().any(|a| name.contains(a))
+            })
+            .ok_or_else(|| {
+                ArchiveError::NoArchiveStatus(
+                    "No obsolete or archived document status is configured in this tenant"
+                        .to_string(),
+                )
+            })?;
+        let request = UpdateDocumentRequest {
+            status_code: Some(status.code.to_string()),
+            ..Default::default()
+        };
+        documents.update_document(uuid, &request).await?;
+        Ok(status.name.clone())
+    }
+
+    /// Set a task to the archive status. Returns the status, or why the task
+    /// cannot be archived.
+    async fn archive_task(&self, id: &str) -> Result<String, ArchiveError> {
+        let request = UpdateTaskRequest {
+            status: Some(TASK_ARCHIVE_STATUS.to_string()),
+            ..Default::default()
+        };
+        self.clients.tasks.update_task(id, &request).await?;
+        Ok(TASK_ARCHIVE_STATUS.to_string())
+    }
+
+    /// Apply a sign-off step to a document after validating its current status.
+    async fn transition_document(
+        &self,
+        uuid: &str,
+        action: ApprovalAction,
+        comment: Option<String>,
+    ) -> Result<Value, McpError> {
+        let documents = &self.clients.documents;
+        let document = documents.get_document(uuid).await.map_err(to_mcp_error)?;
+        let statuses = documents.list_statuses().await.map_err(to_mcp_error)?.value;
+
+        let current = document
+            .status_code
+            .and_then(|code| statuses.iter().find(|s| s.code == code))
+            .map(|s| s.name.clone());
+        let allowed = action.allowed_from();
+        let valid = current
+            .as_deref()
+            .is_some_and(|name| allowed.iter().any(|a| a.eq_ignore_ascii_case(name)));
+        if !valid {
+            return Err(McpError {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(format!(
+                    "Document is in status '{}', expected one of {:?}",
+                    current.as_deref().unwrap_or("unknown"),
+                    allowed
+                )),
+                data: None,
+            });
         }
+
+        let target = statuses
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(action.target_status()))
+            .ok_or_else(|| McpError {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(format!(
+                    "No document status named '{}' is configured in this tenant",
+                    action.target_status()
+                )),
+                data: None,
+            })?;
+
+        // Record the comment as a note at the end of the document content
+        let content = comment.map(|c| {
+            format!(
+                "{}<p><strong>{}:</strong> {}</p>",
+                document.content.unwrap_or_default(),
+                action.label(),
+                escape_html(&c)
+            )
+        });
+
+        let request = UpdateDocumentRequest {
+            status_code: Some(target.code.to_string()),
+            content,
+            ..Default::default()
+        };
+        let updated = documents
+            .update_document(uuid, &request)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(json!({
+            "uuid": uuid,
+            "from_status": current,
+            "to_status": target.name,
+            "document": updated,
+        }))
     }
 }
 
@@ -73,10 +978,23 @@ impl SapCloudAlmServer {
 // ============================================================================
 
 /// Convert any error to McpError
-fn to_mcp_error<E: std::fmt::Display>(e: E) -> McpError {
+fn to_mcp_error<E: std::fmt::Display + 'static>(e: E) -> McpError {
+    // API errors carry whether the API was unreachable, for the write queue
+    let data = (&e as &dyn std::any::Any)
+        .downcast_ref::<ApiError>()
+        .and_then(queue::error_data);
     McpError {
         code: ErrorCode::INTERNAL_ERROR,
         message: Cow::from(e.to_string()),
+        data,
+    }
+}
+
+/// Convert a rejected parameter into an MCP invalid params error.
+fn invalid_params<E: std::fmt::Display>(e: E) -> McpError {
+    McpError {
+        code: ErrorCode::INVALID_PARAMS,
+        message: Cow::from(e.to_string()),
         data: None,
     }
 }
@@ -92,6 +1010,294 @@ fn to_json_result<T: Serialize>(value: &T) -> Result<CallToolResult, McpError> {
     Ok(CallToolResult::success(vec![Content::text(json_str)]))
 }
 
+/// Convert typed entities to JSON values for generic record processing.
+fn to_values<T: Serialize>(items: Vec<T>) -> Vec<Value> {
+    items
+        .into_iter()
+        .filter_map(|i| serde_json::to_value(i).ok())
+        .collect()
+}
+
+/// Document status names (matched as substrings, case-insensitive) used
+/// instead of deleting when prefer_archive_over_delete is set.
+const DOCUMENT_ARCHIVE_STATUSES: &[&str] = &["obsolete", "archive", "retired"];
+
+/// Task status set instead of deleting when prefer_archive_over_delete is set.
+const TASK_ARCHIVE_STATUS: &str = "CIPNOTRELEVANT";
+
+/// Maximum number of IDs accepted by the bulk get tools.
+const MAX_BULK_IDS: usize = 200;
+
+/// Number of concurrent requests of the bulk get tools.
+const BULK_CONCURRENCY: usize = 8;
+
+/// Trim and deduplicate the IDs of a bulk request, keeping their order.
+fn bulk_ids(ids: &[String]) -> Result<Vec<String>, McpError> {
+    let mut seen = HashSet::new();
+    let ids: Vec<String> = ids
+        .iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+        .collect();
+    if ids.is_empty() {
+        return Err(invalid_params("At least one ID is required"));
+    }
+    if ids.len() > MAX_BULK_IDS {
+        return Err(invalid_params(format!(
+            "At most {} IDs can be fetched at once, got {}",
+            MAX_BULK_IDS,
+            ids.len()
+        )));
+    }
+    Ok(ids)
+}
+
+/// Dimension names accepted by `count_by` with the field they group by, per entity type.
+const COUNT_DIMENSIONS: &[(&str, &str, &str)] = &[
+    ("features", "status", "statusCode"),
+    ("features", "priority", "priorityCode"),
+    ("features", "type", "type"),
+    ("features", "assignee", "responsibleId"),
+    ("features", "release", "releaseId"),
+    ("documents", "status", "statusCode"),
+    ("documents", "priority", "priorityCode"),
+    ("documents", "type", "documentTypeCode"),
+    ("test_cases", "status", "statusCode"),
+    ("tasks", "status", "status"),
+    ("tasks", "priority", "priorityId"),
+    ("tasks", "type", "type"),
+    ("tasks", "assignee", "assigneeName"),
+];
+
+/// Records read per page when `count_by` counts client-side.
+const COUNT_PAGE_SIZE: u32 = 1000;
+
+/// Maximum number of records `count_by` reads when counting client-side.
+const MAX_COUNTED_RECORDS: usize = 20_000;
+
+/// Entity types supported by `compare_tenants` with their default match key.
+const COMPARABLE_ENTITIES: &[(&str, &str)] = &[
+    ("features", "title"),
+    ("documents", "title"),
+    ("test_cases", "title"),
+    ("hierarchy_nodes", "title"),
+    ("projects", "name"),
+    ("feature_statuses", "code"),
+    ("feature_priorities", "code"),
+    ("document_types", "code"),
+    ("document_statuses", "code"),
+];
+
+/// Fields that differ between tenants by nature and are skipped when comparing.
+const TENANT_SPECIFIC_FIELDS: &[&str] = &[
+    "uuid",
+    "id",
+    "displayId",
+    "projectId",
+    "parentNodeUuid",
+    "rootNodeUuid",
+    "scopeId",
+    "createdAt",
+    "modifiedAt",
+    "createdBy",
+    "modifiedBy",
+];
+
+/// Fetch records of a comparable entity type as JSON values.
+/// The filter applies to OData collections only.
+async fn fetch_comparable(
+    clients: &ApiClients,
+    entity_type: &str,
+    filter: Option<&str>,
+) -> Result<Vec<Value>, crate::error::ApiError> {
+    // (backend, path, whether the collection accepts OData query options)
+    let (api, path, queryable): (&dyn CalmApiClient, &str, bool) = match entity_type {
+        "features" => (clients.features.api(), "/Features", true),
+        "documents" => (clients.documents.api(), "/Documents", true),
+        "test_cases" => (clients.testmanagement.api(), "/ManualTestCases", true),
+        "hierarchy_nodes" => (clients.processhierarchy.api(), "/HierarchyNodes", true),
+        "projects" => (clients.projects.api(), "/projects", false),
+        "feature_statuses" => (clients.features.api(), "/FeatureStatus", false),
+        "feature_priorities" => (clients.features.api(), "/FeaturePriorities", false),
+        "document_types" => (clients.documents.api(), "/DocumentTypes", false),
+        "document_statuses" => (clients.documents.api(), "/DocumentStatus", false),
+        _ => return Ok(Vec::new()),
+    };
+    let mut query = Vec::new();
+    if queryable {
+        query.push(("$top", "1000".to_string()));
+        if let Some(filter) = filter {
+            query.push(("$filter", filter.to_string()));
+        }
+    }
+    api.list(path, &query).await
+}
+
+/// Total size of the text content of a tool result.
+fn content_bytes(result: &CallToolResult) -> usize {
+    result
+        .content
+        .iter()
+        .filter_map(|c| c.raw.as_text())
+        .map(|t| t.text.len())
+        .sum()
+}
+
+/// Escape text for embedding in HTML document content.
+/// Report a traversal that was cut short by its limits or found cycles.
+fn add_traversal_warning(json: &mut Value, report: &TraversalReport) {
+    if report.is_incomplete() {
+        json["traversal"] = json!(report);
+        json["warning"] = json!(
+            "The hierarchy walk was cut short by the traversal limits or found nodes linked more than once; nodes beyond the limits are missing"
+        );
+    }
+}
+
+/// Name the arguments a create call took from the creation defaults in its
+/// result.
+fn add_defaults_applied(result: &mut CallToolResult, applied: &[String]) {
+    let Some(RawContent::Text(text)) = result.content.first_mut().map(|c| &mut c.raw) else {
+        return;
+    };
+    if let Ok(Value::Object(mut object)) = serde_json::from_str::<Value>(&text.text) {
+        object.insert("defaults_applied".to_string(), json!(applied));
+        if let Ok(updated) = serde_json::to_string_pretty(&object) {
+            text.text = updated;
+        }
+    }
+}
+
+/// Request creating the task described by `create_task` arguments.
+fn task_request(params: CreateTaskParams) -> CreateTaskRequest {
+    CreateTaskRequest {
+        project_id: params.project_id,
+        title: params.title,
+        task_type: params.task_type,
+        description: params.description,
+        priority_id: None,
+        assignee_id: params.assignee_id,
+        due_date: params.due_date,
+    }
+}
+
+/// Add the fields an update changed to the updated entity, as
+/// `changes: [{field, before, after}]`.
+fn add_changes<T: Serialize>(json: &mut Value, before: &T) -> Result<(), McpError> {
+    let before = serde_json::to_value(before).map_err(to_mcp_error)?;
+    let changes = field_changes(&before, json);
+    if let Some(object) = json.as_object_mut() {
+        object.insert("changes".to_string(), json!(changes));
+    }
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Parse an RFC 3339 timestamp parameter named `label`.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+fn parse_timestamp(label: &str, value: &str) -> Result<chrono::DateTime<chrono::Utc>, McpError> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| invalid_params(format!("Invalid '{}' timestamp '{}': {}", label, value, e)))
+}
+
+#[cfg(all(feature = "analytics", feature = "logs"))]
+/// Build the HTML content of a post-incident summary document.
+#[cfg(all(feature = "analytics", feature = "logs"))]
+fn incident_document_html(
+    from: &str,
+    to: &str,
+    events: &[Value],
+    log_lines: &[LogLine],
+    locale: &Locale,
+) -> String {
+    let event_field = |e: &Value, keys: &[&str]| {
+        escape_html(&field_str(e, keys).unwrap_or_else(|| "-".to_string()))
+    };
+    let event_time = |e: &Value| {
+        escape_html(
+            &field_str(e, TIMESTAMP_KEYS)
+                .map_or_else(|| "-".to_string(), |t| locale.format_timestamp(&t)),
+        )
+    };
+
+    let mut services: Vec<String> = events
+        .iter()
+        .filter_map(|e| field_str(e, &["managedObjectName", "serviceName", "systemName"]))
+        .chain(log_lines.iter().filter_map(|l| l.service.clone()))
+        .collect();
+    services.sort();
+    services.dedup();
+
+    let mut html = String::new();
+    html.push_str("<h2>Summary</h2>");
+    html.push_str(&format!(
+        "<p>Window: {} to {}. {} monitoring event(s), {} log line(s) collected.</p>",
+        escape_html(&locale.format_timestamp(from)),
+        escape_html(&locale.format_timestamp(to)),
+        locale.format_integer(events.len() as i64),
+        locale.format_integer(log_lines.len() as i64)
+    ));
+
+    html.push_str("<h2>Timeline</h2>");
+    if events.is_empty() {
+        html.push_str("<p>No monitoring events in the window.</p>");
+    } else {
+        html.push_str(
+            "<table><tr><th>Time</th><th>Event</th><th>Severity</th><th>Status</th></tr>",
+        );
+        for event in events {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                event_time(event),
+                event_field(event, &["eventName", "alertName", "name", "title"]),
+                event_field(event, &["severity", "rating", "priority"]),
+                event_field(event, &["status", "eventStatus"]),
+            ));
+        }
+        html.push_str("</table>");
+    }
+
+    html.push_str("<h2>Affected services</h2>");
+    if services.is_empty() {
+        html.push_str("<p>None identified.</p>");
+    } else {
+        html.push_str("<ul>");
+        for service in &services {
+            html.push_str(&format!("<li>{}</li>", escape_html(service)));
+        }
+        html.push_str("</ul>");
+    }
+
+    html.push_str("<h2>Log excerpts</h2>");
+    if log_lines.is_empty() {
+        html.push_str("<p>No log lines collected.</p>");
+    } else {
+        html.push_str("<pre>");
+        for line in log_lines {
+            html.push_str(&escape_html(&format!(
+                "{} {} {}\n",
+                line.time
+                    .as_deref()
+                    .map_or_else(|| "-".to_string(), |t| locale.format_timestamp(t)),
+                line.severity.as_deref().unwrap_or("-"),
+                line.body
+            )));
+        }
+        html.push_str("</pre>");
+    }
+
+    html.push_str("<h2>Root cause</h2><p>To be completed.</p>");
+    html.push_str("<h2>Follow-up actions</h2><ul><li>To be completed.</li></ul>");
+    html
+}
+
 // ============================================================================
 // Tool Parameter Structs
 // ============================================================================
@@ -103,15 +1309,17 @@ fn build_odata_query(
     orderby: Option<String>,
     top: Option<u32>,
     skip: Option<u32>,
-) -> Option<ODataQuery> {
+    custom_params: Option<BTreeMap<String, String>>,
+) -> Result<Option<ODataQuery>, McpError> {
     if filter.is_none()
         && select.is_none()
         && expand.is_none()
         && orderby.is_none()
         && top.is_none()
         && skip.is_none()
+        && custom_params.is_none()
     {
-        return None;
+        return Ok(None);
     }
 
     let mut query = ODataQuery::new();
@@ -141,7 +1349,10 @@ fn build_odata_query(
     if let Some(s) = skip {
         query = query.skip(s);
     }
-    Some(query)
+    if let Some(params) = custom_params {
+        query = query.custom_params(params).map_err(invalid_params)?;
+    }
+    Ok(Some(query))
 }
 
 // Feature tools params
@@ -159,6 +1370,27 @@ pub struct ListFeaturesParams {
     pub top: Option<u32>,
     /// Number of records to skip for pagination
     pub skip: Option<u32>,
+    /// Additional query options passed through unchanged (e.g. {"sap-language": "DE"})
+    pub custom_params: Option<BTreeMap<String, String>>,
+}
+
+#[cfg(feature = "analytics")]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportAnalyticsParams {
+    /// Data provider name (required)
+    pub provider: String,
+    /// Output file path on the server host (relative to the workspace directory, if configured)
+    pub path: String,
+    /// Output format: csv (default), ndjson or parquet (parquet requires a build with the 'parquet' feature)
+    pub format: Option<String>,
+    /// OData $filter expression
+    pub filter: Option<String>,
+    /// Records fetched per request (default: 1000)
+    pub page_size: Option<u32>,
+    /// Maximum number of records to export (default: 100000)
+    pub max_records: Option<u32>,
+    /// Replace user names and emails by pseudonyms and mask free-text fields (rules: 'anonymization' configuration)
+    pub anonymize: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -171,6 +1403,12 @@ pub struct GetFeatureParams {
     pub expand: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetFeaturesBulkParams {
+    /// Feature UUIDs (at most 200)
+    pub uuids: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateFeatureParams {
     /// Feature title (required)
@@ -215,6 +1453,14 @@ pub struct IdParams {
     pub id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CharmIdParams {
+    /// Solution Manager ChaRM change document number, optionally with transaction type (e.g. "8000001234" or "SMMJ 8000001234")
+    pub charm_id: String,
+    /// Only return features of this project
+    pub project_id: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListExternalReferencesParams {
     /// OData $filter expression
@@ -225,6 +1471,8 @@ pub struct ListExternalReferencesParams {
     pub top: Option<u32>,
     /// Number of records to skip for pagination
     pub skip: Option<u32>,
+    /// Additional query options passed through unchanged (e.g. {"sap-language": "DE"})
+    pub custom_params: Option<BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -247,6 +1495,29 @@ pub struct DeleteExternalReferenceParams {
     pub parent_uuid: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListFeaturesByReleaseParams {
+    /// Project ID to restrict the features to
+    pub project_id: Option<String>,
+    /// Additional OData $filter expression
+    pub filter: Option<String>,
+    /// Maximum number of features to group (default: 500)
+    pub top: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReassignFeaturesParams {
+    /// UUIDs of the features to move
+    pub uuids: Vec<String>,
+    /// Target release ID
+    pub release_id: String,
+    /// Only show the planned changes without applying them (default: true)
+    #[schemars(extend("default" = true))]
+    pub preview: Option<bool>,
+    /// Resume token of an earlier run; features it moved are skipped
+    pub resume_token: Option<String>,
+}
+
 // Document tools params
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListDocumentsParams {
@@ -260,6 +1531,8 @@ pub struct ListDocumentsParams {
     pub top: Option<u32>,
     /// Number of records to skip for pagination
     pub skip: Option<u32>,
+    /// Additional query options passed through unchanged (e.g. {"sap-language": "DE"})
+    pub custom_params: Option<BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -286,6 +1559,14 @@ pub struct UpdateDocumentParams {
     pub status_code: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentApprovalParams {
+    /// Document UUID
+    pub uuid: String,
+    /// Comment recorded with the status change
+    pub comment: Option<String>,
+}
+
 // Task tools params
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListTasksToolParams {
@@ -305,13 +1586,37 @@ pub struct ListTasksToolParams {
     pub offset: Option<u32>,
     /// Maximum number of records to return
     pub limit: Option<u32>,
+    /// Comma-separated fields to keep on each returned record
+    pub include_fields: Option<String>,
+    /// Comma-separated fields to remove from the result
+    pub exclude_fields: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct CreateTaskParams {
-    /// Project ID (required)
-    pub project_id: String,
-    /// Task title (required)
+pub struct GetTaskParams {
+    /// Task UUID
+    pub uuid: String,
+    /// Comma-separated fields to keep on each returned record
+    pub include_fields: Option<String>,
+    /// Comma-separated fields to remove from the result
+    pub exclude_fields: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetTasksBulkParams {
+    /// Task UUIDs (at most 200)
+    pub ids: Vec<String>,
+    /// Comma-separated fields to keep on each returned record
+    pub include_fields: Option<String>,
+    /// Comma-separated fields to remove from the result
+    pub exclude_fields: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateTaskParams {
+    /// Project ID (required)
+    pub project_id: String,
+    /// Task title (required)
     pub title: String,
     /// Task type (required)
     pub task_type: String,
@@ -358,6 +1663,24 @@ pub struct ProjectIdParams {
 }
 
 // Project tools params
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListProjectsParams {
+    /// Comma-separated fields to keep on each returned record
+    pub include_fields: Option<String>,
+    /// Comma-separated fields to remove from the result
+    pub exclude_fields: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetProjectParams {
+    /// Project ID
+    pub id: String,
+    /// Comma-separated fields to keep on each returned record
+    pub include_fields: Option<String>,
+    /// Comma-separated fields to remove from the result
+    pub exclude_fields: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateProjectParams {
     /// Project name (required)
@@ -381,6 +1704,8 @@ pub struct ODataListParams {
     pub top: Option<u32>,
     /// Number of records to skip for pagination
     pub skip: Option<u32>,
+    /// Additional query options passed through unchanged (e.g. {"sap-language": "DE"})
+    pub custom_params: Option<BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -433,6 +1758,32 @@ pub struct CreateTestActionParams {
     pub is_evidence_required: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateTestActionParams {
+    /// Action UUID
+    pub uuid: String,
+    /// New title
+    pub title: Option<String>,
+    /// New description
+    pub description: Option<String>,
+    /// New expected result
+    pub expected_result: Option<String>,
+    /// New sequence number within the activity
+    pub sequence: Option<i32>,
+    /// Whether evidence is required
+    pub is_evidence_required: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReorderTestActionsParams {
+    /// Parent activity UUID
+    pub activity_id: String,
+    /// Action UUIDs in the desired order. Actions not listed keep their relative order after these.
+    pub action_uuids: Vec<String>,
+    /// Resume token of an earlier run; actions it updated are skipped
+    pub resume_token: Option<String>,
+}
+
 // Process Hierarchy tools params
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetHierarchyNodeParams {
@@ -466,7 +1817,39 @@ pub struct UpdateHierarchyNodeParams {
     pub sequence: Option<i32>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportHierarchySnapshotParams {
+    /// UUID of the subtree root node
+    pub root_uuid: String,
+    /// Path of the JSON snapshot file to write
+    pub file_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiffHierarchyParams {
+    /// UUID of the subtree root node
+    pub root_uuid: String,
+    /// Path of a snapshot file previously written by export_hierarchy_snapshot
+    pub snapshot_file: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindHierarchyNodeParams {
+    /// Slash-separated title path, e.g. "Finance/Accounts Payable/Invoice Processing" (case-insensitive)
+    pub path: String,
+}
+
 // Analytics tools params
+#[cfg(feature = "analytics")]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EstimateQueryParams {
+    /// Data provider name (required)
+    pub provider: String,
+    /// OData $filter expression, as it would be passed to query_analytics_dataset
+    pub filter: Option<String>,
+}
+
+#[cfg(feature = "analytics")]
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct QueryDatasetParams {
     /// Data provider name (required)
@@ -481,14 +1864,17 @@ pub struct QueryDatasetParams {
     pub top: Option<u32>,
     /// Number of records to skip for pagination
     pub skip: Option<u32>,
+    /// Additional query options passed through unchanged (e.g. {"sap-language": "DE"})
+    pub custom_params: Option<BTreeMap<String, String>>,
 }
 
 // Logs tools params
+#[cfg(feature = "logs")]
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetLogsToolParams {
     /// Provider name (required)
     pub provider: String,
-    /// Log format
+    /// Log format requested from the API, or "text" for compact lines (timestamp, severity, service, body)
     pub format: Option<String>,
     /// API version
     pub version: Option<String>,
@@ -504,8 +1890,14 @@ pub struct GetLogsToolParams {
     pub offset: Option<u32>,
     /// Service ID filter
     pub service_id: Option<String>,
+    /// Comma-separated fields to keep on each log record (the entries of
+    /// logRecords, e.g. timeUnixNano,severityText,body)
+    pub include_fields: Option<String>,
+    /// Comma-separated fields to remove from the result
+    pub exclude_fields: Option<String>,
 }
 
+#[cfg(feature = "logs")]
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PostLogsToolParams {
     /// Use case identifier (required)
@@ -520,6 +1912,311 @@ pub struct PostLogsToolParams {
     pub tag: Option<String>,
     /// Log data (JSON array of log entries)
     pub logs: Value,
+    /// Maximum size of one POST in bytes; larger payloads are split into
+    /// several POSTs (default: 1048576)
+    pub max_chunk_bytes: Option<usize>,
+}
+
+// Cross-API analysis tools params
+#[cfg(feature = "analytics")]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TestProgressParams {
+    /// Project ID (project_id or plan_id required)
+    pub project_id: Option<String>,
+    /// Test plan ID to restrict the report to
+    pub plan_id: Option<String>,
+    /// Number of top failing test cases to list (default: 10)
+    pub top_failing: Option<u32>,
+}
+
+#[cfg(feature = "analytics")]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SummarizeDefectsParams {
+    /// Project ID
+    pub project_id: String,
+    /// Number of top assignees to list (default: 5)
+    pub top_assignees: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CountByParams {
+    /// Entity type: features, documents, test_cases, tasks
+    pub entity: String,
+    /// Field to group by: status, priority, type, assignee, release (features), or any field name of the entity
+    pub dimension: String,
+    /// OData filter expression (not supported for tasks)
+    pub filter: Option<String>,
+    /// Restrict to a project (required for tasks)
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkloadParams {
+    /// Project ID
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StaleItemsParams {
+    /// Project ID
+    pub project_id: String,
+    /// Minimum number of days since the last modification (default: 30)
+    pub idle_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CompareSprintsParams {
+    /// Project ID
+    pub project_id: String,
+    /// First sprint (timebox ID or name)
+    pub timebox_a: String,
+    /// Second sprint (timebox ID or name)
+    pub timebox_b: String,
+    /// Locale of dates and numbers, e.g. de-DE or en-US (default: configured report_locale)
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureReadinessParams {
+    /// Feature UUID
+    pub uuid: String,
+    /// Criteria to evaluate instead of the configured checklist: tasks_done, tests_passed, transports_assigned, approvals_granted
+    pub criteria: Option<Vec<String>>,
+}
+
+#[cfg(feature = "analytics")]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EscalateAlertParams {
+    /// Alert (monitoring event) ID
+    pub alert_id: String,
+    /// Project ID to create the task in
+    pub project_id: String,
+    /// Task type (default: CALMTASK)
+    pub task_type: Option<String>,
+    /// Link to the alert in Cloud ALM Operations, stored on the task reference
+    pub alert_url: Option<String>,
+}
+
+#[cfg(all(feature = "analytics", feature = "logs"))]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct IncidentDocumentParams {
+    /// Project ID to create the document in
+    pub project_id: String,
+    /// Start of the incident window (ISO 8601, e.g. 2024-06-01T10:00:00Z)
+    pub from: String,
+    /// End of the incident window (ISO 8601)
+    pub to: String,
+    /// Document title (default: "Incident summary <from> - <to>")
+    pub title: Option<String>,
+    /// Log provider to collect log excerpts from (omit to skip logs)
+    pub log_provider: Option<String>,
+    /// Restrict logs to a service ID
+    pub service_id: Option<String>,
+    /// Maximum number of log lines to include (default: 50)
+    pub max_log_lines: Option<u32>,
+    /// Return the generated content without creating the document
+    pub preview: Option<bool>,
+    /// Locale of dates and numbers, e.g. de-DE or en-US (default: configured report_locale)
+    pub locale: Option<String>,
+}
+
+#[cfg(all(feature = "analytics", feature = "logs"))]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CheckSlaBreachesParams {
+    /// Start of the period (ISO 8601, default: all available data)
+    pub from: Option<String>,
+    /// End of the period (ISO 8601, default: now)
+    pub to: Option<String>,
+    /// Target service level in percent for records without their own target (default: 99.5)
+    pub target: Option<f64>,
+    /// OData filter applied to the ServiceLevels provider
+    pub filter: Option<String>,
+    /// Log provider to count error log lines per breaching service (omit to skip logs)
+    pub log_provider: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportRequirementsParams {
+    /// Project ID to create the requirements in
+    pub project_id: String,
+    /// Workshop notes (plain text or markdown)
+    pub text: String,
+    /// Process hierarchy node UUIDs to link each requirement to
+    pub node_uuids: Option<Vec<String>>,
+    /// Keywords that mark a line as a requirement (default: must, shall, should, needs to, required)
+    pub keywords: Option<Vec<String>>,
+    /// Treat every list item as a requirement candidate (default: true)
+    pub include_list_items: Option<bool>,
+    /// Minimum statement length in characters (default: 10)
+    pub min_length: Option<u32>,
+    /// Task type used for requirements (default: CALMREQU)
+    pub task_type: Option<String>,
+    /// Only return the candidates without creating anything (default: true)
+    #[schemars(extend("default" = true))]
+    pub dry_run: Option<bool>,
+    /// Resume token of an earlier run; requirements it created are skipped
+    pub resume_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportReferencesParams {
+    /// Project ID whose feature and task references are exported
+    pub project_id: String,
+    /// File to write the CSV to, relative to the workspace directory if configured (default: return the CSV in the result)
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProjectCalendarParams {
+    /// Project ID
+    pub project_id: String,
+    /// Include due dates of open tasks (default: true)
+    pub include_tasks: Option<bool>,
+    /// File to write the .ics calendar to, relative to the workspace directory if configured (default: return it in the result)
+    pub path: Option<String>,
+    /// Replace user names and emails by pseudonyms and mask free-text fields (rules: 'anonymization' configuration)
+    pub anonymize: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExtractTemplateParams {
+    /// Project ID to extract the template from
+    pub project_id: String,
+    /// File to write the template to, relative to the workspace directory if configured (default: return it in the result)
+    pub path: Option<String>,
+    /// Minimum number of tasks sharing a title pattern for it to count as recurring (default: 2)
+    pub min_occurrences: Option<usize>,
+    /// Replace user names and emails by pseudonyms and mask free-text fields (rules: 'anonymization' configuration)
+    pub anonymize: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportReferencesParams {
+    /// Project ID the referenced features and tasks belong to
+    pub project_id: String,
+    /// Mapping file content: CSV with entity_type (feature/task), entity_id, external_system, external_id, url
+    pub csv: Option<String>,
+    /// Path of a mapping file to read instead of csv (relative to the workspace directory, if configured)
+    pub path: Option<String>,
+    /// Only validate rows and resolve entities without creating references (default: true)
+    #[schemars(extend("default" = true))]
+    pub dry_run: Option<bool>,
+    /// Resume token of an earlier run; rows it linked are skipped
+    pub resume_token: Option<String>,
+}
+
+#[cfg(feature = "analytics")]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProcessOverviewParams {
+    /// Process hierarchy node UUID
+    pub node_uuid: String,
+    /// Project ID to scope requirements, documents, test cases and tasks (tasks are only included with a project)
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExplainErrorParams {
+    /// HTTP status, OData or OAuth error code (e.g. "403", "invalid_client") or a complete error message
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TenantInventoryParams {
+    /// Restrict counts to a project
+    pub project_id: Option<String>,
+    /// Tenant profile to inventory (default: the main tenant)
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CompareTenantsParams {
+    /// Entity type: features, documents, test_cases, hierarchy_nodes, projects, feature_statuses, feature_priorities, document_types, document_statuses
+    pub entity_type: String,
+    /// OData filter applied on both tenants (OData entity types only)
+    pub filter: Option<String>,
+    /// Profile name of the first tenant (default: the main tenant)
+    pub left: Option<String>,
+    /// Profile name of the second tenant, as configured under "profiles"
+    pub right: String,
+    /// Field used to match records across tenants (default: title, name for projects, code for code lists)
+    pub key: Option<String>,
+    /// Additional comma-separated fields to ignore when comparing
+    pub ignore_fields: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FlushOperationsParams {
+    /// IDs of the queued operations to replay (default: all, including failed ones)
+    pub ids: Option<Vec<u64>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiscardOperationsParams {
+    /// IDs of the queued operations to discard
+    pub ids: Option<Vec<u64>>,
+    /// Discard all queued operations (required when no IDs are given)
+    pub all: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WatchEntitiesParams {
+    /// Entity type: feature, task or quality_gate
+    pub entity_type: String,
+    /// IDs of the entities (feature UUIDs, task IDs, quality gate IDs)
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UnwatchEntitiesParams {
+    /// Entity type: feature, task or quality_gate (required with ids)
+    pub entity_type: Option<String>,
+    /// IDs of the entities to stop watching
+    pub ids: Option<Vec<String>>,
+    /// Clear the whole watchlist (required when no IDs are given)
+    pub all: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadWorkspaceFileParams {
+    /// File path relative to the workspace directory, as listed by list_workspace_files
+    pub path: String,
+    /// Byte offset to start reading at (default: 0); use next_offset of the previous chunk
+    pub offset: Option<u64>,
+    /// Maximum number of bytes to read (default: 65536, maximum: 1048576)
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PinEntityParams {
+    /// Entity type: feature, document, task, testcase, hierarchy_node or project
+    pub entity_type: String,
+    /// Entity ID (UUID; features also by display ID, projects by project ID)
+    pub id: String,
+    /// Short note why the entity matters in this session
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UnpinEntityParams {
+    /// Entity type (required with id)
+    pub entity_type: Option<String>,
+    /// Entity ID as pinned
+    pub id: Option<String>,
+    /// Unpin all entities (required when no ID is given)
+    pub all: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunWorkflowParams {
+    /// Workflow definition as JSON or YAML: name, variables (defaults) and steps (id, tool, arguments, optional for_each and continue_on_error)
+    pub definition: Option<String>,
+    /// Path of a workflow definition file to read instead of definition (relative to the workspace directory, if configured)
+    pub path: Option<String>,
+    /// Variable values, overriding the defaults of the definition
+    pub variables: Option<serde_json::Map<String, Value>>,
+    /// Only validate the workflow and return the planned calls (default: true)
+    #[schemars(extend("default" = true))]
+    pub dry_run: Option<bool>,
 }
 
 // ============================================================================
@@ -555,7 +2252,8 @@ impl SapCloudAlmServer {
             orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -636,6 +2334,41 @@ impl SapCloudAlmServer {
         to_json_result(&json)
     }
 
+    #[tool(
+        description = "Get many features by UUID in one call (at most 200), using OData filters instead of one request per feature. Returns a map of UUID to feature and the UUIDs that were not found."
+    )]
+    async fn get_features_bulk(
+        &self,
+        Parameters(params): Parameters<GetFeaturesBulkParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("get_features_bulk", &json!(params));
+
+        let uuids = bulk_ids(&params.uuids)?;
+        let features = self
+            .clients
+            .features
+            .get_features_by_uuids(&uuids)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let mut found = serde_json::Map::new();
+        for feature in features {
+            if let Some(uuid) = feature.uuid.clone() {
+                found.insert(uuid, serde_json::to_value(&feature).map_err(to_mcp_error)?);
+            }
+        }
+        let not_found: Vec<&String> = uuids.iter().filter(|u| !found.contains_key(*u)).collect();
+
+        let json = json!({
+            "features": found,
+            "not_found": not_found,
+        });
+        self.debug.log_tool_result("get_features_bulk", &json);
+
+        to_json_result(&json)
+    }
+
     #[tool(
         description = "[EXPERIMENTAL] Create a new feature. Requires user confirmation before execution. Required: title and project_id."
     )]
@@ -644,6 +2377,7 @@ impl SapCloudAlmServer {
         Parameters(params): Parameters<CreateFeatureParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("create_feature", &json!(params));
+        self.check_project_access(Some(&params.project_id)).await?;
 
         let request = CreateFeatureRequest {
             title: params.title,
@@ -669,13 +2403,23 @@ impl SapCloudAlmServer {
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Update an existing feature. Requires user confirmation before execution. Only provided fields will be updated."
+        description = "[EXPERIMENTAL] Update an existing feature. Requires user confirmation before execution. Only provided fields will be updated. The result lists the changed fields with their values before and after the update under changes."
     )]
     async fn update_feature(
         &self,
         Parameters(params): Parameters<UpdateFeatureParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("update_feature", &json!(params));
+        let current = self
+            .clients
+            .features
+            .get_feature(&params.uuid)
+            .await
+            .map_err(to_mcp_error)?;
+        if self.project_access.is_some() {
+            self.check_project_access(current.project_id.as_deref())
+                .await?;
+        }
 
         let request = UpdateFeatureRequest {
             title: params.title,
@@ -693,7 +2437,8 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let mut json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        add_changes(&mut json, &current)?;
         self.debug.log_tool_result("update_feature", &json);
 
         to_json_result(&json)
@@ -738,7 +2483,8 @@ impl SapCloudAlmServer {
             None,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -754,6 +2500,82 @@ impl SapCloudAlmServer {
         to_json_result(&json)
     }
 
+    #[tool(
+        description = "Find features linked to a SAP Solution Manager ChaRM change document via their external references. Accepts the document number in common spellings (8000001234, SMMJ 8000001234, ChaRM: 1234) and matches references that mention it in their ID, name or URL. Required: charm_id."
+    )]
+    async fn find_feature_by_charm_id(
+        &self,
+        Parameters(params): Parameters<CharmIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("find_feature_by_charm_id", &json!(params));
+
+        let charm_id = charm::parse_charm_id(&params.charm_id).ok_or_else(|| {
+            invalid_params(format!(
+                "'{}' is not a ChaRM change document number",
+                params.charm_id
+            ))
+        })?;
+        // References may store the number with or without leading zeros
+        let digits = charm_id.number.trim_start_matches('0');
+        let filter = format!(
+            "contains(id,'{0}') or contains(name,'{0}') or contains(url,'{0}')",
+            digits
+        );
+        let references = odata::read_all(ODataQuery::new().filter(filter), |q| {
+            self.clients.features.list_external_references(Some(q))
+        })
+        .await
+        .map_err(to_mcp_error)?;
+
+        let mut features: HashMap<String, Value> = HashMap::new();
+        let mut matches = Vec::new();
+        for reference in references.items {
+            let found = charm::reference_charm_ids(
+                reference.id.as_deref(),
+                reference.name.as_deref(),
+                reference.url.as_deref(),
+            );
+            if !found.iter().any(|f| f.number == charm_id.number) {
+                continue;
+            }
+            let Some(parent) = reference.parent_uuid.clone() else {
+                continue;
+            };
+            if !features.contains_key(&parent) {
+                let feature = self
+                    .clients
+                    .features
+                    .get_feature(&parent)
+                    .await
+                    .map_err(to_mcp_error)?;
+                features.insert(
+                    parent.clone(),
+                    serde_json::to_value(&feature).map_err(to_mcp_error)?,
+                );
+            }
+            let feature = &features[&parent];
+            if let Some(project_id) = &params.project_id {
+                if feature.get("projectId").and_then(Value::as_str) != Some(project_id.as_str()) {
+                    continue;
+                }
+            }
+            matches.push(json!({"feature": feature, "reference": reference}));
+        }
+
+        let json = json!({
+            "charm_id": charm_id.number,
+            "transaction_type": charm_id.transaction_type,
+            "match_count": matches.len(),
+            "matches": matches,
+            "truncated": references.truncated,
+        });
+        self.debug
+            .log_tool_result("find_feature_by_charm_id", &json);
+
+        to_json_result(&json)
+    }
+
     #[tool(
         description = "[EXPERIMENTAL] Create an external reference for a feature. Requires user confirmation before execution."
     )]
@@ -763,6 +2585,16 @@ impl SapCloudAlmServer {
     ) -> Result<CallToolResult, McpError> {
         self.debug
             .log_tool_call("create_external_reference", &json!(params));
+        if self.project_access.is_some() {
+            let feature = self
+                .clients
+                .features
+                .get_feature(&params.parent_uuid)
+                .await
+                .map_err(to_mcp_error)?;
+            self.check_project_access(feature.project_id.as_deref())
+                .await?;
+        }
 
         let request = CreateExternalReferenceRequest {
             parent_uuid: params.parent_uuid,
@@ -845,6 +2677,136 @@ impl SapCloudAlmServer {
         to_json_result(&json)
     }
 
+    #[tool(
+        description = "List features grouped by release. Features without a release are grouped under 'unassigned'."
+    )]
+    async fn list_features_by_release(
+        &self,
+        Parameters(params): Parameters<ListFeaturesByReleaseParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("list_features_by_release", &json!(params));
+
+        let filter = match (params.project_id, params.filter) {
+            (Some(p), Some(f)) => Some(format!("projectId eq '{}' and ({})", p, f)),
+            (Some(p), None) => Some(format!("projectId eq '{}'", p)),
+            (None, f) => f,
+        };
+
+        let mut query = ODataQuery::new()
+            .orderby("releaseId", crate::odata::SortOrder::Asc)
+            .top(params.top.unwrap_or(500));
+        if let Some(f) = filter {
+            query = query.filter(f);
+        }
+
+        let result = self
+            .clients
+            .features
+            .list_features(Some(query))
+            .await
+            .map_err(to_mcp_error)?;
+
+        let mut releases: std::collections::BTreeMap<String, Vec<Value>> =
+            std::collections::BTreeMap::new();
+        for feature in &result.value {
+            let release = feature
+                .release_id
+                .clone()
+                .unwrap_or_else(|| "unassigned".to_string());
+            releases.entry(release).or_default().push(json!({
+                "uuid": feature.uuid,
+                "display_id": feature.display_id,
+                "title": feature.title,
+                "status_code": feature.status_code,
+            }));
+        }
+
+        let groups: Vec<Value> = releases
+            .into_iter()
+            .map(|(release_id, features)| {
+                json!({
+                    "release_id": release_id,
+                    "count": features.len(),
+                    "features": features,
+                })
+            })
+            .collect();
+
+        let json = json!({
+            "total": result.value.len(),
+            "releases": groups,
+        });
+        self.debug
+            .log_tool_result("list_features_by_release", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Move features to another release in bulk. Requires user confirmation before execution. Runs as a preview unless preview is set to false."
+    )]
+    async fn reassign_features_to_release(
+        &self,
+        Parameters(params): Parameters<ReassignFeaturesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("reassign_features_to_release", &json!(params));
+
+        let preview = params.preview.unwrap_or(true);
+        let mut result = CompositeResult::resume(
+            "reassign_features_to_release",
+            &json!(params),
+            params.resume_token.as_deref(),
+        )
+        .map_err(invalid_params)?;
+
+        for uuid in &params.uuids {
+            if result.skip_completed(uuid) || result.skip_over_budget(uuid) {
+                continue;
+            }
+            let feature = match self.clients.features.get_feature(uuid).await {
+                Ok(f) => f,
+                Err(e) => {
+                    result.fail(uuid, e, Value::Null);
+                    continue;
+                }
+            };
+
+            let change = json!({
+                "display_id": feature.display_id,
+                "title": feature.title,
+                "from_release": feature.release_id,
+                "to_release": params.release_id,
+            });
+
+            if feature.release_id.as_deref() == Some(params.release_id.as_str()) {
+                result.skip(uuid.as_str(), "already in the target release");
+            } else if preview {
+                result.succeed(uuid.as_str(), change);
+            } else {
+                let request = UpdateFeatureRequest {
+                    release_id: Some(params.release_id.clone()),
+                    ..Default::default()
+                };
+                match self.clients.features.update_feature(uuid, &request).await {
+                    Ok(_) => result.succeed(uuid.as_str(), change),
+                    Err(e) => result.fail(uuid.as_str(), e, change),
+                }
+            }
+        }
+
+        // A preview changes nothing, so there is nothing to resume
+        let result = if preview { result } else { result.finish() };
+        let mut json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        json["preview"] = json!(preview);
+        json["release_id"] = json!(params.release_id);
+        self.debug
+            .log_tool_result("reassign_features_to_release", &json);
+
+        to_json_result(&json)
+    }
+
     // ========================================================================
     // Documents API Tools
     // ========================================================================
@@ -863,7 +2825,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -907,6 +2870,8 @@ impl SapCloudAlmServer {
         Parameters(params): Parameters<CreateDocumentParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("create_document", &json!(params));
+        self.check_project_access(params.project_id.as_deref())
+            .await?;
 
         let request = CreateDocumentRequest {
             title: params.title,
@@ -931,13 +2896,23 @@ impl SapCloudAlmServer {
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Update an existing document. Requires user confirmation before execution."
+        description = "[EXPERIMENTAL] Update an existing document. Requires user confirmation before execution. The result lists the changed fields with their values before and after the update under changes."
     )]
     async fn update_document(
         &self,
         Parameters(params): Parameters<UpdateDocumentParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("update_document", &json!(params));
+        let current = self
+            .clients
+            .documents
+            .get_document(&params.uuid)
+            .await
+            .map_err(to_mcp_error)?;
+        if self.project_access.is_some() {
+            self.check_project_access(current.project_id.as_deref())
+                .await?;
+        }
 
         let request = UpdateDocumentRequest {
             title: params.title,
@@ -954,14 +2929,15 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let mut json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        add_changes(&mut json, &current)?;
         self.debug.log_tool_result("update_document", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Delete a document by UUID. Requires user confirmation before execution."
+        description = "[EXPERIMENTAL] Delete a document by UUID. With prefer_archive_over_delete configured, the document is set to an obsolete/archived status instead where the tenant has one; the result reports the applied semantic. Requires user confirmation before execution."
     )]
     async fn delete_document(
         &self,
@@ -970,17 +2946,27 @@ impl SapCloudAlmServer {
         self.debug
             .log_tool_call("delete_document", &json!({"uuid": params.uuid}));
 
-        self.clients
-            .documents
-            .delete_document(&params.uuid)
-            .await
-            .map_err(to_mcp_error)?;
+        let removal = archive_or_delete(
+            self.prefer_archive,
+            self.archive_document(&params.uuid),
+            self.clients.documents.delete_document(&params.uuid),
+        )
+        .await
+        .map_err(to_mcp_error)?;
+        let archive_error = match removal {
+            Removal::Archived(status) => {
+                let json = json!({"deleted": false, "archived": true, "semantic": "archive", "status": status, "uuid": params.uuid});
+                self.debug.log_tool_result("delete_document", &json);
+                return to_json_result(&json);
+            }
+            Removal::Deleted { no_archive_status } => no_archive_status,
+        };
 
-        self.debug
-            .log_tool_result("delete_document", &json!({"deleted": true}));
+        let json = json!({"deleted": true, "semantic": "delete", "uuid": params.uuid, "archive_error": archive_error});
+        self.debug.log_tool_result("delete_document", &json);
 
         Ok(CallToolResult::success(vec![Content::text(
-            json!({"deleted": true, "uuid": params.uuid}).to_string(),
+            json.to_string(),
         )]))
     }
 
@@ -1019,6 +3005,59 @@ impl SapCloudAlmServer {
         to_json_result(&json)
     }
 
+    #[tool(
+        description = "[EXPERIMENTAL] Submit a document for approval (moves it to 'In Review'). Requires user confirmation before execution."
+    )]
+    async fn submit_document_for_approval(
+        &self,
+        Parameters(params): Parameters<DocumentApprovalParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("submit_document_for_approval", &json!(params));
+
+        let json = self
+            .transition_document(&params.uuid, ApprovalAction::Submit, params.comment)
+            .await?;
+        self.debug
+            .log_tool_result("submit_document_for_approval", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Approve a document that is in review. Requires user confirmation before execution."
+    )]
+    async fn approve_document(
+        &self,
+        Parameters(params): Parameters<DocumentApprovalParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("approve_document", &json!(params));
+
+        let json = self
+            .transition_document(&params.uuid, ApprovalAction::Approve, params.comment)
+            .await?;
+        self.debug.log_tool_result("approve_document", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Reject a document that is in review. Requires user confirmation before execution."
+    )]
+    async fn reject_document(
+        &self,
+        Parameters(params): Parameters<DocumentApprovalParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("reject_document", &json!(params));
+
+        let json = self
+            .transition_document(&params.uuid, ApprovalAction::Reject, params.comment)
+            .await?;
+        self.debug.log_tool_result("reject_document", &json);
+
+        to_json_result(&json)
+    }
+
     // ========================================================================
     // Tasks API Tools
     // ========================================================================
@@ -1037,6 +3076,11 @@ impl SapCloudAlmServer {
             .tags
             .map(|t: String| t.split(',').map(|s: &str| s.trim().to_string()).collect());
 
+        let field_filter = FieldFilter::new(
+            params.include_fields.as_deref(),
+            params.exclude_fields.as_deref(),
+        );
+
         let list_params = ListTasksParams {
             project_id: params.project_id,
             task_type: params.task_type,
@@ -1056,7 +3100,7 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let json = field_filter.apply(serde_json::to_value(&result).map_err(to_mcp_error)?);
         self.debug.log_tool_result("list_tasks", &json);
 
         to_json_result(&json)
@@ -1065,10 +3109,14 @@ impl SapCloudAlmServer {
     #[tool(description = "Get a single task by UUID with full details.")]
     async fn get_task(
         &self,
-        Parameters(params): Parameters<UuidParams>,
+        Parameters(params): Parameters<GetTaskParams>,
     ) -> Result<CallToolResult, McpError> {
-        self.debug
-            .log_tool_call("get_task", &json!({"uuid": params.uuid}));
+        self.debug.log_tool_call("get_task", &json!(params));
+
+        let field_filter = FieldFilter::new(
+            params.include_fields.as_deref(),
+            params.exclude_fields.as_deref(),
+        );
 
         let result = self
             .clients
@@ -1077,12 +3125,71 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let json = field_filter.apply(serde_json::to_value(&result).map_err(to_mcp_error)?);
         self.debug.log_tool_result("get_task", &json);
 
         to_json_result(&json)
     }
 
+    #[tool(
+        description = "Get many tasks by UUID in one call (at most 200). Tasks are fetched with a bounded number of concurrent requests. Returns a map of UUID to task, the UUIDs that were not found and the UUIDs whose request failed."
+    )]
+    async fn get_tasks_bulk(
+        &self,
+        Parameters(params): Parameters<GetTasksBulkParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("get_tasks_bulk", &json!(params));
+
+        let ids = bulk_ids(&params.ids)?;
+        let field_filter = FieldFilter::new(
+            params.include_fields.as_deref(),
+            params.exclude_fields.as_deref(),
+        );
+
+        let permits = Arc::new(tokio::sync::Semaphore::new(BULK_CONCURRENCY));
+        let mut calls = tokio::task::JoinSet::new();
+        for id in ids.iter().cloned() {
+            let tasks = self.clients.tasks.clone();
+            let permits = permits.clone();
+            calls.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let task = tasks.get_task(&id).await;
+                (id, task)
+            });
+        }
+
+        let mut found = serde_json::Map::new();
+        let mut not_found = Vec::new();
+        let mut failed = serde_json::Map::new();
+        for (id, task) in calls.join_all().await {
+            match task {
+                Ok(task) => {
+                    let value = serde_json::to_value(&task).map_err(to_mcp_error)?;
+                    found.insert(id, field_filter.apply(value));
+                }
+                Err(crate::error::ApiError::HttpError { status, .. })
+                    if status == reqwest::StatusCode::NOT_FOUND =>
+                {
+                    not_found.push(id)
+                }
+                Err(e) => {
+                    failed.insert(id, json!(e.to_string()));
+                }
+            }
+        }
+        // Report missing IDs in request order
+        not_found.sort_by_key(|id| ids.iter().position(|i| i == id));
+
+        let json = json!({
+            "tasks": found,
+            "not_found": not_found,
+            "failed": failed,
+        });
+        self.debug.log_tool_result("get_tasks_bulk", &json);
+
+        to_json_result(&json)
+    }
+
     #[tool(
         description = "[EXPERIMENTAL] Create a new task. Requires user confirmation before execution. Required: project_id, title, task_type."
     )]
@@ -1091,17 +3198,9 @@ impl SapCloudAlmServer {
         Parameters(params): Parameters<CreateTaskParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("create_task", &json!(params));
+        self.check_project_access(Some(&params.project_id)).await?;
 
-        let request = CreateTaskRequest {
-            project_id: params.project_id,
-            title: params.title,
-            task_type: params.task_type,
-            description: params.description,
-            priority_id: None,
-            assignee_id: params.assignee_id,
-            due_date: params.due_date,
-        };
-
+        let request = task_request(params);
         let result = self
             .clients
             .tasks
@@ -1116,13 +3215,23 @@ impl SapCloudAlmServer {
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Update an existing task. Requires user confirmation before execution."
+        description = "[EXPERIMENTAL] Update an existing task. Requires user confirmation before execution. The result lists the changed fields with their values before and after the update under changes."
     )]
     async fn update_task(
         &self,
         Parameters(params): Parameters<UpdateTaskParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("update_task", &json!(params));
+        let current = self
+            .clients
+            .tasks
+            .get_task(&params.uuid)
+            .await
+            .map_err(to_mcp_error)?;
+        if self.project_access.is_some() {
+            self.check_project_access(current.project_id.as_deref())
+                .await?;
+        }
 
         let request = UpdateTaskRequest {
             title: params.title,
@@ -1140,14 +3249,15 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let mut json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        add_changes(&mut json, &current)?;
         self.debug.log_tool_result("update_task", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Delete a task by UUID. Requires user confirmation before execution."
+        description = "[EXPERIMENTAL] Delete a task by UUID. With prefer_archive_over_delete configured, the task is set to status Not Relevant instead; the result reports the applied semantic. Requires user confirmation before execution."
     )]
     async fn delete_task(
         &self,
@@ -1156,17 +3266,27 @@ impl SapCloudAlmServer {
         self.debug
             .log_tool_call("delete_task", &json!({"uuid": params.uuid}));
 
-        self.clients
-            .tasks
-            .delete_task(&params.uuid)
-            .await
-            .map_err(to_mcp_error)?;
+        let removal = archive_or_delete(
+            self.prefer_archive,
+            self.archive_task(&params.uuid),
+            self.clients.tasks.delete_task(&params.uuid),
+        )
+        .await
+        .map_err(to_mcp_error)?;
+        let archive_error = match removal {
+            Removal::Archived(status) => {
+                let json = json!({"deleted": false, "archived": true, "semantic": "archive", "status": status, "uuid": params.uuid});
+                self.debug.log_tool_result("delete_task", &json);
+                return to_json_result(&json);
+            }
+            Removal::Deleted { no_archive_status } => no_archive_status,
+        };
 
-        self.debug
-            .log_tool_result("delete_task", &json!({"deleted": true}));
+        let json = json!({"deleted": true, "semantic": "delete", "uuid": params.uuid, "archive_error": archive_error});
+        self.debug.log_tool_result("delete_task", &json);
 
         Ok(CallToolResult::success(vec![Content::text(
-            json!({"deleted": true, "uuid": params.uuid}).to_string(),
+            json.to_string(),
         )]))
     }
 
@@ -1290,8 +3410,16 @@ impl SapCloudAlmServer {
     // ========================================================================
 
     #[tool(description = "List all accessible projects.")]
-    async fn list_projects(&self) -> Result<CallToolResult, McpError> {
-        self.debug.log_tool_call("list_projects", &json!({}));
+    async fn list_projects(
+        &self,
+        Parameters(params): Parameters<ListProjectsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("list_projects", &json!(params));
+
+        let field_filter = FieldFilter::new(
+            params.include_fields.as_deref(),
+            params.exclude_fields.as_deref(),
+        );
 
         let result = self
             .clients
@@ -1300,7 +3428,7 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let json = field_filter.apply(serde_json::to_value(&result).map_err(to_mcp_error)?);
         self.debug.log_tool_result("list_projects", &json);
 
         to_json_result(&json)
@@ -1309,10 +3437,14 @@ impl SapCloudAlmServer {
     #[tool(description = "Get project details by ID.")]
     async fn get_project(
         &self,
-        Parameters(params): Parameters<IdParams>,
+        Parameters(params): Parameters<GetProjectParams>,
     ) -> Result<CallToolResult, McpError> {
-        self.debug
-            .log_tool_call("get_project", &json!({"id": params.id}));
+        self.debug.log_tool_call("get_project", &json!(params));
+
+        let field_filter = FieldFilter::new(
+            params.include_fields.as_deref(),
+            params.exclude_fields.as_deref(),
+        );
 
         let result = self
             .clients
@@ -1321,7 +3453,7 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        let json = field_filter.apply(serde_json::to_value(&result).map_err(to_mcp_error)?);
         self.debug.log_tool_result("get_project", &json);
 
         to_json_result(&json)
@@ -1457,7 +3589,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -1501,6 +3634,8 @@ impl SapCloudAlmServer {
         Parameters(params): Parameters<CreateTestcaseParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("create_testcase", &json!(params));
+        self.check_project_access(params.project_id.as_deref())
+            .await?;
 
         let request = CreateTestCaseRequest {
             title: params.title,
@@ -1529,6 +3664,16 @@ impl SapCloudAlmServer {
         Parameters(params): Parameters<UpdateTestcaseParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("update_testcase", &json!(params));
+        if self.project_access.is_some() {
+            let current = self
+                .clients
+                .testmanagement
+                .get_testcase(&params.uuid)
+                .await
+                .map_err(to_mcp_error)?;
+            self.check_project_access(current.project_id.as_deref())
+                .await?;
+        }
 
         let request = UpdateTestCaseRequest {
             title: params.title,
@@ -1588,7 +3733,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -1612,6 +3758,8 @@ impl SapCloudAlmServer {
     ) -> Result<CallToolResult, McpError> {
         self.debug
             .log_tool_call("create_test_activity", &json!(params));
+        self.check_test_access(Some(&params.parent_id), None)
+            .await?;
 
         let request = CreateTestActivityRequest {
             title: params.title,
@@ -1627,228 +3775,2371 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
-        self.debug.log_tool_result("create_test_activity", &json);
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("create_test_activity", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(description = "List test actions with OData filtering.")]
+    async fn list_test_actions(
+        &self,
+        Parameters(params): Parameters<ODataListParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("list_test_actions", &json!(params));
+
+        let query = build_odata_query(
+            params.filter,
+            params.select,
+            params.expand,
+            params.orderby,
+            params.top,
+            params.skip,
+            params.custom_params,
+        )?;
+
+        let result = self
+            .clients
+            .testmanagement
+            .list_actions(query)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("list_test_actions", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Create a test action for an activity. Requires user confirmation before execution."
+    )]
+    async fn create_test_action(
+        &self,
+        Parameters(params): Parameters<CreateTestActionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("create_test_action", &json!(params));
+        self.check_test_access(None, Some(&params.parent_id))
+            .await?;
+
+        let request = CreateTestActionRequest {
+            title: params.title,
+            parent_id: params.parent_id,
+            description: params.description,
+            expected_result: params.expected_result,
+            sequence: params.sequence,
+            is_evidence_required: params.is_evidence_required,
+        };
+
+        let result = self
+            .clients
+            .testmanagement
+            .create_action(&request)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("create_test_action", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Update a test action (title, description, expected result, sequence, evidence requirement). Requires user confirmation before execution."
+    )]
+    async fn update_test_action(
+        &self,
+        Parameters(params): Parameters<UpdateTestActionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("update_test_action", &json!(params));
+        if self.project_access.is_some() {
+            let current = self
+                .clients
+                .testmanagement
+                .get_action(&params.uuid)
+                .await
+                .map_err(to_mcp_error)?;
+            self.check_test_access(None, current.parent_id.as_deref())
+                .await?;
+        }
+
+        let request = UpdateTestActionRequest {
+            title: params.title,
+            description: params.description,
+            expected_result: params.expected_result,
+            sequence: params.sequence,
+            is_evidence_required: params.is_evidence_required,
+        };
+
+        let result = self
+            .clients
+            .testmanagement
+            .update_action(&params.uuid, &request)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("update_test_action", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Reorder the test actions of an activity by renumbering their sequence. Requires user confirmation before execution. Required: activity_id, action_uuids (desired order)."
+    )]
+    async fn reorder_test_actions(
+        &self,
+        Parameters(params): Parameters<ReorderTestActionsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("reorder_test_actions", &json!(params));
+
+        let actions = self
+            .clients
+            .testmanagement
+            .list_actions_for_activity(&params.activity_id)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let changes =
+            plan_action_sequence(&actions, &params.action_uuids).map_err(invalid_params)?;
+
+        let mut result = CompositeResult::resume(
+            "reorder_test_actions",
+            &json!(params),
+            params.resume_token.as_deref(),
+        )
+        .map_err(invalid_params)?;
+        for (uuid, sequence) in changes {
+            if result.skip_completed(&uuid) || result.skip_over_budget(&uuid) {
+                continue;
+            }
+            let request = UpdateTestActionRequest {
+                sequence: Some(sequence),
+                ..Default::default()
+            };
+            match self
+                .clients
+                .testmanagement
+                .update_action(&uuid, &request)
+                .await
+            {
+                Ok(_) => result.succeed(uuid, json!({"sequence": sequence})),
+                Err(e) => result.fail(uuid, e, json!({"sequence": sequence})),
+            }
+        }
+
+        let mut json = serde_json::to_value(result.finish()).map_err(to_mcp_error)?;
+        json["activity_id"] = json!(params.activity_id);
+        json["actions"] = json!(actions.len());
+        self.debug.log_tool_result("reorder_test_actions", &json);
+
+        to_json_result(&json)
+    }
+
+    // ========================================================================
+    // Process Hierarchy API Tools
+    // ========================================================================
+
+    #[tool(description = "List process hierarchy nodes with OData filtering.")]
+    async fn list_hierarchy_nodes(
+        &self,
+        Parameters(params): Parameters<ODataListParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("list_hierarchy_nodes", &json!(params));
+
+        let query = build_odata_query(
+            params.filter,
+            params.select,
+            params.expand,
+            params.orderby,
+            params.top,
+            params.skip,
+            params.custom_params,
+        )?;
+
+        let result = self
+            .clients
+            .processhierarchy
+            .list_nodes(query)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("list_hierarchy_nodes", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Get a hierarchy node by UUID. Optionally expand toParentNode, toChildNodes, toExternalReferences."
+    )]
+    async fn get_hierarchy_node(
+        &self,
+        Parameters(params): Parameters<GetHierarchyNodeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call(
+            "get_hierarchy_node",
+            &json!({"uuid": params.uuid, "expand": params.expand}),
+        );
+
+        let result = if let Some(ref expand) = params.expand {
+            let expand_list: Vec<&str> = expand.split(',').map(|s: &str| s.trim()).collect();
+            self.clients
+                .processhierarchy
+                .get_node_with_expand(&params.uuid, &expand_list)
+                .await
+        } else {
+            self.clients
+                .processhierarchy
+                .get_node(&params.uuid)
+                .await
+                .map(|n| serde_json::to_value(n).map_err(crate::error::ApiError::JsonParse))
+                .and_then(|r| r)
+        };
+
+        let json = result.map_err(to_mcp_error)?;
+        self.debug.log_tool_result("get_hierarchy_node", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Find a process hierarchy node by its title path, e.g. \"Finance/Accounts Payable/Invoice Processing\", and return its UUID. Titles are matched case-insensitively level by level, starting at a root node (or any node with the first title if no root matches). If a segment is not found, returns the resolved part of the path and the child titles available at that level."
+    )]
+    async fn find_hierarchy_node(
+        &self,
+        Parameters(params): Parameters<FindHierarchyNodeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("find_hierarchy_node", &json!(params));
+
+        let segments = split_title_path(&params.path);
+        let Some(first) = segments.first() else {
+            return Err(invalid_params("path must contain at least one title"));
+        };
+        let hierarchy = &self.clients.processhierarchy;
+
+        let query = ODataQuery::new().filter(title_search_filter(first));
+        let mut matches: Vec<_> = hierarchy
+            .list_nodes(Some(query))
+            .await
+            .map_err(to_mcp_error)?
+            .value
+            .into_iter()
+            .filter(|n| title_matches(n.title.as_deref(), first))
+            .collect();
+        if matches.iter().any(|n| n.parent_node_uuid.is_none()) {
+            matches.retain(|n| n.parent_node_uuid.is_none());
+        }
+
+        // Walk down the children of every match, one segment per level
+        let mut resolved = usize::from(!matches.is_empty());
+        let mut available = Vec::new();
+        for segment in segments.iter().skip(1) {
+            if matches.is_empty() {
+                break;
+            }
+            let mut next = Vec::new();
+            available.clear();
+            for node in &matches {
+                let Some(uuid) = &node.uuid else { continue };
+                let query = ODataQuery::new().filter(format!("parentNodeUuid eq '{}'", uuid));
+                let children = hierarchy
+                    .list_nodes(Some(query))
+                    .await
+                    .map_err(to_mcp_error)?
+                    .value;
+                for child in children {
+                    if title_matches(child.title.as_deref(), segment) {
+                        next.push(child);
+                    } else if let Some(title) = child.title {
+                        available.push(title);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            matches = next;
+            resolved += 1;
+        }
+
+        let json = if resolved == segments.len() {
+            let nodes: Vec<Value> = matches
+                .iter()
+                .map(|n| {
+                    json!({
+                        "uuid": n.uuid,
+                        "display_id": n.display_id,
+                        "title": n.title,
+                        "parent_titles": n.parent_titles,
+                        "hierarchy_level": n.hierarchy_level,
+                    })
+                })
+                .collect();
+            json!({
+                "path": params.path,
+                "found": true,
+                "uuid": if nodes.len() == 1 { nodes[0]["uuid"].clone() } else { Value::Null },
+                "ambiguous": nodes.len() > 1,
+                "matches": nodes,
+            })
+        } else {
+            available.sort();
+            available.dedup();
+            json!({
+                "path": params.path,
+                "found": false,
+                "resolved_path": segments[..resolved].join("/"),
+                "missing_segment": segments[resolved],
+                "available_titles": available,
+            })
+        };
+        self.debug.log_tool_result("find_hierarchy_node", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Create a new hierarchy node. Requires user confirmation before execution. Required: title."
+    )]
+    async fn create_hierarchy_node(
+        &self,
+        Parameters(params): Parameters<CreateHierarchyNodeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("create_hierarchy_node", &json!(params));
+
+        let request = CreateHierarchyNodeRequest {
+            title: params.title,
+            parent_node_uuid: params.parent_node_uuid,
+            description: params.description,
+            sequence: params.sequence,
+        };
+
+        let result = self
+            .clients
+            .processhierarchy
+            .create_node(&request)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("create_hierarchy_node", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Update an existing hierarchy node. Requires user confirmation before execution."
+    )]
+    async fn update_hierarchy_node(
+        &self,
+        Parameters(params): Parameters<UpdateHierarchyNodeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("update_hierarchy_node", &json!(params));
+
+        let request = UpdateHierarchyNodeRequest {
+            title: params.title,
+            description: params.description,
+            sequence: params.sequence,
+        };
+
+        let result = self
+            .clients
+            .processhierarchy
+            .update_node(&params.uuid, &request)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("update_hierarchy_node", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Delete a hierarchy node by UUID. Requires user confirmation before execution."
+    )]
+    async fn delete_hierarchy_node(
+        &self,
+        Parameters(params): Parameters<UuidParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("delete_hierarchy_node", &json!({"uuid": params.uuid}));
+
+        self.clients
+            .processhierarchy
+            .delete_node(&params.uuid)
+            .await
+            .map_err(to_mcp_error)?;
+
+        self.debug
+            .log_tool_result("delete_hierarchy_node", &json!({"deleted": true}));
+
+        Ok(CallToolResult::success(vec![Content::text(
+            json!({"deleted": true, "uuid": params.uuid}).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Export a process hierarchy subtree to a JSON snapshot file for later comparison with diff_hierarchy."
+    )]
+    async fn export_hierarchy_snapshot(
+        &self,
+        Parameters(params): Parameters<ExportHierarchySnapshotParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("export_hierarchy_snapshot", &json!(params));
+
+        let (nodes, traversal) = self
+            .clients
+            .processhierarchy
+            .get_subtree(&params.root_uuid, &self.traversal)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let snapshot = HierarchySnapshot::new(&params.root_uuid, &nodes);
+        snapshot
+            .save(&self.output_path(&params.file_path)?)
+            .map_err(to_mcp_error)?;
+
+        let mut json = json!({
+            "root_uuid": params.root_uuid,
+            "file_path": params.file_path,
+            "node_count": snapshot.nodes.len(),
+            "taken_at": snapshot.taken_at,
+        });
+        add_traversal_warning(&mut json, &traversal);
+        self.debug
+            .log_tool_result("export_hierarchy_snapshot", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Compare the current process hierarchy subtree against a snapshot file and report added, removed, moved and renamed nodes."
+    )]
+    async fn diff_hierarchy(
+        &self,
+        Parameters(params): Parameters<DiffHierarchyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("diff_hierarchy", &json!(params));
+
+        let before = HierarchySnapshot::load(&self.input_path(&params.snapshot_file)?)
+            .map_err(to_mcp_error)?;
+        let (nodes, traversal) = self
+            .clients
+            .processhierarchy
+            .get_subtree(&params.root_uuid, &self.traversal)
+            .await
+            .map_err(to_mcp_error)?;
+        let after = HierarchySnapshot::new(&params.root_uuid, &nodes);
+
+        let diff = diff_snapshots(&before, &after);
+        let mut json = json!({
+            "root_uuid": params.root_uuid,
+            "snapshot_taken_at": before.taken_at,
+            "unchanged": diff.is_empty(),
+            "diff": diff,
+        });
+        add_traversal_warning(&mut json, &traversal);
+        self.debug.log_tool_result("diff_hierarchy", &json);
+
+        to_json_result(&json)
+    }
+
+    // ========================================================================
+    // Cross-API Analysis Tools
+    // ========================================================================
+
+    #[tool(
+        description = "Get the open workload of a project per assignee: number of open tasks and features with counts by due date (overdue, due within 7 days, within 30 days, later, no due date), busiest assignees first. Features are counted for their responsible person and have no due date."
+    )]
+    async fn get_workload(
+        &self,
+        Parameters(params): Parameters<WorkloadParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("get_workload", &json!(params));
+
+        let tasks_request = ListTasksParams {
+            project_id: params.project_id.clone(),
+            ..Default::default()
+        };
+        let feature_query =
+            ODataQuery::new().filter(format!("projectId eq '{}'", params.project_id));
+        let (tasks, features) = tokio::join!(
+            self.clients.tasks.list_tasks(&tasks_request),
+            odata::read_all(feature_query, |q| {
+                self.clients.features.list_features(Some(q))
+            }),
+        );
+        let tasks = tasks.map_err(to_mcp_error)?;
+        let features = features.map_err(to_mcp_error)?;
+
+        let is_open = |status: Option<&str>| !status.is_some_and(is_closed_status);
+        let mut items: Vec<WorkItem> = tasks
+            .iter()
+            .filter(|t| is_open(t.status.as_deref()))
+            .map(|t| WorkItem {
+                kind: WorkKind::Task,
+                assignee_id: t.assignee_id.clone(),
+                assignee_name: t.assignee_name.clone(),
+                due_date: t.due_date.as_deref().and_then(calendar::parse_date),
+            })
+            .collect();
+        items.extend(
+            features
+                .items
+                .iter()
+                .filter(|f| is_open(f.status_code.as_deref()))
+                .map(|f| WorkItem {
+                    kind: WorkKind::Feature,
+                    assignee_id: f.responsible_id.clone(),
+                    assignee_name: None,
+                    due_date: None,
+                }),
+        );
+        let workload = summarize_workload(&items, chrono::Utc::now().date_naive());
+
+        let json = json!({
+            "project_id": params.project_id,
+            "open_tasks": items.iter().filter(|i| i.kind == WorkKind::Task).count(),
+            "open_features": items.iter().filter(|i| i.kind == WorkKind::Feature).count(),
+            "assignees": workload,
+            "truncated": features.truncated,
+        });
+        self.debug.log_tool_result("get_workload", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Find stale items of a project for backlog hygiene reviews: tasks, features and documents in an open status that were not modified for idle_days days (default 30), grouped by owner (task assignee, feature or document responsible), owners with the most stale items first and oldest items first."
+    )]
+    async fn find_stale_items(
+        &self,
+        Parameters(params): Parameters<StaleItemsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("find_stale_items", &json!(params));
+
+        let idle_days = params.idle_days.unwrap_or(30);
+        let project_filter = format!("projectId eq '{}'", params.project_id);
+        let project_query = || ODataQuery::new().filter(&project_filter);
+        let tasks_request = ListTasksParams {
+            project_id: params.project_id.clone(),
+            ..Default::default()
+        };
+        let (tasks, features, documents, document_statuses) = tokio::join!(
+            self.clients.tasks.list_tasks(&tasks_request),
+            odata::read_all(project_query(), |q| {
+                self.clients.features.list_features(Some(q))
+            }),
+            odata::read_all(project_query(), |q| {
+                self.clients.documents.list_documents(Some(q))
+            }),
+            self.clients.documents.list_statuses(),
+        );
+        let tasks = tasks.map_err(to_mcp_error)?;
+        let features = features.map_err(to_mcp_error)?;
+        let mut truncated = features.truncated;
+        let features = features.items;
+
+        let mut warnings = Vec::new();
+        let documents = match documents {
+            Ok(documents) => {
+                truncated |= documents.truncated;
+                documents.items
+            }
+            Err(e) => {
+                warnings.push(format!("Documents unavailable: {}", e));
+                Vec::new()
+            }
+        };
+        let document_statuses: HashMap<i32, String> = match document_statuses {
+            Ok(statuses) => statuses
+                .value
+                .into_iter()
+                .map(|s| (s.code, s.name))
+                .collect(),
+            Err(e) => {
+                warnings.push(format!(
+                    "Document statuses unavailable, all documents are treated as open: {}",
+                    e
+                ));
+                HashMap::new()
+            }
+        };
+
+        let is_open = |status: Option<&str>| !status.is_some_and(is_closed_status);
+        let mut items: Vec<StaleItem> = tasks
+            .iter()
+            .filter(|t| is_open(t.status.as_deref()))
+            .filter_map(|t| {
+                Some(StaleItem {
+                    kind: "task",
+                    id: t.id.clone()?,
+                    display_id: t.external_id.clone(),
+                    title: t.title.clone(),
+                    status: t.status.clone(),
+                    owner: t.assignee_name.clone().or_else(|| t.assignee_id.clone()),
+                    last_modified: t.last_changed_date.clone(),
+                    idle_days: 0,
+                })
+            })
+            .collect();
+        items.extend(
+            features
+                .iter()
+                .filter(|f| is_open(f.status_code.as_deref()))
+                .filter_map(|f| {
+                    Some(StaleItem {
+                        kind: "feature",
+                        id: f.uuid.clone()?,
+                        display_id: f.display_id.clone(),
+                        title: f.title.clone(),
+                        status: f.status_code.clone(),
+                        owner: f.responsible_id.clone(),
+                        last_modified: f.modified_at.clone(),
+                        idle_days: 0,
+                    })
+                }),
+        );
+        for document in &documents {
+            let status = document.status_code.map(|code| {
+                document_statuses
+                    .get(&code)
+                    .cloned()
+                    .unwrap_or_else(|| code.to_string())
+            });
+            let archived = status.as_deref().is_some_and(|name| {
+                let name = name.to_lowercase();
+                DOCUMENT_ARCHIVE_STATUSES.iter().any(|a| name.contains(a))
+            });
+            let Some(uuid) = document.uuid.clone() else {
+                continue;
+            };
+            if archived || !is_open(status.as_deref()) {
+                continue;
+            }
+            let owner = ["responsibleId", "ownerId", "createdBy"]
+                .iter()
+                .find_map(|key| document.extra.get(*key)?.as_str())
+                .map(str::to_string);
+            items.push(StaleItem {
+                kind: "document",
+                id: uuid,
+                display_id: document.display_id.clone(),
+                title: document.title.clone(),
+                status,
+                owner,
+                last_modified: document.modified_at.clone(),
+                idle_days: 0,
+            });
+        }
+
+        let open_items = items.len();
+        let without_date = items.iter().filter(|i| i.last_modified.is_none()).count();
+        let owners = stale_items_by_owner(items, chrono::Utc::now().date_naive(), idle_days.into());
+        let stale: usize = owners.iter().map(|o| o.count).sum();
+
+        let mut json = json!({
+            "project_id": params.project_id,
+            "idle_days": idle_days,
+            "open_items": open_items,
+            "stale_items": stale,
+            "owners": owners,
+            "truncated": truncated,
+        });
+        if without_date > 0 {
+            warnings.push(format!(
+                "{} open items have no modification date and were not evaluated",
+                without_date
+            ));
+        }
+        if !warnings.is_empty() {
+            json["warnings"] = json!(warnings);
+        }
+        self.debug.log_tool_result("find_stale_items", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Check whether a feature is ready for deployment against a checklist: all related tasks done, test runs passed, transports assigned, related documents approved. Returns pass, fail or not_evaluated per criterion with the evidence (tasks, test runs, transports with their URLs, documents) and an overall result. The checklist is configurable (feature_checklist) and can be overridden with criteria. Required: uuid."
+    )]
+    async fn check_feature_readiness(
+        &self,
+        Parameters(params): Parameters<FeatureReadinessParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("check_feature_readiness", &json!(params));
+
+        let criteria = match &params.criteria {
+            Some(names) => checklist::parse_criteria(names).map_err(invalid_params)?,
+            None => self.feature_checklist.clone(),
+        };
+        let feature = self
+            .clients
+            .features
+            .get_feature(&params.uuid)
+            .await
+            .map_err(to_mcp_error)?;
+        let needles: Vec<&str> = [Some(params.uuid.as_str()), feature.display_id.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        let project_id = feature.project_id.clone().unwrap_or_default();
+        let project_filter = format!("projectId eq '{}'", project_id);
+        let project_query = || ODataQuery::new().filter(&project_filter);
+        let tasks_request = ListTasksParams {
+            project_id: project_id.clone(),
+            ..Default::default()
+        };
+        let references_query =
+            ODataQuery::new().filter(format!("parent_uuid eq '{}'", params.uuid));
+
+        #[cfg_attr(not(feature = "analytics"), allow(unused_variables))]
+        let (tasks, documents, document_statuses, references, testcases) = tokio::join!(
+            self.clients.tasks.list_tasks(&tasks_request),
+            odata::read_all(project_query(), |q| {
+                self.clients.documents.list_documents(Some(q))
+            }),
+            self.clients.documents.list_statuses(),
+            odata::read_all(references_query, |q| {
+                self.clients.features.list_external_references(Some(q))
+            }),
+            odata::read_all(project_query(), |q| {
+                self.clients.testmanagement.list_testcases(Some(q))
+            }),
+        );
+
+        let mut results = Vec::new();
+        for criterion in criteria {
+            let evidence: Result<Vec<Evidence>, String> = match criterion {
+                Criterion::TasksDone => tasks.as_ref().map_err(|e| e.to_string()).map(|tasks| {
+                    tasks
+                        .iter()
+                        .filter(|t| {
+                            serde_json::to_value(t).is_ok_and(|v| mentions_any(&v, &needles))
+                        })
+                        .filter_map(|t| {
+                            Some(
+                                Evidence::new("task", t.id.clone()?, t.status.clone())
+                                    .title(t.title.clone()),
+                            )
+                        })
+                        .collect()
+                }),
+                Criterion::TestsPassed => {
+                    #[cfg(feature = "analytics")]
+                    match testcases.as_ref() {
+                        Ok(testcases) => {
+                            // Runs may reference the feature or one of its test cases
+                            let mut ids = needles.clone();
+                            ids.extend(
+                                testcases
+                                    .items
+                                    .iter()
+                                    .filter(|t| {
+                                        serde_json::to_value(t)
+                                            .is_ok_and(|v| mentions_any(&v, &needles))
+                                    })
+                                    .filter_map(|t| t.uuid.as_deref()),
+                            );
+                            self.test_run_evidence(&project_filter, &ids).await
+                        }
+                        Err(e) => Err(e.to_string()),
+                    }
+                    #[cfg(not(feature = "analytics"))]
+                    Err("Test runs are read from the Analytics API, which is not part of this build".to_string())
+                }
+                Criterion::TransportsAssigned => references
+                    .as_ref()
+                    .map_err(|e| e.to_string())
+                    .map(|references| {
+                        let mut evidence = checklist::transports(&feature.extra);
+                        evidence.extend(
+                            references
+                                .items
+                                .iter()
+                                .filter(|r| {
+                                    r.name
+                                        .as_deref()
+                                        .is_some_and(checklist::is_transport_system)
+                                })
+                                .map(|r| {
+                                    let id = r.id.clone().unwrap_or_default();
+                                    Evidence::new("transport", id, None)
+                                        .title(r.name.clone())
+                                        .url(r.url.clone())
+                                }),
+                        );
+                        evidence
+                    }),
+                Criterion::ApprovalsGranted => {
+                    match (documents.as_ref(), document_statuses.as_ref()) {
+                        (Ok(documents), Ok(statuses)) => Ok(documents
+                            .items
+                            .iter()
+                            .filter(|d| {
+                                serde_json::to_value(d).is_ok_and(|v| mentions_any(&v, &needles))
+                            })
+                            .filter_map(|d| {
+                                let status = d.status_code.map(|code| {
+                                    statuses
+                                        .value
+                                        .iter()
+                                        .find(|s| s.code == code)
+                                        .map(|s| s.name.clone())
+                                        .unwrap_or_else(|| code.to_string())
+                                });
+                                Some(
+                                    Evidence::new("document", d.uuid.clone()?, status)
+                                        .title(d.title.clone()),
+                                )
+                            })
+                            .collect()),
+                        (Err(e), _) | (_, Err(e)) => Err(e.to_string()),
+                    }
+                }
+            };
+            results.push(match evidence {
+                Ok(evidence) => checklist::evaluate(criterion, evidence),
+                Err(e) => CriterionResult::not_evaluated(criterion, e),
+            });
+        }
+
+        let truncated = documents.as_ref().is_ok_and(|d| d.truncated)
+            || references.as_ref().is_ok_and(|r| r.truncated)
+            || testcases.as_ref().is_ok_and(|t| t.truncated);
+        let json = json!({
+            "uuid": params.uuid,
+            "display_id": feature.display_id,
+            "title": feature.title,
+            "status": feature.status_code,
+            "ready": checklist::overall(&results),
+            "criteria": results,
+            "truncated": truncated,
+        });
+        self.debug.log_tool_result("check_feature_readiness", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Compare two sprints (timeboxes) of a project for retrospectives: tasks, completed tasks and story points, completion rate, open tasks and defect counts with deltas, and the items carried over (still open) from the first sprint. Tasks belong to the sprint of their timebox; defects are tasks of a defect type. Returns a markdown report. Required: project_id, timebox_a, timebox_b."
+    )]
+    async fn compare_sprints(
+        &self,
+        Parameters(params): Parameters<CompareSprintsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("compare_sprints", &json!(params));
+
+        let locale =
+            Locale::resolve(&self.locale, params.locale.as_deref()).map_err(invalid_params)?;
+        let tasks_request = ListTasksParams {
+            project_id: params.project_id.clone(),
+            ..Default::default()
+        };
+        let (timeboxes, tasks) = tokio::join!(
+            self.clients.projects.list_timeboxes(&params.project_id),
+            self.clients.tasks.list_tasks(&tasks_request),
+        );
+        let timeboxes = timeboxes.map_err(to_mcp_error)?;
+        let tasks = tasks.map_err(to_mcp_error)?;
+
+        let find_timebox = |key: &str| {
+            timeboxes
+                .iter()
+                .find(|t| t.id.as_deref() == Some(key))
+                .or_else(|| {
+                    timeboxes.iter().find(|t| {
+                        t.name
+                            .as_deref()
+                            .is_some_and(|n| n.eq_ignore_ascii_case(key))
+                    })
+                })
+                .ok_or_else(|| {
+                    let names: Vec<&str> =
+                        timeboxes.iter().filter_map(|t| t.name.as_deref()).collect();
+                    invalid_params(format!(
+                        "Unknown timebox '{}' in project {}. Timeboxes: {}",
+                        key,
+                        params.project_id,
+                        names.join(", ")
+                    ))
+                })
+        };
+        let (timebox_a, timebox_b) = (
+            find_timebox(&params.timebox_a)?,
+            find_timebox(&params.timebox_b)?,
+        );
+
+        let sprint_tasks = |name: Option<&str>| -> Vec<SprintTask> {
+            tasks
+                .iter()
+                .filter(|t| match (t.timebox_name.as_deref(), name) {
+                    (Some(task_timebox), Some(name)) => task_timebox.eq_ignore_ascii_case(name),
+                    _ => false,
+                })
+                .filter_map(|t| {
+                    Some(SprintTask {
+                        id: t.id.clone()?,
+                        title: t.title.clone(),
+                        status: t.status.clone(),
+                        is_defect: t
+                            .task_type
+                            .as_deref()
+                            .is_some_and(|kind| kind.to_lowercase().contains("defect")),
+                        points: serde_json::to_value(t)
+                            .ok()
+                            .and_then(|v| field_f64(&v, STORY_POINT_KEYS)),
+                    })
+                })
+                .collect()
+        };
+        let (tasks_a, tasks_b) = (
+            sprint_tasks(timebox_a.name.as_deref()),
+            sprint_tasks(timebox_b.name.as_deref()),
+        );
+        let report = render_sprint_comparison(
+            &Sprint {
+                name: timebox_a.name.as_deref().unwrap_or(&params.timebox_a),
+                start: timebox_a.start_date.as_deref(),
+                end: timebox_a.end_date.as_deref(),
+                tasks: &tasks_a,
+            },
+            &Sprint {
+                name: timebox_b.name.as_deref().unwrap_or(&params.timebox_b),
+                start: timebox_b.start_date.as_deref(),
+                end: timebox_b.end_date.as_deref(),
+                tasks: &tasks_b,
+            },
+            &locale,
+        );
+
+        self.debug.log_tool_result(
+            "compare_sprints",
+            &json!({"tasks_a": tasks_a.len(), "tasks_b": tasks_b.len()}),
+        );
+        Ok(CallToolResult::success(vec![Content::text(report)]))
+    }
+
+    #[tool(
+        description = "Count features, documents, test cases or tasks grouped by a field (status, priority, type, assignee, release or any field name), optionally filtered. Uses server-side aggregation ($apply) where the service supports it and counts client-side otherwise. Answers \"how many X by Y\" questions with a small result."
+    )]
+    async fn count_by(
+        &self,
+        Parameters(params): Parameters<CountByParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("count_by", &json!(params));
+
+        let entity = params.entity.to_lowercase();
+        let field = COUNT_DIMENSIONS
+            .iter()
+            .find(|(e, dimension, _)| {
+                *e == entity && dimension.eq_ignore_ascii_case(&params.dimension)
+            })
+            .map(|(_, _, field)| field.to_string())
+            .unwrap_or_else(|| params.dimension.clone());
+
+        let mut method = "client";
+        let mut note = None;
+        let counts = if entity == "tasks" {
+            if params.filter.is_some() {
+                return Err(invalid_params(
+                    "filter is not supported for tasks; use project_id",
+                ));
+            }
+            let project_id = params
+                .project_id
+                .clone()
+                .ok_or_else(|| invalid_params("project_id is required for tasks"))?;
+            let request = ListTasksParams {
+                project_id,
+                ..Default::default()
+            };
+            let tasks = self
+                .clients
+                .tasks
+                .list_tasks(&request)
+                .await
+                .map_err(to_mcp_error)?;
+            count_by_field(&to_values(tasks), &field)
+        } else {
+            let (api, path) = match entity.as_str() {
+                "features" => (self.clients.features.api(), "/Features"),
+                "documents" => (self.clients.documents.api(), "/Documents"),
+                "test_cases" => (self.clients.testmanagement.api(), "/ManualTestCases"),
+                other => {
+                    return Err(invalid_params(format!(
+                    "Unknown entity '{}'. Valid entities: features, documents, test_cases, tasks",
+                    other
+                )))
+                }
+            };
+            let filter = match (&params.filter, &params.project_id) {
+                (Some(filter), Some(project_id)) => {
+                    Some(format!("({}) and projectId eq '{}'", filter, project_id))
+                }
+                (Some(filter), None) => Some(filter.clone()),
+                (None, Some(project_id)) => Some(format!("projectId eq '{}'", project_id)),
+                (None, None) => None,
+            };
+
+            let groupby = format!("groupby(({}),aggregate($count as count))", field);
+            let apply = match &filter {
+                Some(filter) => format!("filter({})/{}", filter, groupby),
+                None => groupby,
+            };
+            let grouped = api
+                .list(path, &[("$apply", apply)])
+                .await
+                .ok()
+                .and_then(|rows| grouped_counts(&rows, &field));
+            match grouped {
+                Some(counts) => {
+                    method = "$apply";
+                    counts
+                }
+                None => {
+                    // $apply is not supported: page through the records
+                    let mut records = Vec::new();
+                    loop {
+                        if budget::exhausted() {
+                            note = Some(budget::PARTIAL_NOTE.to_string());
+                            break;
+                        }
+                        if records.len() >= MAX_COUNTED_RECORDS {
+                            note = Some(format!(
+                                "Counted the first {} records only; narrow the filter for exact counts.",
+                                MAX_COUNTED_RECORDS
+                            ));
+                            break;
+                        }
+                        let mut query = vec![
+                            ("$select", field.clone()),
+                            ("$top", COUNT_PAGE_SIZE.to_string()),
+                            ("$skip", records.len().to_string()),
+                        ];
+                        if let Some(filter) = &filter {
+                            query.push(("$filter", filter.clone()));
+                        }
+                        let page = api.list(path, &query).await.map_err(to_mcp_error)?;
+                        let done = page.len() < COUNT_PAGE_SIZE as usize;
+                        records.extend(page);
+                        if done {
+                            break;
+                        }
+                    }
+                    count_by_field(&records, &field)
+                }
+            }
+        };
+
+        let mut groups: Vec<(String, usize)> = counts.into_iter().collect();
+        groups.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let mut json = json!({
+            "entity": entity,
+            "field": field,
+            "method": method,
+            "total": groups.iter().map(|(_, count)| count).sum::<usize>(),
+            "counts": groups
+                .iter()
+                .map(|(value, count)| json!({"value": value, "count": count}))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(note) = note {
+            json["note"] = json!(note);
+        }
+        self.debug.log_tool_result("count_by", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Import requirements from Fit-to-Standard workshop notes: splits markdown/plain text into requirement candidates (headings as sections; REQ:/Requirement:/Gap: markers, list items and keyword lines as candidates) and creates them as requirements linked to process hierarchy nodes. dry_run defaults to true and only returns the candidates. Requires user confirmation before execution. Required: project_id, text."
+    )]
+    async fn import_requirements_from_text(
+        &self,
+        Parameters(params): Parameters<ImportRequirementsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("import_requirements_from_text", &json!(params));
+
+        let mut result = CompositeResult::resume(
+            "import_requirements_from_text",
+            &json!(params),
+            params.resume_token.as_deref(),
+        )
+        .map_err(invalid_params)?;
+        let defaults = SplitOptions::default();
+        let options = SplitOptions {
+            keywords: params.keywords.unwrap_or(defaults.keywords),
+            include_list_items: params
+                .include_list_items
+                .unwrap_or(defaults.include_list_items),
+            min_length: params
+                .min_length
+                .map(|l| l as usize)
+                .unwrap_or(defaults.min_length),
+        };
+        let candidates = split_requirements(&params.text, &options);
+
+        // Resolve the nodes up front so invalid UUIDs fail before anything is created
+        let mut nodes = Vec::new();
+        for uuid in params.node_uuids.unwrap_or_default() {
+            let node = self
+                .clients
+                .processhierarchy
+                .get_node(&uuid)
+                .await
+                .map_err(to_mcp_error)?;
+            nodes.push((uuid, node.title.unwrap_or_default()));
+        }
+
+        let dry_run = params.dry_run.unwrap_or(true);
+        let mut warnings = Vec::new();
+        let mut defaults_applied = Vec::new();
+        if !dry_run {
+            self.check_project_access(Some(&params.project_id)).await?;
+            for candidate in &candidates {
+                let id = candidate.id();
+                if result.skip_completed(&id) || result.skip_over_budget(&id) {
+                    continue;
+                }
+                let mut description = candidate.description.clone().unwrap_or_default();
+                if let Some(section) = &candidate.section {
+                    description.push_str(&format!("\nWorkshop section: {}", section));
+                }
+                if !nodes.is_empty() {
+                    let titles: Vec<&str> = nodes.iter().map(|(_, t)| t.as_str()).collect();
+                    description.push_str(&format!("\nProcesses: {}", titles.join(", ")));
+                }
+
+                let (request, applied) = self.internal_task_request(
+                    json!({
+                        "project_id": params.project_id,
+                        "title": candidate.title,
+                        "task_type": params.task_type,
+                        "description": description.trim_start(),
+                    }),
+                    "CALMREQU",
+                )?;
+                defaults_applied = applied;
+                let task = match self.clients.tasks.create_task(&request).await {
+                    Ok(task) => task,
+                    Err(e) => {
+                        result.fail(
+                            id,
+                            e,
+                            json!({"line": candidate.line, "title": candidate.title}),
+                        );
+                        continue;
+                    }
+                };
+
+                if let Some(task_id) = &task.id {
+                    for (uuid, _) in &nodes {
+                        let reference = CreateTaskReferenceRequest {
+                            external_id: uuid.clone(),
+                            external_system: "SAP Cloud ALM Process Hierarchy".to_string(),
+                            url: None,
+                        };
+                        if let Err(e) = self
+                            .clients
+                            .tasks
+                            .create_task_reference(task_id, &reference)
+                            .await
+                        {
+                            warnings.push(format!(
+                                "Requirement {} not linked to node {}: {}",
+                                task_id, uuid, e
+                            ));
+                        }
+                    }
+                }
+                result.succeed(id, serde_json::to_value(&task).map_err(to_mcp_error)?);
+            }
+        }
+
+        let mut json = serde_json::to_value(result.finish()).map_err(to_mcp_error)?;
+        json["dry_run"] = json!(dry_run);
+        json["candidate_count"] = json!(candidates.len());
+        json["candidates"] = json!(candidates);
+        json["nodes"] = nodes
+            .iter()
+            .map(|(uuid, title)| json!({"uuid": uuid, "title": title}))
+            .collect();
+        json["defaults_applied"] = json!(defaults_applied);
+        json["warnings"] = json!(warnings);
+        self.debug
+            .log_tool_result("import_requirements_from_text", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Export all external references of a project's features and tasks as a CSV mapping file (entity_type, entity_id, external_system, external_id, url). Writes to path if given, otherwise returns the CSV. Required: project_id."
+    )]
+    async fn export_external_references(
+        &self,
+        Parameters(params): Parameters<ExportReferencesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("export_external_references", &json!(params));
+
+        let project_filter = format!("projectId eq '{}'", params.project_id);
+
+        let project_query = || ODataQuery::new().filter(&project_filter);
+        let tasks_request = ListTasksParams {
+            project_id: params.project_id.clone(),
+            ..Default::default()
+        };
+        let (features, tasks) = tokio::join!(
+            odata::read_all(project_query(), |q| {
+                self.clients.features.list_features(Some(q))
+            }),
+            self.clients.tasks.list_tasks(&tasks_request),
+        );
+        let features = features.map_err(to_mcp_error)?;
+        let mut truncated = features.truncated;
+        let features = features.items;
+        let tasks = tasks.map_err(to_mcp_error)?;
+
+        // Feature references are exported with the display ID of their feature
+        let feature_ids: HashMap<String, String> = features
+            .iter()
+            .filter_map(|f| {
+                let uuid = f.uuid.clone()?;
+                Some((uuid.clone(), f.display_id.clone().unwrap_or(uuid)))
+            })
+            .collect();
+        let uuids: Vec<&String> = feature_ids.keys().collect();
+
+        let mut rows = Vec::new();
+        let mut warnings = Vec::new();
+        let mut out_of_time = false;
+        for chunk in uuids.chunks(20) {
+            if budget::exhausted() {
+                out_of_time = true;
+                break;
+            }
+            let filter = chunk
+                .iter()
+                .map(|uuid| format!("parent_uuid eq '{}'", uuid))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            let references = odata::read_all(ODataQuery::new().filter(filter), |q| {
+                self.clients.features.list_external_references(Some(q))
+            })
+            .await
+            .map_err(to_mcp_error)?;
+            truncated |= references.truncated;
+            for reference in references.items {
+                let parent = reference.parent_uuid.unwrap_or_default();
+                rows.push(ReferenceRow {
+                    entity_type: EntityType::Feature,
+                    entity_id: feature_ids.get(&parent).cloned().unwrap_or(parent),
+                    external_system: reference.name.unwrap_or_default(),
+                    external_id: reference.id.unwrap_or_default(),
+                    url: reference.url,
+                });
+            }
+        }
+        for task_id in tasks.iter().filter_map(|t| t.id.as_deref()) {
+            if out_of_time || budget::exhausted() {
+                out_of_time = true;
+                break;
+            }
+            match self.clients.tasks.list_task_references(task_id).await {
+                Ok(references) => rows.extend(references.into_iter().map(|r| ReferenceRow {
+                    entity_type: EntityType::Task,
+                    entity_id: task_id.to_string(),
+                    external_system: r.external_system.unwrap_or_default(),
+                    external_id: r.external_id.unwrap_or_default(),
+                    url: r.url,
+                })),
+                Err(e) => {
+                    warnings.push(format!("References of task {} unavailable: {}", task_id, e))
+                }
+            }
+        }
+
+        let csv = refmap::to_csv(&rows);
+        let mut json = json!({
+            "project_id": params.project_id,
+            "reference_count": rows.len(),
+            "feature_count": features.len(),
+            "task_count": tasks.len(),
+            "truncated": truncated,
+            "warnings": warnings,
+        });
+        match &params.path {
+            Some(path) => {
+                std::fs::write(self.output_path(path)?, &csv).map_err(|e| {
+                    invalid_params(format!("Cannot write mapping file '{}': {}", path, e))
+                })?;
+                json["path"] = json!(path);
+            }
+            None => json["csv"] = json!(csv),
+        }
+        if out_of_time {
+            json["note"] = json!(budget::PARTIAL_NOTE);
+        }
+        self.debug
+            .log_tool_result("export_external_references", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Export the schedule of a project as an iCalendar (.ics) file: timeboxes (phases, sprints), milestones and due dates of open tasks as all-day events. Returns the calendar text or writes it to path. Re-importing an updated export replaces the events. anonymize=true replaces assignee names by pseudonyms."
+    )]
+    async fn export_project_calendar(
+        &self,
+        Parameters(params): Parameters<ProjectCalendarParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("export_project_calendar", &json!(params));
+
+        let include_tasks = params.include_tasks.unwrap_or(true);
+        let tasks_request = ListTasksParams {
+            project_id: params.project_id.clone(),
+            ..Default::default()
+        };
+        let (project, timeboxes, tasks) = tokio::join!(
+            self.clients.projects.get_project(&params.project_id),
+            self.clients.projects.list_timeboxes(&params.project_id),
+            async {
+                if include_tasks {
+                    self.clients.tasks.list_tasks(&tasks_request).await
+                } else {
+                    Ok(Vec::new())
+                }
+            },
+        );
+        let project = project.map_err(to_mcp_error)?;
+        let timeboxes = timeboxes.map_err(to_mcp_error)?;
+        let tasks = tasks.map_err(to_mcp_error)?;
+
+        let mut events = Vec::new();
+        let mut skipped = 0;
+        for timebox in &timeboxes {
+            let start = timebox.start_date.as_deref().and_then(calendar::parse_date);
+            let end = timebox.end_date.as_deref().and_then(calendar::parse_date);
+            let Some(start) = start.or(end) else {
+                skipped += 1;
+                continue;
+            };
+            let end = end.unwrap_or(start);
+            let is_milestone = timebox
+                .timebox_type
+                .as_deref()
+                .is_some_and(|t| t.to_lowercase().contains("milestone"))
+                || start == end;
+            let name = timebox
+                .name
+                .clone()
+                .unwrap_or_else(|| "Timebox".to_string());
+            events.push(CalendarEvent {
+                uid: format!(
+                    "timebox-{}@sap-cloud-alm",
+                    timebox.id.as_deref().unwrap_or(&name)
+                ),
+                summary: if is_milestone {
+                    format!("Milestone: {}", name)
+                } else {
+                    name
+                },
+                description: timebox.status.as_ref().map(|s| format!("Status: {}", s)),
+                start,
+                end,
+                category: if is_milestone { "Milestone" } else { "Timebox" },
+            });
+        }
+        for task in &tasks {
+            if task.status.as_deref().is_some_and(is_closed_status) {
+                continue;
+            }
+            let (Some(id), Some(due)) = (
+                task.id.as_deref(),
+                task.due_date.as_deref().and_then(calendar::parse_date),
+            ) else {
+                continue;
+            };
+            let description = [
+                task.task_type.as_ref().map(|t| format!("Type: {}", t)),
+                task.status.as_ref().map(|s| format!("Status: {}", s)),
+                task.assignee_name.as_ref().map(|a| match params.anonymize {
+                    Some(true) => format!("Assignee: {}", self.anonymizer.pseudonym(a)),
+                    _ => format!("Assignee: {}", a),
+                }),
+            ];
+            let description: Vec<String> = description.into_iter().flatten().collect();
+            events.push(CalendarEvent {
+                uid: format!("task-{}@sap-cloud-alm", id),
+                summary: format!("Due: {}", task.title.as_deref().unwrap_or(id)),
+                description: (!description.is_empty()).then(|| description.join("\n")),
+                start: due,
+                end: due,
+                category: "Due date",
+            });
+        }
+        events.sort_by(|a, b| {
+            a.start
+                .cmp(&b.start)
+                .then_with(|| a.summary.cmp(&b.summary))
+        });
+
+        let calendar_name = project
+            .name
+            .clone()
+            .unwrap_or_else(|| params.project_id.clone());
+        let ics = calendar::to_ics(&calendar_name, &events);
+        let milestones = events.iter().filter(|e| e.category == "Milestone").count();
+        let mut json = json!({
+            "project_id": params.project_id,
+            "event_count": events.len(),
+            "timebox_count": events.iter().filter(|e| e.category == "Timebox").count(),
+            "milestone_count": milestones,
+            "due_date_count": events.iter().filter(|e| e.category == "Due date").count(),
+            "skipped_timeboxes_without_dates": skipped,
+            "anonymized": params.anonymize.unwrap_or(false),
+        });
+        match &params.path {
+            Some(path) => {
+                std::fs::write(self.output_path(path)?, &ics).map_err(|e| {
+                    invalid_params(format!("Cannot write calendar file '{}': {}", path, e))
+                })?;
+                json["path"] = json!(path);
+            }
+            None => json["ics"] = json!(ics),
+        }
+        self.debug.log_tool_result("export_project_calendar", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Extract a reusable template from an existing project: workstreams, deliverables, recurring task patterns (task titles that only differ in numbers, e.g. 'Sprint # review') and the document types in use. Returns the template JSON or writes it to path. anonymize=true masks descriptions and replaces user names by pseudonyms, e.g. to share the template outside the company."
+    )]
+    async fn extract_project_template(
+        &self,
+        Parameters(params): Parameters<ExtractTemplateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("extract_project_template", &json!(params));
+
+        let tasks_request = ListTasksParams {
+            project_id: params.project_id.clone(),
+            ..Default::default()
+        };
+        let documents_query =
+            ODataQuery::new().filter(format!("projectId eq '{}'", params.project_id));
+        let (project, tasks, workstreams, deliverables, documents, document_types) = tokio::join!(
+            self.clients.projects.get_project(&params.project_id),
+            self.clients.tasks.list_tasks(&tasks_request),
+            self.clients.tasks.list_workstreams(&params.project_id),
+            self.clients.tasks.list_deliverables(&params.project_id),
+            odata::read_all(documents_query, |q| {
+                self.clients.documents.list_documents(Some(q))
+            }),
+            self.clients.documents.list_types(),
+        );
+        let project = project.map_err(to_mcp_error)?;
+        let tasks = tasks.map_err(to_mcp_error)?;
+
+        let mut warnings = Vec::new();
+        let workstreams = workstreams.unwrap_or_else(|e| {
+            warnings.push(format!("Workstreams unavailable: {}", e));
+            Vec::new()
+        });
+        let deliverables = deliverables.unwrap_or_else(|e| {
+            warnings.push(format!("Deliverables unavailable: {}", e));
+            Vec::new()
+        });
+        let mut truncated = false;
+        let documents = match documents {
+            Ok(documents) => {
+                truncated = documents.truncated;
+                documents.items
+            }
+            Err(e) => {
+                warnings.push(format!("Documents unavailable: {}", e));
+                Vec::new()
+            }
+        };
+        let type_names: HashMap<String, String> = document_types
+            .map(|c| c.value.into_iter().map(|t| (t.code, t.name)).collect())
+            .unwrap_or_default();
+
+        let template = ProjectTemplate {
+            version: TEMPLATE_VERSION,
+            name: project
+                .name
+                .clone()
+                .unwrap_or_else(|| params.project_id.clone()),
+            description: project.description.clone(),
+            source_project_id: params.project_id.clone(),
+            workstreams: template::entries(
+                workstreams.into_iter().map(|w| (w.name, w.description)),
+            ),
+            deliverables: template::entries(
+                deliverables.into_iter().map(|d| (d.name, d.description)),
+            ),
+            task_patterns: template::recurring_task_patterns(
+                tasks
+                    .iter()
+                    .filter_map(|t| Some((t.title.as_deref()?, t.task_type.as_deref()))),
+                params.min_occurrences.unwrap_or(2),
+            ),
+            document_types: template::document_type_usage(
+                documents.iter().filter_map(|d| d.type_code.as_deref()),
+                &type_names,
+            ),
+        };
+
+        let anonymize = params.anonymize.unwrap_or(false);
+        let mut template_json = serde_json::to_value(&template).map_err(to_mcp_error)?;
+        if anonymize {
+            template_json = self.anonymizer.apply(template_json);
+        }
+        let mut json = json!({
+            "project_id": params.project_id,
+            "workstream_count": template.workstreams.len(),
+            "deliverable_count": template.deliverables.len(),
+            "task_pattern_count": template.task_patterns.len(),
+            "document_type_count": template.document_types.len(),
+            "anonymized": anonymize,
+            "truncated": truncated,
+            "warnings": warnings,
+        });
+        match &params.path {
+            Some(path) => {
+                let content = serde_json::to_string_pretty(&template_json).map_err(to_mcp_error)?;
+                std::fs::write(self.output_path(path)?, content).map_err(|e| {
+                    invalid_params(format!("Cannot write template file '{}': {}", path, e))
+                })?;
+                json["path"] = json!(path);
+            }
+            None => json["template"] = template_json,
+        }
+        self.debug
+            .log_tool_result("extract_project_template", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Import an external reference mapping file (CSV with entity_type, entity_id, external_system, external_id, url) and link features and tasks to their external objects, e.g. after a migration from Jira or Solution Manager. Features are identified by UUID or display ID; references that already exist are skipped. dry_run defaults to true and only validates the rows. Requires user confirmation before execution. Required: project_id and csv or path."
+    )]
+    async fn import_external_references(
+        &self,
+        Parameters(params): Parameters<ImportReferencesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("import_external_references", &json!(params));
+
+        let text = match (&params.csv, &params.path) {
+            (Some(csv), _) => csv.clone(),
+            (None, Some(path)) => std::fs::read_to_string(self.input_path(path)?).map_err(|e| {
+                invalid_params(format!("Cannot read mapping file '{}': {}", path, e))
+            })?,
+            (None, None) => return Err(invalid_params("Either csv or path is required")),
+        };
+        let rows = refmap::parse_csv(&text).map_err(invalid_params)?;
+        let dry_run = params.dry_run.unwrap_or(true);
+        if !dry_run {
+            self.check_project_access(Some(&params.project_id)).await?;
+        }
+
+        let mut result = CompositeResult::resume(
+            "import_external_references",
+            &json!(params),
+            params.resume_token.as_deref(),
+        )
+        .map_err(invalid_params)?;
+        let mut feature_uuids: HashMap<String, String> = HashMap::new();
+        let mut existing: HashMap<(EntityType, String), HashSet<(String, String)>> = HashMap::new();
+        let row_count = rows.len();
+        for (line, row) in rows {
+            let id = format!("line {}", line);
+            if result.skip_completed(&id) || result.skip_over_budget(&id) {
+                continue;
+            }
+            let row = match row {
+                Ok(row) => row,
+                Err(reason) => {
+                    result.fail(id, reason, Value::Null);
+                    continue;
+                }
+            };
+            let detail = serde_json::to_value(&row).map_err(to_mcp_error)?;
+
+            let entity = match row.entity_type {
+                EntityType::Feature if !refmap::is_uuid(&row.entity_id) => {
+                    if let Some(uuid) = feature_uuids.get(&row.entity_id) {
+                        uuid.clone()
+                    } else {
+                        match self
+                            .clients
+                            .features
+                            .get_feature_by_display_id(&row.entity_id)
+                            .await
+                            .map(|f| f.uuid)
+                        {
+                            Ok(Some(uuid)) => {
+                                feature_uuids.insert(row.entity_id.clone(), uuid.clone());
+                                uuid
+                            }
+                            Ok(None) => {
+                                result.fail(id, "Feature has no UUID", detail);
+                                continue;
+                            }
+                            Err(e) => {
+                                result.fail(id, e, detail);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                _ => row.entity_id.clone(),
+            };
+
+            let key = (row.entity_type, entity.clone());
+            if !existing.contains_key(&key) {
+                let links = match row.entity_type {
+                    EntityType::Feature => self
+                        .clients
+                        .features
+                        .list_external_references(Some(
+                            ODataQuery::new().filter(format!("parent_uuid eq '{}'", entity)),
+                        ))
+                        .await
+                        .map(|c| {
+                            c.value
+                                .into_iter()
+                                .map(|r| (r.name.unwrap_or_default(), r.id.unwrap_or_default()))
+                                .collect()
+                        }),
+                    EntityType::Task => {
+                        self.clients
+                            .tasks
+                            .list_task_references(&entity)
+                            .await
+                            .map(|refs| {
+                                refs.into_iter()
+                                    .map(|r| {
+                                        (
+                                            r.external_system.unwrap_or_default(),
+                                            r.external_id.unwrap_or_default(),
+                                        )
+                                    })
+                                    .collect()
+                            })
+                    }
+                };
+                match links {
+                    Ok(links) => {
+                        existing.insert(key.clone(), links);
+                    }
+                    Err(e) => {
+                        result.fail(id, e, detail);
+                        continue;
+                    }
+                }
+            }
+            let link = (row.external_system.clone(), row.external_id.clone());
+            if existing[&key].contains(&link) {
+                result.skip(id, "already linked");
+                continue;
+            }
+            if dry_run {
+                result.succeed(id, detail);
+                continue;
+            }
+
+            let created = match row.entity_type {
+                EntityType::Feature => {
+                    let request = CreateExternalReferenceRequest {
+                        id: row.external_id.clone(),
+                        parent_uuid: entity.clone(),
+                        name: row.external_system.clone(),
+                        url: row.url.clone(),
+                    };
+                    self.clients
+                        .features
+                        .create_external_reference(&request)
+                        .await
+                        .map(|_| ())
+                }
+                EntityType::Task => {
+                    let request = CreateTaskReferenceRequest {
+                        external_id: row.external_id.clone(),
+                        external_system: row.external_system.clone(),
+                        url: row.url.clone(),
+                    };
+                    self.clients
+                        .tasks
+                        .create_task_reference(&entity, &request)
+                        .await
+                        .map(|_| ())
+                }
+            };
+            match created {
+                Ok(()) => {
+                    if let Some(links) = existing.get_mut(&key) {
+                        links.insert(link);
+                    }
+                    result.succeed(id, detail);
+                }
+                Err(e) => result.fail(id, e, detail),
+            }
+        }
+
+        // A dry run links nothing, so it must not produce a resume token
+        let result = if dry_run { result } else { result.finish() };
+        let mut json = serde_json::to_value(result).map_err(to_mcp_error)?;
+        json["dry_run"] = json!(dry_run);
+        json["row_count"] = json!(row_count);
+        self.debug
+            .log_tool_result("import_external_references", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Compare an entity type between two tenants (e.g. test vs production) configured as profiles. Runs the same query on both, matches records by a key field and reports records present only in one tenant and field differences. Tenant-specific fields (UUIDs, IDs, timestamps) are ignored. Required: entity_type, right."
+    )]
+    async fn compare_tenants(
+        &self,
+        Parameters(params): Parameters<CompareTenantsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("compare_tenants", &json!(params));
+
+        let default_key = COMPARABLE_ENTITIES
+            .iter()
+            .find(|(entity, _)| *entity == params.entity_type)
+            .map(|(_, key)| *key)
+            .ok_or_else(|| McpError {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(format!(
+                    "Unknown entity_type '{}'. Valid types: {:?}",
+                    params.entity_type,
+                    COMPARABLE_ENTITIES
+                        .iter()
+                        .map(|(e, _)| *e)
+                        .collect::<Vec<_>>()
+                )),
+                data: None,
+            })?;
+        let left_clients = self.profile_clients(params.left.as_deref())?;
+        let right_clients = self.profile_clients(Some(&params.right))?;
+
+        let (left, right) = tokio::join!(
+            fetch_comparable(left_clients, &params.entity_type, params.filter.as_deref()),
+            fetch_comparable(right_clients, &params.entity_type, params.filter.as_deref()),
+        );
+        let left = left.map_err(to_mcp_error)?;
+        let right = right.map_err(to_mcp_error)?;
+
+        let key = params.key.as_deref().unwrap_or(default_key);
+        let mut ignore: Vec<&str> = TENANT_SPECIFIC_FIELDS.to_vec();
+        if let Some(fields) = &params.ignore_fields {
+            ignore.extend(fields.split(',').map(str::trim).filter(|f| !f.is_empty()));
+        }
+        let diff = diff_record_sets(&left, &right, key, &ignore);
+
+        let json = json!({
+            "entity_type": params.entity_type,
+            "left": params.left.as_deref().unwrap_or("default"),
+            "right": params.right,
+            "key": key,
+            "left_count": left.len(),
+            "right_count": right.len(),
+            "diff": diff,
+        });
+        self.debug.log_tool_result("compare_tenants", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Get a high-level inventory of the tenant: number of projects, features by status, open tasks, documents and test cases. Uses $count queries and runs all requests concurrently. Open tasks are counted for at most 50 projects."
+    )]
+    async fn get_tenant_inventory(
+        &self,
+        Parameters(params): Parameters<TenantInventoryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        const MAX_TASK_PROJECTS: usize = 50;
+
+        self.debug
+            .log_tool_call("get_tenant_inventory", &json!(params));
+
+        let clients = self.profile_clients(params.profile.as_deref())?;
+        let project_filter = params
+            .project_id
+            .as_ref()
+            .map(|p| format!("projectId eq '{}'", p));
+        let with_project = |filter: String| match &project_filter {
+            Some(p) => format!("{} and {}", filter, p),
+            None => filter,
+        };
+
+        let (projects, statuses, features, documents, testcases) = tokio::join!(
+            clients.projects.list_projects(),
+            clients.features.list_statuses(),
+            clients.features.count_features(project_filter.clone()),
+            clients.documents.count_documents(project_filter.clone()),
+            clients
+                .testmanagement
+                .count_testcases(project_filter.clone()),
+        );
+
+        let mut warnings = Vec::new();
+        let mut count_or_warn = |label: &str, result: Result<i64, crate::error::ApiError>| {
+            result
+                .map_err(|e| warnings.push(format!("{} count unavailable: {}", label, e)))
+                .ok()
+        };
+        // The total includes features whose status is not in the status list
+        let features = count_or_warn("Feature", features);
+        let documents = count_or_warn("Document", documents);
+        let testcases = count_or_warn("Test case", testcases);
+
+        let projects = projects.map_err(to_mcp_error)?;
+        let mut task_projects: Vec<String> = match &params.project_id {
+            Some(project_id) => vec![project_id.clone()],
+            None => projects.iter().filter_map(|p| p.id.clone()).collect(),
+        };
+        if task_projects.len() > MAX_TASK_PROJECTS {
+            warnings.push(format!(
+                "Open tasks counted for the first {} of {} projects",
+                MAX_TASK_PROJECTS,
+                task_projects.len()
+            ));
+            task_projects.truncate(MAX_TASK_PROJECTS);
+        }
+        let statuses = statuses.map(|s| s.value).unwrap_or_else(|e| {
+            warnings.push(format!("Feature statuses unavailable: {}", e));
+            Vec::new()
+        });
+
+        let mut feature_counts = tokio::task::JoinSet::new();
+        for status in statuses {
+            let features = clients.features.clone();
+            let filter = with_project(format!("statusCode eq '{}'", status.code));
+            feature_counts
+                .spawn(async move { (status.name, features.count_features(Some(filter)).await) });
+        }
+        let mut task_lists = tokio::task::JoinSet::new();
+        for project_id in task_projects {
+            let tasks = clients.tasks.clone();
+            task_lists.spawn(async move {
+                let request = ListTasksParams {
+                    project_id: project_id.clone(),
+                    ..Default::default()
+                };
+                (project_id, tasks.list_tasks(&request).await)
+            });
+        }
+
+        let (feature_counts, task_lists) =
+            tokio::join!(feature_counts.join_all(), task_lists.join_all());
+
+        let mut features_by_status = std::collections::BTreeMap::new();
+        for (status, count) in feature_counts {
+            match count {
+                Ok(count) => {
+                    features_by_status.insert(status, count);
+                }
+                Err(e) => {
+                    warnings.push(format!("Feature count for '{}' unavailable: {}", status, e))
+                }
+            }
+        }
+        let mut open_tasks = 0;
+        for (project_id, tasks) in task_lists {
+            match tasks {
+                Ok(tasks) => {
+                    open_tasks += tasks
+                        .iter()
+                        .filter(|t| !t.status.as_deref().is_some_and(is_closed_status))
+                        .count()
+                }
+                Err(e) => warnings.push(format!(
+                    "Tasks of project {} unavailable: {}",
+                    project_id, e
+                )),
+            }
+        }
+
+        let json = json!({
+            "profile": params.profile.as_deref().unwrap_or("default"),
+            "project_id": params.project_id,
+            "projects": match &params.project_id {
+                Some(_) => None,
+                None => Some(projects.len()),
+            },
+            "features": {
+                "total": features,
+                "by_status": features_by_status,
+            },
+            "open_tasks": open_tasks,
+            "documents": documents,
+            "test_cases": testcases,
+            "warnings": warnings,
+        });
+        self.debug.log_tool_result("get_tenant_inventory", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Explain an SAP Cloud ALM API error: pass an HTTP status, an OData or OAuth error code, or the complete error message of a failed tool call. Returns the likely cause and typical remediations (missing scope, wrong region or tenant, sandbox limitations, ...)."
+    )]
+    async fn explain_error(
+        &self,
+        Parameters(params): Parameters<ExplainErrorParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("explain_error", &json!(params));
+
+        let matches = error_catalog::explain(&params.code);
+        let json = if matches.is_empty() {
+            let known: Vec<&str> = error_catalog::CATALOG
+                .iter()
+                .flat_map(|e| e.codes.iter().copied())
+                .collect();
+            json!({
+                "query": params.code,
+                "matches": [],
+                "message": format!("No catalog entry matches. Known codes: {}", known.join(", ")),
+            })
+        } else {
+            json!({
+                "query": params.code,
+                "matches": matches,
+            })
+        };
+        self.debug.log_tool_result("explain_error", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "List create and update calls buffered while the SAP Cloud ALM API was unreachable (requires write_queue in the configuration). Pending operations are replayed with flush_pending_operations; failed ones were rejected on replay and are kept until flushed or discarded."
+    )]
+    async fn list_pending_operations(&self) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("list_pending_operations", &json!({}));
+
+        let operations = self.queue()?.list();
+        let json = json!({
+            "count": operations.len(),
+            "operations": operations,
+        });
+        self.debug.log_tool_result("list_pending_operations", &json);
 
         to_json_result(&json)
     }
 
-    #[tool(description = "List test actions with OData filtering.")]
-    async fn list_test_actions(
+    #[tool(
+        description = "Replay buffered write operations now, in queue order: all queued operations including failed ones, or only the given IDs. Replay stops at the first operation that fails because the API is still unreachable. Requires user confirmation before execution."
+    )]
+    async fn flush_pending_operations(
         &self,
-        Parameters(params): Parameters<ODataListParams>,
+        Parameters(params): Parameters<FlushOperationsParams>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         self.debug
-            .log_tool_call("list_test_actions", &json!(params));
-
-        let query = build_odata_query(
-            params.filter,
-            params.select,
-            params.expand,
-            params.orderby,
-            params.top,
-            params.skip,
-        );
-
-        let result = self
-            .clients
-            .testmanagement
-            .list_actions(query)
-            .await
-            .map_err(to_mcp_error)?;
+            .log_tool_call("flush_pending_operations", &json!(params));
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
-        self.debug.log_tool_result("list_test_actions", &json);
+        let queue = self.queue()?;
+        let Some(_replay) = queue.try_begin_replay() else {
+            return Err(invalid_params(
+                "A replay of the write queue is already running",
+            ));
+        };
+        let operations: Vec<PendingOperation> = queue
+            .list()
+            .into_iter()
+            .filter(|op| params.ids.as_ref().is_none_or(|ids| ids.contains(&op.id)))
+            .collect();
+        let results = self.replay_operations(queue, operations, &context).await;
+        let json = json!({
+            "replayed": results.iter().filter(|r| r["status"] == "replayed").count(),
+            "results": results,
+            "remaining": queue.list().len(),
+        });
+        self.debug
+            .log_tool_result("flush_pending_operations", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Create a test action for an activity. Requires user confirmation before execution."
+        description = "Discard buffered write operations without replaying them: the given IDs, or all with all: true. Requires user confirmation before execution."
     )]
-    async fn create_test_action(
+    async fn discard_pending_operations(
         &self,
-        Parameters(params): Parameters<CreateTestActionParams>,
+        Parameters(params): Parameters<DiscardOperationsParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug
-            .log_tool_call("create_test_action", &json!(params));
-
-        let request = CreateTestActionRequest {
-            title: params.title,
-            parent_id: params.parent_id,
-            description: params.description,
-            expected_result: params.expected_result,
-            sequence: params.sequence,
-            is_evidence_required: params.is_evidence_required,
+            .log_tool_call("discard_pending_operations", &json!(params));
+
+        let queue = self.queue()?;
+        let discarded = match (&params.ids, params.all) {
+            (Some(ids), _) => queue.discard(Some(ids)),
+            (None, Some(true)) => queue.discard(None),
+            (None, _) => {
+                return Err(invalid_params(
+                    "Pass the IDs of the operations to discard, or all: true",
+                ))
+            }
         };
+        let json = json!({
+            "discarded": discarded,
+            "remaining": queue.list().len(),
+        });
+        self.debug
+            .log_tool_result("discard_pending_operations", &json);
 
-        let result = self
-            .clients
-            .testmanagement
-            .create_action(&request)
-            .await
-            .map_err(to_mcp_error)?;
+        to_json_result(&json)
+    }
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
-        self.debug.log_tool_result("create_test_action", &json);
+    #[tool(
+        description = "Add features, tasks or quality gates to the watchlist. Their current status and assignment are recorded; check_watchlist reports changes since then. Required: entity_type (feature, task, quality_gate), ids."
+    )]
+    async fn add_to_watchlist(
+        &self,
+        Parameters(params): Parameters<WatchEntitiesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("add_to_watchlist", &json!(params));
+
+        let entity_type: WatchedType = params.entity_type.parse().map_err(invalid_params)?;
+        let mut added = Vec::new();
+        let mut already_watched = Vec::new();
+        let mut failed = serde_json::Map::new();
+        for id in &params.ids {
+            match self.watched_state(entity_type, id).await {
+                Ok(state) => {
+                    if self.watchlist.add(entity_type, id, Some(state)) {
+                        added.push(id.clone());
+                    } else {
+                        already_watched.push(id.clone());
+                    }
+                }
+                Err(e) => {
+                    failed.insert(id.clone(), json!(e.to_string()));
+                }
+            }
+        }
+        let json = json!({
+            "entity_type": entity_type,
+            "added": added,
+            "already_watched": already_watched,
+            "failed": failed,
+            "watched_count": self.watchlist.entries().len(),
+        });
+        self.debug.log_tool_result("add_to_watchlist", &json);
 
         to_json_result(&json)
     }
 
-    // ========================================================================
-    // Process Hierarchy API Tools
-    // ========================================================================
-
-    #[tool(description = "List process hierarchy nodes with OData filtering.")]
-    async fn list_hierarchy_nodes(
+    #[tool(
+        description = "Remove entities from the watchlist: the given entity_type and ids, or everything with all: true."
+    )]
+    async fn remove_from_watchlist(
         &self,
-        Parameters(params): Parameters<ODataListParams>,
+        Parameters(params): Parameters<UnwatchEntitiesParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug
-            .log_tool_call("list_hierarchy_nodes", &json!(params));
+            .log_tool_call("remove_from_watchlist", &json!(params));
+
+        let removed = match (&params.entity_type, &params.ids, params.all) {
+            (Some(entity_type), Some(ids), _) => {
+                let entity_type: WatchedType = entity_type.parse().map_err(invalid_params)?;
+                ids.iter()
+                    .filter(|id| self.watchlist.remove(entity_type, id))
+                    .count()
+            }
+            (_, None, Some(true)) => self.watchlist.clear(),
+            _ => return Err(invalid_params("Pass entity_type and ids, or all: true")),
+        };
+        let json = json!({
+            "removed": removed,
+            "watched_count": self.watchlist.entries().len(),
+        });
+        self.debug.log_tool_result("remove_from_watchlist", &json);
 
-        let query = build_odata_query(
-            params.filter,
-            params.select,
-            params.expand,
-            params.orderby,
-            params.top,
-            params.skip,
-        );
+        to_json_result(&json)
+    }
 
-        let result = self
-            .clients
-            .processhierarchy
-            .list_nodes(query)
-            .await
-            .map_err(to_mcp_error)?;
+    #[tool(
+        description = "Check the watchlist: reports status and assignment changes of watched features, tasks and quality gates since they were added or last checked, and records the current state for the next check."
+    )]
+    async fn check_watchlist(&self) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("check_watchlist", &json!({}));
+
+        let entries = self.watchlist.entries();
+        let mut changed = Vec::new();
+        let mut unchanged = 0;
+        let mut failed = Vec::new();
+        let mut unchecked = 0;
+        for entry in &entries {
+            if budget::exhausted() {
+                unchecked += 1;
+                continue;
+            }
+            match self.watched_state(entry.entity_type, &entry.id).await {
+                Ok(state) => {
+                    let title = state.title.clone();
+                    match self.watchlist.update(entry.entity_type, &entry.id, state) {
+                        Some(changes) if !changes.is_empty() => changed.push(json!({
+                            "entity_type": entry.entity_type,
+                            "id": entry.id,
+                            "title": title,
+                            "changes": changes,
+                        })),
+                        _ => unchanged += 1,
+                    }
+                }
+                Err(e) => failed.push(json!({
+                    "entity_type": entry.entity_type,
+                    "id": entry.id,
+                    "error": e.to_string(),
+                })),
+            }
+        }
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
-        self.debug.log_tool_result("list_hierarchy_nodes", &json);
+        let mut json = json!({
+            "watched_count": entries.len(),
+            "changed_count": changed.len(),
+            "unchanged_count": unchanged,
+            "changed": changed,
+            "failed": failed,
+        });
+        if unchecked > 0 {
+            json["unchecked_count"] = json!(unchecked);
+            json["note"] = json!(budget::PARTIAL_NOTE);
+        }
+        self.debug.log_tool_result("check_watchlist", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "Get a hierarchy node by UUID. Optionally expand toParentNode, toChildNodes, toExternalReferences."
+        description = "List the files in the workspace directory (exports, mapping files, calendars, templates written by other tools) with size and modification time. Requires workspace_dir in the configuration."
     )]
-    async fn get_hierarchy_node(
-        &self,
-        Parameters(params): Parameters<GetHierarchyNodeParams>,
-    ) -> Result<CallToolResult, McpError> {
-        self.debug.log_tool_call(
-            "get_hierarchy_node",
-            &json!({"uuid": params.uuid, "expand": params.expand}),
-        );
-
-        let result = if let Some(ref expand) = params.expand {
-            let expand_list: Vec<&str> = expand.split(',').map(|s: &str| s.trim()).collect();
-            self.clients
-                .processhierarchy
-                .get_node_with_expand(&params.uuid, &expand_list)
-                .await
-        } else {
-            self.clients
-                .processhierarchy
-                .get_node(&params.uuid)
-                .await
-                .map(|n| serde_json::to_value(n).map_err(crate::error::ApiError::JsonParse))
-                .and_then(|r| r)
-        };
+    async fn list_workspace_files(&self) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("list_workspace_files", &json!({}));
 
-        let json = result.map_err(to_mcp_error)?;
-        self.debug.log_tool_result("get_hierarchy_node", &json);
+        let workspace = self.workspace()?;
+        let files = workspace.list().map_err(to_mcp_error)?;
+        let json = json!({
+            "value": files,
+            "workspace_dir": workspace.root().display().to_string(),
+        });
+        self.debug.log_tool_result("list_workspace_files", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Create a new hierarchy node. Requires user confirmation before execution. Required: title."
+        description = "Read a file from the workspace directory in chunks. Text is returned as UTF-8, binary content (e.g. Parquet) as base64. Continue with offset: next_offset until next_offset is null. Required: path."
     )]
-    async fn create_hierarchy_node(
+    async fn read_workspace_file(
         &self,
-        Parameters(params): Parameters<CreateHierarchyNodeParams>,
+        Parameters(params): Parameters<ReadWorkspaceFileParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug
-            .log_tool_call("create_hierarchy_node", &json!(params));
+            .log_tool_call("read_workspace_file", &json!(params));
+
+        let chunk = self
+            .workspace()?
+            .read_chunk(
+                &params.path,
+                params.offset.unwrap_or(0),
+                params.max_bytes.unwrap_or(workspace::DEFAULT_CHUNK_BYTES),
+            )
+            .map_err(invalid_params)?;
+        let json = json!(chunk);
+        self.debug.log_tool_result("read_workspace_file", &json);
 
-        let request = CreateHierarchyNodeRequest {
-            title: params.title,
-            parent_node_uuid: params.parent_node_uuid,
-            description: params.description,
-            sequence: params.sequence,
-        };
+        to_json_result(&json)
+    }
 
-        let result = self
-            .clients
-            .processhierarchy
-            .create_node(&request)
-            .await
-            .map_err(to_mcp_error)?;
+    #[tool(
+        description = "Pin an entity under discussion to the session: stores a compact digest (IDs, title, status, assignee, project, dates) and an optional note. Pinning the same entity again refreshes the digest. At most 25 pins per session. Required: entity_type (feature, document, task, testcase, hierarchy_node, project), id."
+    )]
+    async fn pin_entity(
+        &self,
+        Parameters(params): Parameters<PinEntityParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("pin_entity", &json!(params));
+
+        let entity_type: PinType = params.entity_type.parse().map_err(invalid_params)?;
+        let (get_tool, id_argument) = entity_type.read_call(&params.id);
+        let mut arguments = serde_json::Map::new();
+        arguments.insert(id_argument.to_string(), json!(params.id));
+        let request = CallToolRequestParam {
+            name: Cow::from(get_tool),
+            arguments: Some(arguments),
+            task: None,
+        };
+        let read = self
+            .dispatch(request, context, CallOrigin::Internal)
+            .await?;
+        let entity = match read.content.first().map(|c| &c.raw) {
+            Some(RawContent::Text(text)) if read.is_error != Some(true) => {
+                serde_json::from_str::<Value>(&text.text).map_err(to_mcp_error)?
+            }
+            _ => return Ok(read),
+        };
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
-        self.debug.log_tool_result("create_hierarchy_node", &json);
+        let pin = pins::digest(entity_type, &params.id, &entity, params.note);
+        self.pins.pin(pin.clone()).map_err(invalid_params)?;
+        let json = json!({
+            "pinned": pin,
+            "pinned_count": self.pins.list().len(),
+        });
+        self.debug.log_tool_result("pin_entity", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Update an existing hierarchy node. Requires user confirmation before execution."
+        description = "Unpin entities from the session: the given entity_type and id, or all with all: true."
     )]
-    async fn update_hierarchy_node(
+    async fn unpin_entity(
         &self,
-        Parameters(params): Parameters<UpdateHierarchyNodeParams>,
+        Parameters(params): Parameters<UnpinEntityParams>,
     ) -> Result<CallToolResult, McpError> {
-        self.debug
-            .log_tool_call("update_hierarchy_node", &json!(params));
+        self.debug.log_tool_call("unpin_entity", &json!(params));
 
-        let request = UpdateHierarchyNodeRequest {
-            title: params.title,
-            description: params.description,
-            sequence: params.sequence,
+        let removed = match (&params.entity_type, &params.id, params.all) {
+            (Some(entity_type), Some(id), _) => {
+                let entity_type: PinType = entity_type.parse().map_err(invalid_params)?;
+                usize::from(self.pins.unpin(entity_type, id))
+            }
+            (_, None, Some(true)) => self.pins.clear(),
+            _ => return Err(invalid_params("Pass entity_type and id, or all: true")),
         };
+        let json = json!({
+            "unpinned": removed,
+            "pinned_count": self.pins.list().len(),
+        });
+        self.debug.log_tool_result("unpin_entity", &json);
 
-        let result = self
-            .clients
-            .processhierarchy
-            .update_node(&params.uuid, &request)
-            .await
-            .map_err(to_mcp_error)?;
+        to_json_result(&json)
+    }
 
-        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
-        self.debug.log_tool_result("update_hierarchy_node", &json);
+    #[tool(description = "List the entities pinned in this session with their digests and notes.")]
+    async fn list_pinned(&self) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("list_pinned", &json!({}));
+
+        let json = json!(self.pins.list());
+        self.debug.log_tool_result("list_pinned", &json);
 
         to_json_result(&json)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Delete a hierarchy node by UUID. Requires user confirmation before execution."
+        description = "Get a compact text summary of the entities pinned in this session (one line each: type, ID, title, status, assignee, due date, note), to refresh the conversation context without fetching the entities again."
     )]
-    async fn delete_hierarchy_node(
-        &self,
-        Parameters(params): Parameters<UuidParams>,
-    ) -> Result<CallToolResult, McpError> {
+    async fn get_session_context(&self) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("get_session_context", &json!({}));
+
+        let text = pins::render_context(&self.pins.list());
         self.debug
-            .log_tool_call("delete_hierarchy_node", &json!({"uuid": params.uuid}));
+            .log_tool_result("get_session_context", &json!({"context": text}));
 
-        self.clients
-            .processhierarchy
-            .delete_node(&params.uuid)
-            .await
-            .map_err(to_mcp_error)?;
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
 
-        self.debug
-            .log_tool_result("delete_hierarchy_node", &json!({"deleted": true}));
+    #[tool(
+        description = "[EXPERIMENTAL] Run a declarative workflow: a JSON or YAML definition of steps, each calling one tool with arguments, e.g. create a feature, create its tasks, link references and set the status. Arguments reference variables as ${name}, results of earlier steps as ${steps.<id>.<field>} and, in steps with for_each, the current element as ${item}. Stops at the first failed step unless the step sets continue_on_error. dry_run defaults to true and only validates the workflow and returns the planned calls. Requires user confirmation before execution. Required: definition or path."
+    )]
+    async fn run_workflow(
+        &self,
+        Parameters(params): Parameters<RunWorkflowParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call("run_workflow", &json!(params));
+
+        let text = match (&params.definition, &params.path) {
+            (Some(definition), _) => definition.clone(),
+            (None, Some(path)) => std::fs::read_to_string(self.input_path(path)?).map_err(|e| {
+                invalid_params(format!("Cannot read workflow file '{}': {}", path, e))
+            })?,
+            (None, None) => return Err(invalid_params("Either definition or path is required")),
+        };
+        let definition = workflow::parse(&text).map_err(invalid_params)?;
+        let variables = workflow::variables(&definition, params.variables);
+        let problems = workflow::validate(&definition, &variables, |tool| {
+            self.tool_router.has_route(tool)
+        });
+        if !problems.is_empty() {
+            return Err(invalid_params(format!(
+                "Invalid workflow: {}",
+                problems.join("; ")
+            )));
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(
-            json!({"deleted": true, "uuid": params.uuid}).to_string(),
-        )]))
+        let dry_run = params.dry_run.unwrap_or(true);
+        let mut run = workflow::Run::new(variables, dry_run);
+        for step in &definition.steps {
+            if !dry_run && budget::exhausted() {
+                run.stop("not run: time budget exhausted");
+            }
+            let Some(calls) = run.calls(step) else {
+                continue;
+            };
+            let mut outcomes = Vec::new();
+            for arguments in calls {
+                let request = CallToolRequestParam {
+                    name: Cow::from(step.tool.clone()),
+                    arguments: Some(arguments.clone()),
+                    task: None,
+                };
+                let outcome = match self
+                    .dispatch(request, context.clone(), CallOrigin::Internal)
+                    .await
+                {
+                    Ok(result) => workflow::tool_output(&result),
+                    Err(e) => Err(e.message.to_string()),
+                };
+                let failed = outcome.is_err();
+                outcomes.push((arguments, outcome));
+                if failed && !step.continue_on_error {
+                    break;
+                }
+            }
+            run.record(step, outcomes);
+        }
+        let json = run.finish(&definition);
+        self.debug.log_tool_result("run_workflow", &json);
+
+        to_json_result(&json)
     }
+}
 
-    // ========================================================================
-    // Analytics API Tools
-    // ========================================================================
+// ============================================================================
+// Analytics API Tools
+// ============================================================================
 
+#[cfg(feature = "analytics")]
+#[tool_router(router = analytics_tool_router)]
+impl SapCloudAlmServer {
     #[tool(description = "Query a generic analytics dataset by provider name.")]
     async fn query_analytics_dataset(
         &self,
@@ -1862,7 +6153,13 @@ impl SapCloudAlmServer {
         let result = self
             .clients
             .analytics
-            .query_dataset(&params.provider, params.filter, params.top, params.skip)
+            .query_dataset(
+                &params.provider,
+                params.filter,
+                params.top,
+                params.skip,
+                params.custom_params,
+            )
             .await
             .map_err(to_mcp_error)?;
 
@@ -1872,6 +6169,44 @@ impl SapCloudAlmServer {
         to_json_result(&result)
     }
 
+    #[tool(
+        description = "Estimate the size of an analytics query before running it: returns the row count ($count) and the latency of the count probe, the average row size from a 10-row sample, the estimated payload in bytes and tokens, and a recommendation (query directly, narrow the filter, or aggregate with $apply / export to a file). Required: provider."
+    )]
+    async fn estimate_query(
+        &self,
+        Parameters(params): Parameters<EstimateQueryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        const SAMPLE_ROWS: u32 = 10;
+
+        self.debug.log_tool_call("estimate_query", &json!(params));
+
+        let analytics = &self.clients.analytics;
+        let (probe, sample) = tokio::join!(
+            analytics.probe_dataset(&params.provider, params.filter.clone()),
+            analytics.query_dataset(
+                &params.provider,
+                params.filter.clone(),
+                Some(SAMPLE_ROWS),
+                None,
+                None
+            ),
+        );
+        let probe = probe.map_err(to_mcp_error)?;
+        let rows = probe.required_count("/DataSet").map_err(to_mcp_error)?;
+        let sample = sample.map_err(to_mcp_error)?;
+
+        let estimate = estimate_query_size(rows, collection_items(&sample));
+        let json = json!({
+            "provider": params.provider,
+            "filter": params.filter,
+            "estimate": estimate,
+            "probe_latency_ms": probe.latency_ms,
+        });
+        self.debug.log_tool_result("estimate_query", &json);
+
+        to_json_result(&json)
+    }
+
     #[tool(description = "List available analytics data providers.")]
     async fn list_analytics_providers(&self) -> Result<CallToolResult, McpError> {
         self.debug
@@ -1885,6 +6220,113 @@ impl SapCloudAlmServer {
         to_json_result(&result)
     }
 
+    #[tool(
+        description = "Export an analytics dataset to a file (csv, ndjson or parquet) for loading into DuckDB/Pandas. Pages through the provider and writes records as they arrive; the column schema is inferred from the first page. anonymize=true replaces user names and emails by stable pseudonyms and masks free-text fields, e.g. for sharing with external consultants or as test fixtures. Required: provider, path."
+    )]
+    async fn export_analytics_dataset(
+        &self,
+        Parameters(params): Parameters<ExportAnalyticsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("export_analytics_dataset", &json!(params));
+
+        let format: ExportFormat =
+            params
+                .format
+                .as_deref()
+                .unwrap_or("csv")
+                .parse()
+                .map_err(|e: String| McpError {
+                    code: ErrorCode::INVALID_PARAMS,
+                    message: Cow::from(e),
+                    data: None,
+                })?;
+        let page_size = params.page_size.unwrap_or(1000).max(1);
+        let max_records = params.max_records.unwrap_or(100_000).max(1);
+        let anonymize = params.anonymize.unwrap_or(false);
+        let output_path = self.output_path(&params.path)?;
+
+        let mut writer: Option<ExportWriter> = None;
+        let mut fetched: u32 = 0;
+        let mut truncated = false;
+        let mut out_of_time = false;
+        loop {
+            let top = page_size.min(max_records - fetched);
+            let page = self
+                .clients
+                .analytics
+                .query_dataset(
+                    &params.provider,
+                    params.filter.clone(),
+                    Some(top),
+                    Some(fetched),
+                    None,
+                )
+                .await
+                .map_err(to_mcp_error)?;
+            let records = collection_items(&page);
+            let page_records = records.len() as u32;
+            let records: Cow<[Value]> = if anonymize {
+                Cow::Owned(
+                    records
+                        .iter()
+                        .map(|r| self.anonymizer.apply(r.clone()))
+                        .collect(),
+                )
+            } else {
+                Cow::Borrowed(records)
+            };
+
+            let writer = match writer.as_mut() {
+                Some(writer) => writer,
+                None => writer.insert(
+                    ExportWriter::create(&output_path, format, &records).map_err(to_mcp_error)?,
+                ),
+            };
+            writer.write_page(&records).map_err(to_mcp_error)?;
+
+            fetched += page_records;
+            if page_records < top {
+                break;
+            }
+            if fetched >= max_records {
+                truncated = true;
+                break;
+            }
+            if budget::exhausted() {
+                truncated = true;
+                out_of_time = true;
+                break;
+            }
+        }
+
+        let columns = writer
+            .as_ref()
+            .map(|w| serde_json::to_value(w.columns()).unwrap_or_default())
+            .unwrap_or_default();
+        let records = match writer {
+            Some(writer) => writer.finish().map_err(to_mcp_error)?,
+            None => 0,
+        };
+
+        let mut json = json!({
+            "provider": params.provider,
+            "path": params.path,
+            "format": format,
+            "records": records,
+            "truncated": truncated,
+            "columns": columns,
+            "anonymized": anonymize,
+        });
+        if out_of_time {
+            json["note"] = json!(budget::PARTIAL_NOTE);
+        }
+        self.debug
+            .log_tool_result("export_analytics_dataset", &json);
+
+        to_json_result(&json)
+    }
+
     #[tool(description = "Get requirements analytics data.")]
     async fn get_analytics_requirements(
         &self,
@@ -1900,7 +6342,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -1930,7 +6373,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -1939,9 +6383,10 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
-        self.debug.log_tool_result("get_analytics_tasks", &result);
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("get_analytics_tasks", &json);
 
-        to_json_result(&result)
+        to_json_result(&json)
     }
 
     #[tool(description = "Get defects analytics data.")]
@@ -1959,18 +6404,20 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
             .analytics
-            .get_defects(query)
+            .get_defect_records(query)
             .await
             .map_err(to_mcp_error)?;
 
-        self.debug.log_tool_result("get_analytics_defects", &result);
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("get_analytics_defects", &json);
 
-        to_json_result(&result)
+        to_json_result(&json)
     }
 
     #[tool(description = "Get features analytics data.")]
@@ -1988,7 +6435,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2018,18 +6466,20 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
             .analytics
-            .get_tests(query)
+            .get_test_records(query)
             .await
             .map_err(to_mcp_error)?;
 
-        self.debug.log_tool_result("get_analytics_tests", &result);
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
+        self.debug.log_tool_result("get_analytics_tests", &json);
 
-        to_json_result(&result)
+        to_json_result(&json)
     }
 
     #[tool(description = "Get quality gates analytics data.")]
@@ -2047,7 +6497,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2056,10 +6507,11 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
+        let json = serde_json::to_value(&result).map_err(to_mcp_error)?;
         self.debug
-            .log_tool_result("get_analytics_quality_gates", &result);
+            .log_tool_result("get_analytics_quality_gates", &json);
 
-        to_json_result(&result)
+        to_json_result(&json)
     }
 
     #[tool(description = "Get projects analytics data.")]
@@ -2077,7 +6529,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2107,7 +6560,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2137,7 +6591,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2167,7 +6622,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2196,7 +6652,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2226,7 +6683,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2255,7 +6713,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2285,7 +6744,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2315,7 +6775,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2345,7 +6806,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2375,7 +6837,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2390,10 +6853,481 @@ impl SapCloudAlmServer {
         to_json_result(&result)
     }
 
-    // ========================================================================
-    // Process Monitoring API Tools (CALM_PMGE)
-    // ========================================================================
+    #[tool(
+        description = "Find features and requirements of a project that have no test coverage or only failed test runs. Combines Features, Test Management and analytics data."
+    )]
+    async fn find_untested_items(
+        &self,
+        Parameters(params): Parameters<ProjectIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug.log_tool_call(
+            "find_untested_items",
+            &json!({"project_id": params.project_id}),
+        );
+
+        let project_filter = format!("projectId eq '{}'", params.project_id);
+
+        let project_query = || ODataQuery::new().filter(&project_filter);
+
+        let (features, testcases, runs, requirements) = tokio::join!(
+            odata::read_all(project_query(), |q| {
+                self.clients.features.list_features(Some(q))
+            }),
+            odata::read_all(project_query(), |q| {
+                self.clients.testmanagement.list_testcases(Some(q))
+            }),
+            self.clients
+                .analytics
+                .get_tests(Some(ODataQuery::new().filter(&project_filter))),
+            self.clients
+                .analytics
+                .get_requirements(Some(ODataQuery::new().filter(&project_filter))),
+        );
+
+        let features = features.map_err(to_mcp_error)?;
+        let testcases = testcases.map_err(to_mcp_error)?;
+        let truncated = features.truncated || testcases.truncated;
+        let features = features.items;
+        let testcases = testcases
+            .items
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<Value>, _>>()
+            .map_err(to_mcp_error)?;
+
+        // Analytics data is optional: report the gap instead of failing the whole analysis
+        let mut warnings = Vec::new();
+        let runs = match runs {
+            Ok(v) => collection_items(&v).to_vec(),
+            Err(e) => {
+                warnings.push(format!("Tests analytics unavailable: {}", e));
+                Vec::new()
+            }
+        };
+        let requirements = match requirements {
+            Ok(v) => collection_items(&v).to_vec(),
+            Err(e) => {
+                warnings.push(format!("Requirements analytics unavailable: {}", e));
+                Vec::new()
+            }
+        };
+
+        let mut untested = Vec::new();
+        let mut only_failed = Vec::new();
+
+        for feature in &features {
+            let ids: Vec<&str> = [feature.uuid.as_deref(), feature.display_id.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            let entry = json!({
+                "type": "feature",
+                "uuid": feature.uuid,
+                "display_id": feature.display_id,
+                "title": feature.title,
+            });
+            match classify_coverage(&ids, &testcases, &runs) {
+                Coverage::Untested => untested.push(entry),
+                Coverage::OnlyFailed => only_failed.push(entry),
+                Coverage::Covered => {}
+            }
+        }
+
+        for requirement in &requirements {
+            let id = field_str(requirement, &["id", "uuid", "requirementId"]);
+            let display_id = field_str(requirement, &["displayId"]);
+            let ids: Vec<&str> = [id.as_deref(), display_id.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            let entry = json!({
+                "type": "requirement",
+                "id": id,
+                "display_id": display_id,
+                "title": field_str(requirement, &["title", "name"]),
+            });
+            match classify_coverage(&ids, &testcases, &runs) {
+                Coverage::Untested => untested.push(entry),
+                Coverage::OnlyFailed => only_failed.push(entry),
+                Coverage::Covered => {}
+            }
+        }
+
+        let json = json!({
+            "project_id": params.project_id,
+            "checked": {
+                "features": features.len(),
+                "requirements": requirements.len(),
+            },
+            "untested": untested,
+            "only_failed": only_failed,
+            "truncated": truncated,
+            "warnings": warnings,
+        });
+        self.debug.log_tool_result("find_untested_items", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Test execution progress report: per-plan executed/passed/failed/blocked counts and top failing test cases. Combines the Tests analytics provider with Test Management test cases. Required: project_id or plan_id."
+    )]
+    async fn get_test_progress(
+        &self,
+        Parameters(params): Parameters<TestProgressParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("get_test_progress", &json!(params));
+
+        if params.project_id.is_none() && params.plan_id.is_none() {
+            return Err(McpError {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from("Either project_id or plan_id is required"),
+                data: None,
+            });
+        }
+
+        let project_filter = params
+            .project_id
+            .as_ref()
+            .map(|p| format!("projectId eq '{}'", p));
+        let testcase_query = match &project_filter {
+            Some(f) => ODataQuery::new().filter(f),
+            None => ODataQuery::new(),
+        };
+
+        let (runs, testcases) = tokio::join!(
+            self.clients
+                .analytics
+                .get_tests(project_filter.clone().map(|f| ODataQuery::new().filter(f))),
+            odata::read_all(testcase_query, |q| {
+                self.clients.testmanagement.list_testcases(Some(q))
+            }),
+        );
+
+        let runs = runs.map_err(to_mcp_error)?;
+        let mut runs = collection_items(&runs).to_vec();
+        if let Some(plan_id) = &params.plan_id {
+            runs.retain(|run| mentions_any(run, &[plan_id.as_str()]));
+        }
+
+        // Test case titles are optional enrichment for the failing list
+        let mut warnings = Vec::new();
+        let mut truncated = false;
+        let testcases = match testcases {
+            Ok(c) => {
+                truncated = c.truncated;
+                c.items
+            }
+            Err(e) => {
+                warnings.push(format!("Test cases unavailable: {}", e));
+                Vec::new()
+            }
+        };
+
+        let limit = params.top_failing.unwrap_or(10) as usize;
+        let top_failing: Vec<Value> = top_failing_test_cases(&runs, limit)
+            .into_iter()
+            .map(|(test_case, failures)| {
+                let title = testcases
+                    .iter()
+                    .find(|tc| tc.uuid.as_deref() == Some(test_case.as_str()))
+                    .and_then(|tc| tc.title.clone());
+                json!({"test_case": test_case, "title": title, "failures": failures})
+            })
+            .collect();
+
+        let json = json!({
+            "project_id": params.project_id,
+            "plan_id": params.plan_id,
+            "runs": runs.len(),
+            "plans": summarize_test_progress(&runs),
+            "top_failing": top_failing,
+            "test_cases_truncated": truncated,
+            "warnings": warnings,
+        });
+        self.debug.log_tool_result("get_test_progress", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Defect triage summary for a project: counts by status, severity and age bucket plus top assignees, from the Defects analytics provider."
+    )]
+    async fn summarize_defects(
+        &self,
+        Parameters(params): Parameters<SummarizeDefectsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("summarize_defects", &json!(params));
+
+        let query = ODataQuery::new().filter(format!("projectId eq '{}'", params.project_id));
+        let defects = self
+            .clients
+            .analytics
+            .get_defects(Some(query))
+            .await
+            .map_err(to_mcp_error)?;
+
+        let summary = summarize_defects(
+            collection_items(&defects),
+            chrono::Utc::now(),
+            params.top_assignees.unwrap_or(5) as usize,
+        );
+
+        let json = json!({
+            "project_id": params.project_id,
+            "summary": summary,
+        });
+        self.debug.log_tool_result("summarize_defects", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "[EXPERIMENTAL] Escalate an alert to a project task: reads the alert (by eventId) from the MonitoringEvents analytics provider, creates a task with the alert context and adds a backlink reference. Acknowledging the alert is out of scope: the SAP Cloud ALM APIs used by this server cannot change alert status, so acknowledge it in Cloud ALM Operations. Requires user confirmation before execution. Required: alert_id, project_id."
+    )]
+    async fn escalate_alert_to_task(
+        &self,
+        Parameters(params): Parameters<EscalateAlertParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("escalate_alert_to_task", &json!(params));
+        self.check_project_access(Some(&params.project_id)).await?;
+
+        let filter = format!("eventId eq '{}'", params.alert_id.replace('\'', "''"));
+        let events = self
+            .clients
+            .analytics
+            .get_monitoring_events(Some(ODataQuery::new().filter(filter)))
+            .await
+            .map_err(to_mcp_error)?;
+        let alert = collection_items(&events)
+            .iter()
+            .find(|e| field_str(e, &["eventId"]).as_deref() == Some(params.alert_id.as_str()))
+            .cloned()
+            .ok_or_else(|| McpError {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(format!(
+                    "Alert '{}' not found in MonitoringEvents analytics",
+                    params.alert_id
+                )),
+                data: None,
+            })?;
+
+        let name = field_str(&alert, &["eventName", "alertName", "name", "title"])
+            .unwrap_or_else(|| params.alert_id.clone());
+        let context: Vec<String> = [
+            ("Alert ID", Some(params.alert_id.clone())),
+            (
+                "Severity",
+                field_str(&alert, &["severity", "rating", "priority"]),
+            ),
+            ("Status", field_str(&alert, &["status", "eventStatus"])),
+            (
+                "Managed object",
+                field_str(&alert, &["managedObjectName", "serviceName", "systemName"]),
+            ),
+            (
+                "Raised at",
+                field_str(&alert, &["timestamp", "createdAt", "eventTime"]),
+            ),
+            (
+                "Description",
+                field_str(&alert, &["description", "message", "text"]),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| value.map(|v| format!("{}: {}", label, v)))
+        .collect();
+
+        let (request, defaults_applied) = self.internal_task_request(
+            json!({
+                "project_id": params.project_id,
+                "title": format!("[Alert] {}", name),
+                "task_type": params.task_type,
+                "description": context.join("\n"),
+            }),
+            "CALMTASK",
+        )?;
+        let task = self
+            .clients
+            .tasks
+            .create_task(&request)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let mut warnings = Vec::new();
+        let reference = match &task.id {
+            Some(task_id) => {
+                let request = CreateTaskReferenceRequest {
+                    external_id: params.alert_id.clone(),
+                    external_system: "SAP Cloud ALM Alerts".to_string(),
+                    url: params.alert_url,
+                };
+                match self
+                    .clients
+                    .tasks
+                    .create_task_reference(task_id, &request)
+                    .await
+                {
+                    Ok(r) => Some(r),
+                    Err(e) => {
+                        warnings.push(format!("Backlink reference not created: {}", e));
+                        None
+                    }
+                }
+            }
+            None => {
+                warnings.push("Created task has no ID; backlink reference skipped".to_string());
+                None
+            }
+        };
+
+        let json = json!({
+            "alert_id": params.alert_id,
+            "task": task,
+            "reference": reference,
+            "defaults_applied": defaults_applied,
+            "warnings": warnings,
+        });
+        self.debug.log_tool_result("escalate_alert_to_task", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Get a consolidated overview of a process hierarchy node: the node, its child nodes, and the requirements, documents, test cases and open tasks that reference the node's UUID or display ID. Sources are queried concurrently; unavailable sources are reported as warnings. Required: node_uuid."
+    )]
+    async fn get_process_overview(
+        &self,
+        Parameters(params): Parameters<ProcessOverviewParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("get_process_overview", &json!(params));
+
+        let node = self
+            .clients
+            .processhierarchy
+            .get_node(&params.node_uuid)
+            .await
+            .map_err(to_mcp_error)?;
+        let mut needles = vec![params.node_uuid.as_str()];
+        if let Some(display_id) = &node.display_id {
+            needles.push(display_id);
+        }
+
+        let project_filter = params
+            .project_id
+            .as_ref()
+            .map(|p| format!("projectId eq '{}'", p));
+        let scoped = || match &project_filter {
+            Some(f) => ODataQuery::new().filter(f),
+            None => ODataQuery::new(),
+        };
+        let tasks_request = params.project_id.as_ref().map(|p| ListTasksParams {
+            project_id: p.clone(),
+            ..Default::default()
+        });
+
+        let (children, requirements, documents, testcases, tasks) = tokio::join!(
+            self.clients.processhierarchy.list_nodes(Some(
+                ODataQuery::new().filter(format!("parentNodeUuid eq '{}'", params.node_uuid))
+            )),
+            odata::read_all(scoped(), |q| {
+                self.clients.analytics.get_requirements(Some(q))
+            }),
+            odata::read_all(scoped(), |q| self.clients.documents.list_documents(Some(q))),
+            odata::read_all(scoped(), |q| {
+                self.clients.testmanagement.list_testcases(Some(q))
+            }),
+            async {
+                match &tasks_request {
+                    Some(request) => Some(self.clients.tasks.list_tasks(request).await),
+                    None => None,
+                }
+            },
+        );
+
+        let truncated = requirements.as_ref().is_ok_and(|r| r.truncated)
+            || documents.as_ref().is_ok_and(|d| d.truncated)
+            || testcases.as_ref().is_ok_and(|t| t.truncated);
+
+        // Keep records that reference the node, collecting failures as warnings
+        let mut warnings = Vec::new();
+        let mut referencing = |source: &str, records: Result<Vec<Value>, String>| match records {
+            Ok(records) => records
+                .into_iter()
+                .filter(|r| mentions_any(r, &needles))
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                warnings.push(format!("{} unavailable: {}", source, e));
+                Vec::new()
+            }
+        };
+        let requirements = referencing(
+            "Requirements",
+            requirements.map(|r| r.items).map_err(|e| e.to_string()),
+        );
+        let documents = referencing(
+            "Documents",
+            documents
+                .map(|d| to_values(d.items))
+                .map_err(|e| e.to_string()),
+        );
+        let testcases = referencing(
+            "Test cases",
+            testcases
+                .map(|t| to_values(t.items))
+                .map_err(|e| e.to_string()),
+        );
+        let open_tasks: Vec<Value> = match tasks {
+            Some(tasks) => referencing("Tasks", tasks.map(to_values).map_err(|e| e.to_string()))
+                .into_iter()
+                .filter(|t| {
+                    !field_str(t, &["status"]).is_some_and(|status| is_closed_status(&status))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let children = match children {
+            Ok(c) => c.value,
+            Err(e) => {
+                warnings.push(format!("Child nodes unavailable: {}", e));
+                Vec::new()
+            }
+        };
+
+        let json = json!({
+            "node": node,
+            "counts": {
+                "children": children.len(),
+                "requirements": requirements.len(),
+                "documents": documents.len(),
+                "test_cases": testcases.len(),
+                "open_tasks": open_tasks.len(),
+            },
+            "children": children,
+            "requirements": requirements,
+            "documents": documents,
+            "test_cases": testcases,
+            "open_tasks": open_tasks,
+            "truncated": truncated,
+            "warnings": warnings,
+        });
+        self.debug.log_tool_result("get_process_overview", &json);
+
+        to_json_result(&json)
+    }
+}
 
+// ============================================================================
+// Process Monitoring API Tools (CALM_PMGE)
+// ============================================================================
+
+#[cfg(feature = "processmonitoring")]
+#[tool_router(router = processmonitoring_tool_router)]
+impl SapCloudAlmServer {
     #[tool(description = "List business processes with OData filtering.")]
     async fn list_business_processes(
         &self,
@@ -2409,7 +7343,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2459,7 +7394,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2509,7 +7445,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2539,7 +7476,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2569,7 +7507,8 @@ impl SapCloudAlmServer {
             params.orderby,
             params.top,
             params.skip,
-        );
+            params.custom_params,
+        )?;
 
         let result = self
             .clients
@@ -2582,21 +7521,37 @@ impl SapCloudAlmServer {
 
         to_json_result(&result)
     }
+}
 
-    // ========================================================================
-    // Logs API Tools
-    // ========================================================================
+// ============================================================================
+// Logs API Tools
+// ============================================================================
 
-    #[tool(description = "Get logs (outbound) in OpenTelemetry format. Required: provider.")]
+#[cfg(feature = "logs")]
+#[tool_router(router = logs_tool_router)]
+impl SapCloudAlmServer {
+    #[tool(
+        description = "Get logs (outbound) in OpenTelemetry format. With format: \"text\", records are rendered as compact lines (timestamp, severity, service, body) instead of OTel JSON. Required: provider."
+    )]
     async fn get_logs(
         &self,
         Parameters(params): Parameters<GetLogsToolParams>,
     ) -> Result<CallToolResult, McpError> {
         self.debug.log_tool_call("get_logs", &json!(params));
 
+        let as_text = params
+            .format
+            .as_deref()
+            .is_some_and(|f| f.eq_ignore_ascii_case("text"));
+
+        let field_filter = FieldFilter::new(
+            params.include_fields.as_deref(),
+            params.exclude_fields.as_deref(),
+        );
+
         let log_params = GetLogsParams {
             provider: params.provider,
-            format: params.format,
+            format: if as_text { None } else { params.format },
             version: params.version,
             period: params.period,
             from: params.from,
@@ -2615,13 +7570,26 @@ impl SapCloudAlmServer {
             .await
             .map_err(to_mcp_error)?;
 
+        if as_text {
+            let lines = otel_log_lines(&result);
+            self.debug
+                .log_tool_result("get_logs", &json!({"format": "text", "lines": lines.len()}));
+            let text = if lines.is_empty() {
+                "No log records.".to_string()
+            } else {
+                render_log_lines(&lines)
+            };
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
+        }
+
+        let result = field_filter.apply_nested(result, "logRecords");
         self.debug.log_tool_result("get_logs", &result);
 
         to_json_result(&result)
     }
 
     #[tool(
-        description = "[EXPERIMENTAL] Post logs (inbound) in OpenTelemetry format. Requires user confirmation before execution. Required: use_case, service_id, logs."
+        description = "[EXPERIMENTAL] Post logs (inbound) in OpenTelemetry format. Payloads larger than max_chunk_bytes (default 1 MB) are split into several POSTs with per-chunk results and a combined summary. Requires user confirmation before execution. Required: use_case, service_id, logs."
     )]
     async fn post_logs(
         &self,
@@ -2640,12 +7608,61 @@ impl SapCloudAlmServer {
             tag: params.tag,
         };
 
-        let result = self
+        let max_bytes = params.max_chunk_bytes.unwrap_or(MAX_POST_BYTES).max(1);
+        let mut chunks = self
             .clients
             .logs
-            .post_logs(&log_params, &params.logs)
-            .await
-            .map_err(to_mcp_error)?;
+            .post_logs_chunked(&log_params, &params.logs, max_bytes)
+            .await;
+
+        let result = if chunks.len() == 1 {
+            chunks.remove(0).result.map_err(to_mcp_error)?
+        } else {
+            let succeeded = chunks.iter().filter(|c| c.result.is_ok()).count();
+            if succeeded == 0 {
+                let error = chunks.into_iter().find_map(|c| c.result.err());
+                return Err(error.map(to_mcp_error).unwrap_or_else(|| {
+                    invalid_params("The logs payload contains no log records")
+                }));
+            }
+            let records_posted: usize = chunks
+                .iter()
+                .filter(|c| c.result.is_ok())
+                .map(|c| c.records)
+                .sum();
+            let results: Vec<Value> = chunks
+                .iter()
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let mut entry = json!({
+                        "chunk": i + 1,
+                        "records": chunk.records,
+                        "bytes": chunk.bytes,
+                    });
+                    match &chunk.result {
+                        Ok(result) => {
+                            entry["status"] = json!("ok");
+                            entry["result"] = result.clone();
+                        }
+                        Err(e) => {
+                            entry["status"] = json!("error");
+                            entry["error"] = json!(e.to_string());
+                        }
+                    }
+                    entry
+                })
+                .collect();
+            json!({
+                "chunked": true,
+                "max_chunk_bytes": max_bytes,
+                "chunks": chunks.len(),
+                "succeeded": succeeded,
+                "failed": chunks.len() - succeeded,
+                "records": chunks.iter().map(|c| c.records).sum::<usize>(),
+                "records_posted": records_posted,
+                "results": results,
+            })
+        };
 
         self.debug.log_tool_result("post_logs", &result);
 
@@ -2653,22 +7670,351 @@ impl SapCloudAlmServer {
     }
 }
 
+// ============================================================================
+// Incident Tools (Analytics and Logs APIs)
+// ============================================================================
+
+#[cfg(all(feature = "analytics", feature = "logs"))]
+#[tool_router(router = incident_tool_router)]
+impl SapCloudAlmServer {
+    #[tool(
+        description = "[EXPERIMENTAL] Generate a post-incident summary document: collects monitoring events (MonitoringEvents analytics) and optional log excerpts for a time window and creates a document with summary, timeline, affected services, log excerpts and follow-up sections. Use preview=true to review the content first. Requires user confirmation before execution. Required: project_id, from, to."
+    )]
+    async fn generate_incident_document(
+        &self,
+        Parameters(params): Parameters<IncidentDocumentParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("generate_incident_document", &json!(params));
+
+        let from = parse_timestamp("from", &params.from)?;
+        let to = parse_timestamp("to", &params.to)?;
+        let locale =
+            Locale::resolve(&self.locale, params.locale.as_deref()).map_err(invalid_params)?;
+
+        let query = ODataQuery::new().filter(window_filter(TIMESTAMP_KEYS[0], from, to));
+        let events = odata::read_all(query, |q| {
+            self.clients.analytics.get_monitoring_events(Some(q))
+        })
+        .await
+        .map_err(to_mcp_error)?;
+        let events_truncated = events.truncated;
+        let mut events = records_in_window(&events.items, from, to);
+        events.sort_by_key(|e| field_str(e, TIMESTAMP_KEYS));
+
+        let mut warnings = Vec::new();
+        let mut log_lines = Vec::new();
+        if let Some(provider) = &params.log_provider {
+            let request = GetLogsParams {
+                provider: provider.clone(),
+                format: None,
+                version: None,
+                period: None,
+                from: Some(params.from.clone()),
+                to: Some(params.to.clone()),
+                limit: Some(params.max_log_lines.unwrap_or(50)),
+                offset: None,
+                service_id: params.service_id.clone(),
+                observed_timestamp: None,
+                on_limit: None,
+            };
+            match self.clients.logs.get_logs(&request).await {
+                Ok(payload) => log_lines = otel_log_lines(&payload),
+                Err(e) => warnings.push(format!("Logs not collected: {}", e)),
+            }
+            log_lines.truncate(params.max_log_lines.unwrap_or(50) as usize);
+        }
+
+        let title = params.title.unwrap_or_else(|| {
+            format!(
+                "Incident summary {} - {}",
+                locale.format_timestamp(&params.from),
+                locale.format_timestamp(&params.to)
+            )
+        });
+        let content =
+            incident_document_html(&params.from, &params.to, &events, &log_lines, &locale);
+
+        let document = if params.preview.unwrap_or(false) {
+            None
+        } else {
+            self.check_project_access(Some(&params.project_id)).await?;
+            let request = CreateDocumentRequest {
+                title: title.clone(),
+                content: Some(content.clone()),
+                project_id: Some(params.project_id.clone()),
+                type_code: None,
+                status_code: None,
+                priority_code: None,
+            };
+            Some(
+                self.clients
+                    .documents
+                    .create_document(&request)
+                    .await
+                    .map_err(to_mcp_error)?,
+            )
+        };
+
+        let json = json!({
+            "title": title,
+            "event_count": events.len(),
+            "events_truncated": events_truncated,
+            "log_line_count": log_lines.len(),
+            "preview": document.is_none(),
+            "content": content,
+            "document": document,
+            "warnings": warnings,
+        });
+        self.debug
+            .log_tool_result("generate_incident_document", &json);
+
+        to_json_result(&json)
+    }
+
+    #[tool(
+        description = "Check services for SLA breaches in a period: reads the ServiceLevels analytics provider and reports services whose average service level is below target, with average, minimum and sample counts. Optionally counts error log lines per service as supporting evidence."
+    )]
+    async fn check_sla_breaches(
+        &self,
+        Parameters(params): Parameters<CheckSlaBreachesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug
+            .log_tool_call("check_sla_breaches", &json!(params));
+
+        let from = match &params.from {
+            Some(from) => parse_timestamp("from", from)?,
+            None => chrono::DateTime::<chrono::Utc>::UNIX_EPOCH,
+        };
+        let to = match &params.to {
+            Some(to) => parse_timestamp("to", to)?,
+            None => chrono::Utc::now(),
+        };
+        let target = params.target.unwrap_or(99.5);
+
+        let mut query = ODataQuery::new();
+        if let Some(filter) = &params.filter {
+            query = query.filter(filter);
+        }
+        let result = odata::read_all(query, |q| {
+            self.clients.analytics.get_service_levels(Some(q))
+        })
+        .await
+        .map_err(to_mcp_error)?;
+        let records = if params.from.is_some() || params.to.is_some() {
+            records_in_window(&result.items, from, to)
+        } else {
+            result.items
+        };
+
+        let mut warnings = Vec::new();
+        let mut error_logs = Default::default();
+        if let Some(provider) = &params.log_provider {
+            let request = GetLogsParams {
+                provider: provider.clone(),
+                format: None,
+                version: None,
+                period: None,
+                from: Some(from.to_rfc3339()),
+                to: Some(to.to_rfc3339()),
+                limit: Some(1000),
+                offset: None,
+                service_id: None,
+                observed_timestamp: None,
+                on_limit: None,
+            };
+            match self.clients.logs.get_logs(&request).await {
+                Ok(payload) => error_logs = error_log_counts(&otel_log_lines(&payload)),
+                Err(e) => warnings.push(format!("Logs not collected: {}", e)),
+            }
+        }
+
+        let breaches = sla_breaches(&records, target, &error_logs);
+        let json = json!({
+            "from": params.from,
+            "to": params.to,
+            "default_target": target,
+            "records_evaluated": records.len(),
+            "truncated": result.truncated,
+            "breach_count": breaches.len(),
+            "breaches": breaches,
+            "warnings": warnings,
+        });
+        self.debug.log_tool_result("check_sla_breaches", &json);
+
+        to_json_result(&json)
+    }
+}
+
 // ============================================================================
 // Server Handler Implementation
 // ============================================================================
 
-#[tool_handler]
 impl ServerHandler for SapCloudAlmServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.dispatch(request, context, CallOrigin::Client).await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let mut tools = self.tool_router.list_all();
+        if self.read_only {
+            tools.retain(|t| !self.write_tools.contains(t.name.as_ref()));
+        }
+        for tool in tools.iter_mut().filter(|t| verify::is_verifiable(&t.name)) {
+            let schema = Arc::make_mut(&mut tool.input_schema);
+            if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+                properties.insert(
+                    verify::VERIFY_ARGUMENT.to_string(),
+                    json!({
+                        "type": "boolean",
+                        "description": "Read the entity back after the write and report requested values the API did not persist"
+                    }),
+                );
+            }
+        }
+        for tool in tools.iter_mut() {
+            let paged = self
+                .paged_tools
+                .get(tool.name.as_ref())
+                .is_some_and(|p| *p != Paging::Unpaged);
+            if !paged {
+                continue;
+            }
+            let schema = Arc::make_mut(&mut tool.input_schema);
+            if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+                properties.insert(
+                    pagination::CURSOR_ARGUMENT.to_string(),
+                    json!({
+                        "type": "string",
+                        "description": "next_cursor of the previous page; fetches the next page with the same arguments"
+                    }),
+                );
+            }
+        }
+        if let Some(guard) = &self.duplicates {
+            for tool in tools.iter_mut().filter(|t| guard.is_write_tool(&t.name)) {
+                let schema = Arc::make_mut(&mut tool.input_schema);
+                if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+                    properties.insert(
+                        dedup::FORCE_ARGUMENT.to_string(),
+                        json!({
+                            "type": "boolean",
+                            "description": format!(
+                                "Repeat a call identical to one that succeeded in the last {} s",
+                                guard.window().as_secs()
+                            )
+                        }),
+                    );
+                }
+            }
+        }
+        Ok(ListToolsResult {
+            tools,
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let Some(inbox) = &self.webhooks else {
+            return Ok(ListResourcesResult::default());
+        };
+
+        let mut events = RawResource::new(EVENTS_URI, "webhook-events");
+        events.description = Some("Webhook events received from SAP Cloud ALM".to_string());
+        events.mime_type = Some("application/json".to_string());
+        let mut resources = vec![events.no_annotation()];
+        for event in inbox.events().iter().rev() {
+            let mut resource = RawResource::new(event.uri(), format!("webhook-event-{}", event.id));
+            resource.description = Some(format!("Webhook event received at {}", event.received_at));
+            resource.mime_type = Some("application/json".to_string());
+            resources.push(resource.no_annotation());
+        }
+
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let inbox = self.webhook_inbox()?;
+        let value = if request.uri == EVENTS_URI {
+            serde_json::to_value(inbox.events()).map_err(to_mcp_error)?
+        } else {
+            let event = request
+                .uri
+                .strip_prefix(EVENTS_URI)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .and_then(|id| id.parse().ok())
+                .and_then(|id| inbox.get(id))
+                .ok_or_else(|| McpError {
+                    code: ErrorCode::RESOURCE_NOT_FOUND,
+                    message: Cow::from(format!("Resource not found: {}", request.uri)),
+                    data: None,
+                })?;
+            serde_json::to_value(event).map_err(to_mcp_error)?
+        };
+        let text = serde_json::to_string_pretty(&value).map_err(to_mcp_error)?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(text, request.uri)],
+        })
+    }
+
+    // Every connected client is notified about new events, so subscriptions
+    // only need to be accepted.
+    async fn subscribe(
+        &self,
+        _request: SubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.webhook_inbox().map(|_| ())
+    }
+
+    async fn unsubscribe(
+        &self,
+        _request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.webhook_inbox().map(|_| ())
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        if let Some(inbox) = &self.webhooks {
+            inbox.register_peer(context.peer);
+        }
+    }
+
     fn get_info(&self) -> ServerInfo {
+        let capabilities = if self.webhooks.is_some() {
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .enable_resources_list_changed()
+                .build()
+        } else {
+            ServerCapabilities::builder().enable_tools().build()
+        };
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities,
             server_info: Implementation::from_build_env(),
-            instructions: Some(
-                "SAP Cloud ALM MCP Server - Access SAP Cloud ALM APIs for Features, Documents, \
-                Tasks, Projects, Test Management, Process Hierarchy, Analytics, Process Monitoring, \
-                and Logs.".to_string()
-            ),
+            instructions: Some(self.instructions.clone()),
         }
     }
 }
@@ -0,0 +1,196 @@
+//! Reusable project templates.
+//!
+//! `extract_project_template` reads the structure of an existing project
+//! (workstreams, deliverables, recurring task patterns and the document types
+//! in use) into a versioned JSON file from which new projects can be set up
+//! the same way. Tasks recur when their titles only differ in numbers: digits
+//! are replaced with `#`, so "Sprint 3 review" and "Sprint 14 review" form the
+//! pattern "Sprint # review".
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the template file format.
+pub const TEMPLATE_VERSION: u32 = 1;
+
+/// Project template file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectTemplate {
+    pub version: u32,
+    pub name: String,
+    pub description: Option<String>,
+    /// Project the template was extracted from
+    pub source_project_id: String,
+    pub workstreams: Vec<TemplateEntry>,
+    pub deliverables: Vec<TemplateEntry>,
+    pub task_patterns: Vec<TaskPattern>,
+    pub document_types: Vec<DocumentTypeUsage>,
+}
+
+/// Named structure element (workstream or deliverable).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TemplateEntry {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Task title occurring repeatedly in the project.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TaskPattern {
+    /// Title with numbers replaced by `#`
+    pub title: String,
+    pub task_type: Option<String>,
+    pub occurrences: usize,
+}
+
+/// Document type used in the project.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DocumentTypeUsage {
+    pub type_code: String,
+    pub name: Option<String>,
+    pub count: usize,
+}
+
+/// Structure entries with a name, without duplicates, in the given order.
+pub fn entries<I>(items: I) -> Vec<TemplateEntry>
+where
+    I: IntoIterator<Item = (Option<String>, Option<String>)>,
+{
+    let mut entries: Vec<TemplateEntry> = Vec::new();
+    for (name, description) in items {
+        let Some(name) = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) else {
+            continue;
+        };
+        if !entries.iter().any(|e| e.name == name) {
+            entries.push(TemplateEntry { name, description });
+        }
+    }
+    entries
+}
+
+/// Replace each run of digits with `#` and collapse whitespace.
+fn title_pattern(title: &str) -> String {
+    let mut pattern = String::new();
+    for word in title.split_whitespace() {
+        if !pattern.is_empty() {
+            pattern.push(' ');
+        }
+        let mut in_digits = false;
+        for c in word.chars() {
+            if c.is_ascii_digit() {
+                if !in_digits {
+                    pattern.push('#');
+                }
+                in_digits = true;
+            } else {
+                pattern.push(c);
+                in_digits = false;
+            }
+        }
+    }
+    pattern
+}
+
+/// Task patterns occurring at least `min_occurrences` times, most frequent first.
+pub fn recurring_task_patterns<'a, I>(tasks: I, min_occurrences: usize) -> Vec<TaskPattern>
+where
+    I: IntoIterator<Item = (&'a str, Option<&'a str>)>,
+{
+    let mut counts: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for (title, task_type) in tasks {
+        let pattern = title_pattern(title);
+        if pattern.is_empty() {
+            continue;
+        }
+        *counts
+            .entry((pattern, task_type.map(String::from)))
+            .or_default() += 1;
+    }
+    let mut patterns: Vec<TaskPattern> = counts
+        .into_iter()
+        .filter(|(_, occurrences)| *occurrences >= min_occurrences.max(1))
+        .map(|((title, task_type), occurrences)| TaskPattern {
+            title,
+            task_type,
+            occurrences,
+        })
+        .collect();
+    patterns.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then_with(|| a.title.cmp(&b.title))
+            .then_with(|| a.task_type.cmp(&b.task_type))
+    });
+    patterns
+}
+
+/// Count document type codes, most used first, with their names where known.
+pub fn document_type_usage<'a, I>(
+    codes: I,
+    names: &HashMap<String, String>,
+) -> Vec<DocumentTypeUsage>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for code in codes {
+        *counts.entry(code).or_default() += 1;
+    }
+    let mut usage: Vec<DocumentTypeUsage> = counts
+        .into_iter()
+        .map(|(code, count)| DocumentTypeUsage {
+            type_code: code.to_string(),
+            name: names.get(code).cloned(),
+            count,
+        })
+        .collect();
+    usage.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.type_code.cmp(&b.type_code))
+    });
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recurring_task_patterns() {
+        let tasks = [
+            ("Sprint 3 review", Some("CALMTASK")),
+            ("Sprint 14  review", Some("CALMTASK")),
+            ("Sprint 15 review", Some("CALMTASK")),
+            ("Weekly status 2024-05-06", None),
+            ("Weekly status 2024-05-13", None),
+            ("Set up landscape", Some("CALMTASK")),
+        ];
+        let patterns = recurring_task_patterns(tasks, 2);
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].title, "Sprint # review");
+        assert_eq!(patterns[0].occurrences, 3);
+        assert_eq!(patterns[1].title, "Weekly status #-#-#");
+        assert_eq!(patterns[1].task_type, None);
+    }
+
+    #[test]
+    fn test_entries_and_document_types() {
+        let workstreams = entries([
+            (Some("Finance".to_string()), None),
+            (Some(" Finance ".to_string()), Some("dup".to_string())),
+            (None, Some("unnamed".to_string())),
+            (Some("Logistics".to_string()), Some("LO".to_string())),
+        ]);
+        assert_eq!(workstreams.len(), 2);
+        assert_eq!(workstreams[1].description.as_deref(), Some("LO"));
+
+        let names = HashMap::from([("SPEC".to_string(), "Specification".to_string())]);
+        let usage = document_type_usage(["SPEC", "TEST", "SPEC"], &names);
+        assert_eq!(usage[0].type_code, "SPEC");
+        assert_eq!(usage[0].count, 2);
+        assert_eq!(usage[0].name.as_deref(), Some("Specification"));
+        assert_eq!(usage[1].name, None);
+    }
+}
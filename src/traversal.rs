@@ -0,0 +1,178 @@
+//! Bounded traversal of parent/child structures.
+//!
+//! Composite tools that follow child links (hierarchy subtrees, snapshots)
+//! walk breadth-first with a [`Traversal`]. It stops expanding at the
+//! configured depth, stops discovering at the configured number of nodes and
+//! skips nodes it has already visited, so a corrupted or concurrently edited
+//! hierarchy that links back to an ancestor cannot loop forever. Children are
+//! expanded in the order they are discovered; callers sort siblings first so
+//! results are deterministic. The [`TraversalReport`] tells why a walk is
+//! incomplete.
+
+use std::collections::{HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+/// Traversal limits as configured.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TraversalConfig {
+    /// Levels below the start node that are expanded (default: 20)
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// Maximum number of nodes, including the start node (default: 5000)
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
+}
+
+impl Default for TraversalConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: default_max_depth(),
+            max_nodes: default_max_nodes(),
+        }
+    }
+}
+
+fn default_max_depth() -> usize {
+    20
+}
+
+fn default_max_nodes() -> usize {
+    5000
+}
+
+/// Outcome of a traversal.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraversalReport {
+    /// Number of nodes visited, including the start node
+    pub nodes: usize,
+    /// Nodes at the depth limit were not expanded
+    pub depth_limit_reached: bool,
+    /// Further nodes were not visited because of the node limit
+    pub node_limit_reached: bool,
+    /// Nodes reached again through another parent (not visited twice)
+    pub cycles: Vec<String>,
+}
+
+impl TraversalReport {
+    /// True if the traversal may have missed nodes or found cycles.
+    pub fn is_incomplete(&self) -> bool {
+        self.depth_limit_reached || self.node_limit_reached || !self.cycles.is_empty()
+    }
+}
+
+/// Breadth-first traversal state.
+pub struct Traversal {
+    max_depth: usize,
+    max_nodes: usize,
+    visited: HashSet<String>,
+    queue: VecDeque<(String, usize)>,
+    report: TraversalReport,
+}
+
+impl Traversal {
+    /// Start a traversal at the given node (depth 0).
+    pub fn new(root: &str, config: &TraversalConfig) -> Self {
+        Self {
+            max_depth: config.max_depth,
+            max_nodes: config.max_nodes,
+            visited: HashSet::from([root.to_string()]),
+            queue: VecDeque::from([(root.to_string(), 0)]),
+            report: TraversalReport {
+                nodes: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Next node whose children should be fetched, with its depth.
+    pub fn next_node(&mut self) -> Option<(String, usize)> {
+        while let Some((key, depth)) = self.queue.pop_front() {
+            if self.report.node_limit_reached {
+                return None;
+            }
+            if depth >= self.max_depth {
+                self.report.depth_limit_reached = true;
+                continue;
+            }
+            return Some((key, depth));
+        }
+        None
+    }
+
+    /// Register a child of a node at `parent_depth`. Returns false if the
+    /// child was already visited or the node limit is reached; the child must
+    /// then be skipped.
+    pub fn discover(&mut self, key: &str, parent_depth: usize) -> bool {
+        if self.visited.contains(key) {
+            self.report.cycles.push(key.to_string());
+            return false;
+        }
+        if self.report.nodes >= self.max_nodes {
+            self.report.node_limit_reached = true;
+            return false;
+        }
+        self.visited.insert(key.to_string());
+        self.queue.push_back((key.to_string(), parent_depth + 1));
+        self.report.nodes += 1;
+        true
+    }
+
+    /// Finish the traversal.
+    pub fn into_report(self) -> TraversalReport {
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walk a child list, returning the visited keys in order.
+    fn walk(edges: &[(&str, &str)], config: &TraversalConfig) -> (Vec<String>, TraversalReport) {
+        let mut traversal = Traversal::new("root", config);
+        let mut visited = vec!["root".to_string()];
+        while let Some((parent, depth)) = traversal.next_node() {
+            for (_, child) in edges.iter().filter(|(p, _)| *p == parent) {
+                if traversal.discover(child, depth) {
+                    visited.push(child.to_string());
+                }
+            }
+        }
+        (visited, traversal.into_report())
+    }
+
+    #[test]
+    fn test_cycles_are_visited_once() {
+        let edges = [("root", "a"), ("root", "b"), ("a", "c"), ("c", "root")];
+        let (visited, report) = walk(&edges, &TraversalConfig::default());
+        assert_eq!(visited, vec!["root", "a", "b", "c"]);
+        assert_eq!(report.nodes, 4);
+        assert_eq!(report.cycles, vec!["root"]);
+        assert!(report.is_incomplete());
+    }
+
+    #[test]
+    fn test_depth_and_node_limits() {
+        let edges = [("root", "a"), ("root", "b"), ("a", "c"), ("c", "d")];
+        let config = TraversalConfig {
+            max_depth: 2,
+            max_nodes: 100,
+        };
+        let (visited, report) = walk(&edges, &config);
+        assert_eq!(visited, vec!["root", "a", "b", "c"]);
+        assert!(report.depth_limit_reached);
+        assert!(!report.node_limit_reached);
+
+        let config = TraversalConfig {
+            max_depth: 20,
+            max_nodes: 2,
+        };
+        let (visited, report) = walk(&edges, &config);
+        assert_eq!(visited, vec!["root", "a"]);
+        assert!(report.node_limit_reached);
+
+        let (_, report) = walk(&edges[..2], &TraversalConfig::default());
+        assert!(!report.is_incomplete());
+    }
+}
@@ -0,0 +1,173 @@
+//! Read-back verification of create and update calls.
+//!
+//! The SAP Cloud ALM services frequently drop fields they do not support
+//! without an error. With `verify: true`, a create or update call is followed
+//! by a read of the written entity, and every requested value is compared
+//! with the persisted one. Argument names are matched to entity fields
+//! ignoring case and underscores (`status_code` matches `statusCode`);
+//! numbers and numeric strings compare equal, strings are compared trimmed.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::diff::{field_differences, FieldDifference};
+
+/// Argument requesting a read-back.
+pub const VERIFY_ARGUMENT: &str = "verify";
+
+/// Write tools with a read-back: (write tool, get tool, ID argument of the get tool).
+const READ_BACKS: &[(&str, &str, &str)] = &[
+    ("create_feature", "get_feature", "uuid"),
+    ("update_feature", "get_feature", "uuid"),
+    ("create_document", "get_document", "uuid"),
+    ("update_document", "get_document", "uuid"),
+    ("create_task", "get_task", "uuid"),
+    ("update_task", "get_task", "uuid"),
+    ("create_testcase", "get_testcase", "uuid"),
+    ("update_testcase", "get_testcase", "uuid"),
+    ("create_hierarchy_node", "get_hierarchy_node", "uuid"),
+    ("update_hierarchy_node", "get_hierarchy_node", "uuid"),
+    ("create_project", "get_project", "id"),
+];
+
+/// Arguments that select the entity or control the call instead of
+/// carrying a value to write.
+const CONTROL_ARGUMENTS: &[&str] = &["uuid", "id", "preview", "force", "profile"];
+
+/// Keys identifying a written entity in arguments or results.
+const ID_KEYS: &[&str] = &["uuid", "id"];
+
+/// Read-back of a write tool: the get tool and its ID argument.
+pub fn read_back(tool: &str) -> Option<(&'static str, &'static str)> {
+    READ_BACKS
+        .iter()
+        .find(|(write, _, _)| *write == tool)
+        .map(|(_, get, id)| (*get, *id))
+}
+
+/// Check if a write tool supports `verify`.
+pub fn is_verifiable(tool: &str) -> bool {
+    read_back(tool).is_some()
+}
+
+/// Remove the `verify` argument, returning whether it was set.
+pub fn take_verify(arguments: Option<&mut Map<String, Value>>) -> bool {
+    arguments
+        .and_then(|args| args.remove(VERIFY_ARGUMENT))
+        .is_some_and(|v| v.as_bool() == Some(true))
+}
+
+/// ID of the written entity: from the arguments of an update, otherwise
+/// from the returned entity.
+pub fn entity_id(arguments: &Map<String, Value>, result: &Value) -> Option<String> {
+    ID_KEYS
+        .iter()
+        .find_map(|k| arguments.get(*k))
+        .or_else(|| ID_KEYS.iter().find_map(|k| result.get(*k)))
+        .and_then(Value::as_str)
+        .map(String::from)
+}
+
+/// Outcome of a read-back.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    /// True if every checked value was persisted as requested
+    pub verified: bool,
+    /// Fields whose persisted value differs from the requested one
+    /// (`left` is the requested, `right` the persisted value)
+    pub mismatches: Vec<FieldDifference>,
+    /// Requested fields not present on the read entity
+    pub unchecked: Vec<String>,
+}
+
+/// Fold a key for case- and underscore-insensitive comparison.
+fn fold(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Normalize a value for comparison.
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.trim().to_string()),
+        Value::Number(n) => Value::String(n.to_string()),
+        other => other.clone(),
+    }
+}
+
+/// Compare requested argument values with the persisted entity.
+pub fn compare(arguments: &Map<String, Value>, persisted: &Value) -> Verification {
+    let fields = persisted.as_object();
+    let mut requested = Map::new();
+    let mut actual = Map::new();
+    let mut unchecked = Vec::new();
+
+    for (name, value) in arguments {
+        if value.is_null() || CONTROL_ARGUMENTS.contains(&name.as_str()) {
+            continue;
+        }
+        let folded = fold(name);
+        match fields.and_then(|f| f.iter().find(|(key, _)| fold(key) == folded)) {
+            Some((key, persisted)) => {
+                requested.insert(key.clone(), normalize(value));
+                actual.insert(key.clone(), normalize(persisted));
+            }
+            None => unchecked.push(name.clone()),
+        }
+    }
+
+    let mismatches = field_differences(&Value::Object(requested), &Value::Object(actual), &[]);
+    Verification {
+        verified: mismatches.is_empty(),
+        mismatches,
+        unchecked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_compare_flags_dropped_fields() {
+        let arguments = json!({
+            "uuid": "f1",
+            "title": "Invoice check ",
+            "status_code": "CIPDONE",
+            "priority_code": 10,
+            "release_id": "r1",
+            "description": null
+        });
+        let persisted = json!({
+            "uuid": "f1",
+            "title": "Invoice check",
+            "statusCode": "CIPINPROGRESS",
+            "priorityCode": "10",
+        });
+        let verification = compare(arguments.as_object().unwrap(), &persisted);
+        assert!(!verification.verified);
+        assert_eq!(verification.mismatches.len(), 1);
+        assert_eq!(verification.mismatches[0].field, "statusCode");
+        assert_eq!(verification.mismatches[0].left, json!("CIPDONE"));
+        assert_eq!(verification.unchecked, vec!["release_id"]);
+    }
+
+    #[test]
+    fn test_entity_id_and_verify_argument() {
+        let mut arguments = json!({"title": "A", "verify": true})
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(take_verify(Some(&mut arguments)));
+        assert!(!arguments.contains_key(VERIFY_ARGUMENT));
+        assert_eq!(
+            entity_id(&arguments, &json!({"id": "t1"})).as_deref(),
+            Some("t1")
+        );
+        assert_eq!(read_back("update_task"), Some(("get_task", "uuid")));
+        assert!(!is_verifiable("delete_task"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Watchlist of entities for long-running advisory sessions.
+//!
+//! Features, tasks and quality gates added to the watchlist are checked with
+//! `check_watchlist`, which reports changes of status and assignment since
+//! the entity was added or last checked. The watchlist lives in server
+//! memory and is shared by all sessions of the server process.
+
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Type of a watched entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchedType {
+    Feature,
+    Task,
+    QualityGate,
+}
+
+impl FromStr for WatchedType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', ' '], "_").as_str() {
+            "feature" | "features" => Ok(Self::Feature),
+            "task" | "tasks" => Ok(Self::Task),
+            "quality_gate" | "quality_gates" => Ok(Self::QualityGate),
+            other => Err(format!(
+                "Unknown entity type '{}'. Expected feature, task or quality_gate",
+                other
+            )),
+        }
+    }
+}
+
+/// Watched fields of an entity.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WatchedState {
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub assignee: Option<String>,
+}
+
+/// A change of a watched field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateChange {
+    pub field: &'static str,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Changes of status and assignment between two states.
+pub fn state_changes(old: &WatchedState, new: &WatchedState) -> Vec<StateChange> {
+    [
+        ("status", &old.status, &new.status),
+        ("assignee", &old.assignee, &new.assignee),
+    ]
+    .into_iter()
+    .filter(|(_, from, to)| from != to)
+    .map(|(field, from, to)| StateChange {
+        field,
+        from: from.clone(),
+        to: to.clone(),
+    })
+    .collect()
+}
+
+/// A watched entity with the state seen at the last check.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchEntry {
+    pub entity_type: WatchedType,
+    pub id: String,
+    pub state: Option<WatchedState>,
+}
+
+/// Watched entities in the order they were added.
+#[derive(Default)]
+pub struct Watchlist {
+    entries: Mutex<Vec<WatchEntry>>,
+}
+
+impl Watchlist {
+    /// Add an entity with its current state. Returns false if it is already
+    /// watched; the state is then left unchanged.
+    pub fn add(&self, entity_type: WatchedType, id: &str, state: Option<WatchedState>) -> bool {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries
+            .iter()
+            .any(|e| e.entity_type == entity_type && e.id == id)
+        {
+            return false;
+        }
+        entries.push(WatchEntry {
+            entity_type,
+            id: id.to_string(),
+            state,
+        });
+        true
+    }
+
+    /// Remove an entity. Returns false if it was not watched.
+    pub fn remove(&self, entity_type: WatchedType, id: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let before = entries.len();
+        entries.retain(|e| !(e.entity_type == entity_type && e.id == id));
+        entries.len() < before
+    }
+
+    /// Remove all entities, returning how many were watched.
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *entries).len()
+    }
+
+    /// Snapshot of the watched entities.
+    pub fn entries(&self) -> Vec<WatchEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Store the current state of an entity and return the changes since the
+    /// previous state. None if there was no previous state (or the entity is
+    /// no longer watched).
+    pub fn update(
+        &self,
+        entity_type: WatchedType,
+        id: &str,
+        state: WatchedState,
+    ) -> Option<Vec<StateChange>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.entity_type == entity_type && e.id == id)?;
+        let changes = entry.state.as_ref().map(|old| state_changes(old, &state));
+        entry.state = Some(state);
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(status: &str, assignee: Option<&str>) -> WatchedState {
+        WatchedState {
+            title: Some("Invoice check".to_string()),
+            status: Some(status.to_string()),
+            assignee: assignee.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_watchlist_reports_changes_since_last_check() {
+        let watchlist = Watchlist::default();
+        assert!(watchlist.add(WatchedType::Task, "t1", Some(state("OPEN", None))));
+        assert!(!watchlist.add(WatchedType::Task, "t1", None));
+        assert!(watchlist.add(WatchedType::Feature, "t1", None));
+
+        let changes = watchlist
+            .update(WatchedType::Task, "t1", state("IN_PROGRESS", Some("Ana")))
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "status");
+        assert_eq!(changes[0].from.as_deref(), Some("OPEN"));
+        assert_eq!(changes[1].to.as_deref(), Some("Ana"));
+
+        let unchanged =
+            watchlist.update(WatchedType::Task, "t1", state("IN_PROGRESS", Some("Ana")));
+        assert_eq!(unchanged, Some(Vec::new()));
+        assert_eq!(
+            watchlist.update(WatchedType::Feature, "t1", state("10", None)),
+            None
+        );
+
+        assert!(watchlist.remove(WatchedType::Task, "t1"));
+        assert!(!watchlist.remove(WatchedType::Task, "t1"));
+        assert_eq!(watchlist.clear(), 1);
+    }
+
+    #[test]
+    fn test_parse_watched_type() {
+        assert_eq!("Quality-Gate".parse(), Ok(WatchedType::QualityGate));
+        assert_eq!("tasks".parse(), Ok(WatchedType::Task));
+        assert!("document".parse::<WatchedType>().is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! Inbound webhook relay for the HTTP transport.
+//!
+//! SAP Cloud ALM (or a middleware) posts event callbacks to a configured path.
+//! Callbacks carrying the shared secret are kept in a bounded in-memory inbox,
+//! exposed as MCP resources, and announced to connected clients with resource
+//! notifications so agents can react to pushed events instead of polling.
+//! Without the `http-transport` feature no events can be received.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "http-transport")]
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+#[cfg(feature = "http-transport")]
+use rmcp::model::ResourceUpdatedNotificationParam;
+use rmcp::{Peer, RoleServer};
+use serde::Serialize;
+#[cfg(feature = "http-transport")]
+use serde_json::json;
+use serde_json::Value;
+
+/// Resource URI listing all received events.
+pub const EVENTS_URI: &str = "calm://webhooks/events";
+
+#[cfg(feature = "http-transport")]
+/// Header carrying the shared secret (alternatively `Authorization: Bearer <secret>`).
+const SECRET_HEADER: &str = "x-webhook-secret";
+
+/// A received webhook callback.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub id: u64,
+    pub received_at: String,
+    pub payload: Value,
+}
+
+impl WebhookEvent {
+    /// Resource URI of this event.
+    pub fn uri(&self) -> String {
+        format!("{}/{}", EVENTS_URI, self.id)
+    }
+}
+
+#[derive(Default)]
+struct InboxState {
+    #[cfg(feature = "http-transport")]
+    next_id: u64,
+    events: VecDeque<WebhookEvent>,
+    peers: Vec<Peer<RoleServer>>,
+}
+
+/// Bounded inbox of received webhook events, shared by all MCP sessions.
+#[derive(Clone)]
+pub struct WebhookInbox {
+    state: Arc<Mutex<InboxState>>,
+    #[cfg(feature = "http-transport")]
+    capacity: usize,
+}
+
+impl WebhookInbox {
+    /// Create an inbox keeping the most recent `capacity` events.
+    #[cfg(feature = "http-transport")]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(InboxState {
+                next_id: 1,
+                ..Default::default()
+            })),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Store an event, dropping the oldest one when the inbox is full.
+    #[cfg(feature = "http-transport")]
+    pub fn push(&self, payload: Value) -> WebhookEvent {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let event = WebhookEvent {
+            id: state.next_id,
+            received_at: chrono::Utc::now().to_rfc3339(),
+            payload,
+        };
+        state.next_id += 1;
+        state.events.push_back(event.clone());
+        while state.events.len() > self.capacity {
+            state.events.pop_front();
+        }
+        event
+    }
+
+    /// All stored events, oldest first.
+    pub fn events(&self) -> Vec<WebhookEvent> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.events.iter().cloned().collect()
+    }
+
+    /// A stored event by ID.
+    pub fn get(&self, id: u64) -> Option<WebhookEvent> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.events.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// Register a connected client to be notified about new events.
+    pub fn register_peer(&self, peer: Peer<RoleServer>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.peers.retain(|p| !p.is_transport_closed());
+        state.peers.push(peer);
+    }
+
+    /// Notify connected clients that the event list changed.
+    #[cfg(feature = "http-transport")]
+    async fn notify(&self, event: &WebhookEvent) {
+        let peers: Vec<Peer<RoleServer>> = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.peers.retain(|p| !p.is_transport_closed());
+            state.peers.clone()
+        };
+        for peer in peers {
+            let _ = peer.notify_resource_list_changed().await;
+            for uri in [EVENTS_URI.to_string(), event.uri()] {
+                let _ = peer
+                    .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+                    .await;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "http-transport")]
+/// Check the shared secret in the request headers.
+fn authorized(headers: &HeaderMap, secret: &str) -> bool {
+    let provided = headers
+        .get(SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        });
+    provided.is_some_and(|p| constant_time_eq(p.as_bytes(), secret.as_bytes()))
+}
+
+#[cfg(feature = "http-transport")]
+/// Compare secrets without short-circuiting on the first mismatch.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(feature = "http-transport")]
+#[derive(Clone)]
+struct WebhookState {
+    inbox: WebhookInbox,
+    secret: Arc<str>,
+}
+
+#[cfg(feature = "http-transport")]
+/// Build the webhook receiver route at `path`.
+pub fn routes(path: &str, secret: &str, inbox: WebhookInbox) -> Router {
+    Router::new()
+        .route(path, post(receive))
+        .with_state(WebhookState {
+            inbox,
+            secret: Arc::from(secret),
+        })
+}
+
+#[cfg(feature = "http-transport")]
+async fn receive(
+    State(state): State<WebhookState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers, &state.secret) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "invalid or missing webhook secret"})),
+        );
+    }
+    let payload = match serde_json::from_slice::<Value>(&body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": format!("invalid JSON body: {}", e)})),
+            )
+        }
+    };
+
+    let event = state.inbox.push(payload);
+    tracing::info!(id = event.id, "Webhook event received");
+    let inbox = state.inbox.clone();
+    let notified = event.clone();
+    tokio::spawn(async move { inbox.notify(&notified).await });
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({"id": event.id, "uri": event.uri()})),
+    )
+}
+
+#[cfg(all(test, feature = "http-transport"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbox_keeps_most_recent_events() {
+        let inbox = WebhookInbox::new(2);
+        for i in 0..3 {
+            inbox.push(json!({"n": i}));
+        }
+        let ids: Vec<u64> = inbox.events().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(inbox.get(1).is_none());
+        assert_eq!(inbox.get(3).unwrap().payload["n"], 2);
+        assert_eq!(inbox.get(3).unwrap().uri(), "calm://webhooks/events/3");
+    }
+
+    #[test]
+    fn test_authorized_accepts_header_or_bearer() {
+        let mut headers = HeaderMap::new();
+        assert!(!authorized(&headers, "s3cret"));
+
+        headers.insert(SECRET_HEADER, "s3cret".parse().unwrap());
+        assert!(authorized(&headers, "s3cret"));
+        assert!(!authorized(&headers, "other"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert!(authorized(&headers, "s3cret"));
+    }
+}
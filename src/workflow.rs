@@ -0,0 +1,536 @@
+//! Declarative workflows of tool calls.
+//!
+//! `run_workflow` executes a JSON or YAML definition of steps, each calling
+//! one tool, so that recurring multi-step operations (create a feature,
+//! create its tasks, link references, set the status) can be reviewed once
+//! and rerun instead of being improvised by the agent. Step arguments may
+//! reference the workflow variables as `${name}`, results of earlier steps as
+//! `${steps.<id>.<field>}` and, in steps repeated with `for_each`, the
+//! current element as `${item}`. A string consisting of a single reference
+//! takes the referenced value with its JSON type.
+//!
+//! A dry run validates the definition and returns the planned calls with the
+//! variables interpolated; references to step results stay as written. A run
+//! stops at the first failed step unless the step sets `continue_on_error`.
+
+use std::ops::Range;
+
+use rmcp::model::{CallToolResult, RawContent};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// The workflow tool itself, which cannot be a step.
+pub const WORKFLOW_TOOL: &str = "run_workflow";
+
+/// Reference roots that cannot be used as variable names.
+const RESERVED_NAMES: &[&str] = &["steps", "item"];
+
+/// A workflow definition.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workflow {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Default values of the variables
+    #[serde(default)]
+    pub variables: Map<String, Value>,
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// A step calling one tool.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowStep {
+    pub id: String,
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    /// Array, or reference to an array: the tool is called once per element
+    #[serde(default)]
+    pub for_each: Option<Value>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+/// Parse a workflow definition. Definitions starting with `{` are JSON,
+/// others YAML.
+pub fn parse(text: &str) -> Result<Workflow, String> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') {
+        serde_json::from_str(trimmed).map_err(|e| format!("Invalid workflow JSON: {}", e))
+    } else {
+        serde_yaml::from_str(text).map_err(|e| format!("Invalid workflow YAML: {}", e))
+    }
+}
+
+/// Variables of a run: the defaults of the definition, overridden by the
+/// variables passed to the run.
+pub fn variables(workflow: &Workflow, overrides: Option<Map<String, Value>>) -> Map<String, Value> {
+    let mut variables = workflow.variables.clone();
+    variables.extend(overrides.unwrap_or_default());
+    variables
+}
+
+/// References `${...}` in a string: (byte range, path).
+fn references(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut references = Vec::new();
+    let mut rest = 0;
+    while let Some(found) = text[rest..].find("${") {
+        let start = rest + found;
+        let Some(length) = text[start + 2..].find('}') else {
+            break;
+        };
+        let end = start + 2 + length + 1;
+        references.push((start..end, text[start + 2..end - 1].trim()));
+        rest = end;
+    }
+    references
+}
+
+/// Paths of all references in a value.
+fn reference_paths<'a>(value: &'a Value, paths: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => paths.extend(references(text).into_iter().map(|(_, p)| p)),
+        Value::Array(items) => items.iter().for_each(|v| reference_paths(v, paths)),
+        Value::Object(map) => map.values().for_each(|v| reference_paths(v, paths)),
+        _ => {}
+    }
+}
+
+/// Check a definition before running it. Returns the problems found.
+pub fn validate(
+    workflow: &Workflow,
+    variables: &Map<String, Value>,
+    is_tool: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if workflow.steps.is_empty() {
+        problems.push("The workflow has no steps".to_string());
+    }
+    for name in RESERVED_NAMES {
+        if variables.contains_key(*name) {
+            problems.push(format!("'{}' is reserved and cannot be a variable", name));
+        }
+    }
+
+    let mut earlier: Vec<&str> = Vec::new();
+    for (index, step) in workflow.steps.iter().enumerate() {
+        let label = format!("Step {} ({})", index + 1, step.id);
+        if step.id.is_empty() || step.id.contains('.') {
+            problems.push(format!(
+                "{}: the id must be non-empty and without '.'",
+                label
+            ));
+        } else if earlier.contains(&step.id.as_str()) {
+            problems.push(format!("{}: the id is used by an earlier step", label));
+        }
+        if step.tool == WORKFLOW_TOOL {
+            problems.push(format!(
+                "{}: workflows cannot call {}",
+                label, WORKFLOW_TOOL
+            ));
+        } else if !is_tool(&step.tool) {
+            problems.push(format!("{}: unknown tool '{}'", label, step.tool));
+        }
+
+        let mut paths = Vec::new();
+        for value in step.arguments.values().chain(step.for_each.as_ref()) {
+            reference_paths(value, &mut paths);
+        }
+        for path in paths {
+            let mut segments = path.split('.');
+            match segments.next().unwrap_or_default() {
+                "steps" => {
+                    let target = segments.next().unwrap_or_default();
+                    if !earlier.contains(&target) {
+                        problems.push(format!(
+                            "{}: ${{{}}} does not refer to an earlier step",
+                            label, path
+                        ));
+                    }
+                }
+                "item" if step.for_each.is_none() => {
+                    problems.push(format!(
+                        "{}: ${{item}} is only set in for_each steps",
+                        label
+                    ));
+                }
+                "item" => {}
+                name if !variables.contains_key(name) => {
+                    problems.push(format!("{}: undefined variable '{}'", label, name));
+                }
+                _ => {}
+            }
+        }
+        earlier.push(&step.id);
+    }
+    problems
+}
+
+fn lookup<'a>(scope: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(scope, |value, segment| match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => value.get(segment),
+        })
+}
+
+/// Interpolate the references in a value. An unresolved reference is an
+/// error, or kept as written when `lenient`.
+pub fn interpolate(value: &Value, scope: &Value, lenient: bool) -> Result<Value, String> {
+    match value {
+        Value::String(text) => interpolate_text(text, scope, lenient),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| interpolate(v, scope, lenient))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), interpolate(v, scope, lenient)?)))
+            .collect::<Result<Map<_, _>, String>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+fn interpolate_text(text: &str, scope: &Value, lenient: bool) -> Result<Value, String> {
+    let unresolved = |path: &str| format!("Unresolved reference ${{{}}}", path);
+    let references = references(text);
+    if let [(range, path)] = references.as_slice() {
+        if *range == (0..text.len()) {
+            return match lookup(scope, path) {
+                Some(value) => Ok(value.clone()),
+                None if lenient => Ok(Value::String(text.to_string())),
+                None => Err(unresolved(path)),
+            };
+        }
+    }
+    let mut out = String::new();
+    let mut last = 0;
+    for (range, path) in references {
+        out.push_str(&text[last..range.start]);
+        match lookup(scope, path) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(value) => out.push_str(&value.to_string()),
+            None if lenient => out.push_str(&text[range.clone()]),
+            None => return Err(unresolved(path)),
+        }
+        last = range.end;
+    }
+    out.push_str(&text[last..]);
+    Ok(Value::String(out))
+}
+
+/// Output of a tool call: the JSON text content, or the text if it is not
+/// JSON. Tool errors are returned as Err.
+pub fn tool_output(result: &CallToolResult) -> Result<Value, String> {
+    let text = result
+        .content
+        .iter()
+        .filter_map(|c| match &c.raw {
+            RawContent::Text(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if result.is_error == Some(true) {
+        return Err(text);
+    }
+    Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+}
+
+/// Outcome of a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    /// Dry run: the step would be called
+    Planned,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// Result of a step. Steps with `for_each` list the arguments and results
+/// of every call.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub id: String,
+    pub tool: String,
+    pub status: StepStatus,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub arguments: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// State of a workflow run.
+pub struct Run {
+    variables: Map<String, Value>,
+    outputs: Map<String, Value>,
+    steps: Vec<StepResult>,
+    dry_run: bool,
+    stopped: Option<String>,
+}
+
+impl Run {
+    pub fn new(variables: Map<String, Value>, dry_run: bool) -> Self {
+        Self {
+            variables,
+            outputs: Map::new(),
+            steps: Vec::new(),
+            dry_run,
+            stopped: None,
+        }
+    }
+
+    fn scope(&self, item: Option<&Value>) -> Value {
+        let mut scope = self.variables.clone();
+        scope.insert("steps".to_string(), Value::Object(self.outputs.clone()));
+        if let Some(item) = item {
+            scope.insert("item".to_string(), item.clone());
+        }
+        Value::Object(scope)
+    }
+
+    fn push(
+        &mut self,
+        step: &WorkflowStep,
+        status: StepStatus,
+        arguments: Value,
+    ) -> &mut StepResult {
+        self.steps.push(StepResult {
+            id: step.id.clone(),
+            tool: step.tool.clone(),
+            status,
+            arguments,
+            result: None,
+            error: None,
+        });
+        self.steps.last_mut().expect("step was just pushed")
+    }
+
+    /// Skip the remaining steps.
+    pub fn stop(&mut self, reason: &str) {
+        self.stopped.get_or_insert_with(|| reason.to_string());
+    }
+
+    /// Arguments of the calls to make for a step, one per `for_each`
+    /// element. None if the step is not called: the run has stopped, the
+    /// arguments cannot be interpolated, or it is a dry run (the step is
+    /// then recorded as planned).
+    pub fn calls(&mut self, step: &WorkflowStep) -> Option<Vec<Map<String, Value>>> {
+        if let Some(reason) = self.stopped.clone() {
+            self.push(step, StepStatus::Skipped, Value::Null).error = Some(reason);
+            return None;
+        }
+        let scope = self.scope(None);
+        let items = match &step.for_each {
+            None => Ok(None),
+            Some(for_each) => match interpolate(for_each, &scope, self.dry_run) {
+                Ok(Value::Array(items)) => Ok(Some(items)),
+                // Dry run: the elements come from a step result
+                Ok(Value::String(_)) if self.dry_run => Ok(Some(vec![Value::Null])),
+                Ok(_) => Err("for_each is not an array".to_string()),
+                Err(e) => Err(e),
+            },
+        };
+        let calls = items.and_then(|items| {
+            let arguments = Value::Object(step.arguments.clone());
+            let scopes = match &items {
+                None => vec![scope],
+                Some(items) => items.iter().map(|item| self.scope(Some(item))).collect(),
+            };
+            scopes
+                .iter()
+                .map(
+                    |scope| match interpolate(&arguments, scope, self.dry_run)? {
+                        Value::Object(arguments) => Ok(arguments),
+                        _ => unreachable!("an object interpolates to an object"),
+                    },
+                )
+                .collect::<Result<Vec<_>, String>>()
+                .map(|calls| (items.is_some(), calls))
+        });
+
+        match calls {
+            Err(e) => {
+                self.push(step, StepStatus::Failed, Value::Null).error = Some(e.clone());
+                if !step.continue_on_error {
+                    self.stop(&format!("not run: step '{}' failed", step.id));
+                }
+                None
+            }
+            Ok((repeated, calls)) if self.dry_run => {
+                let arguments = if repeated {
+                    json!(calls)
+                } else {
+                    calls
+                        .into_iter()
+                        .next()
+                        .map(Value::Object)
+                        .unwrap_or_default()
+                };
+                self.push(step, StepStatus::Planned, arguments);
+                None
+            }
+            Ok((_, calls)) => Some(calls),
+        }
+    }
+
+    /// Record the calls of a step. The output of a `for_each` step is the
+    /// array of its call results.
+    pub fn record(
+        &mut self,
+        step: &WorkflowStep,
+        calls: Vec<(Map<String, Value>, Result<Value, String>)>,
+    ) {
+        let repeated = step.for_each.is_some();
+        let mut arguments = Vec::new();
+        let mut results = Vec::new();
+        let mut errors = Vec::new();
+        for (index, (call_arguments, outcome)) in calls.into_iter().enumerate() {
+            arguments.push(Value::Object(call_arguments));
+            match outcome {
+                Ok(result) => results.push(result),
+                Err(e) if repeated => {
+                    errors.push(format!("call {}: {}", index + 1, e));
+                    results.push(Value::Null);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        let (arguments, result) = if repeated {
+            (Value::Array(arguments), Value::Array(results))
+        } else {
+            (
+                arguments.pop().unwrap_or_default(),
+                results.pop().unwrap_or_default(),
+            )
+        };
+        let failed = !errors.is_empty();
+        let entry = self.push(
+            step,
+            if failed {
+                StepStatus::Failed
+            } else {
+                StepStatus::Succeeded
+            },
+            arguments,
+        );
+        if failed {
+            entry.error = Some(errors.join("; "));
+        }
+        if !result.is_null() {
+            entry.result = Some(result.clone());
+        }
+        self.outputs.insert(step.id.clone(), result);
+        if failed && !step.continue_on_error {
+            self.stop(&format!("not run: step '{}' failed", step.id));
+        }
+    }
+
+    /// Summary of the run.
+    pub fn finish(self, workflow: &Workflow) -> Value {
+        let count = |status: StepStatus| self.steps.iter().filter(|s| s.status == status).count();
+        json!({
+            "workflow": workflow.name,
+            "description": workflow.description,
+            "dry_run": self.dry_run,
+            "succeeded": count(StepStatus::Failed) == 0 && count(StepStatus::Skipped) == 0,
+            "steps_succeeded": count(StepStatus::Succeeded),
+            "steps_failed": count(StepStatus::Failed),
+            "steps_skipped": count(StepStatus::Skipped),
+            "variables": self.variables,
+            "steps": self.steps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITION: &str = r#"
+name: Feature with tasks
+variables:
+  project_id: p1
+  priority: 20
+steps:
+  - id: feature
+    tool: create_feature
+    arguments:
+      project_id: ${project_id}
+      title: "${title} (prio ${priority})"
+      priority_code: ${priority}
+  - id: tasks
+    tool: create_task
+    for_each: ${task_titles}
+    arguments:
+      project_id: ${project_id}
+      title: ${item}
+      feature: ${steps.feature.uuid}
+"#;
+
+    fn workflow_variables() -> (Workflow, Map<String, Value>) {
+        let workflow = parse(DEFINITION).unwrap();
+        let overrides = json!({"title": "Invoice check", "task_titles": ["Design", "Test"]});
+        let variables = variables(&workflow, overrides.as_object().cloned());
+        (workflow, variables)
+    }
+
+    #[test]
+    fn test_validate_and_dry_run() {
+        let (workflow, variables) = workflow_variables();
+        assert_eq!(
+            validate(&workflow, &variables, |_| true),
+            Vec::<String>::new()
+        );
+        let problems = validate(&workflow, &workflow.variables, |t| t != "create_task");
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+
+        let mut run = Run::new(variables, true);
+        for step in &workflow.steps {
+            assert!(run.calls(step).is_none());
+        }
+        let summary = run.finish(&workflow);
+        let feature = &summary["steps"][0]["arguments"];
+        assert_eq!(feature["title"], "Invoice check (prio 20)");
+        assert_eq!(feature["priority_code"], 20);
+        let tasks = &summary["steps"][1]["arguments"];
+        assert_eq!(tasks[1]["title"], "Test");
+        assert_eq!(tasks[1]["feature"], "${steps.feature.uuid}");
+        assert_eq!(summary["succeeded"], true);
+    }
+
+    #[test]
+    fn test_run_passes_results_and_stops_on_failure() {
+        let (workflow, variables) = workflow_variables();
+        let mut run = Run::new(variables, false);
+
+        let calls = run.calls(&workflow.steps[0]).unwrap();
+        let created = calls.into_iter().map(|c| (c, Ok(json!({"uuid": "f1"}))));
+        run.record(&workflow.steps[0], created.collect());
+
+        let calls = run.calls(&workflow.steps[1]).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0]["feature"], "f1");
+        let outcomes = vec![
+            (calls[0].clone(), Ok(json!({"id": "t1"}))),
+            (calls[1].clone(), Err("HTTP 400".to_string())),
+        ];
+        run.record(&workflow.steps[1], outcomes);
+        assert!(run.calls(&workflow.steps[0]).is_none());
+
+        let summary = run.finish(&workflow);
+        assert_eq!(summary["steps"][1]["status"], "failed");
+        assert_eq!(summary["steps"][1]["error"], "call 2: HTTP 400");
+        assert_eq!(summary["steps"][2]["status"], "skipped");
+        assert_eq!(summary["succeeded"], false);
+    }
+}
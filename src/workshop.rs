@@ -0,0 +1,212 @@
+//! Requirement candidates from Fit-to-Standard workshop notes.
+//!
+//! Notes are split with simple, predictable heuristics instead of an LLM so
+//! the preview is reproducible:
+//!
+//! - markdown headings set the section of the following candidates
+//! - lines with an explicit marker (`REQ:`, `Requirement:`, `Gap:`) are candidates
+//! - list items are candidates (unless disabled)
+//! - other lines are candidates if they contain a requirement keyword
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Explicit markers that always start a requirement candidate.
+const MARKERS: &[&str] = &["req:", "requirement:", "gap:"];
+
+/// Default keywords identifying requirement statements in free text.
+pub const DEFAULT_KEYWORDS: &[&str] = &["must", "shall", "should", "needs to", "required"];
+
+/// Maximum title length; longer statements keep the full text as description.
+const MAX_TITLE_LEN: usize = 120;
+
+/// Heuristics for splitting notes into candidates.
+#[derive(Debug, Clone)]
+pub struct SplitOptions {
+    pub keywords: Vec<String>,
+    pub include_list_items: bool,
+    pub min_length: usize,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        Self {
+            keywords: DEFAULT_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+            include_list_items: true,
+            min_length: 10,
+        }
+    }
+}
+
+/// A requirement candidate extracted from the notes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementCandidate {
+    pub title: String,
+    pub description: Option<String>,
+    pub section: Option<String>,
+    /// 1-based line number in the notes
+    pub line: usize,
+}
+
+impl RequirementCandidate {
+    /// Stable identifier derived from the content, so editing other parts
+    /// of the notes does not change it.
+    pub fn id(&self) -> String {
+        let content = format!(
+            "{}\u{0}{}\u{0}{}",
+            self.section.as_deref().unwrap_or_default(),
+            self.title,
+            self.description.as_deref().unwrap_or_default()
+        );
+        let hash = Sha256::digest(content.as_bytes());
+        let hex: String = hash[..6].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("req-{}", hex)
+    }
+}
+
+/// Split workshop notes into requirement candidates.
+pub fn split_requirements(text: &str, options: &SplitOptions) -> Vec<RequirementCandidate> {
+    let mut candidates = Vec::new();
+    let mut section = None;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('#') {
+            let heading = line.trim_start_matches('#').trim();
+            section = (!heading.is_empty()).then(|| heading.to_string());
+            continue;
+        }
+
+        let (statement, is_list_item) = strip_list_marker(line);
+        let lower = statement.to_lowercase();
+        let (statement, selected) = match MARKERS.iter().find(|m| lower.starts_with(*m)) {
+            Some(marker) => (statement[marker.len()..].trim(), true),
+            None => (
+                statement,
+                (is_list_item && options.include_list_items)
+                    || options
+                        .keywords
+                        .iter()
+                        .any(|k| contains_word(&lower, &k.to_lowercase())),
+            ),
+        };
+        if !selected || statement.chars().count() < options.min_length {
+            continue;
+        }
+
+        let (title, description) = title_and_description(statement);
+        candidates.push(RequirementCandidate {
+            title,
+            description,
+            section: section.clone(),
+            line: index + 1,
+        });
+    }
+
+    candidates
+}
+
+/// Remove a leading bullet (`-`, `*`, `+`) or numbering (`1.`, `2)`).
+fn strip_list_marker(line: &str) -> (&str, bool) {
+    if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        return (rest.trim(), true);
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(rest) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return (rest.trim(), true);
+        }
+    }
+    (line, false)
+}
+
+/// Check for a keyword at word boundaries.
+fn contains_word(text: &str, keyword: &str) -> bool {
+    text.match_indices(keyword).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + keyword.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Use the statement as title, truncated at a word boundary if too long.
+fn title_and_description(statement: &str) -> (String, Option<String>) {
+    if statement.chars().count() <= MAX_TITLE_LEN {
+        return (statement.to_string(), None);
+    }
+    let cut: String = statement.chars().take(MAX_TITLE_LEN).collect();
+    let title = match cut.rfind(' ') {
+        Some(pos) if pos > MAX_TITLE_LEN / 2 => &cut[..pos],
+        _ => cut.as_str(),
+    };
+    (format!("{}...", title), Some(statement.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES: &str = "\
+# Procure to Pay
+Attendees: finance team
+- Three-way match for all purchase orders above 10k
+- PO
+The system must block invoices without goods receipt.
+Gap: Vendor portal integration with Ariba
+## Record to Report
+1. Parallel ledgers for IFRS and local GAAP
+Customs processing is handled externally.
+";
+
+    #[test]
+    fn test_split_requirements_with_sections() {
+        let candidates = split_requirements(NOTES, &SplitOptions::default());
+        let titles: Vec<&str> = candidates.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Three-way match for all purchase orders above 10k",
+                "The system must block invoices without goods receipt.",
+                "Vendor portal integration with Ariba",
+                "Parallel ledgers for IFRS and local GAAP",
+            ]
+        );
+        assert_eq!(candidates[0].section.as_deref(), Some("Procure to Pay"));
+        assert_eq!(candidates[0].line, 3);
+        assert_eq!(candidates[3].section.as_deref(), Some("Record to Report"));
+
+        // Ids depend on the content, not on the line
+        let shifted = split_requirements(&format!("\n\n{}", NOTES), &SplitOptions::default());
+        assert_eq!(shifted[0].line, 5);
+        assert_eq!(shifted[0].id(), candidates[0].id());
+        assert_ne!(candidates[0].id(), candidates[1].id());
+    }
+
+    #[test]
+    fn test_split_requirements_without_list_items() {
+        let options = SplitOptions {
+            include_list_items: false,
+            ..Default::default()
+        };
+        let candidates = split_requirements(NOTES, &options);
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates[1].title.starts_with("Vendor portal"));
+    }
+
+    #[test]
+    fn test_long_statement_is_truncated_into_title() {
+        let statement = format!("REQ: {}", "word ".repeat(40));
+        let candidates = split_requirements(&statement, &SplitOptions::default());
+        assert!(candidates[0].title.ends_with("..."));
+        assert!(candidates[0].title.len() <= MAX_TITLE_LEN + 3);
+        assert!(candidates[0].description.is_some());
+    }
+}
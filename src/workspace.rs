@@ -0,0 +1,264 @@
+//! Workspace directory for generated files.
+//!
+//! With `workspace_dir` configured, tools that write or read files (dataset
+//! exports, reference mapping files, calendars, project templates, hierarchy
+//! snapshots, workflow definitions) resolve their `path` inside the
+//! workspace and refuse paths leading outside it.
+//! `list_workspace_files` and `read_workspace_file` give clients without
+//! access to the server's file system the generated artifacts, read in
+//! chunks so large exports do not have to fit into one result.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Default chunk size of `read_workspace_file`.
+pub const DEFAULT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Largest chunk returned by `read_workspace_file`.
+pub const MAX_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// A file in the workspace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkspaceFile {
+    /// Path relative to the workspace directory, with `/` separators
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+}
+
+/// A chunk of a workspace file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileChunk {
+    pub path: String,
+    pub offset: u64,
+    pub length: usize,
+    pub total_size: u64,
+    /// "utf-8", or "base64" for binary content
+    pub encoding: &'static str,
+    pub content: String,
+    /// Offset of the next chunk, None at the end of the file
+    pub next_offset: Option<u64>,
+}
+
+/// Directory that file tools are confined to.
+pub struct Workspace {
+    root: PathBuf,
+}
+
+impl Workspace {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve a relative path inside the workspace. Absolute paths, paths
+    /// with `..` components and paths that lead outside the workspace through
+    /// a symbolic link are rejected.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(path);
+        let inside = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if path.trim().is_empty() || !inside {
+            return Err(format!(
+                "Invalid path '{}': use a path relative to the workspace directory without '..'",
+                path
+            ));
+        }
+        let resolved = self.root.join(relative);
+        let outside = || {
+            format!(
+                "Invalid path '{}': it leads outside the workspace directory",
+                path
+            )
+        };
+        let canonical = canonicalize_existing(&resolved).map_err(|_| outside())?;
+        let root = canonicalize_existing(&self.root).map_err(|_| outside())?;
+        if !canonical.starts_with(root) {
+            return Err(outside());
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve a path for writing, creating its parent directories.
+    pub fn resolve_for_write(&self, path: &str) -> Result<PathBuf, String> {
+        let resolved = self.resolve(path)?;
+        if let Some(parent) = resolved.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Cannot create directory '{}': {}", parent.display(), e))?;
+        }
+        Ok(resolved)
+    }
+
+    /// All files in the workspace, sorted by path. A missing workspace
+    /// directory has no files.
+    pub fn list(&self) -> std::io::Result<Vec<WorkspaceFile>> {
+        let mut files = Vec::new();
+        if self.root.is_dir() {
+            collect_files(&self.root, &self.root, &mut files)?;
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Read up to `max_bytes` of a file from `offset`. Text is returned as
+    /// UTF-8, cut at a character boundary; other content as base64.
+    pub fn read_chunk(
+        &self,
+        path: &str,
+        offset: u64,
+        max_bytes: usize,
+    ) -> Result<FileChunk, String> {
+        let resolved = self.resolve(path)?;
+        let error = |e: std::io::Error| format!("Cannot read '{}': {}", path, e);
+        let mut file = File::open(&resolved).map_err(error)?;
+        let total_size = file.metadata().map_err(error)?.len();
+        let start = offset.min(total_size);
+        file.seek(SeekFrom::Start(start)).map_err(error)?;
+        let mut bytes = Vec::new();
+        file.take(max_bytes.clamp(1, MAX_CHUNK_BYTES) as u64)
+            .read_to_end(&mut bytes)
+            .map_err(error)?;
+        let chunk = bytes.as_slice();
+
+        let (encoding, content, length) = match std::str::from_utf8(chunk) {
+            Ok(text) => ("utf-8", text.to_string(), chunk.len()),
+            // A character split at the chunk end continues in the next chunk
+            Err(e) if e.error_len().is_none() && e.valid_up_to() > 0 => {
+                let valid = &chunk[..e.valid_up_to()];
+                let text = String::from_utf8_lossy(valid).into_owned();
+                ("utf-8", text, valid.len())
+            }
+            Err(_) => ("base64", BASE64.encode(chunk), chunk.len()),
+        };
+        let next = start + length as u64;
+        Ok(FileChunk {
+            path: path.to_string(),
+            offset: start,
+            length,
+            total_size,
+            encoding,
+            content,
+            next_offset: (next < total_size).then_some(next),
+        })
+    }
+}
+
+/// Canonicalize the longest existing prefix of `path` and append the rest,
+/// so paths of files still to be written resolve their symbolic links too.
+/// A dangling symbolic link cannot be resolved and is an error.
+fn canonicalize_existing(path: &Path) -> std::io::Result<PathBuf> {
+    for ancestor in path.ancestors() {
+        if ancestor.as_os_str().is_empty() || std::fs::symlink_metadata(ancestor).is_err() {
+            continue;
+        }
+        let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+        return Ok(ancestor.canonicalize()?.join(rest));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<WorkspaceFile>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_files(root, &entry.path(), files)?;
+        } else if metadata.is_file() {
+            let relative = entry.path();
+            let relative = relative.strip_prefix(root).unwrap_or(&relative);
+            files.push(WorkspaceFile {
+                path: relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                size: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .ok()
+                    .map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str) -> Workspace {
+        let root =
+            std::env::temp_dir().join(format!("calm-workspace-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        Workspace::new(root)
+    }
+
+    #[test]
+    fn test_paths_are_confined_to_the_workspace() {
+        let workspace = workspace("paths");
+        assert!(workspace.resolve("exports/defects.csv").is_ok());
+        assert!(workspace.resolve("../secrets.json").is_err());
+        assert!(workspace.resolve("exports/../../x").is_err());
+        assert!(workspace.resolve("/etc/passwd").is_err());
+        assert!(workspace.resolve("").is_err());
+        assert_eq!(workspace.list().unwrap(), Vec::new());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symbolic_links_out_of_the_workspace_are_rejected() {
+        let workspace = workspace("links");
+        let outside = workspace.root().with_extension("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::create_dir_all(workspace.root()).unwrap();
+        std::os::unix::fs::symlink(&outside, workspace.root().join("out")).unwrap();
+        std::os::unix::fs::symlink(outside.join("missing"), workspace.root().join("dangling"))
+            .unwrap();
+
+        assert!(workspace.resolve("out/secrets.json").is_err());
+        assert!(workspace.resolve_for_write("out/new/file.csv").is_err());
+        assert!(workspace.resolve("dangling").is_err());
+        assert!(workspace.resolve_for_write("exports/new.csv").is_ok());
+        let _ = std::fs::remove_dir_all(workspace.root());
+        let _ = std::fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn test_list_and_read_in_chunks() {
+        let workspace = workspace("chunks");
+        let path = workspace.resolve_for_write("exports/notes.txt").unwrap();
+        std::fs::write(&path, "Grüße").unwrap();
+        std::fs::write(
+            workspace.resolve_for_write("data.bin").unwrap(),
+            [0xff, 0x00],
+        )
+        .unwrap();
+
+        let files = workspace.list().unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["data.bin", "exports/notes.txt"]);
+
+        // "Grüße" is 7 bytes; a 3-byte chunk would split the "ü"
+        let first = workspace.read_chunk("exports/notes.txt", 0, 3).unwrap();
+        assert_eq!(first.content, "Gr");
+        assert_eq!(first.next_offset, Some(2));
+        let rest = workspace.read_chunk("exports/notes.txt", 2, 100).unwrap();
+        assert_eq!(rest.content, "üße");
+        assert_eq!(rest.next_offset, None);
+
+        let binary = workspace.read_chunk("data.bin", 0, 100).unwrap();
+        assert_eq!(binary.encoding, "base64");
+        assert_eq!(binary.content, "/wA=");
+        let _ = std::fs::remove_dir_all(workspace.root());
+    }
+}